@@ -1,15 +1,47 @@
-use crate::db::{Database, ItemStore, SettingsStore};
-use crate::export::ClaudeExporter;
-use crate::llm::{complete_sync, LlmRequest, LlmResponse};
-use crate::models::{Category, Item};
+use crate::db::{
+    normalize_collection_path, AiActionStore, CollectionStore, ConflictMode, Database, ExportStore,
+    ItemStore, LinkStore, SecretStore, SettingsStore, SortOrder, SyncStatus, UsageStore,
+    UserAction, Vaults,
+};
+use crate::export::{
+    apply_sync, commit_exports, create_gist, expand_home, find_drift, format_export_profiles,
+    github_token, move_export, pull_into, write_zip, Bundle, DataFormat, ExportOptions,
+    ExportProfile, ExportTarget, ExportWatcher, SyncAction, BUNDLE_EXTENSION, FRONTMATTER_TARGETS,
+    ZIP_FILE_NAME,
+};
+use crate::import::{self, ClaudeImporter, ImportSummary, RepoImporter, UrlImporter};
+use crate::llm::{
+    estimate_cost, test_connection, BackgroundCompletion, ChatTurn, CompletionError, LlmConfig,
+    LlmRequest, LlmResponse, Usage,
+};
+use crate::loader::{DataLoader, ListQuery, Loaded, Snapshot};
+use crate::models::{
+    format_variable_presets, parse_variable_presets, template_variables, Category, Item,
+};
 use crate::ui::{
-    AiPopupState, ConfirmDialog, EditField, EditState, HelpState, HistoryState, LlmProvider,
-    SearchState, SettingsField, SettingsState, ViewState,
+    action_document, parse_action_document, parse_list_columns, AiAction, AiPopupState,
+    ConfirmAction, ConfirmDialog, EditField, EditState, ExportDiffState, ExportPopupState,
+    FuzzyFinderState, HelpState, HistoryState, ImportConflictState, InputAction, InputDialog,
+    LinkPickerState, LlmProvider, MessageLog, ResultDiff, SearchScope, SearchState, SettingsField,
+    SettingsState, SyncState, TemplateFormState, VaultPickerState, VersionDiffState, ViewState,
+    VimMode, SUMMARY_PROMPT,
 };
-use color_eyre::eyre::Result;
-use crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
-use ratatui::DefaultTerminal;
-use std::sync::mpsc::{self, Receiver};
+use color_eyre::eyre::{eyre, Result};
+use crossterm::event::{
+    self, DisableBracketedPaste, EnableBracketedPaste, Event, KeyCode, KeyEvent, KeyEventKind,
+    KeyModifiers,
+};
+use crossterm::execute;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use ratatui::prelude::CrosstermBackend;
+use ratatui::Terminal;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::mpsc::{self, Receiver, Sender};
 use std::time::Duration;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -20,6 +52,53 @@ pub enum Screen {
     Search,
     Settings,
     Help,
+    Messages,
+}
+
+/// Sidebar rows: Recent, Favorites, Recent copies, the four categories,
+/// Archived, Stale, Trash, collections, then tags (see `App::tags_start`)
+pub const SIDEBAR_FAVORITES_INDEX: usize = 1;
+pub const SIDEBAR_COPIES_INDEX: usize = 2;
+pub const SIDEBAR_CATEGORIES_START: usize = 3;
+pub const SIDEBAR_ARCHIVED_INDEX: usize = 7;
+pub const SIDEBAR_STALE_INDEX: usize = 8;
+pub const SIDEBAR_TRASH_INDEX: usize = 9;
+pub const SIDEBAR_COLLECTIONS_START: usize = 10;
+
+/// Items kept under Recent copies
+pub const RECENT_COPIES_LIMIT: usize = 10;
+
+/// Rows of Recent loaded at a time; more are fetched when scrolling past
+/// the end
+const RECENT_PAGE_SIZE: usize = 100;
+
+/// Sidebar width in columns: default and the range `<` / `>` adjust within
+const SIDEBAR_DEFAULT_WIDTH: u16 = 20;
+const SIDEBAR_MIN_WIDTH: u16 = 14;
+const SIDEBAR_MAX_WIDTH: u16 = 60;
+
+/// Sidebar lists that are neither a category nor a tag
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SpecialList {
+    Favorites,
+    /// The last items copied, where Enter copies again
+    Copies,
+    Archived,
+    /// Items untouched for the months set in settings, for review
+    Stale,
+    Trash,
+}
+
+impl SpecialList {
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            SpecialList::Favorites => "Favorites",
+            SpecialList::Copies => "Recent copies",
+            SpecialList::Archived => "Archived",
+            SpecialList::Stale => "Stale",
+            SpecialList::Trash => "Trash",
+        }
+    }
 }
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
@@ -28,6 +107,21 @@ pub enum Focus {
     ItemList,
 }
 
+/// A completion in flight, where its reply arrives, and the settings it
+/// was sent with
+type PendingSummary = (
+    BackgroundCompletion,
+    Receiver<Result<LlmResponse, CompletionError>>,
+    LlmConfig,
+);
+
+/// What `--pick` prints for the chosen item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PickOutput {
+    Content,
+    Name,
+}
+
 pub struct App {
     pub should_quit: bool,
     pub screen: Screen,
@@ -35,20 +129,52 @@ pub struct App {
 
     // Database
     pub db: Database,
+    pub vault: String,
 
     // Data
     pub items: Vec<Item>,
     pub category_counts: Vec<(Category, usize)>,
     pub tags: Vec<(String, usize)>,
+    /// Collection paths in tree order, with the items in each subtree
+    pub collections: Vec<(String, usize)>,
+    pub trash_count: usize,
+    pub stale_count: usize,
+    /// Items under Recent copies
+    pub copies_count: usize,
+    pub favorites: HashSet<i64>,
+    pub archived: HashSet<i64>,
+    /// Times each item was copied, for the usage column
+    pub use_counts: HashMap<i64, i64>,
 
     // Selection state
     pub selected_category: Option<Category>,
-    pub selected_tag: Option<String>,
+    /// Tags filtering the list, Space-toggled in the sidebar
+    pub selected_tags: Vec<String>,
+    /// Items must have every selected tag rather than any of them (`a`)
+    pub tag_match_all: bool,
+    /// `/` searches only the selected category or tags (Ctrl+F in search)
+    search_scoped: bool,
+    /// Collection filtering the list, with the ones nested under it
+    pub selected_collection: Option<String>,
+    pub special_list: Option<SpecialList>,
     pub selected_item_index: usize,
+    /// Order of the item list (`o`)
+    pub sort_order: SortOrder,
+    /// Recent has rows past the ones loaded in `items`
+    pub has_more_items: bool,
+    /// Preview pane next to the item list (`p`)
+    pub show_preview: bool,
+    pub sidebar_width: u16,
     pub sidebar_index: usize,
+    /// Items marked for bulk actions (`Space`, `v`)
+    pub marked: HashSet<i64>,
+    /// List index where a `v` range selection started
+    pub mark_anchor: Option<usize>,
 
     // Vim-style key state
     pub pending_key: Option<char>,
+    /// Name prefix typed after `'`, jumping to the first item it starts
+    pub type_ahead: Option<String>,
 
     // Screen states
     pub view_state: ViewState,
@@ -56,98 +182,473 @@ pub struct App {
     pub search_state: SearchState,
     pub settings_state: SettingsState,
     pub help_state: HelpState,
+    /// Status messages and notices of this session (`M`)
+    pub message_log: MessageLog,
 
     // Overlays
     pub confirm_dialog: Option<ConfirmDialog>,
+    pub input_dialog: Option<InputDialog>,
+    pub vault_picker: Option<VaultPickerState>,
+    /// Quick open by fuzzy name match (Ctrl+P)
+    pub fuzzy_finder: Option<FuzzyFinderState>,
+    pub link_picker: Option<LinkPickerState>,
+    /// Values for the `{{variables}}` of an item being copied
+    pub template_form: Option<TemplateFormState>,
     pub show_ai_popup: bool,
+    /// Open the content in `$EDITOR` once the current events are handled
+    pub pending_external_edit: bool,
+    /// Open the selected skeleton in `$EDITOR` from settings
+    pub pending_skeleton_edit: bool,
+    /// User AI action to open in `$EDITOR`; a blank one for a new action
+    pub pending_action_edit: Option<UserAction>,
     pub ai_popup_state: AiPopupState,
     pub show_history_popup: bool,
     pub history_state: Option<HistoryState>,
+    /// Diff of a past version against the current one, opened from history
+    pub version_diff: Option<VersionDiffState>,
+    pub show_export_popup: bool,
+    pub export_popup_state: ExportPopupState,
+    pub export_diff: Option<ExportDiffState>,
+    /// Imported items that clash with the library's, being settled
+    pub import_conflicts: Option<ImportConflictState>,
+    /// Items that differ from the export path, being reviewed for a sync
+    pub sync_state: Option<SyncState>,
+    pub sync_status: HashMap<i64, SyncStatus>,
 
     // Background task receiver for LLM responses
-    pub llm_receiver: Option<Receiver<Result<LlmResponse, String>>>,
+    pub llm_receiver: Option<Receiver<Result<LlmResponse, CompletionError>>>,
+    llm_request_config: Option<LlmConfig>,
+    /// The last request sent from the AI popup, kept for retries
+    llm_last_request: Option<(LlmRequest, LlmConfig)>,
+    /// The running AI request, to cancel it
+    llm_task: Option<BackgroundCompletion>,
+    /// Connection test started from Settings, and the settings it used
+    connection_test: Option<Receiver<Result<(LlmResponse, Duration), String>>>,
+    connection_test_config: Option<LlmConfig>,
+    /// AI summary being written for the open version diff
+    version_summary: Option<PendingSummary>,
+    /// Repository being cloned or updated for an import
+    repo_fetch: Option<Receiver<Result<RepoImporter, String>>>,
+    /// Documents being downloaded for an import from a URL
+    url_fetch: Option<Receiver<Result<UrlImporter, String>>>,
+    /// Gist being created for the item being shared
+    gist_share: Option<Receiver<Result<String, String>>>,
+    /// Commits of exported files running in the background
+    export_commit_tx: Sender<Result<Option<String>, String>>,
+    export_commit_rx: Receiver<Result<Option<String>, String>>,
+    /// Bundle waiting on the user to settle its conflicts
+    pending_bundle: Option<(PathBuf, Bundle)>,
+    /// Generated item waiting on the user to confirm replacing their edits
+    pending_generated: Option<String>,
+    /// Target and destination of an export to a path waiting on the user
+    /// to confirm replacing the file there
+    pending_export_to: Option<(ExportTarget, String)>,
+    /// Destination of a zip export waiting on the user to confirm replacing
+    /// the file there
+    pending_zip: Option<PathBuf>,
+    /// Backup waiting on the user to confirm replacing the database with it
+    pending_restore: Option<String>,
+    /// Skills of a just-exported agent, offered for export the same way
+    pending_skills: Option<(ExportTarget, Option<ExportProfile>, Vec<Item>)>,
+    /// Watches the export path for files edited outside grimoire
+    export_watcher: Option<ExportWatcher>,
+    /// Items whose exported file was edited outside grimoire, with the
+    /// file, waiting to be reviewed
+    external_edits: VecDeque<(i64, PathBuf)>,
+
+    /// Set when running as a picker (`--pick`): Enter ends the session
+    /// with the item as output instead of opening it
+    pub pick: Option<PickOutput>,
+    /// What the picker prints once the TUI is closed
+    picked: Option<String>,
 
     // Message to display
     pub status_message: Option<String>,
+
+    /// Most recently trashed items, restored by `u`
+    last_deleted: Vec<i64>,
+
+    /// Reads lists and search results in the background
+    loader: DataLoader,
+    /// Number of the list reload being waited on
+    pending_refresh: Option<u64>,
+    /// Number of the search being waited on
+    pending_search: Option<u64>,
+    /// Item to select once the list being loaded arrives, and whether to
+    /// open it
+    select_on_load: Option<(i64, bool)>,
 }
 
 impl App {
-    pub fn new() -> Result<Self> {
-        let db = Database::new()?;
+    pub fn new(vault: &str) -> Result<Self> {
+        let db = Database::open(vault)?;
+        Vaults::remember(vault)?;
 
-        // Load settings
-        let settings_store = SettingsStore::new(&db.conn);
-        let mut settings_state = SettingsState::default();
-
-        if let Ok(Some(provider)) = settings_store.get("llm_provider") {
-            settings_state.provider = LlmProvider::from_str(&provider);
-        }
-        if let Ok(Some(key)) = settings_store.get("api_key") {
-            settings_state.api_key = key.trim().to_string();
-        }
-        if let Ok(Some(model)) = settings_store.get("llm_model") {
-            settings_state.llm_model = model.trim().to_string();
-        }
-        if let Ok(Some(path)) = settings_store.get("export_path") {
-            settings_state.export_path = path.trim().to_string();
-        }
+        let mut settings_state = Self::load_settings(&db, vault)?;
+        settings_state.vault = vault.to_string();
+        let view_state = ViewState {
+            render_markdown: SettingsStore::new(&db.conn)
+                .get("view_render_markdown")?
+                .is_some_and(|v| v == "true"),
+            ..ViewState::default()
+        };
+        let show_preview = SettingsStore::new(&db.conn)
+            .get("main_show_preview")?
+            .is_some_and(|v| v == "true");
+        let sidebar_width = SettingsStore::new(&db.conn)
+            .get("sidebar_width")?
+            .and_then(|v| v.trim().parse().ok())
+            .unwrap_or(SIDEBAR_DEFAULT_WIDTH)
+            .clamp(SIDEBAR_MIN_WIDTH, SIDEBAR_MAX_WIDTH);
+        let sort_order = SettingsStore::new(&db.conn)
+            .get("list_sort")?
+            .map(|v| SortOrder::from_str(&v))
+            .unwrap_or_default();
+        let tag_match_all = SettingsStore::new(&db.conn)
+            .get("tag_match")?
+            .is_some_and(|v| v == "all");
+        let search_scoped = SettingsStore::new(&db.conn)
+            .get("search_scope")?
+            .is_some_and(|v| v == "list");
 
+        let (export_commit_tx, export_commit_rx) = mpsc::channel();
         let mut app = Self {
             should_quit: false,
             screen: Screen::Main,
             focus: Focus::ItemList,
             db,
+            vault: vault.to_string(),
             items: Vec::new(),
             category_counts: Vec::new(),
             tags: Vec::new(),
+            collections: Vec::new(),
+            trash_count: 0,
+            stale_count: 0,
+            copies_count: 0,
+            favorites: HashSet::new(),
+            archived: HashSet::new(),
+            use_counts: HashMap::new(),
             selected_category: None,
-            selected_tag: None,
+            selected_tags: Vec::new(),
+            tag_match_all,
+            search_scoped,
+            selected_collection: None,
+            special_list: None,
             selected_item_index: 0,
+            has_more_items: false,
+            sort_order,
+            show_preview,
+            sidebar_width,
             sidebar_index: 0,
+            marked: HashSet::new(),
+            mark_anchor: None,
             pending_key: None,
-            view_state: ViewState::default(),
+            type_ahead: None,
+            view_state,
             edit_state: EditState::new_item(),
             search_state: SearchState::default(),
             settings_state,
             help_state: HelpState::default(),
+            message_log: MessageLog::default(),
             confirm_dialog: None,
+            input_dialog: None,
+            vault_picker: None,
+            fuzzy_finder: None,
+            link_picker: None,
+            template_form: None,
             show_ai_popup: false,
+            pending_external_edit: false,
+            pending_skeleton_edit: false,
+            pending_action_edit: None,
             ai_popup_state: AiPopupState::default(),
             show_history_popup: false,
             history_state: None,
+            version_diff: None,
+            show_export_popup: false,
+            export_popup_state: ExportPopupState::default(),
+            export_diff: None,
+            import_conflicts: None,
+            sync_state: None,
+            sync_status: HashMap::new(),
             llm_receiver: None,
+            llm_request_config: None,
+            llm_last_request: None,
+            llm_task: None,
+            connection_test: None,
+            connection_test_config: None,
+            version_summary: None,
+            repo_fetch: None,
+            url_fetch: None,
+            gist_share: None,
+            export_commit_tx,
+            export_commit_rx,
+            pending_bundle: None,
+            pending_generated: None,
+            pending_export_to: None,
+            pending_zip: None,
+            pending_restore: None,
+            pending_skills: None,
+            export_watcher: None,
+            external_edits: VecDeque::new(),
+            pick: None,
+            picked: None,
             status_message: None,
+            last_deleted: Vec::new(),
+            loader: DataLoader::spawn(vault)?,
+            pending_refresh: None,
+            pending_search: None,
+            select_on_load: None,
         };
 
+        app.reload_user_actions(None)?;
         app.refresh_data()?;
         Ok(app)
     }
 
+    /// Settings as stored in the vault's database (and the API key in the
+    /// keychain)
+    fn load_settings(db: &Database, vault: &str) -> Result<SettingsState> {
+        let settings_store = SettingsStore::new(&db.conn);
+        let secrets = SecretStore::new(vault);
+        let mut settings_state = SettingsState::default();
+
+        if let Ok(Some(provider)) = settings_store.get("llm_provider") {
+            settings_state.provider = LlmProvider::from_str(&provider);
+        }
+        let (api_key, keychain_available) =
+            Self::load_secret(&settings_store, &secrets, "api_key")?;
+        settings_state.api_key = api_key;
+        settings_state.github_token =
+            Self::load_secret(&settings_store, &secrets, "github_token")?.0;
+        settings_state.keychain_available = keychain_available;
+        if let Ok(Some(model)) = settings_store.get("llm_model") {
+            settings_state.llm_model = model.trim().to_string();
+        }
+        if let Ok(Some(model)) = settings_store.get("llm_light_model") {
+            settings_state.light_model = model.trim().to_string();
+        }
+        // A Claude model left over from before a provider switch would be
+        // sent to a provider that doesn't have it
+        if settings_state.provider != LlmProvider::Anthropic {
+            if settings_state.llm_model.starts_with("claude") {
+                settings_state.llm_model = settings_state.provider.default_model().to_string();
+            }
+            if settings_state.light_model.starts_with("claude") {
+                settings_state.light_model.clear();
+            }
+        }
+        if let Ok(Some(base_url)) = settings_store.get("llm_base_url") {
+            settings_state.base_url = base_url.trim().to_string();
+        }
+        if let Ok(Some(timeout)) = settings_store.get("llm_timeout_secs") {
+            settings_state.timeout_secs = timeout.trim().to_string();
+        }
+        if let Ok(Some(retries)) = settings_store.get("llm_max_retries") {
+            settings_state.max_retries = retries.trim().to_string();
+        }
+        if let Ok(Some(path)) = settings_store.get("export_path") {
+            settings_state.export_path = path.trim().to_string();
+        }
+        if let Ok(Some(auto_export)) = settings_store.get("auto_export") {
+            settings_state.auto_export = auto_export == "true";
+        }
+        if let Ok(Some(git_commit)) = settings_store.get("export_git_commit") {
+            settings_state.git_commit = git_commit == "true";
+        }
+        if let Ok(Some(vim_mode)) = settings_store.get("vim_mode") {
+            settings_state.vim_mode = vim_mode == "true";
+        }
+        if let Ok(Some(columns)) = settings_store.get("list_columns") {
+            settings_state.list_columns = columns.trim().to_string();
+        }
+        if let Ok(Some(months)) = settings_store.get("stale_months") {
+            settings_state.stale_months = months.trim().to_string();
+        }
+        if let Ok(Some(variables)) = settings_store.get("template_variables") {
+            settings_state.template_variables = variables.trim().to_string();
+        }
+        if let Ok(Some(profiles)) = settings_store.get("export_profiles") {
+            settings_state.export_profiles = profiles.trim().to_string();
+        }
+        for (target, _, _) in FRONTMATTER_TARGETS {
+            if let Ok(Some(spec)) = settings_store.get(&frontmatter_key(target)) {
+                settings_state
+                    .frontmatter_keys
+                    .insert(target.to_string(), spec.trim().to_string());
+            }
+        }
+        for category in Category::all() {
+            if let Ok(Some(skeleton)) = settings_store.get(&skeleton_key(category)) {
+                settings_state.skeletons.insert(category, skeleton);
+            }
+        }
+
+        settings_state.mark_ai_saved();
+        Ok(settings_state)
+    }
+
+    /// Read a secret (the API key, the GitHub token) from the OS keychain,
+    /// moving one stored in the settings table by older versions into it.
+    /// Without a usable keychain the settings table is used as before.
+    fn load_secret(
+        settings_store: &SettingsStore,
+        secrets: &SecretStore,
+        name: &str,
+    ) -> Result<(String, bool)> {
+        let stored = settings_store
+            .get(name)?
+            .map(|key| key.trim().to_string())
+            .unwrap_or_default();
+
+        match secrets.get(name) {
+            Ok(Some(key)) => Ok((key.trim().to_string(), true)),
+            Ok(None) if stored.is_empty() => Ok((stored, true)),
+            Ok(None) => {
+                if secrets.set(name, &stored).is_ok() {
+                    settings_store.delete(name)?;
+                    Ok((stored, true))
+                } else {
+                    Ok((stored, false))
+                }
+            }
+            Err(_) => Ok((stored, false)),
+        }
+    }
+
+    /// Reload the list and the sidebar counts now. Anything still loading
+    /// in the background is older, so it is dropped.
     pub fn refresh_data(&mut self) -> Result<()> {
-        let store = ItemStore::new(&self.db.conn);
+        self.pending_refresh = None;
+        self.select_on_load = None;
+        let snapshot = Snapshot::load(&self.db.conn, &self.list_query())?;
+        self.apply_snapshot(snapshot);
+        Ok(())
+    }
 
-        self.items = match (&self.selected_category, &self.selected_tag) {
-            (Some(cat), _) => store.list_by_category(*cat)?,
-            (None, Some(tag)) => store.list_by_tag(tag)?,
-            (None, None) => store.list_recent(100)?,
-        };
+    /// Reload in the background, for moves between lists that don't need
+    /// the new items right away. The old items are dropped meanwhile so
+    /// nothing acts on a list that no longer matches the view
+    fn request_refresh(&mut self) {
+        self.pending_refresh = Some(self.loader.refresh(self.list_query()));
+        self.items.clear();
+        self.marked.clear();
+        self.mark_anchor = None;
+    }
+
+    /// Whether the list is being reloaded in the background
+    pub fn is_loading(&self) -> bool {
+        self.pending_refresh.is_some()
+    }
+
+    /// Whether a search is still running
+    pub fn is_searching(&self) -> bool {
+        self.pending_search.is_some()
+    }
+
+    fn list_query(&self) -> ListQuery {
+        ListQuery {
+            special_list: self.special_list,
+            category: self.selected_category,
+            tags: self.selected_tags.clone(),
+            tag_match_all: self.tag_match_all,
+            collection: self.selected_collection.clone(),
+            sort_order: self.sort_order,
+            // Reload as many pages as needed to keep the selection
+            recent_limit: (self.selected_item_index / RECENT_PAGE_SIZE + 1) * RECENT_PAGE_SIZE,
+            stale_months: self.settings_state.stale_months(),
+        }
+    }
 
-        self.category_counts = store.count_by_category()?;
-        self.tags = store.get_tags_with_counts()?;
+    fn apply_snapshot(&mut self, snapshot: Snapshot) {
+        self.items = snapshot.items;
+        self.has_more_items = snapshot.has_more_items;
+        self.category_counts = snapshot.category_counts;
+        self.trash_count = snapshot.trash_count;
+        self.stale_count = snapshot.stale_count;
+        self.copies_count = snapshot.copies_count;
+        self.favorites = snapshot.favorites;
+        self.archived = snapshot.archived;
+        self.use_counts = snapshot.use_counts;
+        self.tags = snapshot.tags;
+        self.collections = snapshot.collections;
+        self.sync_status = snapshot.sync_status;
 
         if self.selected_item_index >= self.items.len() && !self.items.is_empty() {
             self.selected_item_index = self.items.len() - 1;
         }
 
+        // Marks only apply to items in the current list
+        let items = &self.items;
+        self.marked
+            .retain(|id| items.iter().any(|item| item.id == Some(*id)));
+        if self
+            .mark_anchor
+            .is_some_and(|anchor| anchor >= self.items.len())
+        {
+            self.mark_anchor = None;
+        }
+    }
+
+    /// Take in lists and search results loaded in the background, keeping
+    /// only the answers to the latest requests
+    fn poll_loader(&mut self) -> Result<()> {
+        while let Some(loaded) = self.loader.try_recv() {
+            match loaded {
+                Loaded::Snapshot(id, result) if self.pending_refresh == Some(id) => {
+                    self.pending_refresh = None;
+                    match result {
+                        Ok(snapshot) => self.apply_snapshot(*snapshot),
+                        Err(e) => self.status_message = Some(format!("Loading failed: {}", e)),
+                    }
+                    if let Some((id, open)) = self.select_on_load.take() {
+                        self.select_item_with_id(id, open)?;
+                    }
+                }
+                Loaded::Search(id, result) if self.pending_search == Some(id) => {
+                    self.pending_search = None;
+                    self.search_state.results = result.unwrap_or_default();
+                    self.search_state.selected_index = 0;
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Select the item with `id` if the list has it, opening it with `open`
+    fn select_item_with_id(&mut self, id: i64, open: bool) -> Result<()> {
+        if let Some(index) = self.items.iter().position(|i| i.id == Some(id)) {
+            self.selected_item_index = index;
+            if open {
+                self.focus = Focus::ItemList;
+                self.view_selected()?;
+            }
+        }
         Ok(())
     }
 
-    pub fn run(mut self, mut terminal: DefaultTerminal) -> Result<()> {
+    /// Run until quit, returning the picked text in picker mode
+    pub fn run<W: Write>(
+        mut self,
+        mut terminal: Terminal<CrosstermBackend<W>>,
+    ) -> Result<Option<String>> {
         while !self.should_quit {
+            self.log_messages();
             terminal.draw(|frame| crate::ui::draw(frame, &mut self))?;
 
             // Check for LLM response from background task
-            self.poll_llm_response();
+            self.poll_loader()?;
+            self.poll_llm_response()?;
+            if self.ai_popup_state.retry_due() {
+                self.retry_ai_request();
+            }
+            self.poll_connection_test()?;
+            self.poll_version_summary()?;
+            self.poll_repo_fetch()?;
+            self.poll_url_fetch()?;
+            self.poll_gist_share();
+            self.poll_export_commits();
+            self.poll_export_watcher()?;
 
             // Tick loading spinner animation
             self.ai_popup_state.tick_loading();
@@ -170,37 +671,248 @@ impl App {
                     }
                 }
             }
+
+            if std::mem::take(&mut self.pending_external_edit) {
+                self.edit_content_externally(&mut terminal)?;
+            }
+            if std::mem::take(&mut self.pending_skeleton_edit) {
+                self.edit_skeleton_externally(&mut terminal)?;
+            }
+            if let Some(action) = self.pending_action_edit.take() {
+                self.edit_user_action_externally(&mut terminal, action)?;
+            }
         }
 
+        Ok(self.picked)
+    }
+
+    /// Copy newly shown status messages, edit and settings notices, and AI
+    /// errors into the message log
+    fn log_messages(&mut self) {
+        self.message_log.watch(0, self.status_message.as_ref());
+        self.message_log.watch(1, self.edit_state.notice.as_ref());
+        self.message_log
+            .watch(2, self.settings_state.notice.as_ref());
+        self.message_log
+            .watch(3, self.ai_popup_state.error.as_ref());
+    }
+
+    /// Suspend the TUI and edit the item's content in `$VISUAL` / `$EDITOR`
+    /// through a temp file, taking the result back when the editor exits
+    fn edit_content_externally<W: Write>(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+    ) -> Result<()> {
+        let original = self.edit_state.item.content.clone();
+        match edit_in_editor(terminal, &original)? {
+            Ok(content) => {
+                self.edit_state.focused_field = EditField::Content;
+                self.edit_state.cursor_pos =
+                    self.edit_state.cursor_pos.min(content.chars().count());
+                self.edit_state.replace_field(EditField::Content, content);
+            }
+            Err(e) => self.edit_state.notice = Some(format!("Editor failed: {}", e)),
+        }
         Ok(())
     }
 
-    fn poll_llm_response(&mut self) {
-        if let Some(ref receiver) = self.llm_receiver {
-            match receiver.try_recv() {
-                Ok(Ok(response)) => {
-                    self.ai_popup_state.result = Some(response.content);
-                    self.ai_popup_state.is_loading = false;
-                    self.llm_receiver = None;
+    /// Edit the skeleton of the category selected in settings in `$EDITOR`
+    fn edit_skeleton_externally<W: Write>(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+    ) -> Result<()> {
+        let category = self.settings_state.skeleton_category;
+        let original = self.settings_state.skeleton(category).to_string();
+        match edit_in_editor(terminal, &original)? {
+            Ok(content) => {
+                if content != original {
+                    self.settings_state.set_skeleton(content);
                 }
-                Ok(Err(error)) => {
-                    self.ai_popup_state.error = Some(error);
-                    self.ai_popup_state.is_loading = false;
-                    self.llm_receiver = None;
+            }
+            Err(e) => self.settings_state.notice = Some(format!("Editor failed: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Write a user AI action in `$EDITOR`. Emptying the file deletes it;
+    /// leaving it unchanged does nothing.
+    fn edit_user_action_externally<W: Write>(
+        &mut self,
+        terminal: &mut Terminal<CrosstermBackend<W>>,
+        action: UserAction,
+    ) -> Result<()> {
+        let original = action_document(&action);
+        let text = match edit_in_editor(terminal, &original)? {
+            Ok(text) => text,
+            Err(e) => {
+                self.ai_popup_state.error = Some(format!("Editor failed: {}", e));
+                return Ok(());
+            }
+        };
+        if text == original {
+            return Ok(());
+        }
+
+        let store = AiActionStore::new(&self.db.conn);
+        if text.trim().is_empty() {
+            if let Some(id) = action.id {
+                store.delete(id)?;
+                self.ai_popup_state.error = None;
+                self.edit_state.notice = Some(format!("Deleted AI action '{}'", action.label));
+                self.reload_user_actions(None)?;
+            }
+            return Ok(());
+        }
+
+        let saved = parse_action_document(&text, action.id)
+            .map_err(|e| eyre!(e))
+            .and_then(|edited| store.save(&edited).map(|id| (id, edited.label)));
+        match saved {
+            Ok((id, label)) => {
+                self.edit_state.notice = Some(format!("Saved AI action '{}'", label));
+                self.ai_popup_state.error = None;
+                self.reload_user_actions(Some(id))?;
+            }
+            Err(e) => self.ai_popup_state.error = Some(format!("Action not saved: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Read the user's AI actions, selecting the one with `selected_id`
+    fn reload_user_actions(&mut self, selected_id: Option<i64>) -> Result<()> {
+        let actions = AiActionStore::new(&self.db.conn).list()?;
+        self.ai_popup_state.set_user_actions(actions, selected_id);
+        Ok(())
+    }
+
+    fn poll_llm_response(&mut self) -> Result<()> {
+        let Some(ref receiver) = self.llm_receiver else {
+            return Ok(());
+        };
+
+        match receiver.try_recv() {
+            Ok(Ok(response)) => {
+                if let Some(usage) = response.usage {
+                    self.record_usage(usage)?;
                 }
-                Err(mpsc::TryRecvError::Empty) => {
-                    // Still waiting, continue
+                if let Some(user) = self.ai_popup_state.pending_message.take() {
+                    self.ai_popup_state.conversation.push(ChatTurn {
+                        user,
+                        assistant: response.content.clone(),
+                    });
                 }
-                Err(mpsc::TryRecvError::Disconnected) => {
-                    self.ai_popup_state.error = Some("LLM task failed unexpectedly".to_string());
-                    self.ai_popup_state.is_loading = false;
-                    self.llm_receiver = None;
+                let action = self.ai_popup_state.selected_action();
+                if self.ai_popup_state.handled_as(action).rewrites_content()
+                    && self.edit_state.focused_field != EditField::Description
+                {
+                    self.ai_popup_state.diff = Some(ResultDiff::new(
+                        &self.edit_state.item.content,
+                        &response.content,
+                    ));
                 }
+                self.ai_popup_state.result = Some(response.content);
+                self.ai_popup_state.is_loading = false;
+                self.llm_receiver = None;
+                self.llm_task = None;
+            }
+            Ok(Err(error)) => {
+                self.ai_popup_state.pending_message = None;
+                self.ai_popup_state.fail(error);
+                self.llm_receiver = None;
+                self.llm_task = None;
+            }
+            Err(mpsc::TryRecvError::Empty) => {
+                // Still waiting, continue
             }
+            Err(mpsc::TryRecvError::Disconnected) => {
+                self.ai_popup_state.error = Some("LLM task failed unexpectedly".to_string());
+                self.ai_popup_state.is_loading = false;
+                self.llm_receiver = None;
+                self.llm_task = None;
+            }
+        }
+        Ok(())
+    }
+
+    /// Check the settings with a minimal request in the background; the
+    /// result shows as a settings notice
+    fn start_connection_test(&mut self) {
+        if self.connection_test.is_some() {
+            return;
         }
+
+        let config = self.settings_state.llm_config();
+        self.settings_state.notice = Some(format!(
+            "Testing connection to {}...",
+            self.settings_state.provider.display_name()
+        ));
+
+        let (tx, rx) = mpsc::channel();
+        self.connection_test = Some(rx);
+        self.connection_test_config = Some(config.clone());
+        std::thread::spawn(move || {
+            let result = test_connection(&config).map_err(|e| format!("{:#}", e));
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll_connection_test(&mut self) -> Result<()> {
+        let Some(ref receiver) = self.connection_test else {
+            return Ok(());
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return Ok(()),
+            Err(mpsc::TryRecvError::Disconnected) => Err("test stopped unexpectedly".to_string()),
+        };
+        self.connection_test = None;
+        let Some(config) = self.connection_test_config.take() else {
+            return Ok(());
+        };
+
+        self.settings_state.notice = Some(match result {
+            Ok((response, elapsed)) => {
+                if let Some(usage) = response.usage {
+                    let cost = estimate_cost(&config.provider, &config.model, usage);
+                    let store = UsageStore::new(&self.db.conn);
+                    store.record(&config.provider, &config.model, usage, cost)?;
+                    self.settings_state.usage_totals = store.totals()?;
+                }
+                let model = match config.model.trim() {
+                    "" => LlmProvider::from_str(&config.provider).default_model(),
+                    model => model,
+                };
+                format!(
+                    "Connection OK: {} ({}) answered in {} ms",
+                    config.provider,
+                    model,
+                    elapsed.as_millis()
+                )
+            }
+            Err(error) => format!("Connection failed: {}", error),
+        });
+        Ok(())
+    }
+
+    /// Persist token usage of the finished call and show it in the AI popup
+    fn record_usage(&mut self, usage: Usage) -> Result<()> {
+        let Some(config) = self.llm_request_config.take() else {
+            return Ok(());
+        };
+
+        let cost = estimate_cost(&config.provider, &config.model, usage);
+        UsageStore::new(&self.db.conn).record(&config.provider, &config.model, usage, cost)?;
+        self.ai_popup_state.usage = Some((usage, cost));
+        Ok(())
     }
 
     fn handle_paste(&mut self, text: &str) -> Result<()> {
+        if let Some(ref mut form) = self.template_form {
+            form.insert_str(text);
+            return Ok(());
+        }
+
         // Handle pasted text based on current screen
         match self.screen {
             Screen::Settings => {
@@ -212,6 +924,11 @@ impl App {
             Screen::Search => {
                 self.search_state.insert_str(text);
             }
+            Screen::Main => {
+                if let Some(ref mut finder) = self.fuzzy_finder {
+                    finder.insert_str(text);
+                }
+            }
             _ => {}
         }
         Ok(())
@@ -222,24 +939,77 @@ impl App {
             return Ok(());
         }
 
-        // Clear status message on any key press
+        // Clear status message on any key press. Log around it so the same
+        // message shown again after this key is logged again.
+        self.log_messages();
         self.status_message = None;
+        self.log_messages();
 
         // Handle confirmation dialog first
         if self.confirm_dialog.is_some() {
             return self.handle_dialog_key(key);
         }
 
+        // Handle text prompt
+        if self.input_dialog.is_some() {
+            return self.handle_input_dialog_key(key);
+        }
+
+        // Handle vault switcher
+        if self.vault_picker.is_some() {
+            return self.handle_vault_picker_key(key);
+        }
+
+        // Handle fuzzy finder
+        if self.fuzzy_finder.is_some() {
+            return self.handle_fuzzy_finder_key(key);
+        }
+
+        // Handle template variables of an item being copied
+        if self.template_form.is_some() {
+            return self.handle_template_form_key(key);
+        }
+
+        // Handle link picker, opened from the edit screen
+        if self.link_picker.is_some() {
+            return self.handle_link_picker_key(key);
+        }
+
         // Handle AI popup
         if self.show_ai_popup {
             return self.handle_ai_popup_key(key);
         }
 
+        // Handle version diff, opened on top of the history popup
+        if self.version_diff.is_some() {
+            return self.handle_version_diff_key(key);
+        }
+
         // Handle history popup
         if self.show_history_popup {
             return self.handle_history_popup_key(key);
         }
 
+        // Handle export target popup
+        if self.show_export_popup {
+            return self.handle_export_popup_key(key);
+        }
+
+        // Handle export overwrite confirmation
+        if self.export_diff.is_some() {
+            return self.handle_export_diff_key(key);
+        }
+
+        // Handle imported items clashing with the library's
+        if self.import_conflicts.is_some() {
+            return self.handle_import_conflict_key(key);
+        }
+
+        // Handle two-way sync with the export path
+        if self.sync_state.is_some() {
+            return self.handle_sync_key(key);
+        }
+
         // Check for pending vim sequences
         if let Some(pending) = self.pending_key.take() {
             return self.handle_vim_sequence(pending, key.code);
@@ -252,15 +1022,23 @@ impl App {
             Screen::Search => self.handle_search_key(key)?,
             Screen::Settings => self.handle_settings_key(key)?,
             Screen::Help => self.handle_help_key(key)?,
+            Screen::Messages => self.handle_messages_key(key),
         }
 
         Ok(())
     }
 
     fn handle_main_key(&mut self, key: KeyEvent) -> Result<()> {
+        if self.type_ahead.is_some() {
+            return self.handle_type_ahead_key(key);
+        }
+
         match key.code {
+            KeyCode::Char('q') if self.llm_receiver.is_some() => {
+                self.confirm_dialog = Some(ConfirmDialog::quit_during_ai());
+            }
             KeyCode::Char('q') => self.should_quit = true,
-            KeyCode::Char('j') | KeyCode::Down => self.move_down(),
+            KeyCode::Char('j') | KeyCode::Down => self.move_down()?,
             KeyCode::Char('k') | KeyCode::Up => self.move_up(),
             KeyCode::Char('h') | KeyCode::Left => {
                 self.focus = Focus::Sidebar;
@@ -268,39 +1046,100 @@ impl App {
             }
             KeyCode::Char('l') | KeyCode::Right => self.focus = Focus::ItemList,
 
-            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => self.page_down(),
+            KeyCode::Char('d') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.page_down()?
+            }
+            KeyCode::Char('p') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.fuzzy_finder = Some(FuzzyFinderState::new(
+                    ItemStore::new(&self.db.conn).list_all()?,
+                ));
+            }
             KeyCode::Char('u') if key.modifiers.contains(KeyModifiers::CONTROL) => self.page_up(),
 
             KeyCode::Char('g') => self.pending_key = Some('g'),
             KeyCode::Char('d') => self.pending_key = Some('d'),
             KeyCode::Char('y') => self.pending_key = Some('y'),
-            KeyCode::Char('G') => self.go_to_bottom(),
+            KeyCode::Char('G') => self.go_to_bottom()?,
 
             KeyCode::Enter => {
                 if self.focus == Focus::Sidebar {
                     self.handle_sidebar_selection()?;
+                } else if self.special_list == Some(SpecialList::Copies) {
+                    self.copy_selected()?;
                 } else {
                     self.view_selected()?;
                 }
             }
             KeyCode::Char('e') => self.edit_selected()?,
             KeyCode::Char('n') => self.new_item()?,
+            KeyCode::Char('N') => self.new_item_with_ai()?,
+            KeyCode::Char('P') => self.new_item_from_clipboard(),
             KeyCode::Char('c') => self.copy_selected()?,
             KeyCode::Char('/') => self.open_search()?,
             KeyCode::Char('s') => self.open_settings()?,
             KeyCode::Char('x') => self.export_selected()?,
+            KeyCode::Char('X') => self.confirm_export_all()?,
+            KeyCode::Char('I') => self.import_from_export_path()?,
+            KeyCode::Char('D') => self.open_sync()?,
+            KeyCode::Char('R') => self.input_dialog = Some(InputDialog::import_repo()),
+            KeyCode::Char('U') => self.input_dialog = Some(InputDialog::import_url()),
+            KeyCode::Char('S') if !self.in_trash() => self.share_selected(),
+            KeyCode::Char('B') if !self.in_trash() => self.prompt_export_bundle(),
+            KeyCode::Char('O') => self.input_dialog = Some(InputDialog::import_bundle()),
+            KeyCode::Char('V') => self.open_vault_picker()?,
+            KeyCode::Char('p') => {
+                self.show_preview = !self.show_preview;
+                SettingsStore::new(&self.db.conn).set(
+                    "main_show_preview",
+                    if self.show_preview { "true" } else { "false" },
+                )?;
+            }
             KeyCode::Char('?') => self.screen = Screen::Help,
+            KeyCode::Char('M') => {
+                self.message_log.scroll_to_end();
+                self.screen = Screen::Messages;
+            }
+            KeyCode::Char('o') => self.cycle_sort_order()?,
+            KeyCode::Char('<') => self.resize_sidebar(-2)?,
+            KeyCode::Char('>') => self.resize_sidebar(2)?,
 
-            KeyCode::Char('1') => self.select_category(Some(Category::Prompt))?,
-            KeyCode::Char('2') => self.select_category(Some(Category::Agent))?,
-            KeyCode::Char('3') => self.select_category(Some(Category::Skill))?,
-            KeyCode::Char('4') => self.select_category(Some(Category::Command))?,
-            KeyCode::Char('0') => self.select_category(None)?,
+            KeyCode::Char('1') => self.select_category(Some(Category::Prompt)),
+            KeyCode::Char('2') => self.select_category(Some(Category::Agent)),
+            KeyCode::Char('3') => self.select_category(Some(Category::Skill)),
+            KeyCode::Char('4') => self.select_category(Some(Category::Command)),
+            KeyCode::Char('0') => self.select_category(None),
+
+            KeyCode::Char('f') => self.toggle_favorite()?,
+            KeyCode::Char('A') if !self.in_trash() => self.toggle_archived()?,
+            KeyCode::Char(' ') if self.focus == Focus::ItemList => self.toggle_mark()?,
+            KeyCode::Char('v') if self.focus == Focus::ItemList => self.toggle_range_mark(),
+            KeyCode::Char('\'') if self.focus == Focus::ItemList => {
+                self.type_ahead = Some(String::new());
+            }
+            KeyCode::Char('t') if !self.in_trash() => self.prompt_edit_tags(),
+            KeyCode::Char('C') if !self.in_trash() => self.prompt_collections(),
+            KeyCode::Char('r') if self.focused_collection().is_some() => {
+                self.rename_focused_collection()
+            }
+            KeyCode::Char('r') if self.focused_tag().is_some() => self.rename_focused_tag(),
+            KeyCode::Char(' ') if self.focused_tag().is_some() => self.toggle_focused_tag()?,
+            KeyCode::Char('a') if !self.selected_tags.is_empty() => self.toggle_tag_match()?,
+            KeyCode::Char('u') if self.in_trash() => self.restore_selected()?,
+            KeyCode::Char('u') => self.undo_delete()?,
+            KeyCode::Char('E') if self.in_trash() => self.confirm_empty_trash(),
+            KeyCode::Char('K') if self.special_list == Some(SpecialList::Stale) => {
+                self.keep_stale()?
+            }
 
+            KeyCode::Esc if !self.marked.is_empty() || self.mark_anchor.is_some() => {
+                self.clear_marks();
+            }
             KeyCode::Esc => {
                 self.selected_category = None;
-                self.selected_tag = None;
-                self.refresh_data()?;
+                self.selected_tags.clear();
+                self.special_list = None;
+                self.selected_collection = None;
+                self.request_refresh();
             }
 
             _ => {}
@@ -309,23 +1148,78 @@ impl App {
         Ok(())
     }
 
+    /// Keys while typing a name prefix: letters extend it and jump, Enter
+    /// opens the item, Esc stops
+    fn handle_type_ahead_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(mut query) = self.type_ahead.take() else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc => return Ok(()),
+            KeyCode::Enter => return self.view_selected(),
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char(c) if !key.modifiers.contains(KeyModifiers::CONTROL) => query.push(c),
+            _ => {}
+        }
+
+        // The whole list has to be loaded to find names further down
+        while self.has_more_items {
+            self.load_more_items()?;
+        }
+        let prefix = query.to_lowercase();
+        if let Some(index) = self
+            .items
+            .iter()
+            .position(|item| item.name.to_lowercase().starts_with(&prefix))
+        {
+            self.selected_item_index = index;
+        }
+        self.type_ahead = Some(query);
+        Ok(())
+    }
+
     fn handle_sidebar_selection(&mut self) -> Result<()> {
         if self.sidebar_index == 0 {
             // Recent Items
             self.selected_category = None;
-            self.selected_tag = None;
-            self.refresh_data()?;
-        } else if self.sidebar_index <= 4 {
-            // Category selection (indices 1-4)
-            let category = Category::all()[self.sidebar_index - 1];
-            self.select_category(Some(category))?;
+            self.selected_tags.clear();
+            self.special_list = None;
+            self.selected_collection = None;
+            self.request_refresh();
+        } else if self.sidebar_index == SIDEBAR_FAVORITES_INDEX {
+            self.select_special_list(SpecialList::Favorites);
+        } else if self.sidebar_index == SIDEBAR_COPIES_INDEX {
+            self.select_special_list(SpecialList::Copies);
+        } else if self.sidebar_index < SIDEBAR_ARCHIVED_INDEX {
+            // Category selection
+            let category = Category::all()[self.sidebar_index - SIDEBAR_CATEGORIES_START];
+            self.select_category(Some(category));
+        } else if self.sidebar_index == SIDEBAR_ARCHIVED_INDEX {
+            self.select_special_list(SpecialList::Archived);
+        } else if self.sidebar_index == SIDEBAR_STALE_INDEX {
+            self.select_special_list(SpecialList::Stale);
+        } else if self.sidebar_index == SIDEBAR_TRASH_INDEX {
+            self.select_special_list(SpecialList::Trash);
+        } else if let Some((path, _)) = self.focused_collection() {
+            self.selected_category = None;
+            self.selected_tags.clear();
+            self.special_list = None;
+            self.selected_collection = Some(path);
+            self.selected_item_index = 0;
+            self.request_refresh();
         } else {
-            // Tag selection (indices 5+)
-            let tag_index = self.sidebar_index - 5;
+            // Tag selection; a tag already in a multi-tag filter keeps it
+            let tag_index = self.sidebar_index - self.tags_start();
             if let Some((tag, _)) = self.tags.get(tag_index) {
-                self.selected_tag = Some(tag.clone());
+                if !self.selected_tags.contains(tag) {
+                    self.selected_tags = vec![tag.clone()];
+                }
                 self.selected_category = None;
-                self.refresh_data()?;
+                self.special_list = None;
+                self.selected_collection = None;
+                self.request_refresh();
             }
         }
         Ok(())
@@ -336,22 +1230,55 @@ impl App {
             ('g', KeyCode::Char('g')) => self.go_to_top(),
             ('d', KeyCode::Char('d')) => self.delete_selected()?,
             ('y', KeyCode::Char('y')) => self.copy_selected()?,
+            ('y', KeyCode::Char('c')) => self.copy_selected_exported()?,
+            ('y', KeyCode::Char('n')) => self.copy_selected_name(),
+            ('y', KeyCode::Char('p')) => self.copy_selected_export_path()?,
+            ('y', KeyCode::Char('j')) => self.copy_selected_as(DataFormat::Json)?,
+            ('y', KeyCode::Char('a')) => self.copy_selected_as(DataFormat::Yaml)?,
             _ => {}
         }
         Ok(())
     }
 
     fn handle_view_key(&mut self, key: KeyEvent) -> Result<()> {
+        // Typing a `/` query
+        if let Some(ref mut input) = self.view_state.find_input {
+            match key.code {
+                KeyCode::Esc => self.view_state.find_input = None,
+                KeyCode::Enter => {
+                    if let Some(query) = self.view_state.find_input.take() {
+                        if query.is_empty() {
+                            self.view_state.clear_find();
+                        } else {
+                            self.view_state.find(query);
+                        }
+                    }
+                }
+                KeyCode::Char(c) => input.push(c),
+                KeyCode::Backspace => {
+                    input.pop();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
         match key.code {
+            KeyCode::Char('/') => self.view_state.find_input = Some(String::new()),
+            KeyCode::Char('n') => self.view_state.find_step(true),
+            KeyCode::Char('N') => self.view_state.find_step(false),
+            KeyCode::Esc if !self.view_state.find_query.is_empty() => {
+                self.view_state.clear_find();
+            }
             KeyCode::Esc | KeyCode::Char('q') => {
                 // Reset viewing version when leaving view screen
                 self.view_state.viewing_version = None;
                 self.screen = Screen::Main;
             }
-            KeyCode::Char('j') | KeyCode::Down => {
-                if self.view_state.scroll < self.view_state.max_scroll {
-                    self.view_state.scroll += 1;
-                }
+            KeyCode::Char('j') | KeyCode::Down
+                if self.view_state.scroll < self.view_state.max_scroll =>
+            {
+                self.view_state.scroll += 1;
             }
             KeyCode::Char('k') | KeyCode::Up => {
                 self.view_state.scroll = self.view_state.scroll.saturating_sub(1);
@@ -363,6 +1290,17 @@ impl App {
             KeyCode::Char('x') => self.export_selected()?,
             KeyCode::Char('h') => self.open_history_popup()?,
             KeyCode::Char('L') => self.go_to_latest_version()?,
+            KeyCode::Char('f') => self.toggle_favorite()?,
+            KeyCode::Char('A') if !self.in_trash() => self.toggle_archived()?,
+            KeyCode::Char('m') => {
+                let render = !self.view_state.render_markdown;
+                self.view_state.render_markdown = render;
+                self.view_state.scroll = 0;
+                SettingsStore::new(&self.db.conn).set(
+                    "view_render_markdown",
+                    if render { "true" } else { "false" },
+                )?;
+            }
             KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 // Load current item into edit_state for AI to work with
                 if let Some(item) = self.selected_item().cloned() {
@@ -377,15 +1315,59 @@ impl App {
         Ok(())
     }
 
-    fn handle_edit_key(&mut self, key: KeyEvent) -> Result<()> {
-        // Handle category dropdown if open
-        if self.edit_state.show_category_dropdown {
+    /// Vim keys in the content field. Returns false for keys left to the
+    /// regular edit handling: typing in insert mode, Tab, Ctrl shortcuts,
+    /// and Esc in normal mode with nothing pending.
+    fn handle_vim_key(&mut self, key: KeyEvent) -> bool {
+        let state = &mut self.edit_state;
+        if key.modifiers.contains(KeyModifiers::CONTROL) {
+            if state.vim.mode != VimMode::Insert && key.code == KeyCode::Char('r') {
+                state.redo();
+                return true;
+            }
+            return false;
+        }
+
+        match state.vim.mode {
+            VimMode::Insert if key.code == KeyCode::Esc => state.vim_leave_insert(),
+            VimMode::Insert => return false,
+            VimMode::VisualLine { anchor } => state.vim_visual_key(key.code, anchor),
+            VimMode::Normal => match key.code {
+                KeyCode::Tab | KeyCode::BackTab => return false,
+                KeyCode::Esc if state.vim.pending.is_empty() => return false,
+                code => state.vim_normal_key(code),
+            },
+        }
+        true
+    }
+
+    fn handle_edit_key(&mut self, key: KeyEvent) -> Result<()> {
+        self.edit_state.notice = None;
+
+        // Handle the change message prompt if open
+        if let Some(ref mut message) = self.edit_state.version_message {
+            match key.code {
+                KeyCode::Esc => self.edit_state.version_message = None,
+                KeyCode::Enter => self.save_item()?,
+                KeyCode::Char(c) => message.push(c),
+                KeyCode::Backspace => {
+                    message.pop();
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        // Handle category dropdown if open
+        if self.edit_state.show_category_dropdown {
             match key.code {
                 KeyCode::Esc => {
                     self.edit_state.show_category_dropdown = false;
                 }
                 KeyCode::Enter | KeyCode::Char(' ') => {
+                    let previous = self.edit_state.item.category;
                     self.edit_state.select_category_from_dropdown();
+                    self.swap_skeleton(previous);
                 }
                 KeyCode::Char('j') | KeyCode::Down => {
                     self.edit_state.dropdown_next();
@@ -398,6 +1380,13 @@ impl App {
             return Ok(());
         }
 
+        if self.settings_state.vim_mode
+            && self.edit_state.focused_field == EditField::Content
+            && self.handle_vim_key(key)
+        {
+            return Ok(());
+        }
+
         match key.code {
             KeyCode::Esc => {
                 if self.edit_state.has_changes {
@@ -409,14 +1398,28 @@ impl App {
             KeyCode::Tab => self.edit_state.next_field(),
             KeyCode::BackTab => self.edit_state.prev_field(),
             KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                self.save_item()?;
+                // Ask for a change message when saving a new version
+                if !self.edit_state.is_new && self.edit_state.item.validate().is_ok() {
+                    self.edit_state.version_message = Some(String::new());
+                } else {
+                    self.save_item()?;
+                }
+            }
+            KeyCode::Char('l') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.open_link_picker()?;
+            }
+            KeyCode::Char('e') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.pending_external_edit = true;
+            }
+            KeyCode::Char('z') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.edit_state.undo();
+            }
+            KeyCode::Char('y') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.edit_state.redo();
             }
             KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
-                if self.edit_state.focused_field == EditField::Content
-                    || self.edit_state.focused_field == EditField::Description
-                {
-                    self.show_ai_popup = true;
-                }
+                // AI assist only works on the multiline text fields
+                self.show_ai_popup = self.edit_state.focused_field.is_multiline();
             }
             KeyCode::Char(' ') | KeyCode::Enter => {
                 if self.edit_state.focused_field == EditField::Category {
@@ -430,12 +1433,12 @@ impl App {
                     } else {
                         ' '
                     });
+                } else if key.code == KeyCode::Char(' ') {
+                    self.edit_state.insert_char(' ');
                 }
             }
-            KeyCode::Char(c) => {
-                if self.edit_state.focused_field != EditField::Category {
-                    self.edit_state.insert_char(c);
-                }
+            KeyCode::Char(c) if self.edit_state.focused_field != EditField::Category => {
+                self.edit_state.insert_char(c);
             }
             KeyCode::Backspace => self.edit_state.delete_char(),
             KeyCode::Delete => self.edit_state.delete_char_forward(),
@@ -443,10 +1446,7 @@ impl App {
             KeyCode::Right => self.edit_state.move_cursor_right(),
             KeyCode::Up => {
                 // For multiline fields, move cursor up; for others, go to previous field
-                if matches!(
-                    self.edit_state.focused_field,
-                    EditField::Content | EditField::Description
-                ) {
+                if self.edit_state.focused_field.is_multiline() {
                     self.edit_state.move_cursor_up();
                 } else {
                     self.edit_state.prev_field();
@@ -454,10 +1454,7 @@ impl App {
             }
             KeyCode::Down => {
                 // For multiline fields, move cursor down; for others, go to next field
-                if matches!(
-                    self.edit_state.focused_field,
-                    EditField::Content | EditField::Description
-                ) {
+                if self.edit_state.focused_field.is_multiline() {
                     self.edit_state.move_cursor_down();
                 } else {
                     self.edit_state.next_field();
@@ -478,28 +1475,45 @@ impl App {
             }
             KeyCode::Enter => {
                 if let Some(item) = self.search_state.selected_item().cloned() {
-                    // Find item in main list or add it
-                    if let Some(idx) = self.items.iter().position(|i| i.id == item.id) {
-                        self.selected_item_index = idx;
+                    if self.pick.is_some() {
+                        return self.pick_item(item);
+                    }
+                    // Archived results live in the Archived list
+                    let listed = self.items.iter().any(|i| i.id == item.id);
+                    match item.id {
+                        Some(id) if !listed && self.archived.contains(&id) => {
+                            self.sidebar_index = SIDEBAR_ARCHIVED_INDEX;
+                            self.select_special_list(SpecialList::Archived);
+                            self.select_on_load = Some((id, false));
+                        }
+                        Some(id) => self.select_item_with_id(id, false)?,
+                        None => {}
                     }
                     self.screen = Screen::Main;
                     self.search_state.clear();
                 }
             }
+            KeyCode::Char('a') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.search_state.include_archived = !self.search_state.include_archived;
+                self.perform_search();
+            }
+            KeyCode::Char('f') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.toggle_search_scope()?;
+            }
             KeyCode::Char('j') | KeyCode::Down => self.search_state.select_next(),
             KeyCode::Char('k') | KeyCode::Up => self.search_state.select_prev(),
             KeyCode::Char('c') => {
                 if let Some(item) = self.search_state.selected_item().cloned() {
-                    self.copy_content(&item.content);
+                    self.copy_item_content(item)?;
                 }
             }
             KeyCode::Char(c) => {
                 self.search_state.insert_char(c);
-                self.perform_search()?;
+                self.perform_search();
             }
             KeyCode::Backspace => {
                 self.search_state.delete_char();
-                self.perform_search()?;
+                self.perform_search();
             }
             KeyCode::Left => self.search_state.move_cursor_left(),
             KeyCode::Right => self.search_state.move_cursor_right(),
@@ -542,24 +1556,63 @@ impl App {
             KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
                 self.save_settings()?;
             }
-            KeyCode::Enter | KeyCode::Char(' ') => {
-                if self.settings_state.focused_field == SettingsField::Provider {
-                    self.settings_state.open_provider_dropdown();
-                }
+            KeyCode::Char('b') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.prompt_backup()?;
             }
-            KeyCode::Char(c) => self.settings_state.insert_char(c),
-            KeyCode::Backspace => self.settings_state.delete_char(),
-            KeyCode::Left => {
-                if self.settings_state.cursor_pos > 0 {
-                    self.settings_state.cursor_pos -= 1;
-                }
+            KeyCode::Char('r') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.prompt_restore()?;
             }
-            KeyCode::Right => {
-                let len = self.settings_state.current_field_value().chars().count();
-                if self.settings_state.cursor_pos < len {
-                    self.settings_state.cursor_pos += 1;
-                }
+            KeyCode::Char('t') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.start_connection_test();
+            }
+            KeyCode::Enter | KeyCode::Char(' ')
+                if self.settings_state.focused_field == SettingsField::Provider =>
+            {
+                self.settings_state.open_provider_dropdown();
+            }
+            KeyCode::Enter | KeyCode::Char(' ')
+                if self.settings_state.focused_field == SettingsField::AutoExport =>
+            {
+                self.settings_state.toggle_auto_export();
+            }
+            KeyCode::Enter | KeyCode::Char(' ')
+                if self.settings_state.focused_field == SettingsField::GitCommit =>
+            {
+                self.settings_state.toggle_git_commit();
+            }
+            KeyCode::Enter | KeyCode::Char(' ')
+                if self.settings_state.focused_field == SettingsField::VimMode =>
+            {
+                self.settings_state.toggle_vim_mode();
+            }
+            KeyCode::Enter | KeyCode::Char('e')
+                if self.settings_state.focused_field == SettingsField::Skeleton =>
+            {
+                self.pending_skeleton_edit = true;
             }
+            KeyCode::Up | KeyCode::Down
+                if self.settings_state.focused_field == SettingsField::FrontmatterKeys =>
+            {
+                self.settings_state
+                    .cycle_frontmatter_target(key.code == KeyCode::Down);
+            }
+            KeyCode::Left | KeyCode::Right
+                if self.settings_state.focused_field == SettingsField::Skeleton =>
+            {
+                self.settings_state
+                    .cycle_skeleton_category(key.code == KeyCode::Right);
+            }
+            KeyCode::Enter => {}
+            // Space only types into the fields whose format has spaces in it
+            KeyCode::Char(' ')
+                if !matches!(
+                    self.settings_state.focused_field,
+                    SettingsField::ExportProfiles | SettingsField::Variables
+                ) => {}
+            KeyCode::Char(c) => self.settings_state.insert_char(c),
+            KeyCode::Backspace => self.settings_state.delete_char(),
+            KeyCode::Left => self.settings_state.move_cursor_left(),
+            KeyCode::Right => self.settings_state.move_cursor_right(),
             _ => {}
         }
         Ok(())
@@ -575,6 +1628,15 @@ impl App {
         Ok(())
     }
 
+    fn handle_messages_key(&mut self, key: KeyEvent) {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') | KeyCode::Char('M') => self.screen = Screen::Main,
+            KeyCode::Char('j') | KeyCode::Down => self.message_log.scroll_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.message_log.scroll_up(),
+            _ => {}
+        }
+    }
+
     fn handle_dialog_key(&mut self, key: KeyEvent) -> Result<()> {
         if let Some(ref mut dialog) = self.confirm_dialog {
             match key.code {
@@ -587,24 +1649,73 @@ impl App {
                 }
                 KeyCode::Enter => {
                     let confirmed = dialog.selected;
-                    let title = dialog.title.clone();
+                    let action = dialog.action;
                     self.confirm_dialog = None;
 
-                    if confirmed {
-                        if title.contains("Delete") {
-                            self.perform_delete()?;
-                        } else if title.contains("Unsaved") {
-                            // Discard changes
-                            match self.screen {
-                                Screen::Edit => self.screen = Screen::Main,
-                                Screen::Settings => self.screen = Screen::Main,
-                                _ => {}
+                    match action {
+                        ConfirmAction::Overwrite => {
+                            if let Some((target, dest)) = self.pending_export_to.take() {
+                                if confirmed {
+                                    self.write_export_to_path(target, &dest);
+                                }
+                            } else if let Some(path) = self.pending_zip.take() {
+                                if confirmed {
+                                    self.write_export_zip(&path);
+                                }
+                            }
+                        }
+                        ConfirmAction::RestoreDatabase => {
+                            if let Some(path) = self.pending_restore.take() {
+                                if confirmed {
+                                    self.perform_restore(&path)?;
+                                }
+                            }
+                        }
+                        ConfirmAction::ReplaceEdits => {
+                            if let Some(markdown) = self.pending_generated.take() {
+                                if confirmed {
+                                    self.open_generated_item(&markdown);
+                                } else {
+                                    // Back to the result, to keep or refine
+                                    self.ai_popup_state.result = Some(markdown);
+                                }
+                            }
+                        }
+                        ConfirmAction::ExportSkills => {
+                            if let Some((target, profile, skills)) = self.pending_skills.take() {
+                                if confirmed {
+                                    self.export_skills(target, profile, &skills)?;
+                                }
                             }
                         }
+                        // The rest only act on confirm
+                        _ if !confirmed => {}
+                        ConfirmAction::EmptyTrash => self.perform_empty_trash()?,
+                        ConfirmAction::DeleteTag => self.perform_delete_tag()?,
+                        ConfirmAction::DeleteCollection => self.perform_delete_collection()?,
+                        ConfirmAction::Delete => self.perform_delete()?,
+                        ConfirmAction::ExportAll => self.perform_export_all()?,
+                        ConfirmAction::Share => self.perform_share(),
+                        ConfirmAction::Quit => {
+                            self.cancel_llm_request();
+                            self.should_quit = true;
+                        }
+                        ConfirmAction::DiscardChanges => match self.screen {
+                            Screen::Edit => self.screen = Screen::Main,
+                            Screen::Settings => self.screen = Screen::Main,
+                            _ => {}
+                        },
                     }
                 }
                 KeyCode::Esc | KeyCode::Char('q') => {
                     self.confirm_dialog = None;
+                    self.pending_skills = None;
+                    self.pending_export_to = None;
+                    self.pending_zip = None;
+                    self.pending_restore = None;
+                    if let Some(markdown) = self.pending_generated.take() {
+                        self.ai_popup_state.result = Some(markdown);
+                    }
                 }
                 _ => {}
             }
@@ -612,41 +1723,139 @@ impl App {
         Ok(())
     }
 
+    fn handle_input_dialog_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut dialog) = self.input_dialog else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc => self.input_dialog = None,
+            KeyCode::Enter => {
+                if let Some(dialog) = self.input_dialog.take() {
+                    let value = &dialog.value;
+                    match dialog.action {
+                        InputAction::RenameTag => {
+                            self.perform_rename_tag(&dialog.subject, value)?
+                        }
+                        InputAction::EditTags => self.perform_edit_tags(value)?,
+                        InputAction::RenameCollection => {
+                            self.perform_rename_collection(&dialog.subject, value)?
+                        }
+                        InputAction::NewCollection => self.perform_new_collection(value)?,
+                        InputAction::EditCollections => self.perform_edit_collections(value)?,
+                        InputAction::NewVault => self.create_vault(value.trim())?,
+                        InputAction::ImportRepo => self.start_repo_import(value),
+                        InputAction::ImportUrl => self.start_url_import(value),
+                        InputAction::ExportBundle => self.perform_export_bundle(value),
+                        InputAction::ExportZip => self.perform_export_zip(value),
+                        InputAction::ExportToPath => {
+                            self.perform_export_to_path(&dialog.subject, value)
+                        }
+                        InputAction::ImportBundle => self.open_bundle(value)?,
+                        InputAction::BackupDatabase => self.perform_backup(value),
+                        InputAction::RestoreDatabase => self.confirm_restore(value),
+                    }
+                }
+            }
+            KeyCode::Char(c) => dialog.value.push(c),
+            KeyCode::Backspace => {
+                dialog.value.pop();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
     fn handle_ai_popup_key(&mut self, key: KeyEvent) -> Result<()> {
+        if self.ai_popup_state.refining {
+            match key.code {
+                KeyCode::Esc | KeyCode::Tab => self.ai_popup_state.refining = false,
+                KeyCode::Enter if !self.ai_popup_state.custom_input.trim().is_empty() => {
+                    self.run_ai_completion()?;
+                }
+                KeyCode::Char(c) => self.ai_popup_state.insert_char(c),
+                KeyCode::Backspace => self.ai_popup_state.delete_char(),
+                _ => {}
+            }
+            return Ok(());
+        }
+
+        if let Some(ref mut diff) = self.ai_popup_state.diff {
+            match key.code {
+                KeyCode::Esc => {
+                    self.cancel_llm_request();
+                    self.show_ai_popup = false;
+                    self.ai_popup_state.clear();
+                }
+                KeyCode::Char('j') | KeyCode::Down => diff.select_next(),
+                KeyCode::Char('k') | KeyCode::Up => diff.select_prev(),
+                KeyCode::Char('y') => diff.decide(true),
+                KeyCode::Char('n') => diff.decide(false),
+                KeyCode::Char(' ') => diff.toggle(),
+                KeyCode::Tab => self.ai_popup_state.start_refining(),
+                KeyCode::Enter => {
+                    let merged = diff.merged();
+                    self.apply_ai_result(self.ai_popup_state.selected_action(), merged);
+                }
+                _ => {}
+            }
+            return Ok(());
+        }
+
         match key.code {
+            KeyCode::Esc if self.ai_popup_state.retry_at.is_some() => {
+                self.ai_popup_state.retry_at = None;
+            }
             KeyCode::Esc => {
+                // Closing the popup drops a request still running
+                self.cancel_llm_request();
                 self.show_ai_popup = false;
                 self.ai_popup_state.clear();
             }
-            KeyCode::Char('j') | KeyCode::Down => self.ai_popup_state.select_next(),
-            KeyCode::Char('k') | KeyCode::Up => self.ai_popup_state.select_prev(),
+            KeyCode::Char('r')
+                if self.ai_popup_state.retry_delay.is_some()
+                    && !self.ai_popup_state.needs_input() =>
+            {
+                self.retry_ai_request();
+            }
+            KeyCode::Enter if self.ai_popup_state.retry_delay.is_some() => {
+                self.ai_popup_state.schedule_retry();
+            }
+            KeyCode::Tab if self.ai_popup_state.result.is_some() => {
+                self.ai_popup_state.start_refining();
+            }
+            KeyCode::Down => self.ai_popup_state.select_next(),
+            KeyCode::Up => self.ai_popup_state.select_prev(),
+            KeyCode::Char('j') if !self.ai_popup_state.needs_input() => {
+                self.ai_popup_state.select_next()
+            }
+            KeyCode::Char('k') if !self.ai_popup_state.needs_input() => {
+                self.ai_popup_state.select_prev()
+            }
+            KeyCode::Char('n')
+                if !self.ai_popup_state.needs_input() && self.ai_popup_state.result.is_none() =>
+            {
+                self.pending_action_edit = Some(UserAction::default());
+            }
+            KeyCode::Char('e')
+                if matches!(self.ai_popup_state.selected_action(), AiAction::User(_))
+                    && self.ai_popup_state.result.is_none() =>
+            {
+                let action = self.ai_popup_state.selected_action();
+                self.pending_action_edit = self.ai_popup_state.user_action(action).cloned();
+            }
             KeyCode::Enter => {
-                if self.ai_popup_state.result.is_some() {
-                    // Apply the result
-                    if let Some(result) = self.ai_popup_state.result.take() {
-                        // AI popup is primarily for content improvement
-                        // Only apply to description if explicitly focused there
-                        if self.edit_state.focused_field == EditField::Description {
-                            self.edit_state.item.description = Some(result);
-                        } else {
-                            // Default to updating content
-                            self.edit_state.item.content = result;
-                        }
-                        self.edit_state.has_changes = true;
-                    }
-                    self.show_ai_popup = false;
-                    self.ai_popup_state.clear();
-                    // After applying AI result, transition to Edit screen to review
-                    self.screen = Screen::Edit;
+                if let Some(result) = self.ai_popup_state.result.take() {
+                    self.apply_ai_result(self.ai_popup_state.selected_action(), result);
                 } else {
                     // Run AI completion
                     self.run_ai_completion()?;
                 }
             }
-            KeyCode::Char(c) if self.ai_popup_state.is_custom() => {
+            KeyCode::Char(c) if self.ai_popup_state.needs_input() => {
                 self.ai_popup_state.insert_char(c);
             }
-            KeyCode::Backspace if self.ai_popup_state.is_custom() => {
+            KeyCode::Backspace if self.ai_popup_state.needs_input() => {
                 self.ai_popup_state.delete_char();
             }
             _ => {}
@@ -654,63 +1863,257 @@ impl App {
         Ok(())
     }
 
+    /// Apply an accepted AI result to the item being edited, then switch to
+    /// the edit screen to review it
+    fn apply_ai_result(&mut self, action: AiAction, result: String) {
+        match self.ai_popup_state.handled_as(action) {
+            AiAction::GenerateItem => return self.apply_generated_item(&result),
+            AiAction::SuggestTags => {
+                let tags = merge_tags(
+                    self.edit_state.item.tags.as_deref().unwrap_or_default(),
+                    &result,
+                );
+                self.edit_state.replace_field(EditField::Tags, tags);
+            }
+            AiAction::GenerateDescription => {
+                let line = result
+                    .lines()
+                    .find(|l| !l.trim().is_empty())
+                    .unwrap_or_default();
+                self.edit_state.replace_field(
+                    EditField::Description,
+                    line.trim().trim_matches('"').trim().to_string(),
+                );
+            }
+            AiAction::ConvertTo(category) => {
+                let draft = import::draft_from_markdown(category, &result);
+                let item = &mut self.edit_state.item;
+                item.category = category;
+                item.content = draft.content;
+                item.description = draft.description.or(item.description.take());
+                item.tools = draft.tools;
+                item.model = draft.model;
+                item.allowed_tools = draft.allowed_tools;
+                item.argument_hint = draft.argument_hint;
+                item.permission_mode = draft.permission_mode;
+                item.skills = draft.skills;
+            }
+            // AI popup is primarily for content improvement
+            // Only apply to description if explicitly focused there
+            _ if self.edit_state.focused_field == EditField::Description => {
+                self.edit_state
+                    .replace_field(EditField::Description, result);
+            }
+            // Default to updating content
+            _ => self.edit_state.replace_field(EditField::Content, result),
+        }
+
+        self.edit_state.has_changes = true;
+        self.show_ai_popup = false;
+        self.ai_popup_state.clear();
+        self.screen = Screen::Edit;
+    }
+
+    /// Open a generated item as a new, unsaved item in the edit screen
+    /// Open a generated item in the editor, first asking when that would
+    /// throw away unsaved edits
+    fn apply_generated_item(&mut self, markdown: &str) {
+        if self.edit_state.has_changes {
+            self.pending_generated = Some(markdown.to_string());
+            self.confirm_dialog = Some(ConfirmDialog::replace_edits());
+            return;
+        }
+        self.open_generated_item(markdown);
+    }
+
+    fn open_generated_item(&mut self, markdown: &str) {
+        let category = self.selected_category.unwrap_or(Category::Prompt);
+        let item = import::draft_from_markdown(category, markdown);
+
+        let mut edit_state = EditState::new_item();
+        edit_state.item = item;
+        edit_state.cursor_pos = edit_state.item.name.chars().count();
+        edit_state.has_changes = true;
+
+        self.edit_state = edit_state;
+        self.show_ai_popup = false;
+        self.ai_popup_state.clear();
+        self.screen = Screen::Edit;
+    }
+
+    fn new_item_with_ai(&mut self) -> Result<()> {
+        self.new_item()?;
+        self.ai_popup_state.clear();
+        self.ai_popup_state.select_action(AiAction::GenerateItem);
+        self.show_ai_popup = true;
+        Ok(())
+    }
+
     fn run_ai_completion(&mut self) -> Result<()> {
         let content = self.edit_state.item.content.clone();
         let action = self.ai_popup_state.selected_action();
 
-        let system_prompt = action.system_prompt().to_string();
-        let user_message =
-            if self.ai_popup_state.is_custom() && !self.ai_popup_state.custom_input.is_empty() {
-                format!(
-                    "Request: {}\n\nContent to process:\n{}",
-                    self.ai_popup_state.custom_input, content
-                )
-            } else {
-                format!("Content to process:\n{}", content)
-            };
+        let system_prompt = self.ai_popup_state.system_prompt(action).to_string();
+        // User actions are sent like the built-in action for their field
+        let action = self.ai_popup_state.handled_as(action);
+        let user_message = if self.ai_popup_state.refining {
+            // Follow-up on the previous result, answered with the revised result
+            self.ai_popup_state.custom_input.clone()
+        } else if action == AiAction::GenerateItem {
+            if self.ai_popup_state.custom_input.trim().is_empty() {
+                self.ai_popup_state.error = Some("Describe the item to generate".to_string());
+                return Ok(());
+            }
+            format!("Description: {}", self.ai_popup_state.custom_input)
+        } else if let AiAction::ConvertTo(category) = action {
+            let item = &self.edit_state.item;
+            if item.category == category {
+                self.ai_popup_state.error = Some(format!(
+                    "This item is already in {}",
+                    category.display_name()
+                ));
+                return Ok(());
+            }
+            format!(
+                "Name: {}\nCategory: {}\nDescription: {}\n\nContent:\n{}",
+                item.name,
+                item.category.display_name(),
+                item.description.as_deref().unwrap_or_default(),
+                content
+            )
+        } else if action == AiAction::GenerateDescription {
+            format!(
+                "Name: {}\nCategory: {}\n\nContent:\n{}",
+                self.edit_state.item.name,
+                self.edit_state.item.category.display_name(),
+                content
+            )
+        } else if action == AiAction::SuggestTags {
+            let item = &self.edit_state.item;
+            let library_tags: Vec<&str> = self.tags.iter().map(|(tag, _)| tag.as_str()).collect();
+            format!(
+                "Name: {}\nCategory: {}\nDescription: {}\nCurrent tags: {}\n\
+                 Tags used in the library: {}\n\nContent:\n{}",
+                item.name,
+                item.category.display_name(),
+                item.description.as_deref().unwrap_or_default(),
+                item.tags.as_deref().unwrap_or_default(),
+                library_tags.join(", "),
+                content
+            )
+        } else if self.ai_popup_state.needs_input() && !self.ai_popup_state.custom_input.is_empty()
+        {
+            format!(
+                "Request: {}\n\nContent to process:\n{}",
+                self.ai_popup_state.custom_input, content
+            )
+        } else {
+            format!("Content to process:\n{}", content)
+        };
 
+        // A fresh run from the action list starts a new conversation
+        if !self.ai_popup_state.refining {
+            self.ai_popup_state.conversation.clear();
+        }
+        self.ai_popup_state.refining = false;
+        self.ai_popup_state.result = None;
+        self.ai_popup_state.diff = None;
+        self.ai_popup_state.pending_message = Some(user_message.clone());
         self.ai_popup_state.is_loading = true;
         self.ai_popup_state.error = None;
+        self.ai_popup_state.retry_delay = None;
 
         let request = LlmRequest {
             system_prompt,
+            history: self.ai_popup_state.conversation.clone(),
             user_message,
             max_tokens: 4096,
         };
 
         // Clone settings for the background thread
-        let provider = self.settings_state.provider.display_name().to_string();
-        let api_key = self.settings_state.api_key.clone();
-        let llm_model = self.settings_state.llm_model.clone();
+        let config = if action.is_lightweight() {
+            self.settings_state.light_llm_config()
+        } else {
+            self.settings_state.llm_config()
+        };
+        self.send_ai_request(request, config);
+
+        Ok(())
+    }
+
+    fn send_ai_request(&mut self, request: LlmRequest, config: LlmConfig) {
+        self.llm_request_config = Some(config.clone());
+        self.llm_last_request = Some((request.clone(), config.clone()));
 
         // Create channel for response
         let (tx, rx) = mpsc::channel();
         self.llm_receiver = Some(rx);
 
         // Spawn background thread
-        std::thread::spawn(move || {
-            let result =
-                complete_sync(&provider, &api_key, &llm_model, request).map_err(|e| e.to_string());
-            let _ = tx.send(result);
-        });
+        self.llm_task = Some(BackgroundCompletion::spawn(config, request, tx));
+    }
 
-        Ok(())
+    /// Send the last AI request again, after a rate limit or overload
+    fn retry_ai_request(&mut self) {
+        let Some((request, config)) = self.llm_last_request.clone() else {
+            return;
+        };
+        self.ai_popup_state.retry_at = None;
+        self.ai_popup_state.retry_delay = None;
+        self.ai_popup_state.error = None;
+        self.ai_popup_state.pending_message = Some(request.user_message.clone());
+        self.ai_popup_state.is_loading = true;
+        self.send_ai_request(request, config);
+    }
+
+    /// Stop the AI request in flight, if any, discarding its result
+    fn cancel_llm_request(&mut self) {
+        if let Some(task) = self.llm_task.take() {
+            task.cancel();
+        }
+        self.llm_receiver = None;
+        self.llm_request_config = None;
+        self.ai_popup_state.pending_message = None;
+        self.ai_popup_state.is_loading = false;
+        self.ai_popup_state.retry_at = None;
     }
 
     // Navigation helpers
-    fn move_down(&mut self) {
+    fn move_down(&mut self) -> Result<()> {
         match self.focus {
             Focus::ItemList => {
+                if self.selected_item_index + 1 >= self.items.len() {
+                    self.load_more_items()?;
+                }
                 if !self.items.is_empty() {
                     self.selected_item_index =
                         (self.selected_item_index + 1).min(self.items.len() - 1);
                 }
             }
             Focus::Sidebar => {
-                let max_index = 5 + self.tags.len(); // Recent + 4 categories + tags
+                let max_index = self.tags_start() + self.tags.len();
                 self.sidebar_index = (self.sidebar_index + 1).min(max_index.saturating_sub(1));
             }
         }
+        Ok(())
+    }
+
+    /// Append the next page of Recent after the last loaded row
+    fn load_more_items(&mut self) -> Result<()> {
+        if !self.has_more_items {
+            return Ok(());
+        }
+
+        let page = ItemStore::new(&self.db.conn).list_recent(
+            self.sort_order,
+            self.items.last(),
+            RECENT_PAGE_SIZE,
+        )?;
+        self.has_more_items = page.len() == RECENT_PAGE_SIZE;
+        self.sync_status
+            .extend(ExportStore::new(&self.db.conn).statuses(&page)?);
+        self.items.extend(page);
+        Ok(())
     }
 
     fn move_up(&mut self) {
@@ -731,24 +2134,35 @@ impl App {
         }
     }
 
-    fn go_to_bottom(&mut self) {
+    fn go_to_bottom(&mut self) -> Result<()> {
         match self.focus {
             Focus::ItemList => {
+                while self.has_more_items {
+                    self.load_more_items()?;
+                }
                 if !self.items.is_empty() {
                     self.selected_item_index = self.items.len() - 1;
                 }
             }
             Focus::Sidebar => {
-                let max_index = 5 + self.tags.len(); // Recent + 4 categories + tags
+                let max_index = self.tags_start() + self.tags.len();
                 self.sidebar_index = max_index.saturating_sub(1);
             }
         }
+        Ok(())
     }
 
-    fn page_down(&mut self) {
-        if self.focus == Focus::ItemList && !self.items.is_empty() {
+    fn page_down(&mut self) -> Result<()> {
+        if self.focus != Focus::ItemList {
+            return Ok(());
+        }
+        if self.selected_item_index + 10 >= self.items.len() {
+            self.load_more_items()?;
+        }
+        if !self.items.is_empty() {
             self.selected_item_index = (self.selected_item_index + 10).min(self.items.len() - 1);
         }
+        Ok(())
     }
 
     fn page_up(&mut self) {
@@ -758,18 +2172,44 @@ impl App {
     }
 
     // Action helpers
-    fn select_category(&mut self, category: Option<Category>) -> Result<()> {
+    fn select_category(&mut self, category: Option<Category>) {
         self.selected_category = category;
-        self.selected_tag = None;
+        self.selected_tags.clear();
+        self.special_list = None;
+        self.selected_collection = None;
         self.selected_item_index = 0;
-        self.refresh_data()
+        self.request_refresh();
+    }
+
+    fn select_special_list(&mut self, list: SpecialList) {
+        self.selected_category = None;
+        self.selected_collection = None;
+        self.selected_tags.clear();
+        self.special_list = Some(list);
+        self.selected_item_index = 0;
+        self.request_refresh();
+    }
+
+    pub fn in_trash(&self) -> bool {
+        self.special_list == Some(SpecialList::Trash)
     }
 
     fn view_selected(&mut self) -> Result<()> {
+        if let (Some(_), Some(item)) = (self.pick, self.items.get(self.selected_item_index)) {
+            return self.pick_item(item.clone());
+        }
         if !self.items.is_empty() {
             let item = &self.items[self.selected_item_index];
-            self.view_state = ViewState::default();
+            self.view_state = ViewState {
+                render_markdown: self.view_state.render_markdown,
+                ..ViewState::default()
+            };
             self.view_state.max_version = item.version;
+            if let Some(id) = item.id {
+                let links = LinkStore::new(&self.db.conn);
+                self.view_state.links = links.targets(id)?.into_iter().map(|i| i.name).collect();
+                self.view_state.used_by = links.sources(id)?.into_iter().map(|i| i.name).collect();
+            }
             self.screen = Screen::View;
         }
         Ok(())
@@ -777,7 +2217,15 @@ impl App {
 
     fn edit_selected(&mut self) -> Result<()> {
         if let Some(item) = self.items.get(self.selected_item_index).cloned() {
+            let links = match item.id {
+                Some(id) => LinkStore::new(&self.db.conn).targets(id)?,
+                None => Vec::new(),
+            };
             self.edit_state = EditState::edit_item(item);
+            self.edit_state.links = links
+                .into_iter()
+                .filter_map(|link| Some((link.id?, link.name)))
+                .collect();
             self.screen = Screen::Edit;
         }
         Ok(())
@@ -789,136 +2237,1966 @@ impl App {
         if let Some(cat) = self.selected_category {
             new_state.item.category = cat;
         }
+        new_state.item.content = self
+            .settings_state
+            .skeleton(new_state.item.category)
+            .to_string();
         self.edit_state = new_state;
         self.screen = Screen::Edit;
         Ok(())
     }
 
-    fn copy_selected(&mut self) -> Result<()> {
-        if let Some(content) = self
-            .items
-            .get(self.selected_item_index)
-            .map(|i| i.content.clone())
+    /// When a new item's category changes and its content is still the old
+    /// category's skeleton, swap in the new one's
+    fn swap_skeleton(&mut self, previous: Category) {
+        let category = self.edit_state.item.category;
+        let content = &self.edit_state.item.content;
+        if !self.edit_state.is_new
+            || category == previous
+            || (!content.trim().is_empty() && content != self.settings_state.skeleton(previous))
         {
-            self.copy_content(&content);
+            return;
         }
-        Ok(())
+        let skeleton = self.settings_state.skeleton(category).to_string();
+        self.edit_state.replace_field(EditField::Content, skeleton);
     }
 
-    fn copy_content(&mut self, content: &str) {
-        #[cfg(target_os = "linux")]
-        {
-            // Try wl-copy (Wayland) first, then xclip (X11)
-            use std::io::Write;
-            use std::process::{Command, Stdio};
+    /// Open the clipboard's text as a new, unsaved item, filling fields from
+    /// its frontmatter
+    fn new_item_from_clipboard(&mut self) {
+        let Some(text) = self.read_clipboard() else {
+            return;
+        };
+        if text.trim().is_empty() {
+            self.status_message = Some("Clipboard is empty".to_string());
+            return;
+        }
 
-            let result = Command::new("wl-copy")
-                .stdin(Stdio::piped())
-                .spawn()
-                .and_then(|mut child| {
-                    if let Some(stdin) = child.stdin.as_mut() {
-                        stdin.write_all(content.as_bytes())?;
-                    }
-                    child.wait()
-                })
-                .or_else(|_| {
-                    // Fallback to xclip
-                    Command::new("xclip")
-                        .args(["-selection", "clipboard"])
-                        .stdin(Stdio::piped())
-                        .spawn()
-                        .and_then(|mut child| {
-                            if let Some(stdin) = child.stdin.as_mut() {
-                                stdin.write_all(content.as_bytes())?;
-                            }
-                            child.wait()
-                        })
-                });
+        let category = self.selected_category.unwrap_or(Category::Prompt);
+        let mut edit_state = EditState::new_item();
+        edit_state.item = import::draft_from_paste(category, &text);
+        edit_state.cursor_pos = edit_state.item.name.chars().count();
+        edit_state.has_changes = true;
 
-            match result {
-                Ok(status) if status.success() => {
-                    self.status_message = Some("Copied to clipboard".to_string());
-                }
-                _ => {
-                    self.status_message = Some("Copy failed: install wl-copy or xclip".to_string());
-                }
-            }
+        self.edit_state = edit_state;
+        self.screen = Screen::Edit;
+    }
+
+    fn copy_selected(&mut self) -> Result<()> {
+        if let Some(item) = self.items.get(self.selected_item_index).cloned() {
+            self.copy_item_content(item)?;
         }
+        Ok(())
+    }
 
-        #[cfg(not(target_os = "linux"))]
-        {
-            match arboard::Clipboard::new() {
-                Ok(mut clipboard) => match clipboard.set_text(content) {
-                    Ok(_) => self.status_message = Some("Copied to clipboard".to_string()),
-                    Err(e) => self.status_message = Some(format!("Copy failed: {}", e)),
-                },
-                Err(e) => {
-                    self.status_message = Some(format!("Clipboard error: {}", e));
-                }
-            }
+    /// Choose an item in picker mode, asking for the values of its
+    /// `{{variables}}` first when the content is what gets printed
+    fn pick_item(&mut self, item: Item) -> Result<()> {
+        match self.pick {
+            Some(PickOutput::Name) => self.finish_pick(&item, item.name.clone()),
+            _ => self.copy_item_content(item),
         }
     }
 
-    fn delete_selected(&mut self) -> Result<()> {
-        if let Some(item) = self.items.get(self.selected_item_index) {
-            self.confirm_dialog = Some(ConfirmDialog::delete(&item.name));
+    /// End the picker session with `text` as its output, counting the use
+    fn finish_pick(&mut self, item: &Item, text: String) -> Result<()> {
+        if let Some(id) = item.id {
+            ItemStore::new(&self.db.conn).record_use(id)?;
         }
+        self.picked = Some(text);
+        self.should_quit = true;
         Ok(())
     }
 
-    fn perform_delete(&mut self) -> Result<()> {
+    /// Copy an item's content (print it, in picker mode), first asking for
+    /// the values of any `{{variables}}` in it
+    fn copy_item_content(&mut self, item: Item) -> Result<()> {
+        let variables = template_variables(&item.content);
+        if variables.is_empty() && self.pick.is_some() {
+            self.finish_pick(&item, item.content.clone())?;
+        } else if variables.is_empty() {
+            self.copy_item(&item, &item.content)?;
+        } else {
+            let presets =
+                parse_variable_presets(&self.settings_state.template_variables).unwrap_or_default();
+            self.template_form = Some(TemplateFormState::new(item, variables, &presets));
+        }
+        Ok(())
+    }
+
+    /// Save the values typed in the template form as presets, keeping other
+    /// saved variables
+    fn save_variable_presets(&mut self) -> Result<()> {
+        let Some(ref form) = self.template_form else {
+            return Ok(());
+        };
+        let mut presets =
+            parse_variable_presets(&self.settings_state.template_variables).unwrap_or_default();
+        let mut saved = 0;
+        // `;` separates saved entries, so values holding one can't be kept
+        let values = form
+            .fields
+            .iter()
+            .filter(|(_, value)| !value.is_empty() && !value.contains(';'));
+        for (name, value) in values {
+            presets.retain(|(preset, _)| preset != name);
+            presets.push((name.clone(), value.clone()));
+            saved += 1;
+        }
+
+        let spec = format_variable_presets(&presets);
+        SettingsStore::new(&self.db.conn).set("template_variables", &spec)?;
+        self.settings_state.template_variables = spec;
+        self.status_message = Some(format!("Saved {} variable preset(s)", saved));
+        Ok(())
+    }
+
+    fn handle_template_form_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut form) = self.template_form else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc => self.template_form = None,
+            KeyCode::Char('s') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                self.save_variable_presets()?;
+            }
+            KeyCode::Tab | KeyCode::Down => form.select_next(),
+            KeyCode::BackTab | KeyCode::Up => form.select_prev(),
+            KeyCode::Left => form.move_cursor_left(),
+            KeyCode::Right => form.move_cursor_right(),
+            KeyCode::Backspace => form.delete_char(),
+            KeyCode::Char(c) => form.insert_str(c.encode_utf8(&mut [0; 4])),
+            KeyCode::Enter => {
+                if let Some(form) = self.template_form.take() {
+                    if self.pick.is_some() {
+                        self.finish_pick(&form.item, form.filled())?;
+                    } else {
+                        self.copy_item(&form.item, &form.filled())?;
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Copy the selected item as the Claude exporter writes it, frontmatter
+    /// included
+    fn copy_selected_exported(&mut self) -> Result<()> {
+        let Some(item) = self.items.get(self.selected_item_index).cloned() else {
+            return Ok(());
+        };
+        match self
+            .settings_state
+            .export_options()
+            .claude_exporter()
+            .render(&item)
+        {
+            Ok((_, text)) => {
+                if self.copy_item(&item, &text)? {
+                    self.status_message = Some(format!("Copied {} as exported", item.name));
+                }
+            }
+            Err(e) => self.status_message = Some(format!("Copy failed: {}", e)),
+        }
+        Ok(())
+    }
+
+    /// Copy the selected item with all its fields as JSON or YAML
+    fn copy_selected_as(&mut self, format: DataFormat) -> Result<()> {
+        let Some(item) = self.items.get(self.selected_item_index).cloned() else {
+            return Ok(());
+        };
+        match format.render(&item) {
+            Ok(text) => {
+                if self.copy_item(&item, &text)? {
+                    self.status_message =
+                        Some(format!("Copied {} as {}", item.name, format.display_name()));
+                }
+            }
+            Err(e) => self.status_message = Some(format!("Copy failed: {}", e)),
+        }
+        Ok(())
+    }
+
+    fn copy_selected_name(&mut self) {
+        if let Some(name) = self
+            .items
+            .get(self.selected_item_index)
+            .map(|i| i.name.clone())
+        {
+            if self.copy_content(&name) {
+                self.status_message = Some(format!("Copied name: {}", name));
+            }
+        }
+    }
+
+    /// Copy the path the selected item was last exported to
+    fn copy_selected_export_path(&mut self) -> Result<()> {
+        let Some(item) = self.items.get(self.selected_item_index).cloned() else {
+            return Ok(());
+        };
+        let path = match item.id {
+            Some(id) => ExportStore::new(&self.db.conn).latest_path(id)?,
+            None => None,
+        };
+        match path {
+            Some(path) => {
+                if self.copy_content(&path) {
+                    self.status_message = Some(format!("Copied path: {}", path));
+                }
+            }
+            None => {
+                self.status_message = Some(format!(
+                    "Copy failed: {} hasn't been exported yet",
+                    item.name
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    /// Copy `text` for an item, counting the use when it succeeds. Returns
+    /// whether it was copied.
+    fn copy_item(&mut self, item: &Item, text: &str) -> Result<bool> {
+        let copied = self.copy_content(text);
+        if let (true, Some(id)) = (copied, item.id) {
+            let store = ItemStore::new(&self.db.conn);
+            store.record_use(id)?;
+            *self.use_counts.entry(id).or_default() += 1;
+            self.copies_count = store.count_copied()?.min(RECENT_COPIES_LIMIT);
+        }
+        Ok(copied)
+    }
+
+    /// Put `content` on the clipboard, reporting the result in the status
+    /// bar. Returns whether it was copied.
+    fn copy_content(&mut self, content: &str) -> bool {
+        #[cfg(target_os = "linux")]
+        {
+            // Try wl-copy (Wayland) first, then xclip (X11)
+            use std::io::Write;
+            use std::process::{Command, Stdio};
+
+            let result = Command::new("wl-copy")
+                .stdin(Stdio::piped())
+                .spawn()
+                .and_then(|mut child| {
+                    if let Some(stdin) = child.stdin.as_mut() {
+                        stdin.write_all(content.as_bytes())?;
+                    }
+                    child.wait()
+                })
+                .or_else(|_| {
+                    // Fallback to xclip
+                    Command::new("xclip")
+                        .args(["-selection", "clipboard"])
+                        .stdin(Stdio::piped())
+                        .spawn()
+                        .and_then(|mut child| {
+                            if let Some(stdin) = child.stdin.as_mut() {
+                                stdin.write_all(content.as_bytes())?;
+                            }
+                            child.wait()
+                        })
+                });
+
+            match result {
+                Ok(status) if status.success() => {
+                    self.status_message = Some("Copied to clipboard".to_string());
+                    true
+                }
+                _ => {
+                    self.status_message = Some("Copy failed: install wl-copy or xclip".to_string());
+                    false
+                }
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            match arboard::Clipboard::new() {
+                Ok(mut clipboard) => match clipboard.set_text(content) {
+                    Ok(_) => {
+                        self.status_message = Some("Copied to clipboard".to_string());
+                        true
+                    }
+                    Err(e) => {
+                        self.status_message = Some(format!("Copy failed: {}", e));
+                        false
+                    }
+                },
+                Err(e) => {
+                    self.status_message = Some(format!("Clipboard error: {}", e));
+                    false
+                }
+            }
+        }
+    }
+
+    /// Text on the clipboard, or `None` with the reason in the status bar
+    fn read_clipboard(&mut self) -> Option<String> {
+        #[cfg(target_os = "linux")]
+        {
+            // Try wl-paste (Wayland) first, then xclip (X11)
+            use std::process::Command;
+
+            let output = Command::new("wl-paste")
+                .arg("--no-newline")
+                .output()
+                .or_else(|_| {
+                    Command::new("xclip")
+                        .args(["-selection", "clipboard", "-o"])
+                        .output()
+                });
+
+            match output {
+                Ok(output) if output.status.success() => {
+                    Some(String::from_utf8_lossy(&output.stdout).into_owned())
+                }
+                _ => {
+                    self.status_message =
+                        Some("Paste failed: install wl-paste or xclip".to_string());
+                    None
+                }
+            }
+        }
+
+        #[cfg(not(target_os = "linux"))]
+        {
+            match arboard::Clipboard::new().and_then(|mut clipboard| clipboard.get_text()) {
+                Ok(text) => Some(text),
+                Err(e) => {
+                    self.status_message = Some(format!("Paste failed: {}", e));
+                    None
+                }
+            }
+        }
+    }
+
+    fn delete_selected(&mut self) -> Result<()> {
+        if let Some((path, count)) = self.focused_collection() {
+            self.confirm_dialog = Some(ConfirmDialog::delete_collection(&path, count));
+            return Ok(());
+        }
+        if let Some((tag, count)) = self.focused_tag() {
+            self.confirm_dialog = Some(ConfirmDialog::delete_tag(&tag, count));
+            return Ok(());
+        }
+
+        let marked = self.marked_ids();
+        if !marked.is_empty() {
+            self.confirm_dialog = Some(if self.in_trash() {
+                ConfirmDialog::delete_many_forever(marked.len())
+            } else {
+                ConfirmDialog::delete_many(marked.len())
+            });
+            return Ok(());
+        }
+
+        if let Some(item) = self.items.get(self.selected_item_index) {
+            self.confirm_dialog = Some(if self.in_trash() {
+                ConfirmDialog::delete_forever(&item.name)
+            } else {
+                ConfirmDialog::delete(&item.name)
+            });
+        }
+        Ok(())
+    }
+
+    fn perform_delete(&mut self) -> Result<()> {
+        let marked = self.marked_ids();
+        if !marked.is_empty() {
+            let store = ItemStore::new(&self.db.conn);
+            if self.in_trash() {
+                for id in &marked {
+                    store.purge(*id)?;
+                }
+                self.status_message = Some(format!("Deleted {} item(s) permanently", marked.len()));
+            } else {
+                for id in &marked {
+                    store.delete(*id)?;
+                }
+                self.status_message = Some(format!(
+                    "Moved {} item(s) to the trash (u to undo)",
+                    marked.len()
+                ));
+                self.last_deleted = marked;
+            }
+            self.clear_marks();
+            return self.refresh_data();
+        }
+
         if let Some(item) = self.items.get(self.selected_item_index) {
             if let Some(id) = item.id {
+                let name = item.name.clone();
                 let store = ItemStore::new(&self.db.conn);
-                store.delete(id)?;
+                if self.in_trash() {
+                    store.purge(id)?;
+                    self.status_message = Some(format!("Deleted '{}' permanently", name));
+                } else {
+                    store.delete(id)?;
+                    self.last_deleted = vec![id];
+                    self.status_message =
+                        Some(format!("Moved '{}' to the trash (u to undo)", name));
+                }
+                self.refresh_data()?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Mark or unmark the selected item for bulk actions, then move down
+    fn toggle_mark(&mut self) -> Result<()> {
+        let Some(id) = self.selected_item().and_then(|item| item.id) else {
+            return Ok(());
+        };
+        if !self.marked.remove(&id) {
+            self.marked.insert(id);
+        }
+        self.move_down()
+    }
+
+    /// Start a range selection at the cursor, or mark every item between
+    /// its start and the cursor
+    fn toggle_range_mark(&mut self) {
+        match self.mark_anchor {
+            Some(_) => self.commit_range_mark(),
+            None if !self.items.is_empty() => self.mark_anchor = Some(self.selected_item_index),
+            None => {}
+        }
+    }
+
+    fn commit_range_mark(&mut self) {
+        let Some(anchor) = self.mark_anchor.take() else {
+            return;
+        };
+        let (start, end) = (
+            anchor.min(self.selected_item_index),
+            anchor.max(self.selected_item_index),
+        );
+        let ids = self.items[start..=end.min(self.items.len() - 1)]
+            .iter()
+            .filter_map(|item| item.id);
+        self.marked.extend(ids);
+    }
+
+    /// Whether the item at `index` is marked or inside the open range
+    pub fn is_marked(&self, index: usize) -> bool {
+        let in_range = self.mark_anchor.is_some_and(|anchor| {
+            (anchor.min(self.selected_item_index)..=anchor.max(self.selected_item_index))
+                .contains(&index)
+        });
+        in_range
+            || self
+                .items
+                .get(index)
+                .and_then(|item| item.id)
+                .is_some_and(|id| self.marked.contains(&id))
+    }
+
+    /// Ids of the marked items in list order, closing an open range first.
+    /// Empty outside the main screen, where actions apply to one item.
+    fn marked_ids(&mut self) -> Vec<i64> {
+        if self.screen != Screen::Main {
+            return Vec::new();
+        }
+        self.commit_range_mark();
+        self.items
+            .iter()
+            .filter_map(|item| item.id)
+            .filter(|id| self.marked.contains(id))
+            .collect()
+    }
+
+    fn clear_marks(&mut self) {
+        self.marked.clear();
+        self.mark_anchor = None;
+    }
+
+    /// Ask for tags to add or remove on the marked items (or the selected
+    /// one when nothing is marked)
+    fn prompt_edit_tags(&mut self) {
+        let count = match self.marked_ids().len() {
+            0 if self.selected_item().is_none() => return,
+            0 => 1,
+            count => count,
+        };
+        self.input_dialog = Some(InputDialog::edit_tags(count));
+    }
+
+    fn perform_edit_tags(&mut self, input: &str) -> Result<()> {
+        let mut ids = self.marked_ids();
+        if ids.is_empty() {
+            ids.extend(self.selected_item().and_then(|item| item.id));
+        }
+
+        let (mut add, mut remove) = (Vec::new(), Vec::new());
+        for word in input.split(|c: char| c.is_whitespace() || c == ',') {
+            let (list, tag) = match word.strip_prefix('-') {
+                Some(tag) => (&mut remove, tag),
+                None => (&mut add, word.trim_start_matches('+')),
+            };
+            let tag = tag.trim_start_matches('#').to_lowercase();
+            if !tag.is_empty() {
+                list.push(tag);
+            }
+        }
+        if add.is_empty() && remove.is_empty() {
+            return Ok(());
+        }
+
+        let changed = ItemStore::new(&self.db.conn).edit_tags(&ids, &add, &remove)?;
+        let summary: Vec<String> = add
+            .iter()
+            .map(|tag| format!("+#{}", tag))
+            .chain(remove.iter().map(|tag| format!("-#{}", tag)))
+            .collect();
+        self.status_message = Some(format!(
+            "Updated tags on {} item(s): {}",
+            changed,
+            summary.join(" ")
+        ));
+        self.clear_marks();
+        self.refresh_data()
+    }
+
+    /// First sidebar row of the tags, after the collections
+    pub fn tags_start(&self) -> usize {
+        SIDEBAR_COLLECTIONS_START + self.collections.len()
+    }
+
+    /// The collection under the cursor when the sidebar has focus, with its
+    /// item count
+    fn focused_collection(&self) -> Option<(String, usize)> {
+        if self.screen != Screen::Main || self.focus != Focus::Sidebar {
+            return None;
+        }
+        let index = self.sidebar_index.checked_sub(SIDEBAR_COLLECTIONS_START)?;
+        self.collections.get(index).cloned()
+    }
+
+    /// Ask which collections to put the marked items (or the selected one)
+    /// in. With the sidebar focused, ask for a new collection instead,
+    /// nested under the focused one.
+    fn prompt_collections(&mut self) {
+        if self.focus == Focus::Sidebar {
+            let parent = self
+                .focused_collection()
+                .map(|(path, _)| format!("{}/", path))
+                .unwrap_or_default();
+            self.input_dialog = Some(InputDialog::new_collection(&parent));
+            return;
+        }
+        let count = match self.marked_ids().len() {
+            0 if self.selected_item().is_none() => return,
+            0 => 1,
+            count => count,
+        };
+        self.input_dialog = Some(InputDialog::edit_collections(count));
+    }
+
+    fn perform_new_collection(&mut self, path: &str) -> Result<()> {
+        let store = CollectionStore::new(&self.db.conn);
+        if let Err(e) = store.create(path) {
+            self.status_message = Some(format!("Create failed: {}", e));
+            return Ok(());
+        }
+        self.refresh_data()?;
+        self.focus_collection(path);
+        Ok(())
+    }
+
+    /// Add the marked items (or the selected one) to the collections named
+    /// in `input`, or take them out of the ones prefixed with `-`
+    fn perform_edit_collections(&mut self, input: &str) -> Result<()> {
+        let mut ids = self.marked_ids();
+        if ids.is_empty() {
+            ids.extend(self.selected_item().and_then(|item| item.id));
+        }
+
+        let (mut add, mut remove) = (Vec::new(), Vec::new());
+        for entry in input.split(',').map(str::trim) {
+            let (list, path) = match entry.strip_prefix('-') {
+                Some(path) => (&mut remove, path),
+                None => (&mut add, entry.trim_start_matches('+')),
+            };
+            let path = normalize_collection_path(path);
+            if !path.is_empty() {
+                list.push(path);
+            }
+        }
+        if add.is_empty() && remove.is_empty() {
+            return Ok(());
+        }
+
+        let changed = CollectionStore::new(&self.db.conn).edit_items(&ids, &add, &remove)?;
+        let summary: Vec<String> = add
+            .iter()
+            .map(|path| format!("+{}", path))
+            .chain(remove.iter().map(|path| format!("-{}", path)))
+            .collect();
+        self.status_message = Some(format!(
+            "Updated collections of {} item(s): {}",
+            changed,
+            summary.join(" ")
+        ));
+        self.clear_marks();
+        self.refresh_data()
+    }
+
+    fn rename_focused_collection(&mut self) {
+        if let Some((path, _)) = self.focused_collection() {
+            self.input_dialog = Some(InputDialog::rename_collection(&path));
+        }
+    }
+
+    fn perform_rename_collection(&mut self, from: &str, to: &str) -> Result<()> {
+        let to = normalize_collection_path(to);
+        if let Err(e) = CollectionStore::new(&self.db.conn).rename(from, &to) {
+            self.status_message = Some(format!("Rename failed: {}", e));
+            return Ok(());
+        }
+
+        // A filter on the collection, or on one inside it, follows the rename
+        if let Some(ref selected) = self.selected_collection {
+            if selected == from {
+                self.selected_collection = Some(to.clone());
+            } else if let Some(rest) = selected.strip_prefix(&format!("{}/", from)) {
+                self.selected_collection = Some(format!("{}/{}", to, rest));
+            }
+        }
+        self.status_message = Some(format!("Renamed {} to {}", from, to));
+        self.refresh_data()?;
+        self.focus_collection(&to);
+        Ok(())
+    }
+
+    fn perform_delete_collection(&mut self) -> Result<()> {
+        let Some((path, _)) = self.focused_collection() else {
+            return Ok(());
+        };
+
+        let count = CollectionStore::new(&self.db.conn).delete(&path)?;
+        let nested = format!("{}/", path);
+        if self
+            .selected_collection
+            .as_ref()
+            .is_some_and(|selected| *selected == path || selected.starts_with(&nested))
+        {
+            self.selected_collection = None;
+        }
+        self.status_message = Some(format!(
+            "Deleted collection {} ({} items kept in the library)",
+            path, count
+        ));
+        self.refresh_data()?;
+
+        let max_index = self.tags_start() + self.tags.len();
+        self.sidebar_index = self.sidebar_index.min(max_index.saturating_sub(1));
+        Ok(())
+    }
+
+    /// Move the sidebar cursor to a collection, which may have moved
+    fn focus_collection(&mut self, path: &str) {
+        let path = normalize_collection_path(path).to_lowercase();
+        if let Some(index) = self
+            .collections
+            .iter()
+            .position(|(p, _)| p.to_lowercase() == path)
+        {
+            self.sidebar_index = SIDEBAR_COLLECTIONS_START + index;
+        }
+    }
+
+    /// The tag under the cursor when the sidebar has focus, with its count
+    fn focused_tag(&self) -> Option<(String, usize)> {
+        if self.screen != Screen::Main || self.focus != Focus::Sidebar {
+            return None;
+        }
+        let index = self.sidebar_index.checked_sub(self.tags_start())?;
+        self.tags.get(index).cloned()
+    }
+
+    fn rename_focused_tag(&mut self) {
+        if let Some((tag, _)) = self.focused_tag() {
+            self.input_dialog = Some(InputDialog::rename_tag(&tag));
+        }
+    }
+
+    fn perform_rename_tag(&mut self, from: &str, to: &str) -> Result<()> {
+        let store = ItemStore::new(&self.db.conn);
+        let to = to.trim().trim_start_matches('#').trim().to_lowercase();
+        let merging = to != from && self.tags.iter().any(|(tag, _)| *tag == to);
+
+        let count = match if merging {
+            store.merge_tag(from, &to)
+        } else {
+            store.rename_tag(from, &to)
+        } {
+            Ok(count) => count,
+            Err(e) => {
+                self.status_message = Some(format!("Rename failed: {}", e));
+                return Ok(());
+            }
+        };
+
+        if let Some(index) = self.selected_tags.iter().position(|tag| tag == from) {
+            self.selected_tags.remove(index);
+            if !self.selected_tags.contains(&to) {
+                self.selected_tags.insert(index, to.clone());
+            }
+        }
+        self.status_message = Some(if merging {
+            format!("Merged #{} into #{} ({} items)", from, to, count)
+        } else {
+            format!("Renamed #{} to #{} ({} items)", from, to, count)
+        });
+        self.refresh_data()?;
+
+        // Keep the cursor on the renamed tag, which may have moved
+        if let Some(index) = self.tags.iter().position(|(tag, _)| *tag == to) {
+            self.sidebar_index = self.tags_start() + index;
+        }
+        Ok(())
+    }
+
+    fn perform_delete_tag(&mut self) -> Result<()> {
+        let Some((tag, _)) = self.focused_tag() else {
+            return Ok(());
+        };
+
+        let count = ItemStore::new(&self.db.conn).delete_tag(&tag)?;
+        self.selected_tags.retain(|selected| *selected != tag);
+        self.status_message = Some(format!("Removed #{} from {} items", tag, count));
+        self.refresh_data()?;
+
+        let max_index = self.tags_start() + self.tags.len();
+        self.sidebar_index = self.sidebar_index.min(max_index.saturating_sub(1));
+        Ok(())
+    }
+
+    /// Add the focused sidebar tag to the tag filter, or take it out
+    fn toggle_focused_tag(&mut self) -> Result<()> {
+        let Some((tag, _)) = self.focused_tag() else {
+            return Ok(());
+        };
+        match self
+            .selected_tags
+            .iter()
+            .position(|selected| *selected == tag)
+        {
+            Some(index) => {
+                self.selected_tags.remove(index);
+            }
+            None => self.selected_tags.push(tag),
+        }
+        self.selected_category = None;
+        self.special_list = None;
+        self.selected_collection = None;
+        self.selected_item_index = 0;
+        self.request_refresh();
+        Ok(())
+    }
+
+    /// Switch a multi-tag filter between matching any and all of its tags
+    fn toggle_tag_match(&mut self) -> Result<()> {
+        self.tag_match_all = !self.tag_match_all;
+        SettingsStore::new(&self.db.conn)
+            .set("tag_match", if self.tag_match_all { "all" } else { "any" })?;
+        self.selected_item_index = 0;
+        self.request_refresh();
+        Ok(())
+    }
+
+    /// Switch to the next list order, keeping the selected item selected
+    fn cycle_sort_order(&mut self) -> Result<()> {
+        let selected = self.selected_item().and_then(|item| item.id);
+        self.sort_order = self.sort_order.next();
+        SettingsStore::new(&self.db.conn).set("list_sort", self.sort_order.as_str())?;
+        self.request_refresh();
+        self.select_on_load = selected.map(|id| (id, false));
+        self.status_message = Some(format!("Sorted by {}", self.sort_order.as_str()));
+        Ok(())
+    }
+
+    fn resize_sidebar(&mut self, delta: i16) -> Result<()> {
+        let width = self
+            .sidebar_width
+            .saturating_add_signed(delta)
+            .clamp(SIDEBAR_MIN_WIDTH, SIDEBAR_MAX_WIDTH);
+        if width != self.sidebar_width {
+            self.sidebar_width = width;
+            SettingsStore::new(&self.db.conn).set("sidebar_width", &width.to_string())?;
+        }
+        Ok(())
+    }
+
+    fn toggle_favorite(&mut self) -> Result<()> {
+        let Some(id) = self.selected_item().and_then(|item| item.id) else {
+            return Ok(());
+        };
+
+        let favorite = !self.favorites.contains(&id);
+        ItemStore::new(&self.db.conn).set_favorite(id, favorite)?;
+        self.refresh_data()
+    }
+
+    /// Keep the marked items (or the selected one) after review, taking
+    /// them off the stale list until they go stale again
+    fn keep_stale(&mut self) -> Result<()> {
+        let mut ids = self.marked_ids();
+        if ids.is_empty() {
+            ids.extend(self.selected_item().and_then(|item| item.id));
+        }
+        if ids.is_empty() {
+            return Ok(());
+        }
+
+        let store = ItemStore::new(&self.db.conn);
+        for id in &ids {
+            store.mark_reviewed(*id)?;
+        }
+        self.status_message = Some(match ids.as_slice() {
+            [id] => {
+                let name = self
+                    .items
+                    .iter()
+                    .find(|item| item.id == Some(*id))
+                    .map_or("item", |item| item.name.as_str());
+                format!("Kept '{}'", name)
+            }
+            _ => format!("Kept {} items", ids.len()),
+        });
+        self.clear_marks();
+        self.refresh_data()
+    }
+
+    /// Move the selected item in or out of the archive. Archived items
+    /// keep their history but leave Recent, the category and tag lists,
+    /// and search.
+    fn toggle_archived(&mut self) -> Result<()> {
+        let Some((id, name)) = self
+            .selected_item()
+            .and_then(|item| Some((item.id?, item.name.clone())))
+        else {
+            return Ok(());
+        };
+
+        let archived = !self.archived.contains(&id);
+        ItemStore::new(&self.db.conn).set_archived(id, archived)?;
+        self.status_message = Some(if archived {
+            format!("Archived '{}'", name)
+        } else {
+            format!("Unarchived '{}'", name)
+        });
+        self.refresh_data()?;
+
+        // Keep viewing the item if it is still in the list
+        if self.screen == Screen::View {
+            match self.items.iter().position(|i| i.id == Some(id)) {
+                Some(index) => self.selected_item_index = index,
+                None => {
+                    self.view_state.viewing_version = None;
+                    self.screen = Screen::Main;
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Restore the most recently trashed item, or items for a bulk delete
+    fn undo_delete(&mut self) -> Result<()> {
+        let ids = std::mem::take(&mut self.last_deleted);
+        let store = ItemStore::new(&self.db.conn);
+        match ids.as_slice() {
+            [] => return Ok(()),
+            [id] => {
+                store.restore(*id)?;
+                if let Some(item) = store.get(*id)? {
+                    self.status_message = Some(format!("Restored '{}'", item.name));
+                }
+            }
+            ids => {
+                for id in ids {
+                    store.restore(*id)?;
+                }
+                self.status_message = Some(format!("Restored {} item(s)", ids.len()));
+            }
+        }
+        self.refresh_data()
+    }
+
+    /// Take the selected item out of the trash
+    fn restore_selected(&mut self) -> Result<()> {
+        if let Some(item) = self.items.get(self.selected_item_index) {
+            if let Some(id) = item.id {
+                let name = item.name.clone();
+                ItemStore::new(&self.db.conn).restore(id)?;
+                self.last_deleted.retain(|deleted| *deleted != id);
+                self.status_message = Some(format!("Restored '{}'", name));
                 self.refresh_data()?;
             }
         }
         Ok(())
     }
 
-    fn export_selected(&mut self) -> Result<()> {
-        if let Some(item) = self.items.get(self.selected_item_index) {
-            if item.category == Category::Prompt {
-                self.status_message = Some("Prompts are copy-only (press 'c' to copy)".to_string());
+    fn confirm_empty_trash(&mut self) {
+        if self.trash_count > 0 {
+            self.confirm_dialog = Some(ConfirmDialog::empty_trash(self.trash_count));
+        }
+    }
+
+    fn perform_empty_trash(&mut self) -> Result<()> {
+        let count = ItemStore::new(&self.db.conn).empty_trash()?;
+        self.last_deleted.clear();
+        self.clear_marks();
+        self.status_message = Some(format!("Permanently deleted {} item(s)", count));
+        self.refresh_data()
+    }
+
+    fn export_selected(&mut self) -> Result<()> {
+        if self.selected_item().is_some() {
+            self.export_popup_state
+                .set_profiles(self.settings_state.export_profiles());
+            self.show_export_popup = true;
+        }
+        Ok(())
+    }
+
+    fn handle_export_popup_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.show_export_popup = false,
+            KeyCode::Char('j') | KeyCode::Down => self.export_popup_state.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => self.export_popup_state.select_prev(),
+            KeyCode::Enter | KeyCode::Char('p')
+                if self.export_popup_state.selected_target() == ExportTarget::Zip =>
+            {
+                self.show_export_popup = false;
+                let profile = self.export_popup_state.selected_profile().cloned();
+                self.prompt_export_zip(profile.as_ref());
+            }
+            KeyCode::Enter => {
+                self.show_export_popup = false;
+                let profile = self.export_popup_state.selected_profile().cloned();
+                self.perform_export(self.export_popup_state.selected_target(), profile)?;
+            }
+            KeyCode::Char('p') => {
+                let target = self.export_popup_state.selected_target();
+                let suggested = self
+                    .selected_item()
+                    .filter(|item| target.supports(item.category))
+                    .map(|item| target.suggested_path(item));
+                if let Some(path) = suggested {
+                    self.show_export_popup = false;
+                    self.input_dialog = Some(InputDialog::export_to_path(&target, &path));
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Export options for `profile`, or the ones from Settings without one
+    fn export_options_for(&self, profile: Option<&ExportProfile>) -> ExportOptions {
+        let options = self.settings_state.export_options();
+        match profile {
+            Some(profile) => profile.options(&options),
+            None => options,
+        }
+    }
+
+    fn perform_export(
+        &mut self,
+        target: ExportTarget,
+        profile: Option<ExportProfile>,
+    ) -> Result<()> {
+        let options = self.export_options_for(profile.as_ref());
+        let key = profile
+            .as_ref()
+            .map_or_else(|| target.key().to_string(), ExportProfile::key);
+        if target.is_combined() {
+            let items: Vec<Item> = self
+                .items
+                .iter()
+                .filter(|i| target.supports(i.category))
+                .cloned()
+                .collect();
+            self.status_message = Some(match target.export_combined(&items, &options) {
+                Ok(path) => {
+                    for item in &items {
+                        self.record_export(item, &key, &path)?;
+                    }
+                    format!("Exported {} items to {}", items.len(), path.display())
+                }
+                Err(e) => format!("Export failed: {}", e),
+            });
+            return Ok(());
+        }
+
+        if let Some(item) = self.items.get(self.selected_item_index) {
+            if matches!(target, ExportTarget::Claude | ExportTarget::ClaudeProject)
+                && item.category == Category::Prompt
+            {
+                self.status_message = Some("Prompts are copy-only (press 'c' to copy)".to_string());
+                return Ok(());
+            }
+
+            // Ask before clobbering a file that was edited outside grimoire
+            if let Ok(Some((path, content))) = target.render(item, &options) {
+                if let Ok(on_disk) = std::fs::read_to_string(&path) {
+                    if on_disk != content {
+                        let mut diff = ExportDiffState::new(target, path, &on_disk, &content);
+                        diff.profile = profile;
+                        self.export_diff = Some(diff);
+                        return Ok(());
+                    }
+                }
+            }
+
+            self.write_export(target, profile)?;
+        }
+        Ok(())
+    }
+
+    /// Write the selected item to a path of the user's choosing, asking
+    /// first when that replaces a different file. The file isn't tracked
+    /// like the target's own exports, so sync leaves it be.
+    fn perform_export_to_path(&mut self, target_key: &str, dest: &str) {
+        let (Some(target), Some(item)) = (ExportTarget::from_key(target_key), self.selected_item())
+        else {
+            return;
+        };
+        if let Ok((path, content)) =
+            target.render_to(item, &self.settings_state.export_options(), dest)
+        {
+            if std::fs::read_to_string(&path).is_ok_and(|on_disk| on_disk != content) {
+                self.confirm_dialog = Some(ConfirmDialog::overwrite(&path.display().to_string()));
+                self.pending_export_to = Some((target, dest.to_string()));
+                return;
+            }
+        }
+        self.write_export_to_path(target, dest);
+    }
+
+    fn write_export_to_path(&mut self, target: ExportTarget, dest: &str) {
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+        self.status_message = Some(
+            match target.export_to(item, &self.settings_state.export_options(), dest) {
+                Ok(path) => format!("Exported {} to {}", item.name, path.display()),
+                Err(e) => format!("Export failed: {}", e),
+            },
+        );
+    }
+
+    fn handle_export_diff_key(&mut self, key: KeyEvent) -> Result<()> {
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('n') | KeyCode::Char('q') => {
+                if let Some(diff) = self.export_diff.take() {
+                    self.status_message = Some(match diff.edited_item {
+                        Some(_) => format!("Left {} as it is", diff.path.display()),
+                        None => "Export cancelled".to_string(),
+                    });
+                }
+            }
+            KeyCode::Char('r') => {
+                if let Some(diff) = self.export_diff.take() {
+                    match diff.edited_item {
+                        Some(id) => self.reimport_external_edit(id, &diff.path)?,
+                        None => self.export_diff = Some(diff),
+                    }
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down => {
+                if let Some(ref mut diff) = self.export_diff {
+                    diff.scroll_down();
+                }
+            }
+            KeyCode::Char('k') | KeyCode::Up => {
+                if let Some(ref mut diff) = self.export_diff {
+                    diff.scroll_up();
+                }
+            }
+            KeyCode::Char('y') | KeyCode::Enter => {
+                if let Some(diff) = self.export_diff.take() {
+                    match diff.edited_item {
+                        Some(id) => self.overwrite_external_edit(id)?,
+                        None => self.write_export(diff.target, diff.profile)?,
+                    }
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn write_export(&mut self, target: ExportTarget, profile: Option<ExportProfile>) -> Result<()> {
+        if let Some(item) = self.items.get(self.selected_item_index).cloned() {
+            match target.export(&item, &self.export_options_for(profile.as_ref())) {
+                Ok(path) => {
+                    let key = profile
+                        .as_ref()
+                        .map_or_else(|| target.key().to_string(), ExportProfile::key);
+                    self.record_export(&item, &key, &path)?;
+                    self.status_message = Some(format!("Exported to {}", path.display()));
+                    // Only the export path is committed, not a profile's
+                    if target == ExportTarget::Claude && profile.is_none() {
+                        self.note_export_commit(
+                            &export_commit_message("Export", std::slice::from_ref(&item)),
+                            vec![path.clone()],
+                        );
+                    }
+                    if item.category == Category::Agent {
+                        self.offer_agent_skills(&item, target, profile)?;
+                    }
+                }
+                Err(e) => {
+                    self.status_message = Some(format!("Export failed: {}", e));
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Compare the library with the export path and list what differs
+    fn open_sync(&mut self) -> Result<()> {
+        let drifts = match find_drift(&self.db.conn, &self.settings_state.export_options()) {
+            Ok(drifts) => drifts,
+            Err(e) => {
+                self.status_message = Some(format!("Sync failed: {}", e));
+                return Ok(());
+            }
+        };
+        if drifts.is_empty() {
+            self.status_message = Some(format!("In sync with {}", self.settings_state.export_path));
+        } else {
+            self.sync_state = Some(SyncState::new(drifts));
+        }
+        Ok(())
+    }
+
+    fn handle_sync_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut sync) = self.sync_state else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.sync_state = None;
+                self.status_message = Some("Sync cancelled".to_string());
+            }
+            KeyCode::Char('j') | KeyCode::Down => sync.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => sync.select_prev(),
+            KeyCode::Char('J') => sync.scroll_down(),
+            KeyCode::Char('K') => sync.scroll_up(),
+            KeyCode::Char('>') => sync.set_action(SyncAction::Push),
+            KeyCode::Char('<') => sync.set_action(SyncAction::Pull),
+            KeyCode::Char('s') => sync.set_action(SyncAction::Skip),
+            KeyCode::Char(' ') => sync.cycle_action(),
+            KeyCode::Enter => {
+                if let Some(sync) = self.sync_state.take() {
+                    let summary = apply_sync(
+                        &self.db.conn,
+                        &self.settings_state.export_options(),
+                        &sync.drifts,
+                    );
+                    self.status_message = Some(summary.describe());
+                    if !summary.pushed.is_empty() {
+                        self.note_export_commit(
+                            &export_commit_message("Sync", &summary.pushed),
+                            summary.written,
+                        );
+                    }
+                    self.refresh_data()?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Watch the export path, restarting when it changes in Settings. A
+    /// directory that doesn't exist yet is watched once an export makes it.
+    fn watch_export_path(&mut self) {
+        let dir = expand_home(self.settings_state.export_path.trim());
+        if self
+            .export_watcher
+            .as_ref()
+            .is_some_and(|watcher| watcher.dir() == dir)
+        {
+            return;
+        }
+        self.external_edits.clear();
+        self.export_watcher = if dir.is_dir() {
+            ExportWatcher::new(&dir).ok()
+        } else {
+            None
+        };
+    }
+
+    /// Queue exported files edited outside grimoire, and bring up the next
+    /// one for review once nothing else is open
+    fn poll_export_watcher(&mut self) -> Result<()> {
+        self.watch_export_path();
+        let Some(ref watcher) = self.export_watcher else {
+            return Ok(());
+        };
+        let files = watcher.changed_files();
+
+        let exporter = self.settings_state.export_options().claude_exporter();
+        let store = ItemStore::new(&self.db.conn);
+        for path in files {
+            let Some((category, name)) = exporter.item_at(&path) else {
+                continue;
+            };
+            let Some(item) = store
+                .get_by_name(&name)?
+                .filter(|item| item.category == category)
+            else {
+                continue;
+            };
+            // Files grimoire just wrote match what it renders
+            let (Some(id), Ok((_, content)), Ok(on_disk)) = (
+                item.id,
+                exporter.render(&item),
+                std::fs::read_to_string(&path),
+            ) else {
+                continue;
+            };
+            if on_disk != content && !self.external_edits.iter().any(|(queued, _)| *queued == id) {
+                self.external_edits.push_back((id, path));
+            }
+        }
+
+        if self.screen == Screen::Main && !self.has_popup() {
+            self.review_next_external_edit()?;
+        }
+        Ok(())
+    }
+
+    fn has_popup(&self) -> bool {
+        self.confirm_dialog.is_some()
+            || self.input_dialog.is_some()
+            || self.vault_picker.is_some()
+            || self.fuzzy_finder.is_some()
+            || self.template_form.is_some()
+            || self.link_picker.is_some()
+            || self.show_ai_popup
+            || self.version_diff.is_some()
+            || self.show_history_popup
+            || self.show_export_popup
+            || self.export_diff.is_some()
+            || self.import_conflicts.is_some()
+            || self.sync_state.is_some()
+            || self.type_ahead.is_some()
+    }
+
+    /// Show the diff of the next queued file edited outside grimoire,
+    /// unless it has been brought back in line since
+    fn review_next_external_edit(&mut self) -> Result<()> {
+        while let Some((id, path)) = self.external_edits.pop_front() {
+            let Some(item) = ItemStore::new(&self.db.conn).get(id)? else {
+                continue;
+            };
+            let rendered = self
+                .settings_state
+                .export_options()
+                .claude_exporter()
+                .render(&item);
+            let (Ok((_, content)), Ok(on_disk)) = (rendered, std::fs::read_to_string(&path)) else {
+                continue;
+            };
+            if on_disk != content {
+                self.export_diff =
+                    Some(ExportDiffState::external_edit(id, path, &on_disk, &content));
+                return Ok(());
+            }
+        }
+        Ok(())
+    }
+
+    /// Take an exported file edited outside grimoire back in as the item's
+    /// new version
+    fn reimport_external_edit(&mut self, id: i64, path: &Path) -> Result<()> {
+        let store = ItemStore::new(&self.db.conn);
+        let Some(item) = store.get(id)? else {
+            return Ok(());
+        };
+        let parsed = std::fs::read_to_string(path)
+            .map_err(Into::into)
+            .and_then(|text| {
+                let keys = self.settings_state.export_options().keys("claude");
+                import::item_from_export(item.category, &item.name, &text, &keys, Some(&item))
+            })
+            .and_then(|parsed| pull_into(&store, &item, parsed));
+        let updated = match parsed {
+            Ok(updated) => updated,
+            Err(e) => {
+                self.status_message = Some(format!("Re-import failed: {}", e));
                 return Ok(());
             }
+        };
+        self.record_export(&updated, ExportTarget::Claude.key(), path)?;
+        self.status_message = Some(format!(
+            "Re-imported {} from {}",
+            updated.name,
+            path.display()
+        ));
+        self.refresh_data()
+    }
 
-            let exporter = ClaudeExporter::new(&self.settings_state.export_path);
-            match exporter.export(item) {
+    /// Write grimoire's version of an item over a file edited outside it
+    fn overwrite_external_edit(&mut self, id: i64) -> Result<()> {
+        let Some(item) = ItemStore::new(&self.db.conn).get(id)? else {
+            return Ok(());
+        };
+        self.status_message = Some(
+            match self
+                .settings_state
+                .export_options()
+                .claude_exporter()
+                .export(&item)
+            {
                 Ok(path) => {
-                    self.status_message = Some(format!("Exported to {}", path.display()));
+                    self.record_export(&item, ExportTarget::Claude.key(), &path)?;
+                    format!("Overwrote {}", path.display())
                 }
-                Err(e) => {
-                    self.status_message = Some(format!("Export failed: {}", e));
+                Err(e) => format!("Export failed: {}", e),
+            },
+        );
+        Ok(())
+    }
+
+    /// Ask whether to export the skills `agent` uses along with it: the
+    /// skills it links to and the ones named in its `skills` field, unless
+    /// the target already has them as grimoire would write them
+    fn offer_agent_skills(
+        &mut self,
+        agent: &Item,
+        target: ExportTarget,
+        profile: Option<ExportProfile>,
+    ) -> Result<()> {
+        if !target.supports(Category::Skill) {
+            return Ok(());
+        }
+        let store = ItemStore::new(&self.db.conn);
+        // Skills in the trash aren't offered
+        let trashed = |item: &Item| store.is_trashed(item.id.unwrap_or_default());
+        let mut skills = Vec::new();
+        if let Some(id) = agent.id {
+            for item in LinkStore::new(&self.db.conn).targets(id)? {
+                if item.category == Category::Skill && !trashed(&item)? {
+                    skills.push(item);
+                }
+            }
+        }
+        let mut unknown = Vec::new();
+        let named = agent.skills.as_deref().unwrap_or_default().split(',');
+        for name in named.map(str::trim).filter(|name| !name.is_empty()) {
+            if skills.iter().any(|skill| skill.name == name) {
+                continue;
+            }
+            match store.get_by_name(name)? {
+                Some(item) if trashed(&item)? => {}
+                Some(item) if item.category == Category::Skill => skills.push(item),
+                _ => unknown.push(name.to_string()),
+            }
+        }
+
+        let options = self.export_options_for(profile.as_ref());
+        skills.retain(|skill| match target.render(skill, &options) {
+            Ok(Some((path, content))) => {
+                std::fs::read_to_string(path).map_or(true, |on_disk| on_disk != content)
+            }
+            _ => false,
+        });
+        if !unknown.is_empty() {
+            self.status_message = Some(format!(
+                "Exported {}; no skill named {} in the library",
+                agent.name,
+                unknown.join(", ")
+            ));
+        }
+        if skills.is_empty() {
+            return Ok(());
+        }
+
+        let names: Vec<String> = skills.iter().map(|skill| skill.name.clone()).collect();
+        let destination = profile
+            .as_ref()
+            .map_or(target.display_name(), |profile| profile.name.as_str());
+        self.confirm_dialog = Some(ConfirmDialog::export_skills(
+            &agent.name,
+            &names,
+            destination,
+        ));
+        self.pending_skills = Some((target, profile, skills));
+        Ok(())
+    }
+
+    /// Export an agent's skills to where the agent went, overwriting
+    /// whatever is there
+    fn export_skills(
+        &mut self,
+        target: ExportTarget,
+        profile: Option<ExportProfile>,
+        skills: &[Item],
+    ) -> Result<()> {
+        let options = self.export_options_for(profile.as_ref());
+        let key = profile
+            .as_ref()
+            .map_or_else(|| target.key().to_string(), ExportProfile::key);
+        let mut written = Vec::new();
+        let mut failures = Vec::new();
+        let mut paths = Vec::new();
+        for skill in skills {
+            match target.export(skill, &options) {
+                Ok(path) => {
+                    self.record_export(skill, &key, &path)?;
+                    written.push(skill.clone());
+                    paths.push(path);
                 }
+                Err(e) => failures.push(format!("{}: {}", skill.name, e)),
+            }
+        }
+
+        let mut message = format!("Exported {} skill(s)", written.len());
+        if let Some(failure) = failures.first() {
+            message.push_str(&format!(", {} failed ({})", failures.len(), failure));
+        }
+        self.status_message = Some(message);
+        if target == ExportTarget::Claude && profile.is_none() && !written.is_empty() {
+            self.note_export_commit(&export_commit_message("Export", &written), paths);
+        }
+        Ok(())
+    }
+
+    /// Add an export to the manifest and refresh the item's sync status
+    fn record_export(&mut self, item: &Item, key: &str, path: &Path) -> Result<()> {
+        ExportStore::new(&self.db.conn).record(item, key, path)?;
+        if let Some(id) = item.id {
+            self.sync_status.insert(id, SyncStatus::Exported);
+        }
+        Ok(())
+    }
+
+    /// The marked items, or the whole list when nothing is marked
+    fn bulk_export_items(&mut self) -> Vec<Item> {
+        let marked = self.marked_ids();
+        if marked.is_empty() {
+            return self.items.clone();
+        }
+        self.items
+            .iter()
+            .filter(|item| item.id.is_some_and(|id| marked.contains(&id)))
+            .cloned()
+            .collect()
+    }
+
+    fn confirm_export_all(&mut self) -> Result<()> {
+        let items = self.bulk_export_items();
+        let count = items
+            .iter()
+            .filter(|i| i.category != Category::Prompt)
+            .count();
+
+        if count == 0 {
+            self.status_message = Some("Nothing to export (prompts are copy-only)".to_string());
+        } else {
+            let changed = self
+                .settings_state
+                .export_options()
+                .claude_exporter()
+                .count_changed_on_disk(&items);
+            self.confirm_dialog = Some(ConfirmDialog::export_all(
+                count,
+                &self.settings_state.export_path,
+                changed,
+            ));
+        }
+        Ok(())
+    }
+
+    fn perform_export_all(&mut self) -> Result<()> {
+        let items = self.bulk_export_items();
+        let exporter = self.settings_state.export_options().claude_exporter();
+        let summary = exporter.export_all(&items);
+        for (name, path) in &summary.written {
+            if let Some(item) = items.iter().find(|i| &i.name == name) {
+                self.record_export(item, ExportTarget::Claude.key(), path)?;
+            }
+        }
+        self.status_message = Some(summary.describe());
+        let written: Vec<Item> = items
+            .iter()
+            .filter(|item| summary.written.iter().any(|(name, _)| *name == item.name))
+            .cloned()
+            .collect();
+        if !written.is_empty() {
+            let paths = summary.written.into_iter().map(|(_, path)| path).collect();
+            self.note_export_commit(&export_commit_message("Export", &written), paths);
+        }
+        self.clear_marks();
+        Ok(())
+    }
+
+    /// With the Git setting on, commit the files an export wrote or
+    /// removed under the export path, `paths`, in the background; the
+    /// outcome is added to the status message when it's done
+    fn note_export_commit(&mut self, message: &str, paths: Vec<PathBuf>) {
+        if !self.settings_state.git_commit {
+            return;
+        }
+        let dir = expand_home(self.settings_state.export_path.trim());
+        let message = message.to_string();
+        let tx = self.export_commit_tx.clone();
+        std::thread::spawn(move || {
+            let result = commit_exports(&dir, &paths, &message).map_err(|e| e.to_string());
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll_export_commits(&mut self) {
+        while let Ok(result) = self.export_commit_rx.try_recv() {
+            let note = match result {
+                Ok(Some(hash)) => format!("committed {}", hash),
+                Ok(None) => continue,
+                Err(e) => format!("commit failed: {}", e),
+            };
+            self.status_message = Some(match self.status_message.take() {
+                Some(status) => format!("{}; {}", status, note),
+                None => note,
+            });
+        }
+    }
+
+    fn import_from_export_path(&mut self) -> Result<()> {
+        let importer = ClaudeImporter::new(&self.settings_state.export_path);
+        let summary = import::import_new(&ItemStore::new(&self.db.conn), importer.scan())?;
+        self.finish_import(importer.base_path().display().to_string(), summary)
+    }
+
+    /// Report an import, first asking what to do with each item that
+    /// clashes with the library's
+    fn finish_import(&mut self, source: String, mut summary: ImportSummary) -> Result<()> {
+        let conflicts = std::mem::take(&mut summary.conflicts);
+        if !conflicts.is_empty() {
+            self.import_conflicts = Some(ImportConflictState::new(source, conflicts, summary));
+            return self.refresh_data();
+        }
+
+        self.status_message = Some(match summary.failures.first() {
+            Some((path, error)) => format!(
+                "{} from {} (import failed for {}: {})",
+                summary.describe(),
+                source,
+                path.display(),
+                error
+            ),
+            None => format!("{} from {}", summary.describe(), source),
+        });
+        self.refresh_data()
+    }
+
+    fn handle_import_conflict_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut state) = self.import_conflicts else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Char('m') => state.decide(ConflictMode::Skip),
+            KeyCode::Char('t') => state.decide(ConflictMode::Overwrite),
+            KeyCode::Char('b') => state.decide(ConflictMode::Duplicate),
+            KeyCode::Char('M') | KeyCode::Esc => state.decide_rest(ConflictMode::Skip),
+            KeyCode::Char('T') => state.decide_rest(ConflictMode::Overwrite),
+            KeyCode::Char('B') => state.decide_rest(ConflictMode::Duplicate),
+            KeyCode::Char('j') | KeyCode::Down => state.scroll_down(),
+            KeyCode::Char('k') | KeyCode::Up => state.scroll_up(),
+            _ => {}
+        }
+
+        if !state.is_settled() {
+            return Ok(());
+        }
+        let Some(state) = self.import_conflicts.take() else {
+            return Ok(());
+        };
+        let (source, mut summary, decided) = state.into_decisions();
+
+        // A bundle is loaded in one go, with the choice made for each name
+        if let Some((path, bundle)) = self.pending_bundle.take() {
+            let modes: HashMap<String, ConflictMode> = decided
+                .into_iter()
+                .map(|(conflict, mode)| (conflict.incoming.name, mode))
+                .collect();
+            return self.load_bundle(&path, &bundle, |name| {
+                modes.get(name).copied().unwrap_or(ConflictMode::Skip)
+            });
+        }
+
+        let store = ItemStore::new(&self.db.conn);
+        for (conflict, mode) in decided {
+            let name = PathBuf::from(&conflict.incoming.name);
+            match import::resolve(&store, conflict, mode) {
+                Ok(outcome) => summary.record(outcome),
+                Err(e) => summary.failures.push((name, e.to_string())),
+            }
+        }
+        self.finish_import(source, summary)
+    }
+
+    /// Clone or update a repository in the background; its items are
+    /// imported once it is fetched
+    fn start_repo_import(&mut self, url: &str) {
+        if self.repo_fetch.is_some() {
+            self.status_message = Some("A repository import is already running".to_string());
+            return;
+        }
+        let importer = match RepoImporter::new(url) {
+            Ok(importer) => importer,
+            Err(e) => {
+                self.status_message = Some(format!("Import failed: {}", e));
+                return;
+            }
+        };
+
+        self.status_message = Some(format!("Fetching {}...", importer.url()));
+        let (tx, rx) = mpsc::channel();
+        self.repo_fetch = Some(rx);
+        std::thread::spawn(move || {
+            let result = importer
+                .fetch()
+                .map(|_| importer)
+                .map_err(|e| format!("{:#}", e));
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll_repo_fetch(&mut self) -> Result<()> {
+        let Some(ref receiver) = self.repo_fetch else {
+            return Ok(());
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return Ok(()),
+            Err(mpsc::TryRecvError::Disconnected) => Err("fetch stopped unexpectedly".to_string()),
+        };
+        self.repo_fetch = None;
+
+        let importer = match result {
+            Ok(importer) => importer,
+            Err(error) => {
+                self.status_message = Some(format!("Import failed: {}", error));
+                return Ok(());
+            }
+        };
+        let summary = import::import_new(&ItemStore::new(&self.db.conn), importer.scan())?;
+        self.finish_import(importer.url().to_string(), summary)
+    }
+
+    /// Download a URL in the background; items are created from it once
+    /// it arrives
+    fn start_url_import(&mut self, url: &str) {
+        if self.url_fetch.is_some() {
+            self.status_message = Some("A URL import is already running".to_string());
+            return;
+        }
+        let mut importer = match UrlImporter::new(url) {
+            Ok(importer) => importer,
+            Err(e) => {
+                self.status_message = Some(format!("Import failed: {}", e));
+                return;
+            }
+        };
+
+        self.status_message = Some(format!("Downloading {}...", importer.url()));
+        let (tx, rx) = mpsc::channel();
+        self.url_fetch = Some(rx);
+        std::thread::spawn(move || {
+            let result = importer
+                .fetch()
+                .map(|_| importer)
+                .map_err(|e| format!("{:#}", e));
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll_url_fetch(&mut self) -> Result<()> {
+        let Some(ref receiver) = self.url_fetch else {
+            return Ok(());
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return Ok(()),
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err("download stopped unexpectedly".to_string())
+            }
+        };
+        self.url_fetch = None;
+
+        let importer = match result {
+            Ok(importer) => importer,
+            Err(error) => {
+                self.status_message = Some(format!("Import failed: {}", error));
+                return Ok(());
+            }
+        };
+        let summary = import::import_new(&ItemStore::new(&self.db.conn), importer.scan())?;
+        self.finish_import(importer.url().to_string(), summary)
+    }
+
+    /// Ask where to write a bundle of the marked items, or of the whole
+    /// list when nothing is marked
+    fn prompt_export_bundle(&mut self) {
+        let items = self.bulk_export_items();
+        let stem = match items.as_slice() {
+            [] => {
+                self.status_message = Some("Nothing to bundle".to_string());
+                return;
+            }
+            [item] => item.name.clone(),
+            _ => "bundle".to_string(),
+        };
+        self.input_dialog = Some(InputDialog::export_bundle(
+            items.len(),
+            &format!("./{}.{}", stem, BUNDLE_EXTENSION),
+        ));
+    }
+
+    /// Ask where to write a zip of the marked items, or of the whole list
+    /// when nothing is marked, starting from the profile's directory
+    fn prompt_export_zip(&mut self, profile: Option<&ExportProfile>) {
+        let items = self.bulk_export_items();
+        let file_name = match items.as_slice() {
+            [] => {
+                self.status_message = Some("Nothing to export".to_string());
+                return;
+            }
+            [item] => format!("{}.zip", item.name),
+            _ => ZIP_FILE_NAME.to_string(),
+        };
+        let options = self.export_options_for(profile);
+        self.input_dialog = Some(InputDialog::export_zip(
+            items.len(),
+            &options.project_destination(&file_name),
+        ));
+    }
+
+    /// Write the zip, first asking before replacing a file already there
+    fn perform_export_zip(&mut self, path: &str) {
+        let path = expand_home(path.trim());
+        if path.is_file() {
+            self.confirm_dialog = Some(ConfirmDialog::overwrite(&path.display().to_string()));
+            self.pending_zip = Some(path);
+            return;
+        }
+        self.write_export_zip(&path);
+    }
+
+    /// Like copies written to a path, the zip isn't tracked
+    fn write_export_zip(&mut self, path: &Path) {
+        let items = self.bulk_export_items();
+        self.status_message = Some(match write_zip(&items, path) {
+            Ok(count) => format!("Zipped {} items into {}", count, path.display()),
+            Err(e) => format!("Export failed: {}", e),
+        });
+    }
+
+    fn perform_export_bundle(&mut self, path: &str) {
+        let path = expand_home(path.trim());
+        let items = self.bulk_export_items();
+        self.status_message = Some(match Bundle::write(&self.db.conn, &items, &path) {
+            Ok(count) => format!("Bundled {} items into {}", count, path.display()),
+            Err(e) => format!("Bundle failed: {}", e),
+        });
+    }
+
+    /// Read a bundle and load it, first asking what to do with each of its
+    /// items that differs from the library's item of the same name
+    fn open_bundle(&mut self, path: &str) -> Result<()> {
+        let path = expand_home(path.trim());
+        let bundle = match Bundle::open(&path) {
+            Ok(bundle) => bundle,
+            Err(e) => {
+                self.status_message = Some(format!("Import failed: {}", e));
+                return Ok(());
+            }
+        };
+
+        let conflicts = bundle.conflicts(&ItemStore::new(&self.db.conn))?;
+        if conflicts.is_empty() {
+            return self.load_bundle(&path, &bundle, |_| ConflictMode::Skip);
+        }
+        self.import_conflicts = Some(ImportConflictState::new(
+            path.display().to_string(),
+            conflicts,
+            ImportSummary::default(),
+        ));
+        self.pending_bundle = Some((path, bundle));
+        Ok(())
+    }
+
+    fn load_bundle(
+        &mut self,
+        path: &Path,
+        bundle: &Bundle,
+        mode: impl Fn(&str) -> ConflictMode,
+    ) -> Result<()> {
+        let summary = bundle.load_into(&self.db.conn, mode)?;
+        self.status_message = Some(match summary.failures.first() {
+            Some((name, error)) => format!(
+                "{} from {} (failed for {}: {})",
+                summary.describe(),
+                path.display(),
+                name,
+                error
+            ),
+            None => format!("{} from {}", summary.describe(), path.display()),
+        });
+        self.refresh_data()
+    }
+
+    /// Post the selected item as a secret gist in the background; its URL
+    /// is copied once it is created
+    fn share_selected(&mut self) {
+        let Some(item) = self.selected_item() else {
+            return;
+        };
+        if self.gist_share.is_some() {
+            self.status_message = Some("Already sharing an item".to_string());
+            return;
+        }
+        if github_token(&self.settings_state.github_token).is_none() {
+            self.status_message = Some(
+                "Sharing needs a GitHub token: add one in Settings or set $GITHUB_TOKEN"
+                    .to_string(),
+            );
+            return;
+        }
+        self.confirm_dialog = Some(ConfirmDialog::share(&item.name));
+    }
+
+    fn perform_share(&mut self) {
+        let Some(item) = self.selected_item().cloned() else {
+            return;
+        };
+        let Some(token) = github_token(&self.settings_state.github_token) else {
+            return;
+        };
+
+        self.status_message = Some(format!("Sharing '{}' as a gist...", item.name));
+        let (tx, rx) = mpsc::channel();
+        self.gist_share = Some(rx);
+        std::thread::spawn(move || {
+            let result = create_gist(&token, &item).map_err(|e| format!("{:#}", e));
+            let _ = tx.send(result);
+        });
+    }
+
+    fn poll_gist_share(&mut self) {
+        let Some(ref receiver) = self.gist_share else {
+            return;
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err("sharing stopped unexpectedly".to_string())
+            }
+        };
+        self.gist_share = None;
+
+        match result {
+            Ok(url) => {
+                let copied = self.copy_content(&url);
+                self.status_message = Some(if copied {
+                    format!("Shared as {} (URL copied)", url)
+                } else {
+                    format!("Shared as {}", url)
+                });
             }
+            Err(error) => self.status_message = Some(format!("Share failed: {}", error)),
         }
-        Ok(())
     }
 
     fn open_search(&mut self) -> Result<()> {
-        self.search_state = SearchState::default();
+        self.search_state = SearchState {
+            scope: self.search_scope(),
+            ..SearchState::default()
+        };
         self.screen = Screen::Search;
         Ok(())
     }
 
+    /// The main list's category or tags, when searches are scoped to them
+    fn search_scope(&self) -> Option<SearchScope> {
+        if !self.search_scoped || self.special_list.is_some() {
+            return None;
+        }
+        match (self.selected_category, self.selected_tags.as_slice()) {
+            (Some(category), _) => Some(SearchScope::Category(category)),
+            (None, []) => None,
+            (None, tags) => Some(SearchScope::Tags {
+                tags: tags.to_vec(),
+                match_all: self.tag_match_all,
+            }),
+        }
+    }
+
+    /// Switch between searching the whole library and only the current
+    /// category or tags, remembering the choice for later searches
+    fn toggle_search_scope(&mut self) -> Result<()> {
+        self.search_scoped = !self.search_scoped;
+        SettingsStore::new(&self.db.conn).set(
+            "search_scope",
+            if self.search_scoped {
+                "list"
+            } else {
+                "library"
+            },
+        )?;
+        self.search_state.scope = self.search_scope();
+        if self.search_scoped && self.search_state.scope.is_none() {
+            self.status_message =
+                Some("Select a category or tags to search within them".to_string());
+        }
+        self.perform_search();
+        Ok(())
+    }
+
     fn open_settings(&mut self) -> Result<()> {
         self.settings_state.has_changes = false;
+        self.settings_state.notice = None;
+        self.settings_state.usage_totals = UsageStore::new(&self.db.conn).totals()?;
         self.screen = Screen::Settings;
         Ok(())
     }
 
-    fn perform_search(&mut self) -> Result<()> {
+    /// Search in the background; the old results are dropped until the
+    /// new ones arrive
+    fn perform_search(&mut self) {
+        self.search_state.results.clear();
+        self.search_state.selected_index = 0;
         if self.search_state.query.is_empty() {
-            self.search_state.results.clear();
-            return Ok(());
+            self.pending_search = None;
+            return;
         }
 
-        let store = ItemStore::new(&self.db.conn);
-        self.search_state.results = store.search(&self.search_state.query)?;
-        self.search_state.selected_index = 0;
-        Ok(())
+        self.pending_search = Some(
+            self.loader.search(
+                &self.search_state.query,
+                self.search_state
+                    .scope
+                    .as_ref()
+                    .map(SearchScope::filters)
+                    .unwrap_or_default(),
+                self.search_state.include_archived,
+            ),
+        );
     }
 
     fn save_item(&mut self) -> Result<()> {
@@ -930,10 +4208,73 @@ impl App {
 
         let store = ItemStore::new(&self.db.conn);
 
+        // Names are unique across the trash too
+        if let Some(other) = store.get_by_name(&self.edit_state.item.name)? {
+            if other.id != self.edit_state.item.id || self.edit_state.is_new {
+                let name = &self.edit_state.item.name;
+                self.edit_state.notice = Some(if store.is_trashed(other.id.unwrap_or_default())? {
+                    format!("'{}' is in the trash; restore it or rename", name)
+                } else {
+                    format!("'{}' is already taken", name)
+                });
+                return Ok(());
+            }
+        }
+
+        // Version as last saved, to tell our own exports from outside edits
+        let previous = match self.edit_state.item.id {
+            Some(id) if !self.edit_state.is_new => store.get(id)?,
+            _ => None,
+        };
+
         if self.edit_state.is_new {
-            store.insert(&self.edit_state.item)?;
+            let id = store.insert(&self.edit_state.item)?;
+            self.edit_state.item.id = Some(id);
         } else {
-            store.update(&self.edit_state.item)?;
+            let message = self.edit_state.version_message.take();
+            store.update(&self.edit_state.item, message.as_deref())?;
+        }
+
+        if let (true, Some(id)) = (self.edit_state.links_changed, self.edit_state.item.id) {
+            let target_ids: Vec<i64> = self.edit_state.links.iter().map(|(id, _)| *id).collect();
+            LinkStore::new(&self.db.conn).set(id, &target_ids)?;
+        }
+
+        let moved = match previous.as_ref() {
+            Some(previous) => self.move_renamed_exports(previous, &self.edit_state.item)?,
+            None => None,
+        };
+
+        let exported =
+            self.settings_state.auto_export && self.edit_state.item.category != Category::Prompt;
+        if exported {
+            self.status_message = Some(self.auto_export(&self.edit_state.item, previous.as_ref())?);
+        }
+        if let Some(note) = moved.as_ref() {
+            self.status_message = Some(match self.status_message.take() {
+                Some(message) => format!("{}; {}", message, note),
+                None => format!("Saved; {}", note),
+            });
+        }
+        if exported || moved.is_some() {
+            let item = &self.edit_state.item;
+            let message = match previous.as_ref() {
+                Some(previous) if previous.name != item.name => format!(
+                    "Rename {} {} to {}",
+                    item.category.as_str(),
+                    previous.name,
+                    item.name
+                ),
+                _ => export_commit_message("Update", std::slice::from_ref(item)),
+            };
+            // The files under the old and new names, whichever changed
+            let exporter = self.settings_state.export_options().claude_exporter();
+            let paths = std::iter::once(item)
+                .chain(previous.as_ref())
+                .filter_map(|item| exporter.render(item).ok())
+                .map(|(path, _)| path)
+                .collect();
+            self.note_export_commit(&message, paths);
         }
 
         self.edit_state.has_changes = false;
@@ -942,26 +4283,485 @@ impl App {
         Ok(())
     }
 
+    /// Re-export a saved item to the export path. The file is only replaced
+    /// if it is missing or still matches the previous version's export, so
+    /// edits made outside grimoire are never lost silently.
+    fn auto_export(&self, item: &Item, previous: Option<&Item>) -> Result<String> {
+        let exporter = self.settings_state.export_options().claude_exporter();
+
+        if let Ok((path, content)) = exporter.render(item) {
+            if let Ok(on_disk) = std::fs::read_to_string(&path) {
+                let ours = on_disk == content
+                    || previous
+                        .and_then(|p| exporter.render(p).ok())
+                        .is_some_and(|(_, previous_content)| previous_content == on_disk);
+                if !ours {
+                    return Ok(
+                        "Saved; not exported because the file changed on disk (press 'x' to review)"
+                            .to_string(),
+                    );
+                }
+            }
+        }
+
+        Ok(match exporter.export(item) {
+            Ok(path) => {
+                ExportStore::new(&self.db.conn).record(item, ExportTarget::Claude.key(), &path)?;
+                format!("Saved and exported to {}", path.display())
+            }
+            Err(e) => format!("Saved; export failed: {}", e),
+        })
+    }
+
+    /// After a rename or category change, move the item's exported files to
+    /// where it now renders so files under the old name don't pile up. Only
+    /// whole-file exports made from the current base directories are moved,
+    /// and files edited outside grimoire stay where they are. Returns a note
+    /// for the status bar when there was anything to move.
+    fn move_renamed_exports(&self, previous: &Item, item: &Item) -> Result<Option<String>> {
+        let Some(id) = item.id else {
+            return Ok(None);
+        };
+        let options = self.settings_state.export_options();
+        let exports = ExportStore::new(&self.db.conn);
+        let (mut moved, mut left) = (0, 0);
+
+        let profiles = self.settings_state.export_profiles();
+        for (key, path) in exports.paths(id)? {
+            let (target, options) = match ExportTarget::from_key(&key) {
+                Some(target) => (target, options.clone()),
+                None => match profiles.iter().find(|profile| profile.key() == key) {
+                    Some(profile) => (profile.target, profile.options(&options)),
+                    None => continue,
+                },
+            };
+            let (Ok(Some((old_path, _))), Ok(Some((new_path, _)))) = (
+                target.render(previous, &options),
+                target.render(item, &options),
+            ) else {
+                continue;
+            };
+            if old_path != Path::new(&path) || old_path == new_path {
+                continue;
+            }
+            let Ok(on_disk) = std::fs::read_to_string(&old_path) else {
+                continue;
+            };
+
+            if !self.is_own_export(id, target, &options, &on_disk)? {
+                left += 1;
+                continue;
+            }
+            match move_export(&old_path, &new_path, || target.export(item, &options)) {
+                Ok(written) => {
+                    exports.record(item, &key, &written)?;
+                    moved += 1;
+                }
+                Err(_) => left += 1,
+            }
+        }
+
+        let plural = |n: usize| if n == 1 { "" } else { "s" };
+        Ok(match (moved, left) {
+            (0, 0) => None,
+            (moved, 0) => Some(format!(
+                "moved {} export{} to the new name",
+                moved,
+                plural(moved)
+            )),
+            (0, left) => Some(format!(
+                "left {} export{} under the old name",
+                left,
+                plural(left)
+            )),
+            (moved, left) => Some(format!(
+                "moved {} export{}, left {} under the old name",
+                moved,
+                plural(moved),
+                left
+            )),
+        })
+    }
+
+    /// Whether `on_disk` is what some saved version of the item exports to,
+    /// meaning nobody has edited the file outside grimoire
+    fn is_own_export(
+        &self,
+        id: i64,
+        target: ExportTarget,
+        options: &ExportOptions,
+        on_disk: &str,
+    ) -> Result<bool> {
+        let store = ItemStore::new(&self.db.conn);
+        for version in store.list_versions(id)? {
+            let Some(item) = store.get_version(id, version.version)? else {
+                continue;
+            };
+            if let Ok(Some((_, content))) = target.render(&item, options) {
+                if content == on_disk {
+                    return Ok(true);
+                }
+            }
+        }
+        Ok(false)
+    }
+
+    fn open_link_picker(&mut self) -> Result<()> {
+        let candidates = LinkStore::new(&self.db.conn).candidates(self.edit_state.item.id)?;
+        self.link_picker = Some(LinkPickerState::new(candidates, &self.edit_state.links));
+        Ok(())
+    }
+
+    fn handle_link_picker_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut picker) = self.link_picker else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc => self.link_picker = None,
+            KeyCode::Char('j') | KeyCode::Down => picker.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => picker.select_prev(),
+            KeyCode::Char(' ') => picker.toggle(),
+            KeyCode::Enter => {
+                let links = picker.links();
+                let state = &mut self.edit_state;
+                let previous: Vec<(i64, String)> = std::mem::replace(
+                    &mut state.links,
+                    links
+                        .iter()
+                        .filter_map(|link| Some((link.id?, link.name.clone())))
+                        .collect(),
+                );
+
+                // Agents list their skills in frontmatter; keep it in step,
+                // dropping unlinked skills and adding linked ones but keeping
+                // the skills listed by hand
+                if state.item.category == Category::Agent {
+                    let unlinked = |name: &str| {
+                        previous.iter().any(|(_, linked)| linked == name)
+                            && !links.iter().any(|link| link.name == name)
+                    };
+                    let mut skills: Vec<String> = state
+                        .item
+                        .skills
+                        .as_deref()
+                        .unwrap_or_default()
+                        .split(',')
+                        .map(str::trim)
+                        .filter(|name| !name.is_empty() && !unlinked(name))
+                        .map(str::to_string)
+                        .collect();
+                    for link in links.iter().filter(|link| link.category == Category::Skill) {
+                        if !skills.contains(&link.name) {
+                            skills.push(link.name.clone());
+                        }
+                    }
+                    state.item.skills = (!skills.is_empty()).then(|| skills.join(", "));
+                }
+
+                state.links_changed = true;
+                state.has_changes = true;
+                self.link_picker = None;
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn handle_fuzzy_finder_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut finder) = self.fuzzy_finder else {
+            return Ok(());
+        };
+        let ctrl = key.modifiers.contains(KeyModifiers::CONTROL);
+
+        match key.code {
+            KeyCode::Esc => self.fuzzy_finder = None,
+            KeyCode::Down => finder.select_next(),
+            KeyCode::Up => finder.select_prev(),
+            KeyCode::Char('n') | KeyCode::Char('j') if ctrl => finder.select_next(),
+            KeyCode::Char('p') | KeyCode::Char('k') if ctrl => finder.select_prev(),
+            KeyCode::Enter => {
+                let Some(item) = finder.selected_item().cloned() else {
+                    return Ok(());
+                };
+                self.fuzzy_finder = None;
+
+                let Some(id) = item.id else {
+                    return Ok(());
+                };
+                if self.items.iter().any(|i| i.id == item.id) {
+                    return self.select_item_with_id(id, true);
+                }
+                // Open the item's category when the current list lacks it
+                self.select_category(Some(item.category));
+                self.select_on_load = Some((id, true));
+                self.sidebar_index = SIDEBAR_CATEGORIES_START
+                    + Category::all()
+                        .iter()
+                        .position(|c| *c == item.category)
+                        .unwrap_or(0);
+            }
+            KeyCode::Backspace => finder.delete_char(),
+            KeyCode::Char(c) if !ctrl => finder.insert_char(c),
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn open_vault_picker(&mut self) -> Result<()> {
+        self.vault_picker = Some(VaultPickerState::new(Vaults::list()?, &self.vault));
+        Ok(())
+    }
+
+    fn handle_vault_picker_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut picker) = self.vault_picker else {
+            return Ok(());
+        };
+
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => self.vault_picker = None,
+            KeyCode::Char('j') | KeyCode::Down => picker.select_next(),
+            KeyCode::Char('k') | KeyCode::Up => picker.select_prev(),
+            KeyCode::Char('n') => self.input_dialog = Some(InputDialog::new_vault()),
+            KeyCode::Enter => {
+                let vault = picker.selected_vault().map(|v| v.to_string());
+                self.vault_picker = None;
+                if let Some(vault) = vault {
+                    self.switch_vault(&vault)?;
+                }
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    fn create_vault(&mut self, name: &str) -> Result<()> {
+        self.vault_picker = None;
+        if let Err(e) = Vaults::validate(name) {
+            self.status_message = Some(e.to_string());
+            return Ok(());
+        }
+        if Vaults::path(name)?.exists() {
+            self.status_message = Some(format!("Vault '{}' already exists", name));
+            return Ok(());
+        }
+
+        self.switch_vault(name)
+    }
+
+    /// Open another vault's database and reload everything from it
+    fn switch_vault(&mut self, vault: &str) -> Result<()> {
+        if vault == self.vault {
+            return Ok(());
+        }
+
+        self.db = Database::open(vault)?;
+        self.vault = vault.to_string();
+        Vaults::remember(vault)?;
+
+        self.settings_state = Self::load_settings(&self.db, vault)?;
+        self.settings_state.vault = vault.to_string();
+        self.reload_user_actions(None)?;
+        self.special_list = None;
+        self.selected_collection = None;
+        self.selected_category = None;
+        self.selected_tags.clear();
+        self.last_deleted.clear();
+        self.clear_marks();
+        self.selected_item_index = 0;
+        self.sidebar_index = 0;
+        self.status_message = Some(format!("Opened vault '{}'", vault));
+        self.refresh_data()
+    }
+
+    fn prompt_backup(&mut self) -> Result<()> {
+        let file_name = format!(
+            "grimoire-{}.db",
+            chrono::Local::now().format("%Y%m%d-%H%M%S")
+        );
+        let path = Database::backup_dir()?.join(file_name);
+        self.input_dialog = Some(InputDialog::backup_database(&path.to_string_lossy()));
+        Ok(())
+    }
+
+    /// Offer the most recent backup in the backup directory
+    fn prompt_restore(&mut self) -> Result<()> {
+        let dir = Database::backup_dir()?;
+        let latest = std::fs::read_dir(&dir)
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+            .max();
+
+        let default_path = latest.unwrap_or_else(|| dir.join(""));
+        self.input_dialog = Some(InputDialog::restore_database(
+            &default_path.to_string_lossy(),
+        ));
+        Ok(())
+    }
+
+    fn perform_backup(&mut self, path: &str) {
+        let path = expand_home(path.trim());
+        self.settings_state.notice = Some(match self.db.backup(&path) {
+            Ok(()) => format!("Backed up to {}", path.display()),
+            Err(e) => format!("Backup failed: {}", e),
+        });
+    }
+
+    /// Ask before a restore replaces the database
+    fn confirm_restore(&mut self, path: &str) {
+        let shown = expand_home(path.trim());
+        let file_name = shown.file_name().unwrap_or(shown.as_os_str());
+        self.confirm_dialog = Some(ConfirmDialog::restore_database(
+            &file_name.to_string_lossy(),
+        ));
+        self.pending_restore = Some(path.to_string());
+    }
+
+    fn perform_restore(&mut self, path: &str) -> Result<()> {
+        let path = expand_home(path.trim());
+        if let Err(e) = self.db.restore(&path) {
+            self.settings_state.notice = Some(format!("Restore failed: {}", e));
+            return Ok(());
+        }
+
+        // Everything on screen came from the old database
+        let usage_totals = UsageStore::new(&self.db.conn).totals()?;
+        self.settings_state = SettingsState {
+            focused_field: self.settings_state.focused_field,
+            vault: self.vault.clone(),
+            usage_totals,
+            notice: Some(format!("Restored from {}", path.display())),
+            ..Self::load_settings(&self.db, &self.vault)?
+        };
+        self.reload_user_actions(None)?;
+        self.special_list = None;
+        self.selected_collection = None;
+        self.selected_category = None;
+        self.selected_tags.clear();
+        self.last_deleted.clear();
+        self.clear_marks();
+        self.refresh_data()
+    }
+
     fn save_settings(&mut self) -> Result<()> {
         let store = SettingsStore::new(&self.db.conn);
 
         // Trim whitespace from values before saving
         let api_key = self.settings_state.api_key.trim();
+        let github_token = self.settings_state.github_token.trim();
         let llm_model = self.settings_state.llm_model.trim();
+        let light_model = self.settings_state.light_model.trim();
+        let base_url = self.settings_state.base_url.trim();
+        // Parsed values, so empty or invalid numbers fall back to defaults
+        let config = self.settings_state.llm_config();
         let export_path = self.settings_state.export_path.trim();
+        if let Some((field, problem)) = self.settings_state.validate() {
+            self.settings_state.focused_field = field;
+            self.settings_state.cursor_pos =
+                self.settings_state.current_field_value().chars().count();
+            self.settings_state.notice = Some(format!("Save failed: {}", problem));
+            return Ok(());
+        }
+        let list_columns = parse_list_columns(&self.settings_state.list_columns)
+            .unwrap_or_default()
+            .iter()
+            .map(|(column, width)| format!("{}:{}", column.as_str(), width))
+            .collect::<Vec<_>>()
+            .join(",");
+        let template_variables = format_variable_presets(
+            &parse_variable_presets(&self.settings_state.template_variables).unwrap_or_default(),
+        );
+        let export_profiles = format_export_profiles(&self.settings_state.export_profiles());
 
         store.set("llm_provider", self.settings_state.provider.display_name())?;
-        store.set("api_key", api_key)?;
+        let secrets = self
+            .settings_state
+            .keychain_available
+            .then(|| SecretStore::new(&self.vault));
+        let key_in_keychain = save_secret(&store, secrets.as_ref(), "api_key", api_key)?;
+        let token_in_keychain =
+            save_secret(&store, secrets.as_ref(), "github_token", github_token)?;
+        let saved_in_keychain = key_in_keychain && token_in_keychain;
+        let key_in_database = (!key_in_keychain && !api_key.is_empty())
+            || (!token_in_keychain && !github_token.is_empty());
         store.set("llm_model", llm_model)?;
+        store.set("llm_light_model", light_model)?;
+        store.set("llm_base_url", base_url)?;
+        store.set("llm_timeout_secs", &config.timeout_secs.to_string())?;
+        store.set("llm_max_retries", &config.max_retries.to_string())?;
         store.set("export_path", export_path)?;
+        store.set(
+            "auto_export",
+            if self.settings_state.auto_export {
+                "true"
+            } else {
+                "false"
+            },
+        )?;
+        store.set(
+            "export_git_commit",
+            if self.settings_state.git_commit {
+                "true"
+            } else {
+                "false"
+            },
+        )?;
+        store.set(
+            "vim_mode",
+            if self.settings_state.vim_mode {
+                "true"
+            } else {
+                "false"
+            },
+        )?;
+        store.set("export_profiles", &export_profiles)?;
+        for (target, _, _) in FRONTMATTER_TARGETS {
+            match self.settings_state.frontmatter_keys.get(target) {
+                Some(spec) if !spec.trim().is_empty() => {
+                    store.set(&frontmatter_key(target), spec.trim())?
+                }
+                _ => store.delete(&frontmatter_key(target))?,
+            }
+        }
+        store.set("list_columns", &list_columns)?;
+        store.set(
+            "stale_months",
+            &self.settings_state.stale_months().to_string(),
+        )?;
+        store.set("template_variables", &template_variables)?;
+        for category in Category::all() {
+            match self.settings_state.skeletons.get(&category) {
+                Some(skeleton) => store.set(&skeleton_key(category), skeleton)?,
+                None => store.delete(&skeleton_key(category))?,
+            }
+        }
 
         // Update state with trimmed values
         self.settings_state.api_key = api_key.to_string();
+        self.settings_state.github_token = github_token.to_string();
         self.settings_state.llm_model = llm_model.to_string();
+        self.settings_state.light_model = light_model.to_string();
+        self.settings_state.base_url = base_url.to_string();
+        self.settings_state.timeout_secs = config.timeout_secs.to_string();
+        self.settings_state.max_retries = config.max_retries.to_string();
         self.settings_state.export_path = export_path.to_string();
+        self.settings_state.list_columns = list_columns;
+        self.settings_state.stale_months = self.settings_state.stale_months().to_string();
+        self.settings_state.template_variables = template_variables;
+        self.settings_state.export_profiles = export_profiles;
 
+        self.settings_state.keychain_available = saved_in_keychain;
+        self.settings_state.mark_ai_saved();
         self.settings_state.has_changes = false;
-        self.status_message = Some("Settings saved".to_string());
+        self.settings_state.notice = None;
+        self.status_message = Some(if key_in_database {
+            "Settings saved (keychain unavailable, keys stored in the database)".to_string()
+        } else {
+            "Settings saved".to_string()
+        });
         Ok(())
     }
 
@@ -985,6 +4785,7 @@ impl App {
                 let item_name = item.name.clone();
                 self.history_state = Some(HistoryState::new(versions, item_name));
                 self.show_history_popup = true;
+                self.load_history_preview()?;
             }
         }
         Ok(())
@@ -1008,11 +4809,23 @@ impl App {
                 if let Some(ref mut state) = self.history_state {
                     state.select_next();
                 }
+                self.load_history_preview()?;
             }
             KeyCode::Char('k') | KeyCode::Up => {
                 if let Some(ref mut state) = self.history_state {
                     state.select_previous();
                 }
+                self.load_history_preview()?;
+            }
+            KeyCode::Char('J') | KeyCode::PageDown => {
+                if let Some(ref mut state) = self.history_state {
+                    state.scroll_preview_down();
+                }
+            }
+            KeyCode::Char('K') | KeyCode::PageUp => {
+                if let Some(ref mut state) = self.history_state {
+                    state.scroll_preview_up();
+                }
             }
             KeyCode::Enter => {
                 // View the selected version
@@ -1022,11 +4835,141 @@ impl App {
                 // Restore to selected version
                 self.restore_selected_version()?;
             }
+            KeyCode::Char('d') => self.diff_selected_version()?,
+            KeyCode::Char('a') => {
+                self.diff_selected_version()?;
+                self.summarize_version_diff();
+            }
+            _ => {}
+        }
+        Ok(())
+    }
+
+    /// Load the selected version into the history preview pane
+    fn load_history_preview(&mut self) -> Result<()> {
+        let Some(item_id) = self.selected_item().and_then(|item| item.id) else {
+            return Ok(());
+        };
+        let Some(ref mut state) = self.history_state else {
+            return Ok(());
+        };
+        let Some(version) = state.selected_version().map(|v| v.version) else {
+            return Ok(());
+        };
+
+        let store = ItemStore::new(&self.db.conn);
+        state.set_preview(store.get_version(item_id, version)?);
+        Ok(())
+    }
+
+    /// Compare the selected version with the current version of the item
+    fn diff_selected_version(&mut self) -> Result<()> {
+        let Some(version) = self
+            .history_state
+            .as_ref()
+            .and_then(|state| state.selected_version())
+            .map(|v| v.version)
+        else {
+            return Ok(());
+        };
+        let Some(item_id) = self.selected_item().and_then(|item| item.id) else {
+            return Ok(());
+        };
+
+        let store = ItemStore::new(&self.db.conn);
+        if let (Some(old), Some(current)) =
+            (store.get_version(item_id, version)?, store.get(item_id)?)
+        {
+            self.version_diff = Some(VersionDiffState::new(&old, &current));
+        }
+        Ok(())
+    }
+
+    fn handle_version_diff_key(&mut self, key: KeyEvent) -> Result<()> {
+        let Some(ref mut diff) = self.version_diff else {
+            return Ok(());
+        };
+        match key.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                self.version_diff = None;
+                if let Some((task, _, _)) = self.version_summary.take() {
+                    task.cancel();
+                }
+            }
+            KeyCode::Char('j') | KeyCode::Down => diff.scroll_down(),
+            KeyCode::Char('k') | KeyCode::Up => diff.scroll_up(),
+            KeyCode::Char('a') => self.summarize_version_diff(),
             _ => {}
         }
         Ok(())
     }
 
+    /// Ask the AI, in the background, for a short account of the changes
+    /// in the open version diff
+    fn summarize_version_diff(&mut self) {
+        let Some(ref mut diff) = self.version_diff else {
+            return;
+        };
+        if diff.summarizing || diff.lines.is_empty() {
+            return;
+        }
+        if !self.settings_state.has_llm() {
+            diff.summary =
+                Some("Summaries need an AI provider: set one up in Settings (s)".to_string());
+            return;
+        }
+
+        let request = LlmRequest {
+            system_prompt: SUMMARY_PROMPT.to_string(),
+            history: Vec::new(),
+            user_message: diff.summary_request(),
+            max_tokens: 400,
+        };
+        diff.summarizing = true;
+        diff.summary = None;
+
+        let config = self.settings_state.light_llm_config();
+        let (tx, rx) = mpsc::channel();
+        let task = BackgroundCompletion::spawn(config.clone(), request, tx);
+        self.version_summary = Some((task, rx, config));
+    }
+
+    fn poll_version_summary(&mut self) -> Result<()> {
+        let Some((_, ref receiver, _)) = self.version_summary else {
+            return Ok(());
+        };
+
+        let result = match receiver.try_recv() {
+            Ok(result) => result,
+            Err(mpsc::TryRecvError::Empty) => return Ok(()),
+            Err(mpsc::TryRecvError::Disconnected) => Err("summary stopped unexpectedly".into()),
+        };
+        let Some((_, _, config)) = self.version_summary.take() else {
+            return Ok(());
+        };
+
+        let summary = match result {
+            Ok(response) => {
+                if let Some(usage) = response.usage {
+                    let cost = estimate_cost(&config.provider, &config.model, usage);
+                    UsageStore::new(&self.db.conn).record(
+                        &config.provider,
+                        &config.model,
+                        usage,
+                        cost,
+                    )?;
+                }
+                response.content.trim().to_string()
+            }
+            Err(error) => format!("Summary failed: {}", error),
+        };
+        if let Some(ref mut diff) = self.version_diff {
+            diff.summary = Some(summary);
+            diff.summarizing = false;
+        }
+        Ok(())
+    }
+
     fn view_selected_version(&mut self) -> Result<()> {
         if let Some(ref state) = self.history_state {
             if let Some(version) = state.selected_version() {
@@ -1094,3 +5037,152 @@ impl App {
         Ok(())
     }
 }
+
+/// Suspend the TUI and edit `text` in `$VISUAL` / `$EDITOR` through a temp
+/// file. The outer error is the terminal failing to come back; the inner one
+/// is the editor failing.
+fn edit_in_editor<W: Write>(
+    terminal: &mut Terminal<CrosstermBackend<W>>,
+    text: &str,
+) -> Result<Result<String>> {
+    let path = create_temp_file(text)?;
+
+    let _ = execute!(
+        terminal.backend_mut(),
+        DisableBracketedPaste,
+        LeaveAlternateScreen
+    );
+    let _ = disable_raw_mode();
+    let result = run_editor(&path).and_then(|()| Ok(std::fs::read_to_string(&path)?));
+    enable_raw_mode()?;
+    execute!(
+        terminal.backend_mut(),
+        EnterAlternateScreen,
+        EnableBracketedPaste
+    )?;
+    terminal.clear()?;
+    let _ = std::fs::remove_file(&path);
+
+    // Editors add a final newline; don't let it pile up
+    Ok(result.map(|mut edited| {
+        if !text.ends_with('\n') && edited.ends_with('\n') {
+            edited.pop();
+        }
+        edited
+    }))
+}
+
+/// Write `text` to a new file in the temp directory that only the user can
+/// read. The file must not exist yet, so another user can't plant a file or
+/// a symlink under the name beforehand.
+fn create_temp_file(text: &str) -> Result<PathBuf> {
+    let mut options = std::fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    std::os::unix::fs::OpenOptionsExt::mode(&mut options, 0o600);
+
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default()
+        .subsec_nanos();
+    for attempt in 0..100u32 {
+        let path = std::env::temp_dir().join(format!(
+            "grimoire-{}-{:08x}.md",
+            std::process::id(),
+            nanos.wrapping_add(attempt.wrapping_mul(0x9e37_79b9))
+        ));
+        match options.open(&path) {
+            Ok(mut file) => {
+                file.write_all(text.as_bytes())?;
+                return Ok(path);
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(eyre!("could not create a temp file to edit"))
+}
+
+/// Run `$VISUAL`, `$EDITOR`, or `vi` on `path` and wait for it to exit.
+/// The variable may include arguments, e.g. `code --wait`.
+fn run_editor(path: &Path) -> Result<()> {
+    let editor = ["VISUAL", "EDITOR"]
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .find(|e| !e.trim().is_empty())
+        .unwrap_or_else(|| "vi".to_string());
+
+    let mut parts = editor.split_whitespace();
+    let program = parts.next().unwrap_or("vi");
+    let status = Command::new(program)
+        .args(parts)
+        .arg(path)
+        .status()
+        .map_err(|e| eyre!("could not run '{}': {}", editor, e))?;
+
+    if !status.success() {
+        return Err(eyre!("'{}' exited with {}", editor, status));
+    }
+    Ok(())
+}
+
+/// Keep a secret in the vault's keychain entry, or in the settings table
+/// when the keychain is unavailable. Returns whether the keychain took it.
+fn save_secret(
+    store: &SettingsStore,
+    secrets: Option<&SecretStore>,
+    name: &str,
+    value: &str,
+) -> Result<bool> {
+    let in_keychain = secrets.is_some_and(|secrets| {
+        if value.is_empty() {
+            secrets.delete(name).is_ok()
+        } else {
+            secrets.set(name, value).is_ok()
+        }
+    });
+    if in_keychain {
+        store.delete(name)?;
+    } else {
+        store.set(name, value)?;
+    }
+    Ok(in_keychain)
+}
+
+/// Settings key of an export target's frontmatter changes
+fn frontmatter_key(target: &str) -> String {
+    format!("frontmatter_{}", target)
+}
+
+/// Settings key of a category's custom skeleton
+fn skeleton_key(category: Category) -> String {
+    format!("skeleton_{}", category.as_str())
+}
+
+/// Commit message for exported items: the item in the subject, or the
+/// count with one line per item in the body
+fn export_commit_message(verb: &str, items: &[Item]) -> String {
+    match items {
+        [item] => format!("{} {} {}", verb, item.category.as_str(), item.name),
+        _ => {
+            let mut message = format!("{} {} items\n", verb, items.len());
+            for item in items {
+                message.push_str(&format!("\n- {} {}", item.category.as_str(), item.name));
+            }
+            message
+        }
+    }
+}
+
+/// Merge comma-separated tag suggestions into existing tags: lowercased,
+/// without `#`, and without duplicates
+fn merge_tags(existing: &str, suggested: &str) -> String {
+    let mut tags: Vec<String> = Vec::new();
+    for tag in existing.split(',').chain(suggested.split([',', '\n'])) {
+        let tag = tag.trim().trim_start_matches('#').trim().to_lowercase();
+        if !tag.is_empty() && !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    tags.join(", ")
+}
@@ -0,0 +1,725 @@
+use crate::db::{
+    normalize_collection_path, ConflictMode, Database, Dump, ItemStore, SettingsStore, SortOrder,
+    Vaults, DEFAULT_VAULT,
+};
+use crate::export::{
+    apply_sync, find_drift, item_document, write_inventory, Bundle, DataFormat, ExportOptions,
+    FrontmatterKeys, SyncAction, FRONTMATTER_TARGETS,
+};
+use crate::import::{ClaudeImporter, ContinueImporter, CursorImporter, RepoImporter, UrlImporter};
+use crate::models::{Category, Item};
+use crate::server::ApiServer;
+use clap::{Parser, Subcommand};
+use clap_complete::env::Shells;
+use clap_complete::{ArgValueCandidates, CompletionCandidate};
+use color_eyre::eyre::{bail, eyre, Result};
+use std::io::{self, IsTerminal, Read, Write};
+use std::path::{Path, PathBuf};
+
+#[derive(Parser)]
+#[command(name = "grimoire", version, about)]
+pub struct Cli {
+    /// Vault (separate library) to use; defaults to the last one opened
+    #[arg(long, global = true, add = ArgValueCandidates::new(vault_names))]
+    pub vault: Option<String>,
+
+    /// Create the vault given with --vault if it doesn't exist yet; without
+    /// this, an unknown vault name is an error
+    #[arg(long, global = true, requires = "vault")]
+    pub create_vault: bool,
+
+    /// Log debug detail (database changes, exports, AI requests) to the
+    /// log file in the data directory
+    #[arg(long, global = true)]
+    pub debug: bool,
+
+    /// Open the TUI as a picker: Enter closes it and prints the item's
+    /// content to stdout
+    #[arg(long)]
+    pub pick: bool,
+
+    /// With --pick, print the item's name instead of its content
+    #[arg(long, requires = "pick")]
+    pub print_name: bool,
+
+    #[command(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Add an item with content read from stdin, without opening the TUI
+    Add {
+        /// Item name (must be unique)
+        #[arg(long)]
+        name: String,
+
+        /// Item category
+        #[arg(long, default_value = "prompt", value_parser = ["prompt", "agent", "skill", "command"])]
+        category: String,
+
+        /// Comma-separated tags
+        #[arg(long)]
+        tags: Option<String>,
+
+        /// Short description (required for agents and skills)
+        #[arg(long)]
+        description: Option<String>,
+    },
+
+    /// Print an item's content to stdout, e.g. to pipe a prompt into
+    /// another tool
+    Get {
+        /// Item name
+        #[arg(add = ArgValueCandidates::new(item_names))]
+        name: String,
+
+        /// Print the content exactly as stored, without a trailing newline
+        #[arg(long, conflicts_with = "with_frontmatter")]
+        raw: bool,
+
+        /// Print the item as the markdown file it exports to, frontmatter
+        /// included
+        #[arg(long, conflicts_with = "format")]
+        with_frontmatter: bool,
+
+        /// Print the whole item, every field included, as JSON or YAML
+        #[arg(long, conflicts_with = "raw", value_parser = ["json", "yaml"])]
+        format: Option<String>,
+    },
+
+    /// Import agents, commands, and skills from a .claude directory, a git
+    /// repository, or a URL, prompts and agents from Cursor rules, or
+    /// Continue's prompts
+    Import {
+        /// Directory to scan (defaults to the configured export path)
+        #[arg(long)]
+        path: Option<PathBuf>,
+
+        /// Git repository to clone (or update) and import from, as a URL or
+        /// GitHub `owner/repo`
+        #[arg(long, conflicts_with = "path")]
+        repo: Option<String>,
+
+        /// Raw markdown URL, file on GitHub, or gist to create items from
+        #[arg(long, conflicts_with_all = ["path", "repo"])]
+        url: Option<String>,
+
+        /// Project whose Cursor rules (`.cursor/rules/*.mdc` and
+        /// `.cursorrules`) to import (defaults to the current directory)
+        #[arg(long, num_args = 0..=1, default_missing_value = ".", conflicts_with_all = ["path", "repo", "url"])]
+        cursor: Option<PathBuf>,
+
+        /// `.continue` directory, or project containing one, whose prompt
+        /// files and config prompts to import (defaults to `~/.continue`)
+        #[arg(
+            long = "continue",
+            num_args = 0..=1,
+            default_missing_value = "~/.continue",
+            conflicts_with_all = ["path", "repo", "url", "cursor"]
+        )]
+        continue_dir: Option<PathBuf>,
+
+        /// What to do with items whose name already exists with a different
+        /// definition
+        #[arg(long, default_value = "skip", value_parser = ["skip", "overwrite", "duplicate"])]
+        on_conflict: String,
+    },
+
+    /// Back up the database (items, versions, and settings) to a file
+    Backup {
+        /// Destination file (must not exist)
+        path: PathBuf,
+    },
+
+    /// Replace the database with a backup made by `grimoire backup`
+    Restore {
+        /// Backup file to restore
+        path: PathBuf,
+    },
+
+    /// Write every item, with its versions and tags, to stdout as JSON
+    Dump,
+
+    /// Write a CSV index of the library (name, category, tags,
+    /// description, dates) to stdout, for auditing it in a spreadsheet
+    Inventory,
+
+    /// Add the items of a JSON dump made by `grimoire dump`
+    Load {
+        /// Dump file to load
+        path: PathBuf,
+
+        /// What to do with items whose name already exists
+        #[arg(long, default_value = "skip", value_parser = ["skip", "overwrite", "duplicate"])]
+        on_conflict: String,
+    },
+
+    /// Pack items into a .grimoire bundle file to share with another
+    /// grimoire user
+    Bundle {
+        /// Bundle file to write
+        path: PathBuf,
+
+        /// Items to include (defaults to every item outside the archive)
+        #[arg(add = ArgValueCandidates::new(item_names))]
+        names: Vec<String>,
+
+        /// Include the items in this collection and the ones nested under it
+        #[arg(long, conflicts_with = "names")]
+        collection: Option<String>,
+
+        /// Include the items with this tag
+        #[arg(long, conflicts_with_all = ["names", "collection"])]
+        tag: Option<String>,
+    },
+
+    /// Add the items of a bundle made by `grimoire bundle`
+    Unbundle {
+        /// Bundle file to read
+        path: PathBuf,
+
+        /// What to do with items whose name already exists
+        #[arg(long, default_value = "skip", value_parser = ["skip", "overwrite", "duplicate"])]
+        on_conflict: String,
+    },
+
+    /// Compare the library's agents, commands, and skills with the export
+    /// path both ways, and list what differs
+    Sync {
+        /// Settle each difference in favor of the side changed last
+        #[arg(long)]
+        apply: bool,
+
+        /// Write the library's version of everything that differs
+        #[arg(long, conflicts_with_all = ["apply", "pull"])]
+        push: bool,
+
+        /// Take every differing file on disk into the library
+        #[arg(long, conflicts_with = "apply")]
+        pull: bool,
+    },
+
+    /// Serve a JSON API on localhost for editor plugins and scripts
+    Serve {
+        /// Port to listen on (127.0.0.1 only)
+        #[arg(long, value_name = "PORT")]
+        http: u16,
+
+        /// File holding the bearer token that allows creating items (the
+        /// token can also be given in $GRIMOIRE_API_TOKEN); without one the
+        /// API is read-only
+        #[arg(long, value_name = "PATH")]
+        token_file: Option<PathBuf>,
+    },
+
+    /// Print the script that sets up tab completion for a shell, e.g.
+    /// `source <(grimoire completions bash)` in ~/.bashrc
+    Completions {
+        /// Shell to complete in
+        #[arg(value_parser = ["bash", "zsh", "fish", "elvish", "powershell"])]
+        shell: String,
+    },
+}
+
+pub fn run(command: Command, vault: &str) -> Result<()> {
+    match command {
+        Command::Add {
+            name,
+            category,
+            tags,
+            description,
+        } => add(
+            vault,
+            name,
+            Category::from_str(&category),
+            tags,
+            description,
+        ),
+        Command::Get {
+            name,
+            raw,
+            with_frontmatter,
+            format,
+        } => get(
+            vault,
+            &name,
+            raw,
+            with_frontmatter,
+            format.as_deref().and_then(DataFormat::from_key),
+        ),
+        Command::Import {
+            path,
+            repo,
+            url,
+            cursor,
+            continue_dir,
+            on_conflict,
+        } => {
+            let mode = ConflictMode::from_str(&on_conflict);
+            match (repo, url, cursor, continue_dir) {
+                (Some(url), ..) => import_repo(vault, &url, mode),
+                (_, Some(url), ..) => import_url(vault, &url, mode),
+                (_, _, Some(path), _) => import_cursor(vault, &path, mode),
+                (.., Some(path)) => import_continue(vault, &path, mode),
+                _ => import(vault, path, mode),
+            }
+        }
+        Command::Backup { path } => backup(vault, path),
+        Command::Restore { path } => restore(vault, path),
+        Command::Dump => dump(vault),
+        Command::Inventory => inventory(vault),
+        Command::Load { path, on_conflict } => {
+            load(vault, path, ConflictMode::from_str(&on_conflict))
+        }
+        Command::Bundle {
+            path,
+            names,
+            collection,
+            tag,
+        } => bundle(vault, path, names, collection, tag),
+        Command::Unbundle { path, on_conflict } => {
+            unbundle(vault, path, ConflictMode::from_str(&on_conflict))
+        }
+        Command::Sync { apply, push, pull } => {
+            let forced = match (push, pull) {
+                (true, _) => Some(SyncAction::Push),
+                (_, true) => Some(SyncAction::Pull),
+                _ => None,
+            };
+            sync(vault, apply || forced.is_some(), forced)
+        }
+        Command::Serve { http, token_file } => serve(vault, http, token_file.as_deref()),
+        Command::Completions { shell } => completions(&shell),
+    }
+}
+
+fn add(
+    vault: &str,
+    name: String,
+    category: Category,
+    tags: Option<String>,
+    description: Option<String>,
+) -> Result<()> {
+    let mut stdin = io::stdin();
+    if stdin.is_terminal() {
+        bail!("No content on stdin. Pipe it in, e.g. `cat prompt.md | grimoire add --name foo`");
+    }
+
+    let mut content = String::new();
+    stdin.read_to_string(&mut content)?;
+
+    let mut item = Item::new(
+        name.trim().to_string(),
+        category,
+        content.trim_end().to_string(),
+    );
+    item.tags = tags.map(|t| t.trim().to_string()).filter(|t| !t.is_empty());
+    item.description = description
+        .map(|d| d.trim().to_string())
+        .filter(|d| !d.is_empty());
+
+    if let Err(errors) = item.validate() {
+        bail!(errors.join(", "));
+    }
+
+    let db = Database::open(vault)?;
+    let store = ItemStore::new(&db.conn);
+
+    if store.get_by_name(&item.name)?.is_some() {
+        bail!("An item named '{}' already exists", item.name);
+    }
+
+    let id = store.insert(&item)?;
+    println!("Added {} '{}' (id {})", category.as_str(), item.name, id);
+
+    Ok(())
+}
+
+fn get(
+    vault: &str,
+    name: &str,
+    raw: bool,
+    with_frontmatter: bool,
+    format: Option<DataFormat>,
+) -> Result<()> {
+    let db = Database::open(vault)?;
+    let store = ItemStore::new(&db.conn);
+    let Some(item) = store.get_by_name(name)? else {
+        bail!("No item named '{}'", name);
+    };
+    if store.is_trashed(item.id.unwrap_or_default())? {
+        bail!("'{}' is in the trash", name);
+    }
+
+    let mut text = if let Some(format) = format {
+        format.render(&item)?
+    } else if with_frontmatter {
+        item_document(&item)
+    } else {
+        item.content.clone()
+    };
+    if !raw && !text.ends_with('\n') {
+        text.push('\n');
+    }
+
+    let mut stdout = io::stdout().lock();
+    match stdout
+        .write_all(text.as_bytes())
+        .and_then(|_| stdout.flush())
+    {
+        // The reader (e.g. `head`) stopped early; the item was still used
+        Err(e) if e.kind() != io::ErrorKind::BrokenPipe => return Err(e.into()),
+        _ => {}
+    }
+    if let Some(id) = item.id {
+        store.record_use(id)?;
+    }
+
+    Ok(())
+}
+
+fn import(vault: &str, path: Option<PathBuf>, mode: ConflictMode) -> Result<()> {
+    let db = Database::open(vault)?;
+
+    let path = match path {
+        Some(path) => path,
+        None => PathBuf::from(export_path(&db)?),
+    };
+
+    let importer = ClaudeImporter::new(&path);
+    let store = ItemStore::new(&db.conn);
+    let summary = importer.import(&store, mode)?;
+
+    for (path, error) in &summary.failures {
+        eprintln!("Failed to import {}: {}", path.display(), error);
+    }
+    println!(
+        "{} (from {})",
+        summary.describe(),
+        importer.base_path().display()
+    );
+
+    Ok(())
+}
+
+fn import_repo(vault: &str, url: &str, mode: ConflictMode) -> Result<()> {
+    let db = Database::open(vault)?;
+    let importer = RepoImporter::new(url)?;
+
+    eprintln!("Fetching {}...", importer.url());
+    importer.fetch()?;
+    let summary = importer.import(&ItemStore::new(&db.conn), mode)?;
+
+    for (path, error) in &summary.failures {
+        eprintln!("Failed to import {}: {}", path.display(), error);
+    }
+    println!("{} (from {})", summary.describe(), importer.url());
+
+    Ok(())
+}
+
+fn import_url(vault: &str, url: &str, mode: ConflictMode) -> Result<()> {
+    let db = Database::open(vault)?;
+    let mut importer = UrlImporter::new(url)?;
+
+    importer.fetch()?;
+    let summary = importer.import(&ItemStore::new(&db.conn), mode)?;
+
+    for (file, error) in &summary.failures {
+        eprintln!("Failed to import {}: {}", file.display(), error);
+    }
+    println!("{} (from {})", summary.describe(), importer.url());
+
+    Ok(())
+}
+
+fn import_cursor(vault: &str, path: &Path, mode: ConflictMode) -> Result<()> {
+    let db = Database::open(vault)?;
+    let importer = CursorImporter::new(path);
+    let summary = importer.import(&ItemStore::new(&db.conn), mode)?;
+
+    for (path, error) in &summary.failures {
+        eprintln!("Failed to import {}: {}", path.display(), error);
+    }
+    println!(
+        "{} (from {})",
+        summary.describe(),
+        importer.project_root().display()
+    );
+
+    Ok(())
+}
+
+fn import_continue(vault: &str, path: &Path, mode: ConflictMode) -> Result<()> {
+    let db = Database::open(vault)?;
+    let importer = ContinueImporter::new(path);
+    let summary = importer.import(&ItemStore::new(&db.conn), mode)?;
+
+    for (path, error) in &summary.failures {
+        eprintln!("Failed to import {}: {}", path.display(), error);
+    }
+    println!(
+        "{} (from {})",
+        summary.describe(),
+        importer.base_path().display()
+    );
+
+    Ok(())
+}
+
+fn backup(vault: &str, path: PathBuf) -> Result<()> {
+    let db = Database::open(vault)?;
+    db.backup(&path)?;
+    println!("Backed up the database to {}", path.display());
+
+    Ok(())
+}
+
+fn restore(vault: &str, path: PathBuf) -> Result<()> {
+    let mut db = Database::open(vault)?;
+    db.restore(&path)?;
+
+    let count: usize = ItemStore::new(&db.conn)
+        .count_by_category()?
+        .iter()
+        .map(|(_, count)| count)
+        .sum();
+    println!("Restored {} items from {}", count, path.display());
+    println!(
+        "The previous database was saved to {}.pre-restore.bak",
+        Vaults::path(vault)?.display()
+    );
+
+    Ok(())
+}
+
+fn dump(vault: &str) -> Result<()> {
+    let db = Database::open(vault)?;
+    let dump = Dump::from_db(&db.conn)?;
+    println!("{}", serde_json::to_string_pretty(&dump)?);
+
+    Ok(())
+}
+
+fn inventory(vault: &str) -> Result<()> {
+    let db = Database::open(vault)?;
+    write_inventory(&db.conn, io::stdout().lock())?;
+
+    Ok(())
+}
+
+fn load(vault: &str, path: PathBuf, mode: ConflictMode) -> Result<()> {
+    let text = std::fs::read_to_string(&path)?;
+    let dump: Dump = serde_json::from_str(&text)?;
+
+    let db = Database::open(vault)?;
+    let summary = dump.load_into(&db.conn, mode)?;
+
+    for (name, error) in &summary.failures {
+        eprintln!("Failed to load {}: {}", name, error);
+    }
+    println!("{} (from {})", summary.describe(), path.display());
+
+    Ok(())
+}
+
+fn bundle(
+    vault: &str,
+    path: PathBuf,
+    names: Vec<String>,
+    collection: Option<String>,
+    tag: Option<String>,
+) -> Result<()> {
+    let db = Database::open(vault)?;
+    let store = ItemStore::new(&db.conn);
+
+    let items = if let Some(collection) = collection {
+        store.list_by_collection(&normalize_collection_path(&collection), SortOrder::Name)?
+    } else if let Some(tag) = tag {
+        store.list_by_tags(&[tag], false, SortOrder::Name)?
+    } else if names.is_empty() {
+        store.list_all()?
+    } else {
+        let mut items = Vec::new();
+        for name in &names {
+            match store.get_by_name(name)? {
+                Some(item) => items.push(item),
+                None => bail!("No item named '{}'", name),
+            }
+        }
+        items
+    };
+    if items.is_empty() {
+        bail!("No items to bundle");
+    }
+
+    let count = Bundle::write(&db.conn, &items, &path)?;
+    println!("Bundled {} items into {}", count, path.display());
+
+    Ok(())
+}
+
+fn unbundle(vault: &str, path: PathBuf, mode: ConflictMode) -> Result<()> {
+    let bundle = Bundle::open(&path)?;
+
+    let db = Database::open(vault)?;
+    let summary = bundle.load_into(&db.conn, |_| mode)?;
+
+    for (name, error) in &summary.failures {
+        eprintln!("Failed to load {}: {}", name, error);
+    }
+    println!("{} (from {})", summary.describe(), path.display());
+
+    Ok(())
+}
+
+fn serve(vault: &str, port: u16, token_file: Option<&Path>) -> Result<()> {
+    // Not taken as an argument, where other users could read it in `ps`
+    let token = match token_file {
+        Some(path) => Some(
+            std::fs::read_to_string(path)
+                .map_err(|e| eyre!("could not read {}: {}", path.display(), e))?,
+        ),
+        None => std::env::var("GRIMOIRE_API_TOKEN").ok(),
+    };
+    let server = ApiServer::new(Database::open(vault)?, token, port);
+
+    eprintln!(
+        "Serving vault '{}' on http://127.0.0.1:{} ({})",
+        vault,
+        port,
+        if server.accepts_writes() {
+            "writes need the token"
+        } else {
+            "read-only"
+        }
+    );
+    server.run()
+}
+
+/// Sync the export path. Without `apply` the differences are only listed;
+/// `forced` replaces the newer-side-wins choice, skipping items it
+/// doesn't fit.
+fn sync(vault: &str, apply: bool, forced: Option<SyncAction>) -> Result<()> {
+    let db = Database::open(vault)?;
+    let options = export_options(&db)?;
+    let mut drifts = find_drift(&db.conn, &options)?;
+
+    if drifts.is_empty() {
+        println!("In sync with {}", options.claude_path);
+        return Ok(());
+    }
+    for drift in &mut drifts {
+        if let Some(action) = forced {
+            drift.action = if drift.actions().contains(&action) {
+                action
+            } else {
+                SyncAction::Skip
+            };
+        }
+        println!(
+            "{:8} {:8} {} ({})",
+            drift.action.label(),
+            drift.category.as_str(),
+            drift.name,
+            drift.describe()
+        );
+    }
+    if !apply {
+        println!("Run with --apply (newer side wins), --push, or --pull to sync");
+        return Ok(());
+    }
+
+    let summary = apply_sync(&db.conn, &options, &drifts);
+    for (name, error) in &summary.failures {
+        eprintln!("Failed to sync {}: {}", name, error);
+    }
+    println!("{} (with {})", summary.describe(), options.claude_path);
+
+    Ok(())
+}
+
+/// Write the shell code that calls back into grimoire for completions.
+/// It's generated on every shell start rather than saved, so it always
+/// matches the installed version.
+fn completions(shell: &str) -> Result<()> {
+    let shells = Shells::builtins();
+    let Some(completer) = shells.completer(shell) else {
+        bail!("Unsupported shell '{}'", shell);
+    };
+    completer.write_registration(
+        "COMPLETE",
+        "grimoire",
+        "grimoire",
+        "grimoire",
+        &mut io::stdout(),
+    )?;
+
+    Ok(())
+}
+
+/// Item names in the vault being completed for, offered when completing a
+/// name. A vault that doesn't exist offers nothing rather than being created.
+fn item_names() -> Vec<CompletionCandidate> {
+    let vault = completion_vault();
+    if !Vaults::exists(&vault) {
+        return Vec::new();
+    }
+    let Ok(db) = Database::open(&vault) else {
+        return Vec::new();
+    };
+    let items = ItemStore::new(&db.conn).list_all().unwrap_or_default();
+    items
+        .into_iter()
+        .map(|item| CompletionCandidate::new(item.name).help(item.description.map(Into::into)))
+        .collect()
+}
+
+/// The `--vault` given on the command line being completed, or the last
+/// used vault. The completion script passes that command line after `--`.
+fn completion_vault() -> String {
+    let words: Vec<String> = std::env::args().skip_while(|arg| arg != "--").collect();
+    words
+        .iter()
+        .zip(words.iter().skip(1))
+        .find_map(|(flag, value)| (flag == "--vault").then(|| value.clone()))
+        .or_else(|| {
+            words
+                .iter()
+                .find_map(|word| word.strip_prefix("--vault=").map(str::to_string))
+        })
+        .or_else(Vaults::last_used)
+        .unwrap_or_else(|| DEFAULT_VAULT.to_string())
+}
+
+fn vault_names() -> Vec<CompletionCandidate> {
+    Vaults::list()
+        .unwrap_or_default()
+        .into_iter()
+        .map(CompletionCandidate::new)
+        .collect()
+}
+
+/// The configured export path, `~/.claude` by default
+fn export_path(db: &Database) -> Result<String> {
+    Ok(SettingsStore::new(&db.conn)
+        .get("export_path")?
+        .map(|p| p.trim().to_string())
+        .unwrap_or_else(|| "~/.claude".to_string()))
+}
+
+/// The export path with the frontmatter changes made in Settings
+fn export_options(db: &Database) -> Result<ExportOptions> {
+    let settings = SettingsStore::new(&db.conn);
+    let mut options = ExportOptions::new(export_path(db)?);
+    for (target, _, _) in FRONTMATTER_TARGETS {
+        let Some(spec) = settings.get(&format!("frontmatter_{}", target))? else {
+            continue;
+        };
+        let keys = FrontmatterKeys::parse(target, &spec)
+            .map_err(|e| eyre!("frontmatter keys for {}: {}", target, e))?;
+        options.frontmatter.insert(target.to_string(), keys);
+    }
+    Ok(options)
+}
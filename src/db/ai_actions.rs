@@ -0,0 +1,114 @@
+use color_eyre::eyre::{eyre, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// Field of the item a user-defined AI action writes its answer to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ActionTarget {
+    #[default]
+    Content,
+    Description,
+    Tags,
+}
+
+impl ActionTarget {
+    pub fn all() -> &'static [ActionTarget] {
+        &[
+            ActionTarget::Content,
+            ActionTarget::Description,
+            ActionTarget::Tags,
+        ]
+    }
+
+    pub fn key(&self) -> &'static str {
+        match self {
+            ActionTarget::Content => "content",
+            ActionTarget::Description => "description",
+            ActionTarget::Tags => "tags",
+        }
+    }
+
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|target| target.key() == key.trim().to_lowercase())
+    }
+}
+
+/// An AI popup action written by the user: its label, the system prompt
+/// sent with the item, and the field the answer goes to
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct UserAction {
+    pub id: Option<i64>,
+    pub label: String,
+    pub system_prompt: String,
+    pub target: ActionTarget,
+}
+
+pub struct AiActionStore<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> AiActionStore<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// All user actions, in the order they were created
+    pub fn list(&self) -> Result<Vec<UserAction>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, label, system_prompt, target FROM ai_actions ORDER BY id")?;
+        let actions = stmt
+            .query_map([], |row| {
+                Ok(UserAction {
+                    id: Some(row.get(0)?),
+                    label: row.get(1)?,
+                    system_prompt: row.get(2)?,
+                    target: ActionTarget::from_key(&row.get::<_, String>(3)?).unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+        Ok(actions)
+    }
+
+    /// Insert a new action, or update the one with the same id. Returns
+    /// the action's id.
+    pub fn save(&self, action: &UserAction) -> Result<i64> {
+        let label = action.label.trim();
+        let taken: Option<i64> = self
+            .conn
+            .query_row(
+                "SELECT id FROM ai_actions WHERE label = ? COLLATE NOCASE",
+                [label],
+                |row| row.get(0),
+            )
+            .optional()?;
+        if taken.is_some_and(|id| Some(id) != action.id) {
+            return Err(eyre!("an action named '{}' already exists", label));
+        }
+
+        match action.id {
+            Some(id) => {
+                self.conn.execute(
+                    "UPDATE ai_actions SET label = ?, system_prompt = ?, target = ? WHERE id = ?",
+                    params![label, action.system_prompt, action.target.key(), id],
+                )?;
+                Ok(id)
+            }
+            None => {
+                self.conn.execute(
+                    "INSERT INTO ai_actions (label, system_prompt, target) VALUES (?, ?, ?)",
+                    params![label, action.system_prompt, action.target.key()],
+                )?;
+                Ok(self.conn.last_insert_rowid())
+            }
+        }
+    }
+
+    pub fn delete(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM ai_actions WHERE id = ?", [id])?;
+        Ok(())
+    }
+}
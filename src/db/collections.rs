@@ -0,0 +1,224 @@
+use color_eyre::eyre::{eyre, Result};
+use rusqlite::{params, Connection, OptionalExtension};
+
+/// User-created collections of items. Collections nest by path, e.g.
+/// `Work/Rust tooling` sits inside `Work`, and an item can be in any number
+/// of them.
+pub struct CollectionStore<'a> {
+    conn: &'a Connection,
+}
+
+/// Tidy a typed collection path: segments trimmed, empty ones dropped
+pub fn normalize_collection_path(path: &str) -> String {
+    path.split('/')
+        .map(str::trim)
+        .filter(|segment| !segment.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
+
+/// SQL condition matching the path in `column` against `path` and
+/// everything nested under it
+fn subtree(column: &str, path: &str) -> String {
+    format!(
+        "({c} = {p} OR substr({c}, 1, length({p}) + 1) = {p} || '/')",
+        c = column,
+        p = path
+    )
+}
+
+impl<'a> CollectionStore<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// Every collection path in tree order, with the number of items in it
+    /// or in the collections nested under it
+    pub fn list_with_counts(&self) -> Result<Vec<(String, usize)>> {
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT c.path, (
+                SELECT COUNT(DISTINCT ic.item_id)
+                FROM item_collections ic
+                JOIN collections d ON d.id = ic.collection_id
+                JOIN items i ON i.id = ic.item_id
+                WHERE {} AND i.deleted_at IS NULL AND i.archived = 0
+            )
+            FROM collections c
+            "#,
+            subtree("d.path", "c.path")
+        ))?;
+        let mut collections = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<(String, usize)>, _>>()?;
+
+        // Compare segment by segment so children follow their parent
+        collections.sort_by_cached_key(|(path, _)| {
+            path.split('/').map(str::to_lowercase).collect::<Vec<_>>()
+        });
+        Ok(collections)
+    }
+
+    /// Paths of the collections an item is directly in
+    pub fn for_item(&self, item_id: i64) -> Result<Vec<String>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT c.path FROM item_collections ic
+            JOIN collections c ON c.id = ic.collection_id
+            WHERE ic.item_id = ?
+            ORDER BY c.path COLLATE NOCASE
+            "#,
+        )?;
+        let paths = stmt
+            .query_map([item_id], |row| row.get(0))?
+            .collect::<Result<Vec<String>, _>>()?;
+        Ok(paths)
+    }
+
+    /// Create a collection and any missing parents, returning its id. An
+    /// existing path is matched ignoring case.
+    pub fn create(&self, path: &str) -> Result<i64> {
+        let path = normalize_collection_path(path);
+        if path.is_empty() {
+            return Err(eyre!("collection name is empty"));
+        }
+
+        let mut id = 0;
+        let mut prefix = String::new();
+        for segment in path.split('/') {
+            if !prefix.is_empty() {
+                prefix.push('/');
+            }
+            prefix.push_str(segment);
+            id = match self.find(&prefix)? {
+                Some((existing_id, existing)) => {
+                    // Keep the stored spelling for the rest of the path
+                    prefix = existing;
+                    existing_id
+                }
+                None => {
+                    self.conn
+                        .execute("INSERT INTO collections (path) VALUES (?)", [&prefix])?;
+                    self.conn.last_insert_rowid()
+                }
+            };
+        }
+        Ok(id)
+    }
+
+    fn find(&self, path: &str) -> Result<Option<(i64, String)>> {
+        let found = self
+            .conn
+            .query_row(
+                "SELECT id, path FROM collections WHERE path = ? COLLATE NOCASE",
+                [path],
+                |row| Ok((row.get(0)?, row.get(1)?)),
+            )
+            .optional()?;
+        Ok(found)
+    }
+
+    /// Add the items to the `add` collections (created when missing) and
+    /// take them out of the `remove` ones. Returns how many items changed.
+    pub fn edit_items(&self, item_ids: &[i64], add: &[String], remove: &[String]) -> Result<usize> {
+        let add_ids = add
+            .iter()
+            .map(|path| self.create(path))
+            .collect::<Result<Vec<_>>>()?;
+        let mut remove_ids = Vec::new();
+        for path in remove {
+            remove_ids.extend(
+                self.find(&normalize_collection_path(path))?
+                    .map(|(id, _)| id),
+            );
+        }
+
+        let mut changed = 0;
+        for item_id in item_ids {
+            let mut rows = 0;
+            for collection_id in &add_ids {
+                rows += self.conn.execute(
+                    "INSERT OR IGNORE INTO item_collections (item_id, collection_id) VALUES (?, ?)",
+                    params![item_id, collection_id],
+                )?;
+            }
+            for collection_id in &remove_ids {
+                rows += self.conn.execute(
+                    "DELETE FROM item_collections WHERE item_id = ? AND collection_id = ?",
+                    params![item_id, collection_id],
+                )?;
+            }
+            if rows > 0 {
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Rename a collection, moving the ones nested under it along. Fails
+    /// when the new path is already taken.
+    pub fn rename(&self, from: &str, to: &str) -> Result<()> {
+        let to = normalize_collection_path(to);
+        if to.is_empty() {
+            return Err(eyre!("collection name is empty"));
+        }
+        if let Some((_, existing)) = self.find(&to)? {
+            if existing.to_lowercase() != from.to_lowercase() {
+                return Err(eyre!("'{}' already exists", existing));
+            }
+        }
+
+        self.conn.execute(
+            &format!(
+                "UPDATE collections SET path = ?2 || substr(path, length(?1) + 1) WHERE {}",
+                subtree("path", "?1")
+            ),
+            params![from, to],
+        )?;
+        // The new path may sit inside collections that don't exist yet
+        if let Some((parent, _)) = to.rsplit_once('/') {
+            self.create(parent)?;
+        }
+        Ok(())
+    }
+
+    /// Delete a collection and the ones nested under it. Their items stay
+    /// in the library. Returns how many items were in them.
+    pub fn delete(&self, path: &str) -> Result<usize> {
+        let count = self.conn.query_row(
+            &format!(
+                r#"
+                SELECT COUNT(DISTINCT ic.item_id) FROM item_collections ic
+                JOIN collections c ON c.id = ic.collection_id
+                WHERE {}
+                "#,
+                subtree("c.path", "?1")
+            ),
+            [path],
+            |row| row.get(0),
+        )?;
+        self.conn.execute(
+            &format!(
+                r#"
+                DELETE FROM item_collections WHERE collection_id IN (
+                    SELECT id FROM collections WHERE {}
+                )
+                "#,
+                subtree("path", "?1")
+            ),
+            [path],
+        )?;
+        self.conn.execute(
+            &format!("DELETE FROM collections WHERE {}", subtree("path", "?1")),
+            [path],
+        )?;
+        Ok(count)
+    }
+
+    /// Take an item out of every collection
+    pub fn remove_item(&self, item_id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM item_collections WHERE item_id = ?", [item_id])?;
+        Ok(())
+    }
+}
@@ -0,0 +1,355 @@
+use super::{CollectionStore, ItemStore, LinkStore};
+use crate::models::Item;
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Result};
+use rusqlite::{params, Connection};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Version of the dump file layout, bumped on incompatible changes
+pub const DUMP_FORMAT: u32 = 1;
+
+/// Plain-text copy of a library: every item outside the trash with its
+/// version history, tags, and collections
+#[derive(Serialize, Deserialize)]
+pub struct Dump {
+    pub format: u32,
+    pub exported_at: DateTime<Utc>,
+    pub items: Vec<DumpItem>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DumpItem {
+    #[serde(flatten)]
+    pub item: Item,
+    #[serde(default)]
+    pub favorite: bool,
+    #[serde(default)]
+    pub archived: bool,
+    #[serde(default)]
+    pub version_message: Option<String>,
+    /// Earlier versions, oldest first
+    #[serde(default)]
+    pub versions: Vec<DumpVersion>,
+    /// Names of the skills and commands this item links to
+    #[serde(default)]
+    pub links: Vec<String>,
+    /// Paths of the collections the item is in
+    #[serde(default)]
+    pub collections: Vec<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct DumpVersion {
+    #[serde(default)]
+    pub message: Option<String>,
+    #[serde(flatten)]
+    pub item: Item,
+}
+
+/// What to do when a loaded item has the name of an existing one
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConflictMode {
+    Skip,
+    Overwrite,
+    Duplicate,
+}
+
+impl ConflictMode {
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "overwrite" => ConflictMode::Overwrite,
+            "duplicate" => ConflictMode::Duplicate,
+            _ => ConflictMode::Skip,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct LoadSummary {
+    pub added: usize,
+    pub overwritten: usize,
+    pub duplicated: usize,
+    pub skipped: usize,
+    pub failures: Vec<(String, String)>,
+}
+
+impl LoadSummary {
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "Loaded: {} new, {} overwritten, {} duplicated, {} skipped",
+            self.added, self.overwritten, self.duplicated, self.skipped
+        );
+        if !self.failures.is_empty() {
+            text.push_str(&format!(", {} failed", self.failures.len()));
+        }
+        text
+    }
+}
+
+impl Dump {
+    pub fn from_db(conn: &Connection) -> Result<Self> {
+        let store = ItemStore::new(conn);
+        let favorites = store.favorite_ids()?;
+        let archived = store.archived_ids()?;
+
+        let mut stmt = conn.prepare(
+            "SELECT id, version_message FROM items WHERE deleted_at IS NULL ORDER BY name COLLATE NOCASE",
+        )?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut items = Vec::new();
+        for (id, version_message) in rows {
+            let Some(item) = store.get(id)? else {
+                continue;
+            };
+
+            let mut versions = Vec::new();
+            let mut stmt = conn.prepare(
+                "SELECT version, message FROM item_versions WHERE item_id = ? ORDER BY version",
+            )?;
+            let history = stmt
+                .query_map([id], |row| {
+                    Ok((row.get::<_, i64>(0)?, row.get::<_, Option<String>>(1)?))
+                })?
+                .collect::<Result<Vec<_>, _>>()?;
+            for (version, message) in history {
+                if let Some(snapshot) = store.get_version(id, version)? {
+                    versions.push(DumpVersion {
+                        message,
+                        item: snapshot,
+                    });
+                }
+            }
+
+            let links = LinkStore::new(conn)
+                .targets(id)?
+                .into_iter()
+                .map(|link| link.name)
+                .collect();
+            let collections = CollectionStore::new(conn).for_item(id)?;
+
+            items.push(DumpItem {
+                item,
+                favorite: favorites.contains(&id),
+                archived: archived.contains(&id),
+                version_message,
+                versions,
+                links,
+                collections,
+            });
+        }
+
+        Ok(Self {
+            format: DUMP_FORMAT,
+            exported_at: Utc::now(),
+            items,
+        })
+    }
+
+    /// Add the dump's items to the database in one transaction. Items whose
+    /// name is taken are handled according to `mode`; an overwritten item
+    /// keeps its own history, with its current state saved as a version.
+    /// A name in the trash or of another category is a failure.
+    pub fn load_into(&self, conn: &Connection, mode: ConflictMode) -> Result<LoadSummary> {
+        if self.format > DUMP_FORMAT {
+            return Err(eyre!(
+                "Dump format {} is newer than this grimoire supports ({})",
+                self.format,
+                DUMP_FORMAT
+            ));
+        }
+
+        let tx = conn.unchecked_transaction()?;
+        let store = ItemStore::new(&tx);
+        let mut summary = LoadSummary::default();
+        // Links are resolved by name once every item is in, following renames
+        let mut written: Vec<(i64, &DumpItem)> = Vec::new();
+        let mut renamed: HashMap<&str, String> = HashMap::new();
+
+        for entry in &self.items {
+            let mut item = entry.item.clone();
+            item.name = item.name.trim().to_string();
+            if let Err(errors) = item.validate() {
+                summary.failures.push((item.name, errors.join(", ")));
+                continue;
+            }
+
+            let existing = store.get_by_name(&item.name)?;
+            if let Some(ref existing) = existing {
+                let refusal = if store.is_trashed(existing.id.unwrap_or_default())? {
+                    Some(format!(
+                        "'{}' is in the trash; restore or delete it to load",
+                        item.name
+                    ))
+                } else if existing.category != item.category {
+                    Some(format!(
+                        "'{}' is already a {}; rename one of them to load",
+                        item.name,
+                        existing.category.as_str()
+                    ))
+                } else {
+                    None
+                };
+                if let Some(refusal) = refusal {
+                    summary.failures.push((item.name, refusal));
+                    continue;
+                }
+            }
+
+            match (existing, mode) {
+                (None, _) => {
+                    let id = store.insert(&item)?;
+                    write_entry(&tx, &store, id, &item, entry)?;
+                    written.push((id, entry));
+                    summary.added += 1;
+                }
+                (Some(_), ConflictMode::Skip) => summary.skipped += 1,
+                (Some(existing), ConflictMode::Overwrite) => {
+                    let id = existing
+                        .id
+                        .ok_or_else(|| eyre!("Item '{}' has no id", item.name))?;
+                    item.id = Some(id);
+                    store.update(&item, Some("Loaded from dump"))?;
+                    write_state(&tx, &store, id, &item, entry)?;
+                    written.push((id, entry));
+                    summary.overwritten += 1;
+                }
+                (Some(_), ConflictMode::Duplicate) => {
+                    item.name = unused_name(&store, &item.name)?;
+                    renamed.insert(entry.item.name.as_str(), item.name.clone());
+                    let id = store.insert(&item)?;
+                    write_entry(&tx, &store, id, &item, entry)?;
+                    written.push((id, entry));
+                    summary.duplicated += 1;
+                }
+            }
+        }
+
+        let links = LinkStore::new(&tx);
+        for (id, entry) in written {
+            let mut target_ids = Vec::new();
+            for name in &entry.links {
+                let name = renamed.get(name.as_str()).unwrap_or(name);
+                if let Some(target_id) = store.get_by_name(name)?.and_then(|t| t.id) {
+                    target_ids.push(target_id);
+                }
+            }
+            links.set(id, &target_ids)?;
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    }
+}
+
+/// Give a newly added item the dump entry's fields and history, keeping
+/// its original timestamps and version numbers
+fn write_entry(
+    conn: &Connection,
+    store: &ItemStore,
+    id: i64,
+    item: &Item,
+    entry: &DumpItem,
+) -> Result<()> {
+    conn.execute(
+        r#"
+        UPDATE items
+        SET name = ?, category = ?, description = ?, content = ?, model = ?,
+            tools = ?, allowed_tools = ?, argument_hint = ?, permission_mode = ?,
+            skills = ?, version = ?, version_message = ?,
+            created_at = COALESCE(?, created_at),
+            updated_at = COALESCE(?, CURRENT_TIMESTAMP)
+        WHERE id = ?
+        "#,
+        params![
+            item.name,
+            item.category.as_str(),
+            item.description,
+            item.content,
+            item.model,
+            item.tools,
+            item.allowed_tools,
+            item.argument_hint,
+            item.permission_mode,
+            item.skills,
+            item.version.max(1),
+            entry.version_message,
+            item.created_at.map(sqlite_datetime),
+            item.updated_at.map(sqlite_datetime),
+            id,
+        ],
+    )?;
+    write_state(conn, store, id, item, entry)?;
+
+    for version in &entry.versions {
+        let snapshot = &version.item;
+        conn.execute(
+            r#"
+            INSERT INTO item_versions (item_id, version, name, category, description, content,
+                                      model, tools, allowed_tools, argument_hint,
+                                      permission_mode, skills, tags, message, created_at)
+            VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, COALESCE(?, CURRENT_TIMESTAMP))
+            "#,
+            params![
+                id,
+                snapshot.version,
+                snapshot.name,
+                snapshot.category.as_str(),
+                snapshot.description,
+                snapshot.content,
+                snapshot.model,
+                snapshot.tools,
+                snapshot.allowed_tools,
+                snapshot.argument_hint,
+                snapshot.permission_mode,
+                snapshot.skills,
+                snapshot.tags,
+                version.message,
+                snapshot.created_at.map(sqlite_datetime),
+            ],
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Give an item the dump entry's tags, collections, and favorite and
+/// archived flags
+fn write_state(
+    conn: &Connection,
+    store: &ItemStore,
+    id: i64,
+    item: &Item,
+    entry: &DumpItem,
+) -> Result<()> {
+    conn.execute(
+        "UPDATE items SET favorite = ?, archived = ? WHERE id = ?",
+        params![entry.favorite, entry.archived, id],
+    )?;
+    store.set_tags(id, &item.tags_vec())?;
+    let collections = CollectionStore::new(conn);
+    collections.remove_item(id)?;
+    collections.edit_items(&[id], &entry.collections, &[])?;
+    Ok(())
+}
+
+/// First of `name-2`, `name-3`, ... not used by another item
+pub fn unused_name(store: &ItemStore, name: &str) -> Result<String> {
+    let mut n = 2;
+    loop {
+        let candidate = format!("{}-{}", name, n);
+        if store.get_by_name(&candidate)?.is_none() {
+            return Ok(candidate);
+        }
+        n += 1;
+    }
+}
+
+fn sqlite_datetime(dt: DateTime<Utc>) -> String {
+    dt.format("%Y-%m-%d %H:%M:%S").to_string()
+}
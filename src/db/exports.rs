@@ -0,0 +1,110 @@
+use crate::models::Item;
+use color_eyre::eyre::Result;
+use rusqlite::{params, Connection};
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+
+/// Whether an item's latest definition has been exported
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncStatus {
+    Exported,
+    Modified,
+    NeverExported,
+}
+
+impl SyncStatus {
+    pub fn symbol(&self) -> &'static str {
+        match self {
+            SyncStatus::Exported => "✓",
+            SyncStatus::Modified => "●",
+            SyncStatus::NeverExported => "-",
+        }
+    }
+}
+
+/// Manifest of exported files, one row per item and target
+pub struct ExportStore<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> ExportStore<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// Record that `item` was written to `path` for the given target
+    pub fn record(&self, item: &Item, target: &str, path: &Path) -> Result<()> {
+        let Some(item_id) = item.id else {
+            return Ok(());
+        };
+
+        self.conn.execute(
+            r#"
+            INSERT INTO exports (item_id, target, path, content_hash, exported_at)
+            VALUES (?, ?, ?, ?, CURRENT_TIMESTAMP)
+            ON CONFLICT(item_id, target) DO UPDATE SET
+                path = excluded.path,
+                content_hash = excluded.content_hash,
+                exported_at = excluded.exported_at
+            "#,
+            params![item_id, target, path.to_string_lossy(), item.content_hash()],
+        )?;
+        Ok(())
+    }
+
+    /// Target key and path of each of the item's exports
+    pub fn paths(&self, item_id: i64) -> Result<Vec<(String, String)>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT target, path FROM exports WHERE item_id = ? ORDER BY id")?;
+        let paths = stmt
+            .query_map(params![item_id], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+        Ok(paths)
+    }
+
+    /// Path of the item's most recent export, if it has been exported
+    pub fn latest_path(&self, item_id: i64) -> Result<Option<String>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT path FROM exports WHERE item_id = ? ORDER BY exported_at DESC, id DESC LIMIT 1",
+        )?;
+        let mut rows = stmt.query_map(params![item_id], |row| row.get(0))?;
+        Ok(rows.next().transpose()?)
+    }
+
+    /// Items exported to the given target at least once
+    pub fn exported_ids(&self, target: &str) -> Result<HashSet<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT DISTINCT item_id FROM exports WHERE target = ?")?;
+        let ids = stmt
+            .query_map([target], |row| row.get(0))?
+            .collect::<Result<_, _>>()?;
+        Ok(ids)
+    }
+
+    /// Sync status of each item, based on its most recent export
+    pub fn statuses(&self, items: &[Item]) -> Result<HashMap<i64, SyncStatus>> {
+        // Later rows overwrite earlier ones, leaving the latest export per item
+        let mut stmt = self
+            .conn
+            .prepare("SELECT item_id, content_hash FROM exports ORDER BY exported_at, id")?;
+
+        let hashes: HashMap<i64, String> = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<_, _>>()?;
+
+        Ok(items
+            .iter()
+            .filter_map(|item| {
+                let id = item.id?;
+                let status = match hashes.get(&id) {
+                    Some(hash) if *hash == item.content_hash() => SyncStatus::Exported,
+                    Some(_) => SyncStatus::Modified,
+                    None => SyncStatus::NeverExported,
+                };
+                Some((id, status))
+            })
+            .collect())
+    }
+}
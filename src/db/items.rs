@@ -1,46 +1,156 @@
+use super::query::{Filter, SearchQuery};
+use super::{CollectionStore, LinkStore};
 use crate::models::{Category, Item};
 use color_eyre::eyre::Result;
-use rusqlite::{params, Connection};
+use rusqlite::{params, params_from_iter, types::Value, Connection};
+use std::collections::{HashMap, HashSet};
+use tracing::debug;
+
+/// Last time an item was updated, copied, or kept in stale review
+const LAST_TOUCHED: &str =
+    "max(updated_at, COALESCE(last_used_at, updated_at), COALESCE(reviewed_at, updated_at))";
 
 pub struct ItemStore<'a> {
     conn: &'a Connection,
 }
 
+/// Order of the item lists (`o` on the main screen)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum SortOrder {
+    /// Most recently updated first
+    #[default]
+    Updated,
+    /// Most recently created first
+    Created,
+    /// Alphabetical, ignoring case
+    Name,
+    /// Grouped by category, then alphabetical
+    Category,
+}
+
+impl SortOrder {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SortOrder::Updated => "updated",
+            SortOrder::Created => "created",
+            SortOrder::Name => "name",
+            SortOrder::Category => "category",
+        }
+    }
+
+    pub fn from_str(s: &str) -> Self {
+        match s.to_lowercase().as_str() {
+            "created" => SortOrder::Created,
+            "name" => SortOrder::Name,
+            "category" => SortOrder::Category,
+            _ => SortOrder::Updated,
+        }
+    }
+
+    pub fn next(&self) -> Self {
+        match self {
+            SortOrder::Updated => SortOrder::Created,
+            SortOrder::Created => SortOrder::Name,
+            SortOrder::Name => SortOrder::Category,
+            SortOrder::Category => SortOrder::Updated,
+        }
+    }
+
+    /// `ORDER BY` terms, ending with `id` so the order is total
+    fn order_by(&self) -> &'static str {
+        match self {
+            SortOrder::Updated => "updated_at DESC, id DESC",
+            SortOrder::Created => "created_at DESC, id DESC",
+            SortOrder::Name => "name COLLATE NOCASE, id",
+            SortOrder::Category => "category, name COLLATE NOCASE, id",
+        }
+    }
+
+    /// Condition for rows after the keyset cursor bound to `?1` (sort
+    /// key), `?2` (name, for Category), and `?3` (id)
+    fn after(&self) -> &'static str {
+        match self {
+            SortOrder::Updated => "(updated_at, id) < (?1, ?3)",
+            SortOrder::Created => "(created_at, id) < (?1, ?3)",
+            SortOrder::Name => "(name COLLATE NOCASE, id) > (?1, ?3)",
+            SortOrder::Category => "(category, name COLLATE NOCASE, id) > (?1, ?2, ?3)",
+        }
+    }
+
+    /// Keyset cursor values of `item` for `after`
+    fn cursor(&self, item: &Item) -> Option<(String, String, i64)> {
+        let format = |dt: Option<chrono::DateTime<chrono::Utc>>| {
+            dt.map(|dt| dt.format("%Y-%m-%d %H:%M:%S").to_string())
+        };
+        let key = match self {
+            SortOrder::Updated => format(item.updated_at)?,
+            SortOrder::Created => format(item.created_at)?,
+            SortOrder::Name => item.name.clone(),
+            SortOrder::Category => item.category.as_str().to_string(),
+        };
+        Some((key, item.name.clone(), item.id?))
+    }
+}
+
 impl<'a> ItemStore<'a> {
     pub fn new(conn: &'a Connection) -> Self {
         Self { conn }
     }
 
-    pub fn list_recent(&self, limit: usize) -> Result<Vec<Item>> {
-        let mut stmt = self.conn.prepare(
+    /// Items in `sort` order, `limit` at a time. Pass the last item of the
+    /// previous page as `after` to get the next one.
+    pub fn list_recent(
+        &self,
+        sort: SortOrder,
+        after: Option<&Item>,
+        limit: usize,
+    ) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(&format!(
             r#"
             SELECT id, name, category, description, content, model, tools,
                    allowed_tools, argument_hint, permission_mode, skills,
                    tags, created_at, updated_at, version
             FROM items
-            ORDER BY updated_at DESC
-            LIMIT ?
+            WHERE deleted_at IS NULL AND archived = 0
+              AND (?1 IS NULL OR {})
+            ORDER BY {}
+            LIMIT ?4
             "#,
-        )?;
+            sort.after(),
+            sort.order_by()
+        ))?;
+
+        // Keyset cursor: sort key and id of the last row already shown
+        let (key, name, id) = match after.and_then(|item| sort.cursor(item)) {
+            Some((key, name, id)) => (Some(key), Some(name), Some(id)),
+            None => (None, None, None),
+        };
 
         let items = stmt
-            .query_map([limit], Item::from_row)?
+            .query_map(params![key, name, id, limit], Item::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(items)
     }
 
-    pub fn list_by_category(&self, category: Category) -> Result<Vec<Item>> {
-        let mut stmt = self.conn.prepare(
+    /// Every item outside the trash and the archive, most recently updated
+    /// first
+    pub fn list_all(&self) -> Result<Vec<Item>> {
+        self.list_recent(SortOrder::Updated, None, i64::MAX as usize)
+    }
+
+    pub fn list_by_category(&self, category: Category, sort: SortOrder) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(&format!(
             r#"
             SELECT id, name, category, description, content, model, tools,
                    allowed_tools, argument_hint, permission_mode, skills,
                    tags, created_at, updated_at, version
             FROM items
-            WHERE category = ?
-            ORDER BY updated_at DESC
+            WHERE category = ? AND deleted_at IS NULL AND archived = 0
+            ORDER BY {}
             "#,
-        )?;
+            sort.order_by()
+        ))?;
 
         let items = stmt
             .query_map([category.as_str()], Item::from_row)?
@@ -49,21 +159,66 @@ impl<'a> ItemStore<'a> {
         Ok(items)
     }
 
-    pub fn list_by_tag(&self, tag: &str) -> Result<Vec<Item>> {
-        let pattern = format!("%{}%", tag);
-        let mut stmt = self.conn.prepare(
+    /// Items with any of `tags`, or all of them when `match_all` is set
+    pub fn list_by_tags(
+        &self,
+        tags: &[String],
+        match_all: bool,
+        sort: SortOrder,
+    ) -> Result<Vec<Item>> {
+        let placeholders = vec!["?"; tags.len()].join(", ");
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE id IN (
+                SELECT it.item_id FROM item_tags it
+                JOIN tags t ON t.id = it.tag_id
+                WHERE t.name IN ({})
+                GROUP BY it.item_id
+                HAVING COUNT(*) >= {}
+            )
+              AND deleted_at IS NULL AND archived = 0
+            ORDER BY {}
+            "#,
+            placeholders,
+            if match_all { tags.len() } else { 1 },
+            sort.order_by()
+        ))?;
+
+        let items = stmt
+            .query_map(
+                params_from_iter(tags.iter().map(|tag| tag.to_lowercase())),
+                Item::from_row,
+            )?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Items in a collection or in the collections nested under it
+    pub fn list_by_collection(&self, path: &str, sort: SortOrder) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(&format!(
             r#"
             SELECT id, name, category, description, content, model, tools,
                    allowed_tools, argument_hint, permission_mode, skills,
                    tags, created_at, updated_at, version
             FROM items
-            WHERE tags LIKE ?
-            ORDER BY updated_at DESC
+            WHERE id IN (
+                SELECT ic.item_id FROM item_collections ic
+                JOIN collections c ON c.id = ic.collection_id
+                WHERE c.path = ?1 OR substr(c.path, 1, length(?1) + 1) = ?1 || '/'
+            )
+              AND deleted_at IS NULL AND archived = 0
+            ORDER BY {}
             "#,
-        )?;
+            sort.order_by()
+        ))?;
 
         let items = stmt
-            .query_map([pattern], Item::from_row)?
+            .query_map([path], Item::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(items)
@@ -84,6 +239,22 @@ impl<'a> ItemStore<'a> {
         Ok(item)
     }
 
+    /// Look up an item by its unique name, including items in the trash
+    pub fn get_by_name(&self, name: &str) -> Result<Option<Item>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE name = ?
+            "#,
+        )?;
+
+        let item = stmt.query_row([name], Item::from_row).optional()?;
+        Ok(item)
+    }
+
     pub fn insert(&self, item: &Item) -> Result<i64> {
         self.conn.execute(
             r#"
@@ -106,24 +277,34 @@ impl<'a> ItemStore<'a> {
             ],
         )?;
 
-        Ok(self.conn.last_insert_rowid())
+        let id = self.conn.last_insert_rowid();
+        self.set_tags(id, &item.tags_vec())?;
+        debug!(id, name = %item.name, category = item.category.as_str(), "inserted item");
+        Ok(id)
     }
 
-    /// Update an item, creating a version snapshot of the current state first
-    pub fn update(&self, item: &Item) -> Result<()> {
+    /// Update an item, creating a version snapshot of the current state first.
+    /// `message` describes the change and is shown in the history list.
+    pub fn update(&self, item: &Item, message: Option<&str>) -> Result<()> {
         let item_id = item
             .id
             .ok_or_else(|| color_eyre::eyre::eyre!("Item must have an id to update"))?;
 
         // Get current item to save as version
         if let Some(current) = self.get(item_id)? {
+            let current_message: Option<String> = self.conn.query_row(
+                "SELECT version_message FROM items WHERE id = ?",
+                [item_id],
+                |row| row.get(0),
+            )?;
+
             // Save current state to item_versions
             self.conn.execute(
                 r#"
                 INSERT INTO item_versions (item_id, version, name, category, description, content,
                                           model, tools, allowed_tools, argument_hint,
-                                          permission_mode, skills, tags)
-                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
+                                          permission_mode, skills, tags, message)
+                VALUES (?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?, ?)
                 "#,
                 params![
                     item_id,
@@ -139,6 +320,7 @@ impl<'a> ItemStore<'a> {
                     current.permission_mode,
                     current.skills,
                     current.tags,
+                    current_message,
                 ],
             )?;
         }
@@ -149,7 +331,7 @@ impl<'a> ItemStore<'a> {
             UPDATE items
             SET name = ?, category = ?, description = ?, content = ?, model = ?,
                 tools = ?, allowed_tools = ?, argument_hint = ?, permission_mode = ?,
-                skills = ?, tags = ?, updated_at = CURRENT_TIMESTAMP,
+                skills = ?, tags = ?, version_message = ?, updated_at = CURRENT_TIMESTAMP,
                 version = version + 1
             WHERE id = ?
             "#,
@@ -165,43 +347,370 @@ impl<'a> ItemStore<'a> {
                 item.permission_mode,
                 item.skills,
                 item.tags,
+                message.map(str::trim).filter(|m| !m.is_empty()),
                 item_id,
             ],
         )?;
 
+        self.set_tags(item_id, &item.tags_vec())?;
+        debug!(id = item_id, name = %item.name, message, "updated item");
         Ok(())
     }
 
+    /// Move an item to the trash
     pub fn delete(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE items SET deleted_at = CURRENT_TIMESTAMP WHERE id = ?",
+            [id],
+        )?;
+        debug!(id, "moved item to trash");
+        Ok(())
+    }
+
+    /// Take an item back out of the trash
+    pub fn restore(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("UPDATE items SET deleted_at = NULL WHERE id = ?", [id])?;
+        debug!(id, "restored item from trash");
+        Ok(())
+    }
+
+    /// Permanently delete an item, with its versions and export records
+    pub fn purge(&self, id: i64) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM item_versions WHERE item_id = ?", [id])?;
+        self.conn
+            .execute("DELETE FROM exports WHERE item_id = ?", [id])?;
+        self.set_tags(id, &[])?;
+        LinkStore::new(self.conn).remove_item(id)?;
+        CollectionStore::new(self.conn).remove_item(id)?;
         self.conn.execute("DELETE FROM items WHERE id = ?", [id])?;
+        debug!(id, "purged item");
+        Ok(())
+    }
+
+    /// Permanently delete every item in the trash, returning how many there were
+    pub fn empty_trash(&self) -> Result<usize> {
+        let trashed = self.list_trash()?;
+        for item in &trashed {
+            if let Some(id) = item.id {
+                self.purge(id)?;
+            }
+        }
+        Ok(trashed.len())
+    }
+
+    pub fn set_favorite(&self, id: i64, favorite: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE items SET favorite = ? WHERE id = ?",
+            params![favorite, id],
+        )?;
         Ok(())
     }
 
-    pub fn search(&self, query: &str) -> Result<Vec<Item>> {
+    pub fn list_favorites(&self, sort: SortOrder) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE favorite = 1 AND deleted_at IS NULL
+            ORDER BY {}
+            "#,
+            sort.order_by()
+        ))?;
+
+        let items = stmt
+            .query_map([], Item::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Ids of all favorite items, for the star in the item list
+    pub fn favorite_ids(&self) -> Result<HashSet<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM items WHERE favorite = 1 AND deleted_at IS NULL")?;
+
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        Ok(ids)
+    }
+
+    /// Count a copy of the item's content, for the usage column and stale
+    /// review
+    pub fn record_use(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            // Milliseconds keep copies made in the same second in order
+            r#"
+            UPDATE items
+            SET use_count = use_count + 1,
+                last_used_at = strftime('%Y-%m-%d %H:%M:%f', 'now')
+            WHERE id = ?
+            "#,
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// The `limit` items copied most recently, last copy first
+    pub fn list_recently_copied(&self, limit: usize) -> Result<Vec<Item>> {
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT i.id, i.name, i.category, i.description, i.content, i.model, i.tools,
-                   i.allowed_tools, i.argument_hint, i.permission_mode, i.skills,
-                   i.tags, i.created_at, i.updated_at, i.version
-            FROM items i
-            JOIN items_fts fts ON i.id = fts.rowid
-            WHERE items_fts MATCH ?
-            ORDER BY rank
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE last_used_at IS NOT NULL AND deleted_at IS NULL
+            ORDER BY last_used_at DESC
+            LIMIT ?
+            "#,
+        )?;
+
+        let items = stmt
+            .query_map([limit as i64], Item::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    pub fn count_copied(&self) -> Result<usize> {
+        let count = self.conn.query_row(
+            "SELECT COUNT(*) FROM items WHERE last_used_at IS NOT NULL AND deleted_at IS NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// How often each item has been copied, for items used at least once
+    pub fn use_counts(&self) -> Result<HashMap<i64, i64>> {
+        let mut stmt = self.conn.prepare(
+            "SELECT id, use_count FROM items WHERE use_count > 0 AND deleted_at IS NULL",
+        )?;
+
+        let counts = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<HashMap<_, _>, _>>()?;
+
+        Ok(counts)
+    }
+
+    pub fn set_archived(&self, id: i64, archived: bool) -> Result<()> {
+        self.conn.execute(
+            "UPDATE items SET archived = ? WHERE id = ?",
+            params![archived, id],
+        )?;
+        Ok(())
+    }
+
+    /// Archived items outside the trash
+    pub fn list_archived(&self, sort: SortOrder) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE archived = 1 AND deleted_at IS NULL
+            ORDER BY {}
+            "#,
+            sort.order_by()
+        ))?;
+
+        let items = stmt
+            .query_map([], Item::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    /// Ids of all archived items outside the trash
+    pub fn archived_ids(&self) -> Result<HashSet<i64>> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id FROM items WHERE archived = 1 AND deleted_at IS NULL")?;
+
+        let ids = stmt
+            .query_map([], |row| row.get(0))?
+            .collect::<Result<HashSet<_>, _>>()?;
+
+        Ok(ids)
+    }
+
+    /// Items not updated, copied, or kept in review for `months` months,
+    /// least recently touched first. Archived items are already dealt with
+    /// and left out.
+    pub fn list_stale(&self, months: u32) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE {} < datetime('now', ?)
+              AND deleted_at IS NULL AND archived = 0
+            ORDER BY {}
+            "#,
+            LAST_TOUCHED, LAST_TOUCHED
+        ))?;
+
+        let items = stmt
+            .query_map([format!("-{} months", months)], Item::from_row)?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(items)
+    }
+
+    pub fn count_stale(&self, months: u32) -> Result<usize> {
+        let count = self.conn.query_row(
+            &format!(
+                r#"
+                SELECT COUNT(*) FROM items
+                WHERE {} < datetime('now', ?)
+                  AND deleted_at IS NULL AND archived = 0
+                "#,
+                LAST_TOUCHED
+            ),
+            [format!("-{} months", months)],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Keep an item after review, taking it off the stale list until it
+    /// goes stale again
+    pub fn mark_reviewed(&self, id: i64) -> Result<()> {
+        self.conn.execute(
+            "UPDATE items SET reviewed_at = CURRENT_TIMESTAMP WHERE id = ?",
+            [id],
+        )?;
+        Ok(())
+    }
+
+    /// Items in the trash, most recently deleted first
+    pub fn list_trash(&self) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE deleted_at IS NOT NULL
+            ORDER BY deleted_at DESC
             "#,
         )?;
 
         let items = stmt
-            .query_map([query], Item::from_row)?
+            .query_map([], Item::from_row)?
             .collect::<Result<Vec<_>, _>>()?;
 
         Ok(items)
     }
 
+    /// Whether the item with `id` is in the trash
+    pub fn is_trashed(&self, id: i64) -> Result<bool> {
+        let trashed = self.conn.query_row(
+            "SELECT deleted_at IS NOT NULL FROM items WHERE id = ?",
+            [id],
+            |row| row.get(0),
+        )?;
+        Ok(trashed)
+    }
+
+    pub fn count_trash(&self) -> Result<usize> {
+        let count = self.conn.query_row(
+            "SELECT COUNT(*) FROM items WHERE deleted_at IS NOT NULL",
+            [],
+            |row| row.get(0),
+        )?;
+        Ok(count)
+    }
+
+    /// Items matching a query in the search syntax (see `SearchQuery`) and
+    /// passing every filter in `scope`. A query with no words to match
+    /// lists what its filters and exclusions leave by name.
+    pub fn search(
+        &self,
+        query: &str,
+        scope: &[Filter],
+        include_archived: bool,
+    ) -> Result<Vec<SearchHit>> {
+        let mut query = SearchQuery::parse(query);
+        if query.is_empty() {
+            return Ok(Vec::new());
+        }
+        for filter in scope {
+            query.add_filter(filter.clone());
+        }
+
+        let mut conditions = vec![
+            "i.deleted_at IS NULL".to_string(),
+            "(i.archived = 0 OR ?)".to_string(),
+        ];
+        let mut values = vec![Value::from(include_archived)];
+        let (join, snippet, order) = match query.match_expression() {
+            Some(expr) => {
+                conditions.push("items_fts MATCH ?".to_string());
+                values.push(Value::from(expr));
+                (
+                    "JOIN items_fts fts ON i.id = fts.rowid",
+                    "snippet(items_fts, -1, char(1), char(2), '…', 10)",
+                    "rank",
+                )
+            }
+            None => ("", "substr(i.content, 1, 200)", "i.name COLLATE NOCASE"),
+        };
+        if let Some(expr) = query.exclude_expression() {
+            conditions.push(
+                "i.id NOT IN (SELECT rowid FROM items_fts WHERE items_fts MATCH ?)".to_string(),
+            );
+            values.push(Value::from(expr));
+        }
+        for (condition, filter_values) in query.filter_conditions() {
+            conditions.push(condition);
+            values.extend(filter_values);
+        }
+
+        let mut stmt = self.conn.prepare(&format!(
+            r#"
+            SELECT i.id, i.name, i.category, i.description, i.content, i.model, i.tools,
+                   i.allowed_tools, i.argument_hint, i.permission_mode, i.skills,
+                   i.tags, i.created_at, i.updated_at, i.version,
+                   {}
+            FROM items i
+            {}
+            WHERE {}
+            ORDER BY {}
+            "#,
+            snippet,
+            join,
+            conditions.join(" AND "),
+            order
+        ))?;
+
+        let hits = stmt
+            .query_map(params_from_iter(values), |row| {
+                Ok(SearchHit {
+                    item: Item::from_row(row)?,
+                    snippet: row.get::<_, Option<String>>(15)?.unwrap_or_default(),
+                })
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(hits)
+    }
+
     pub fn count_by_category(&self) -> Result<Vec<(Category, usize)>> {
         let mut stmt = self.conn.prepare(
             r#"
             SELECT category, COUNT(*) as count
             FROM items
+            WHERE deleted_at IS NULL AND archived = 0
             GROUP BY category
             "#,
         )?;
@@ -219,55 +728,194 @@ impl<'a> ItemStore<'a> {
     }
 
     pub fn get_tags_with_counts(&self) -> Result<Vec<(String, usize)>> {
-        // This is a simplified implementation - tags are comma-separated
-        // A production version might use a separate tags table
-        let mut stmt = self
-            .conn
-            .prepare("SELECT tags FROM items WHERE tags IS NOT NULL")?;
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT t.name, COUNT(*) AS count
+            FROM tags t
+            JOIN item_tags it ON it.tag_id = t.id
+            JOIN items i ON i.id = it.item_id
+            WHERE i.deleted_at IS NULL AND i.archived = 0
+            GROUP BY t.id
+            ORDER BY count DESC, t.name ASC
+            "#,
+        )?;
 
-        let mut tag_counts: std::collections::HashMap<String, usize> =
-            std::collections::HashMap::new();
+        let tags = stmt
+            .query_map([], |row| Ok((row.get(0)?, row.get(1)?)))?
+            .collect::<Result<Vec<_>, _>>()?;
 
-        let rows = stmt.query_map([], |row| {
-            let tags: String = row.get(0)?;
-            Ok(tags)
-        })?;
+        Ok(tags)
+    }
+
+    /// Replace an item's tags. `items.tags` is kept as a copy of the tag
+    /// names for full-text search and version snapshots.
+    pub(super) fn set_tags(&self, item_id: i64, tags: &[String]) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM item_tags WHERE item_id = ?", [item_id])?;
 
-        for tags in rows.flatten() {
-            for tag in tags.split(',') {
-                let tag = tag.trim().to_lowercase();
-                if !tag.is_empty() {
-                    *tag_counts.entry(tag).or_insert(0) += 1;
+        for tag in tags {
+            self.conn
+                .execute("INSERT OR IGNORE INTO tags (name) VALUES (?)", [tag])?;
+            self.conn.execute(
+                r#"
+                INSERT OR IGNORE INTO item_tags (item_id, tag_id)
+                SELECT ?, id FROM tags WHERE name = ?
+                "#,
+                params![item_id, tag],
+            )?;
+        }
+
+        let joined = (!tags.is_empty()).then(|| tags.join(", "));
+        self.conn.execute(
+            "UPDATE items SET tags = ? WHERE id = ?",
+            params![joined, item_id],
+        )?;
+
+        self.conn.execute(
+            "DELETE FROM tags WHERE id NOT IN (SELECT tag_id FROM item_tags)",
+            [],
+        )?;
+        Ok(())
+    }
+
+    /// Tags of every item carrying `tag`, keyed by item id
+    fn items_with_tag(&self, tag: &str) -> Result<Vec<(i64, Vec<String>)>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT it.item_id
+            FROM item_tags it
+            JOIN tags t ON t.id = it.tag_id
+            WHERE t.name = ?
+            "#,
+        )?;
+        let ids = stmt
+            .query_map([tag], |row| row.get::<_, i64>(0))?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        let mut result = Vec::new();
+        for id in ids {
+            if let Some(item) = self.get(id)? {
+                result.push((id, item.tags_vec()));
+            }
+        }
+        Ok(result)
+    }
+
+    /// Rename a tag on every item. Renaming to an existing tag merges the two.
+    /// Returns the number of items changed.
+    pub fn rename_tag(&self, from: &str, to: &str) -> Result<usize> {
+        let from = from.trim().to_lowercase();
+        let to = to.trim().trim_start_matches('#').trim().to_lowercase();
+        if to.is_empty() || to.contains(',') {
+            return Err(color_eyre::eyre::eyre!("Invalid tag name '{}'", to));
+        }
+        if from == to {
+            return Ok(0);
+        }
+
+        let affected = self.items_with_tag(&from)?;
+        for (item_id, tags) in &affected {
+            let mut renamed: Vec<String> = Vec::new();
+            for tag in tags {
+                let tag = if *tag == from {
+                    to.clone()
+                } else {
+                    tag.clone()
+                };
+                if !renamed.contains(&tag) {
+                    renamed.push(tag);
                 }
             }
+            self.set_tags(*item_id, &renamed)?;
         }
+        Ok(affected.len())
+    }
 
-        let mut tags: Vec<_> = tag_counts.into_iter().collect();
-        // Sort by count descending, then by name ascending for stable ordering
-        tags.sort_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)));
+    /// Merge `from` into `into`: items tagged `from` get `into` instead
+    pub fn merge_tag(&self, from: &str, into: &str) -> Result<usize> {
+        self.rename_tag(from, into)
+    }
 
-        Ok(tags)
+    /// Remove a tag from every item. Returns the number of items changed.
+    pub fn delete_tag(&self, tag: &str) -> Result<usize> {
+        let tag = tag.trim().to_lowercase();
+        let affected = self.items_with_tag(&tag)?;
+        for (item_id, tags) in &affected {
+            let remaining: Vec<String> = tags.iter().filter(|t| **t != tag).cloned().collect();
+            self.set_tags(*item_id, &remaining)?;
+        }
+        Ok(affected.len())
+    }
+
+    /// Add and remove tags on several items. Returns the number of items
+    /// whose tags changed.
+    pub fn edit_tags(&self, ids: &[i64], add: &[String], remove: &[String]) -> Result<usize> {
+        let mut changed = 0;
+        for id in ids {
+            let Some(item) = self.get(*id)? else {
+                continue;
+            };
+            let current = item.tags_vec();
+            let mut tags: Vec<String> = current
+                .iter()
+                .filter(|tag| !remove.contains(tag))
+                .cloned()
+                .collect();
+            for tag in add {
+                if !tags.contains(tag) {
+                    tags.push(tag.clone());
+                }
+            }
+            if tags != current {
+                self.set_tags(*id, &tags)?;
+                changed += 1;
+            }
+        }
+        Ok(changed)
+    }
+
+    /// Fill `tags` and `item_tags` from the comma-separated `items.tags`
+    /// column of databases created before tags were normalized
+    pub fn backfill_tags(&self) -> Result<()> {
+        let mut stmt = self
+            .conn
+            .prepare("SELECT id, tags FROM items WHERE tags IS NOT NULL")?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, i64>(0)?, row.get::<_, String>(1)?))
+            })?
+            .collect::<Result<Vec<_>, _>>()?;
+
+        for (id, tags) in rows {
+            let item = Item {
+                tags: Some(tags),
+                ..Item::default()
+            };
+            self.set_tags(id, &item.tags_vec())?;
+        }
+        Ok(())
     }
 
     /// List all versions of an item (version number and created_at)
     pub fn list_versions(&self, item_id: i64) -> Result<Vec<ItemVersion>> {
         // First get the current version from items table
-        let current: Option<(i64, String)> = self
+        let current: Option<(i64, String, Option<String>)> = self
             .conn
             .query_row(
-                "SELECT version, updated_at FROM items WHERE id = ?",
+                "SELECT version, updated_at, version_message FROM items WHERE id = ?",
                 [item_id],
-                |row| Ok((row.get(0)?, row.get(1)?)),
+                |row| Ok((row.get(0)?, row.get(1)?, row.get(2)?)),
             )
             .optional()?;
 
         let mut versions = Vec::new();
 
         // Add current version (latest)
-        if let Some((version, updated_at)) = current {
+        if let Some((version, updated_at, message)) = current {
             versions.push(ItemVersion {
                 version,
                 created_at: updated_at,
+                message,
                 is_current: true,
             });
         }
@@ -275,7 +923,7 @@ impl<'a> ItemStore<'a> {
         // Get historical versions from item_versions table
         let mut stmt = self.conn.prepare(
             r#"
-            SELECT version, created_at
+            SELECT version, created_at, message
             FROM item_versions
             WHERE item_id = ?
             ORDER BY version DESC
@@ -286,6 +934,7 @@ impl<'a> ItemStore<'a> {
             Ok(ItemVersion {
                 version: row.get(0)?,
                 created_at: row.get(1)?,
+                message: row.get(2)?,
                 is_current: false,
             })
         })?;
@@ -355,7 +1004,7 @@ impl<'a> ItemStore<'a> {
             .ok_or_else(|| color_eyre::eyre::eyre!("Version not found"))?;
 
         // Update the item with the old content (this will auto-increment version)
-        self.update(&old_version)?;
+        self.update(&old_version, Some(&format!("Restored v{}", version)))?;
 
         Ok(())
     }
@@ -366,9 +1015,23 @@ impl<'a> ItemStore<'a> {
 pub struct ItemVersion {
     pub version: i64,
     pub created_at: String,
+    /// Optional change message given when the version was saved
+    pub message: Option<String>,
     pub is_current: bool,
 }
 
+/// A search result with the text around the match
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    pub item: Item,
+    /// Matching context, with each matched term wrapped in
+    /// `SNIPPET_START` / `SNIPPET_END`
+    pub snippet: String,
+}
+
+pub const SNIPPET_START: char = '\u{1}';
+pub const SNIPPET_END: char = '\u{2}';
+
 /// Parse SQLite datetime format (YYYY-MM-DD HH:MM:SS) to DateTime<Utc>
 fn parse_sqlite_datetime(s: &str) -> Option<chrono::DateTime<chrono::Utc>> {
     chrono::NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S")
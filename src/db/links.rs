@@ -0,0 +1,105 @@
+use crate::models::{Category, Item};
+use color_eyre::eyre::Result;
+use rusqlite::{params, Connection};
+
+/// Dependencies between items, e.g. the skills and commands an agent uses
+pub struct LinkStore<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> LinkStore<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    /// Items that `item_id` links to, outside the trash
+    pub fn targets(&self, item_id: i64) -> Result<Vec<Item>> {
+        self.query(
+            r#"
+            SELECT i.id, i.name, i.category, i.description, i.content, i.model, i.tools,
+                   i.allowed_tools, i.argument_hint, i.permission_mode, i.skills,
+                   i.tags, i.created_at, i.updated_at, i.version
+            FROM item_links l
+            JOIN items i ON i.id = l.target_id
+            WHERE l.item_id = ? AND i.deleted_at IS NULL
+            ORDER BY i.name COLLATE NOCASE
+            "#,
+            item_id,
+        )
+    }
+
+    /// Items that link to `item_id`, outside the trash
+    pub fn sources(&self, item_id: i64) -> Result<Vec<Item>> {
+        self.query(
+            r#"
+            SELECT i.id, i.name, i.category, i.description, i.content, i.model, i.tools,
+                   i.allowed_tools, i.argument_hint, i.permission_mode, i.skills,
+                   i.tags, i.created_at, i.updated_at, i.version
+            FROM item_links l
+            JOIN items i ON i.id = l.item_id
+            WHERE l.target_id = ? AND i.deleted_at IS NULL
+            ORDER BY i.name COLLATE NOCASE
+            "#,
+            item_id,
+        )
+    }
+
+    /// Skills and commands an item can link to, excluding the item itself
+    pub fn candidates(&self, item_id: Option<i64>) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(
+            r#"
+            SELECT id, name, category, description, content, model, tools,
+                   allowed_tools, argument_hint, permission_mode, skills,
+                   tags, created_at, updated_at, version
+            FROM items
+            WHERE category IN (?, ?) AND deleted_at IS NULL AND id IS NOT ?
+            ORDER BY category, name COLLATE NOCASE
+            "#,
+        )?;
+
+        let items = stmt
+            .query_map(
+                params![
+                    Category::Skill.as_str(),
+                    Category::Command.as_str(),
+                    item_id
+                ],
+                Item::from_row,
+            )?
+            .filter_map(|r| r.ok())
+            .collect();
+
+        Ok(items)
+    }
+
+    /// Replace the links of `item_id`
+    pub fn set(&self, item_id: i64, target_ids: &[i64]) -> Result<()> {
+        self.conn
+            .execute("DELETE FROM item_links WHERE item_id = ?", [item_id])?;
+        for target_id in target_ids {
+            self.conn.execute(
+                "INSERT OR IGNORE INTO item_links (item_id, target_id) VALUES (?, ?)",
+                params![item_id, target_id],
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Drop every link from or to `item_id`
+    pub fn remove_item(&self, item_id: i64) -> Result<()> {
+        self.conn.execute(
+            "DELETE FROM item_links WHERE item_id = ?1 OR target_id = ?1",
+            [item_id],
+        )?;
+        Ok(())
+    }
+
+    fn query(&self, sql: &str, item_id: i64) -> Result<Vec<Item>> {
+        let mut stmt = self.conn.prepare(sql)?;
+        let items = stmt
+            .query_map([item_id], Item::from_row)?
+            .filter_map(|r| r.ok())
+            .collect();
+        Ok(items)
+    }
+}
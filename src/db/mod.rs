@@ -1,7 +1,25 @@
+mod ai_actions;
+mod collections;
+mod dump;
+mod exports;
 mod items;
+mod links;
+mod query;
 mod schema;
+mod secrets;
 mod settings;
+mod usage;
+mod vaults;
 
-pub use items::{ItemStore, ItemVersion};
+pub use ai_actions::{ActionTarget, AiActionStore, UserAction};
+pub use collections::{normalize_collection_path, CollectionStore};
+pub use dump::{unused_name, ConflictMode, Dump, LoadSummary};
+pub use exports::{ExportStore, SyncStatus};
+pub use items::{ItemStore, ItemVersion, SearchHit, SortOrder, SNIPPET_END, SNIPPET_START};
+pub use links::LinkStore;
+pub use query::Filter;
 pub use schema::Database;
+pub use secrets::SecretStore;
 pub use settings::SettingsStore;
+pub use usage::{UsageStore, UsageTotals};
+pub use vaults::{Vaults, DEFAULT_VAULT};
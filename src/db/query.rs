@@ -0,0 +1,218 @@
+use crate::models::Category;
+use rusqlite::types::Value;
+
+/// A word or quoted phrase of a search
+#[derive(Debug, Clone)]
+struct Term {
+    text: String,
+    phrase: bool,
+}
+
+impl Term {
+    /// The term as an FTS5 string, so punctuation in it is never read as
+    /// query syntax
+    fn quoted(&self) -> String {
+        format!("\"{}\"", self.text.replace('"', "\"\""))
+    }
+}
+
+/// A `field:value` part of a search, checked against a column instead of
+/// the full-text index
+#[derive(Debug, Clone)]
+pub enum Filter {
+    /// `category:agent`; the start of a category's name is enough
+    Category(String),
+    /// `tag:rust`, the whole tag; a scoped search can allow any of several
+    Tags(Vec<String>),
+    /// `name:review`, anywhere in the name
+    Name(String),
+}
+
+impl Filter {
+    /// The filter for `field:value`, if `field` is one searches know and
+    /// `value` isn't empty
+    fn parse(field: &str, value: &str) -> Option<Self> {
+        let value = value.trim().to_lowercase();
+        if value.is_empty() {
+            return None;
+        }
+        match field.to_lowercase().as_str() {
+            "category" => {
+                let category = Category::all().into_iter().find(|category| {
+                    category.as_str().starts_with(&value)
+                        || category.display_name().to_lowercase() == value
+                });
+                // An unknown category is kept as typed and matches nothing
+                Some(Self::Category(
+                    category.map_or(value, |category| category.as_str().to_string()),
+                ))
+            }
+            "tag" => Some(Self::Tags(vec![value])),
+            "name" => Some(Self::Name(value)),
+            _ => None,
+        }
+    }
+
+    /// SQL condition on `items i`, with the values for its parameters
+    fn condition(&self) -> (String, Vec<Value>) {
+        match self {
+            Self::Category(category) => (
+                "i.category = ?".to_string(),
+                vec![Value::from(category.clone())],
+            ),
+            Self::Tags(tags) => (
+                format!(
+                    "i.id IN (SELECT it.item_id FROM item_tags it \
+                     JOIN tags t ON t.id = it.tag_id WHERE t.name IN ({}))",
+                    vec!["?"; tags.len()].join(", ")
+                ),
+                tags.iter()
+                    .map(|tag| Value::from(tag.to_lowercase()))
+                    .collect(),
+            ),
+            Self::Name(name) => {
+                let escaped = name
+                    .replace('\\', "\\\\")
+                    .replace('%', "\\%")
+                    .replace('_', "\\_");
+                (
+                    "i.name LIKE ? ESCAPE '\\'".to_string(),
+                    vec![Value::from(format!("%{}%", escaped))],
+                )
+            }
+        }
+    }
+}
+
+/// A search as typed: words and `"exact phrases"` that must all match
+/// (either one, around `OR`), `-excluded` ones that must not, and
+/// `field:value` filters. The last word matches as a prefix.
+#[derive(Debug, Default)]
+pub struct SearchQuery {
+    /// Terms to match, each with whether it's an alternative to the one
+    /// before it
+    include: Vec<(Term, bool)>,
+    exclude: Vec<Term>,
+    /// Filters, each with whether it's negated (`-tag:draft`)
+    filters: Vec<(Filter, bool)>,
+}
+
+impl SearchQuery {
+    pub fn parse(input: &str) -> Self {
+        let mut query = Self::default();
+        let mut alternative = false;
+        let mut rest = input;
+
+        loop {
+            rest = rest.trim_start();
+            if rest.is_empty() {
+                break;
+            }
+            let excluded = rest.len() > 1 && rest.starts_with('-');
+            if excluded {
+                rest = &rest[1..];
+            }
+
+            if let Some((field, value)) = rest.split_once(':') {
+                if matches!(field.to_lowercase().as_str(), "category" | "tag" | "name") {
+                    let (value, _, after) = next_text(value);
+                    rest = after;
+                    // `tag:` alone, still being typed, narrows nothing yet
+                    if let Some(filter) = Filter::parse(field, value) {
+                        query.filters.push((filter, excluded));
+                    }
+                    continue;
+                }
+            }
+
+            let (text, phrase, after) = next_text(rest);
+            rest = after;
+            if text == "OR" && !phrase && !excluded {
+                alternative = !query.include.is_empty();
+                continue;
+            }
+            let term = Term {
+                text: text.to_string(),
+                phrase,
+            };
+
+            // Nothing in it would be indexed, so it can't match anything
+            if !term.text.chars().any(char::is_alphanumeric) {
+                continue;
+            }
+            if excluded {
+                query.exclude.push(term);
+            } else {
+                query.include.push((term, alternative));
+                alternative = false;
+            }
+        }
+
+        query
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.include.is_empty() && self.exclude.is_empty() && self.filters.is_empty()
+    }
+
+    /// Add a filter the search must also pass, such as its scope
+    pub fn add_filter(&mut self, filter: Filter) {
+        self.filters.push((filter, false));
+    }
+
+    /// SQL conditions on `items i` for the filters, with their parameters
+    pub fn filter_conditions(&self) -> Vec<(String, Vec<Value>)> {
+        self.filters
+            .iter()
+            .map(|(filter, negated)| {
+                let (condition, values) = filter.condition();
+                if *negated {
+                    (format!("NOT ({})", condition), values)
+                } else {
+                    (condition, values)
+                }
+            })
+            .collect()
+    }
+
+    /// FTS5 expression for the terms to match, if there are any
+    pub fn match_expression(&self) -> Option<String> {
+        let last = self.include.len().checked_sub(1)?;
+        let mut expr = String::new();
+        for (i, (term, alternative)) in self.include.iter().enumerate() {
+            if i > 0 {
+                expr.push_str(if *alternative { " OR " } else { " AND " });
+            }
+            expr.push_str(&term.quoted());
+            if i == last && !term.phrase {
+                expr.push('*');
+            }
+        }
+        Some(expr)
+    }
+
+    /// FTS5 expression matching any of the excluded terms, if there are any
+    pub fn exclude_expression(&self) -> Option<String> {
+        if self.exclude.is_empty() {
+            return None;
+        }
+        Some(
+            self.exclude
+                .iter()
+                .map(Term::quoted)
+                .collect::<Vec<_>>()
+                .join(" OR "),
+        )
+    }
+}
+
+/// The word or quoted phrase at the start of `text`, whether it was quoted,
+/// and what follows it. An unclosed quote runs to the end.
+fn next_text(text: &str) -> (&str, bool, &str) {
+    if let Some(inner) = text.strip_prefix('"') {
+        let (phrase, after) = inner.split_once('"').unwrap_or((inner, ""));
+        return (phrase, true, after);
+    }
+    let end = text.find(char::is_whitespace).unwrap_or(text.len());
+    (&text[..end], false, &text[end..])
+}
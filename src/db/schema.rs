@@ -1,34 +1,108 @@
-use color_eyre::eyre::Result;
-use rusqlite::Connection;
-use std::path::PathBuf;
+use super::{ItemStore, Vaults};
+use color_eyre::eyre::{eyre, Result};
+use rusqlite::backup::Progress;
+use rusqlite::{Connection, DatabaseName, OpenFlags};
+use std::path::{Path, PathBuf};
+use tracing::{debug, info};
 
 pub struct Database {
     pub conn: Connection,
 }
 
 impl Database {
-    pub fn new() -> Result<Self> {
-        let db_path = Self::db_path()?;
+    /// Open (creating if needed) the database of the named vault
+    pub fn open(vault: &str) -> Result<Self> {
+        Vaults::validate(vault)?;
+        let db_path = Vaults::path(vault)?;
 
         // Create parent directory if it doesn't exist
         if let Some(parent) = db_path.parent() {
             std::fs::create_dir_all(parent)?;
         }
 
+        debug!(vault, path = %db_path.display(), "opening database");
         let conn = Connection::open(&db_path)?;
+        // The TUI reads on a second connection, and `serve` may run
+        // alongside it; wait out the other's writes instead of failing
+        conn.busy_timeout(std::time::Duration::from_secs(5))?;
         let db = Self { conn };
         db.init_schema()?;
         Ok(db)
     }
 
-    pub fn db_path() -> Result<PathBuf> {
+    pub fn data_dir() -> Result<PathBuf> {
         let proj_dirs = directories::ProjectDirs::from("", "", "grimoire")
             .ok_or_else(|| color_eyre::eyre::eyre!("Could not determine home directory"))?;
 
-        Ok(proj_dirs.data_dir().join("grimoire.db"))
+        Ok(proj_dirs.data_dir().to_path_buf())
+    }
+
+    /// Default location for backups made from Settings
+    pub fn backup_dir() -> Result<PathBuf> {
+        Ok(Self::data_dir()?.join("backups"))
+    }
+
+    /// Copy the whole database (items, versions, settings) to `path` with
+    /// SQLite's online backup API, so a concurrent write can't tear it
+    pub fn backup(&self, path: &Path) -> Result<()> {
+        if path.exists() {
+            return Err(eyre!("{} already exists", path.display()));
+        }
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+
+        self.conn.backup(DatabaseName::Main, path, None)?;
+        info!(path = %path.display(), "backed up database");
+        Ok(())
+    }
+
+    /// Replace the database with the backup at `path`. The current database
+    /// is first saved as `grimoire.db.pre-restore.bak`, and older backups
+    /// are migrated to the current schema.
+    pub fn restore(&mut self, path: &Path) -> Result<()> {
+        Self::check_backup(path)?;
+
+        if let Some(db_path) = self.conn.path().filter(|p| !p.is_empty()) {
+            let safety_path = PathBuf::from(format!("{}.pre-restore.bak", db_path));
+            if safety_path.exists() {
+                std::fs::remove_file(&safety_path)?;
+            }
+            self.backup(&safety_path)?;
+        }
+
+        self.conn
+            .restore(DatabaseName::Main, path, None::<fn(Progress)>)?;
+        info!(path = %path.display(), "restored database");
+        self.init_schema()
+    }
+
+    /// Make sure `path` is a grimoire database this version can read
+    fn check_backup(path: &Path) -> Result<()> {
+        if !path.is_file() {
+            return Err(eyre!("{} not found", path.display()));
+        }
+
+        let conn = Connection::open_with_flags(path, OpenFlags::SQLITE_OPEN_READ_ONLY)?;
+        let backup = Self { conn };
+        if !backup.has_table("items").unwrap_or(false) {
+            return Err(eyre!("{} is not a grimoire database", path.display()));
+        }
+
+        let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+        if backup.has_table("schema_version")? && backup.schema_version()? > latest {
+            return Err(eyre!(
+                "{} was made by a newer grimoire (schema v{})",
+                path.display(),
+                backup.schema_version()?
+            ));
+        }
+        Ok(())
     }
 
     fn init_schema(&self) -> Result<()> {
+        let is_new = !self.has_table("items")?;
+
         self.conn.execute_batch(
             r#"
             -- Items table
@@ -111,27 +185,346 @@ impl Database {
             );
 
             CREATE INDEX IF NOT EXISTS idx_versions_item ON item_versions(item_id, version DESC);
+
+            -- Export manifest: where each item was last written, per target
+            CREATE TABLE IF NOT EXISTS exports (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                item_id INTEGER NOT NULL,
+                target TEXT NOT NULL,
+                path TEXT NOT NULL,
+                content_hash TEXT NOT NULL,
+                exported_at DATETIME DEFAULT CURRENT_TIMESTAMP,
+
+                UNIQUE (item_id, target),
+                FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE CASCADE
+            );
+
+            -- Token usage and estimated cost of AI calls
+            CREATE TABLE IF NOT EXISTS llm_usage (
+                id INTEGER PRIMARY KEY AUTOINCREMENT,
+                provider TEXT NOT NULL,
+                model TEXT NOT NULL,
+                input_tokens INTEGER NOT NULL,
+                output_tokens INTEGER NOT NULL,
+                cost_usd REAL,
+                created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+            );
             "#,
         )?;
 
-        // Run migrations for existing databases
-        self.run_migrations()?;
+        self.run_migrations(is_new)?;
 
         Ok(())
     }
 
-    fn run_migrations(&self) -> Result<()> {
-        // Migration: Add version column to items table
-        let has_version_column: bool = self
-            .conn
-            .prepare("SELECT version FROM items LIMIT 1")
-            .is_ok();
+    /// Apply pending migrations in order, recording each in `schema_version`.
+    /// Existing databases are copied to `grimoire.db.pre-v<N>.bak` before
+    /// migration N runs.
+    fn run_migrations(&self, is_new: bool) -> Result<()> {
+        let had_version_table = self.has_table("schema_version")?;
+        self.conn.execute_batch(
+            "CREATE TABLE IF NOT EXISTS schema_version (version INTEGER NOT NULL);",
+        )?;
 
-        if !has_version_column {
+        let current = if had_version_table {
+            self.schema_version()?
+        } else if is_new {
+            0
+        } else {
+            // Databases from before schema_version: detect which of the
+            // probe-based migrations already ran
+            self.legacy_version()
+        };
+
+        if !had_version_table {
             self.conn
-                .execute("ALTER TABLE items ADD COLUMN version INTEGER DEFAULT 1", [])?;
+                .execute("INSERT INTO schema_version (version) VALUES (?)", [current])?;
+        }
+
+        let latest = MIGRATIONS.last().map_or(0, |m| m.version);
+        if current > latest {
+            return Err(eyre!(
+                "Database schema v{} is newer than this grimoire supports (v{}); please upgrade",
+                current,
+                latest
+            ));
+        }
+
+        for migration in MIGRATIONS.iter().filter(|m| m.version > current) {
+            if !is_new {
+                self.backup_before(migration.version)?;
+            }
+
+            let tx = self.conn.unchecked_transaction()?;
+            (migration.apply)(&tx).map_err(|e| {
+                eyre!(
+                    "Migration {} ({}) failed: {}",
+                    migration.version,
+                    migration.description,
+                    e
+                )
+            })?;
+            tx.execute("UPDATE schema_version SET version = ?", [migration.version])?;
+            tx.commit()?;
+            info!(
+                version = migration.version,
+                migration = migration.description,
+                "migrated schema"
+            );
         }
 
         Ok(())
     }
+
+    pub fn schema_version(&self) -> Result<u32> {
+        let version = self
+            .conn
+            .query_row("SELECT version FROM schema_version", [], |row| row.get(0))?;
+        Ok(version)
+    }
+
+    fn has_table(&self, name: &str) -> Result<bool> {
+        let count: i64 = self.conn.query_row(
+            "SELECT COUNT(*) FROM sqlite_master WHERE type = 'table' AND name = ?",
+            [name],
+            |row| row.get(0),
+        )?;
+        Ok(count > 0)
+    }
+
+    fn legacy_version(&self) -> u32 {
+        let probes = [
+            "SELECT version FROM items LIMIT 1",
+            "SELECT version_message FROM items LIMIT 1",
+            "SELECT deleted_at FROM items LIMIT 1",
+            "SELECT favorite FROM items LIMIT 1",
+            "SELECT id FROM tags LIMIT 1",
+        ];
+
+        probes
+            .iter()
+            .take_while(|probe| self.conn.prepare(probe).is_ok())
+            .count() as u32
+    }
+
+    /// Snapshot the database next to itself before a migration
+    fn backup_before(&self, version: u32) -> Result<()> {
+        let Some(db_path) = self.conn.path().filter(|p| !p.is_empty()) else {
+            return Ok(());
+        };
+
+        let backup_path = PathBuf::from(format!("{}.pre-v{}.bak", db_path, version));
+        if backup_path.exists() {
+            std::fs::remove_file(&backup_path)?;
+        }
+
+        self.conn
+            .execute("VACUUM INTO ?", [backup_path.to_string_lossy().to_string()])?;
+        Ok(())
+    }
 }
+
+struct Migration {
+    version: u32,
+    description: &'static str,
+    apply: fn(&Connection) -> Result<()>,
+}
+
+/// Schema changes on top of the base tables in `init_schema`. Append new
+/// migrations at the end with the next version number; never edit or
+/// reorder released ones.
+const MIGRATIONS: &[Migration] = &[
+    Migration {
+        version: 1,
+        description: "item version numbers",
+        apply: |conn| {
+            conn.execute("ALTER TABLE items ADD COLUMN version INTEGER DEFAULT 1", [])?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 2,
+        description: "version change messages",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE items ADD COLUMN version_message TEXT;
+                ALTER TABLE item_versions ADD COLUMN message TEXT;
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 3,
+        description: "trash",
+        apply: |conn| {
+            conn.execute("ALTER TABLE items ADD COLUMN deleted_at DATETIME", [])?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 4,
+        description: "favorites",
+        apply: |conn| {
+            conn.execute(
+                "ALTER TABLE items ADD COLUMN favorite INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 5,
+        description: "normalized tags",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE tags (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    name TEXT NOT NULL UNIQUE
+                );
+
+                CREATE TABLE item_tags (
+                    item_id INTEGER NOT NULL,
+                    tag_id INTEGER NOT NULL,
+
+                    PRIMARY KEY (item_id, tag_id),
+                    FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE CASCADE,
+                    FOREIGN KEY (tag_id) REFERENCES tags(id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX idx_item_tags_tag ON item_tags(tag_id);
+                "#,
+            )?;
+            // Fill from the comma-separated column
+            ItemStore::new(conn).backfill_tags()
+        },
+    },
+    Migration {
+        version: 6,
+        description: "item links",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE item_links (
+                    item_id INTEGER NOT NULL,
+                    target_id INTEGER NOT NULL,
+
+                    PRIMARY KEY (item_id, target_id),
+                    FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE CASCADE,
+                    FOREIGN KEY (target_id) REFERENCES items(id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX idx_item_links_target ON item_links(target_id);
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 7,
+        description: "archive",
+        apply: |conn| {
+            conn.execute(
+                "ALTER TABLE items ADD COLUMN archived INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 8,
+        description: "prefix search",
+        apply: |conn| {
+            // FTS5 options are fixed at creation, so the index is rebuilt
+            // with prefix indexes for 2-4 character prefixes
+            conn.execute_batch(
+                r#"
+                DROP TABLE items_fts;
+
+                CREATE VIRTUAL TABLE items_fts USING fts5(
+                    name, description, content, tags,
+                    content='items',
+                    content_rowid='id',
+                    prefix='2 3 4'
+                );
+
+                INSERT INTO items_fts(items_fts) VALUES('rebuild');
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 9,
+        description: "usage counts",
+        apply: |conn| {
+            conn.execute(
+                "ALTER TABLE items ADD COLUMN use_count INTEGER NOT NULL DEFAULT 0",
+                [],
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 10,
+        description: "collections",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE collections (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    -- Nested collections are written as parent/child
+                    path TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+
+                CREATE TABLE item_collections (
+                    item_id INTEGER NOT NULL,
+                    collection_id INTEGER NOT NULL,
+
+                    PRIMARY KEY (item_id, collection_id),
+                    FOREIGN KEY (item_id) REFERENCES items(id) ON DELETE CASCADE,
+                    FOREIGN KEY (collection_id) REFERENCES collections(id) ON DELETE CASCADE
+                );
+
+                CREATE INDEX idx_item_collections_collection ON item_collections(collection_id);
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 11,
+        description: "last use and review times",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                ALTER TABLE items ADD COLUMN last_used_at DATETIME;
+                ALTER TABLE items ADD COLUMN reviewed_at DATETIME;
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+    Migration {
+        version: 12,
+        description: "user-defined AI actions",
+        apply: |conn| {
+            conn.execute_batch(
+                r#"
+                CREATE TABLE ai_actions (
+                    id INTEGER PRIMARY KEY AUTOINCREMENT,
+                    label TEXT NOT NULL UNIQUE COLLATE NOCASE,
+                    system_prompt TEXT NOT NULL,
+                    -- content, description, or tags
+                    target TEXT NOT NULL DEFAULT 'content',
+                    created_at DATETIME DEFAULT CURRENT_TIMESTAMP
+                );
+                "#,
+            )?;
+            Ok(())
+        },
+    },
+];
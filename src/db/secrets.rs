@@ -0,0 +1,49 @@
+use super::DEFAULT_VAULT;
+use color_eyre::eyre::Result;
+
+const SERVICE: &str = "grimoire";
+
+/// Secrets (API keys) kept in the OS keychain: macOS Keychain, Windows
+/// Credential Manager, or the Secret Service on Linux. Each vault has its
+/// own entries; the default vault keeps the plain key names it had before
+/// there were vaults.
+pub struct SecretStore {
+    vault: String,
+}
+
+impl SecretStore {
+    pub fn new(vault: &str) -> Self {
+        Self {
+            vault: vault.to_string(),
+        }
+    }
+
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        match self.entry(key)?.get_password() {
+            Ok(value) => Ok(Some(value)),
+            Err(keyring::Error::NoEntry) => Ok(None),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    pub fn set(&self, key: &str, value: &str) -> Result<()> {
+        self.entry(key)?.set_password(value)?;
+        Ok(())
+    }
+
+    pub fn delete(&self, key: &str) -> Result<()> {
+        match self.entry(key)?.delete_credential() {
+            Ok(()) | Err(keyring::Error::NoEntry) => Ok(()),
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    fn entry(&self, key: &str) -> Result<keyring::Entry> {
+        let account = if self.vault == DEFAULT_VAULT {
+            key.to_string()
+        } else {
+            format!("{}/{}", self.vault, key)
+        };
+        Ok(keyring::Entry::new(SERVICE, &account)?)
+    }
+}
@@ -28,7 +28,6 @@ impl<'a> SettingsStore<'a> {
         Ok(())
     }
 
-    #[allow(dead_code)]
     pub fn delete(&self, key: &str) -> Result<()> {
         self.conn
             .execute("DELETE FROM settings WHERE key = ?", [key])?;
@@ -0,0 +1,67 @@
+use crate::llm::Usage;
+use color_eyre::eyre::Result;
+use rusqlite::{params, Connection};
+
+/// Cumulative AI usage across all recorded calls
+#[derive(Debug, Clone, Copy, Default)]
+pub struct UsageTotals {
+    pub calls: u64,
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+    pub cost_usd: f64,
+}
+
+/// Token usage and estimated cost of each LLM call
+pub struct UsageStore<'a> {
+    conn: &'a Connection,
+}
+
+impl<'a> UsageStore<'a> {
+    pub fn new(conn: &'a Connection) -> Self {
+        Self { conn }
+    }
+
+    pub fn record(
+        &self,
+        provider: &str,
+        model: &str,
+        usage: Usage,
+        cost_usd: Option<f64>,
+    ) -> Result<()> {
+        self.conn.execute(
+            r#"
+            INSERT INTO llm_usage (provider, model, input_tokens, output_tokens, cost_usd)
+            VALUES (?, ?, ?, ?, ?)
+            "#,
+            params![
+                provider,
+                model,
+                usage.input_tokens as i64,
+                usage.output_tokens as i64,
+                cost_usd
+            ],
+        )?;
+        Ok(())
+    }
+
+    /// Calls without a cost estimate count towards tokens but not cost
+    pub fn totals(&self) -> Result<UsageTotals> {
+        let totals = self.conn.query_row(
+            r#"
+            SELECT COUNT(*), COALESCE(SUM(input_tokens), 0), COALESCE(SUM(output_tokens), 0),
+                   COALESCE(SUM(cost_usd), 0.0)
+            FROM llm_usage
+            "#,
+            [],
+            |row| {
+                Ok(UsageTotals {
+                    calls: row.get::<_, i64>(0)? as u64,
+                    input_tokens: row.get::<_, i64>(1)? as u64,
+                    output_tokens: row.get::<_, i64>(2)? as u64,
+                    cost_usd: row.get(3)?,
+                })
+            },
+        )?;
+        Ok(totals)
+    }
+}
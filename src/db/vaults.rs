@@ -0,0 +1,77 @@
+use super::Database;
+use color_eyre::eyre::{eyre, Result};
+use std::path::PathBuf;
+
+/// The vault backed by the original `grimoire.db`
+pub const DEFAULT_VAULT: &str = "default";
+
+/// Separate libraries, each in its own SQLite file. The default vault is
+/// `grimoire.db`; others live in `vaults/<name>.db` in the data directory.
+pub struct Vaults;
+
+impl Vaults {
+    pub fn path(name: &str) -> Result<PathBuf> {
+        let data_dir = Database::data_dir()?;
+        if name == DEFAULT_VAULT {
+            Ok(data_dir.join("grimoire.db"))
+        } else {
+            Ok(data_dir.join("vaults").join(format!("{}.db", name)))
+        }
+    }
+
+    /// Vault names, the default vault first
+    pub fn list() -> Result<Vec<String>> {
+        let mut names: Vec<String> = std::fs::read_dir(Database::data_dir()?.join("vaults"))
+            .into_iter()
+            .flatten()
+            .filter_map(|entry| entry.ok())
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "db"))
+            .filter_map(|path| Some(path.file_stem()?.to_string_lossy().to_string()))
+            .filter(|name| name != DEFAULT_VAULT)
+            .collect();
+        names.sort();
+        names.insert(0, DEFAULT_VAULT.to_string());
+        Ok(names)
+    }
+
+    pub fn validate(name: &str) -> Result<()> {
+        let valid = !name.is_empty()
+            && name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+        if valid {
+            Ok(())
+        } else {
+            Err(eyre!(
+                "Invalid vault name '{}' (use letters, digits, - and _)",
+                name
+            ))
+        }
+    }
+
+    /// Whether `name` is a valid vault whose database is already there
+    pub fn exists(name: &str) -> bool {
+        Self::validate(name).is_ok() && Self::path(name).is_ok_and(|path| path.exists())
+    }
+
+    /// The vault opened last in the TUI, if it still exists
+    pub fn last_used() -> Option<String> {
+        let name = std::fs::read_to_string(Self::last_used_file().ok()?).ok()?;
+        let name = name.trim().to_string();
+        Self::exists(&name).then_some(name)
+    }
+
+    pub fn remember(name: &str) -> Result<()> {
+        let file = Self::last_used_file()?;
+        if let Some(parent) = file.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(file, name)?;
+        Ok(())
+    }
+
+    fn last_used_file() -> Result<PathBuf> {
+        Ok(Database::data_dir()?.join("last_vault"))
+    }
+}
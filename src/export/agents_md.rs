@@ -0,0 +1,96 @@
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+const BEGIN_MARKER: &str = "<!-- grimoire:begin -->";
+const END_MARKER: &str = "<!-- grimoire:end -->";
+
+/// Exports agents and commands into a single `AGENTS.md` (Codex CLI and
+/// other AGENTS.md-consuming tools).
+pub struct AgentsMdExporter {
+    file_path: PathBuf,
+}
+
+impl AgentsMdExporter {
+    /// `project_root` is the directory containing (or receiving) `AGENTS.md`
+    pub fn new(project_root: impl AsRef<Path>) -> Self {
+        Self {
+            file_path: project_root.as_ref().join("AGENTS.md"),
+        }
+    }
+
+    /// Write the agents and commands among `items` into the grimoire block of
+    /// `AGENTS.md`, replacing the previous block. Text outside the block is kept.
+    pub fn export(&self, items: &[Item]) -> Result<PathBuf> {
+        let agents: Vec<&Item> = items
+            .iter()
+            .filter(|i| i.category == Category::Agent)
+            .collect();
+        let commands: Vec<&Item> = items
+            .iter()
+            .filter(|i| i.category == Category::Command)
+            .collect();
+
+        if agents.is_empty() && commands.is_empty() {
+            return Err(eyre!("AGENTS.md only includes agents and commands"));
+        }
+
+        let block = self.format_block(&agents, &commands);
+        let existing = fs::read_to_string(&self.file_path).unwrap_or_default();
+
+        let updated = match (existing.find(BEGIN_MARKER), existing.find(END_MARKER)) {
+            (Some(start), Some(end)) if end > start => format!(
+                "{}{}{}",
+                &existing[..start],
+                block,
+                &existing[end + END_MARKER.len()..]
+            ),
+            _ if existing.trim().is_empty() => format!("# AGENTS.md\n\n{}\n", block),
+            _ => format!("{}\n\n{}\n", existing.trim_end(), block),
+        };
+
+        fs::write(&self.file_path, updated)?;
+        Ok(self.file_path.clone())
+    }
+
+    fn format_block(&self, agents: &[&Item], commands: &[&Item]) -> String {
+        let mut sections = vec![BEGIN_MARKER.to_string()];
+
+        if !agents.is_empty() {
+            sections.push("## Agents".to_string());
+            for item in agents {
+                sections.push(self.format_item(item));
+            }
+        }
+
+        if !commands.is_empty() {
+            sections.push("## Commands".to_string());
+            for item in commands {
+                sections.push(self.format_item(item));
+            }
+        }
+
+        sections.push(END_MARKER.to_string());
+        sections.join("\n\n")
+    }
+
+    fn format_item(&self, item: &Item) -> String {
+        let mut lines = vec![format!("### {}", item.name)];
+
+        if let Some(ref desc) = item.description {
+            lines.push(String::new());
+            lines.push(format!("> {}", desc.replace('\n', " ")));
+        }
+
+        if let Some(ref hint) = item.argument_hint {
+            lines.push(String::new());
+            lines.push(format!("Arguments: `{}`", hint));
+        }
+
+        lines.push(String::new());
+        lines.push(item.content.trim_end().to_string());
+
+        lines.join("\n")
+    }
+}
@@ -0,0 +1,34 @@
+use super::bundle::archive_path;
+use super::gist::item_document;
+use crate::models::Item;
+use color_eyre::eyre::{eyre, Result};
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// File name of a zip export when no other is given
+pub const ZIP_FILE_NAME: &str = "grimoire-export.zip";
+
+/// Write `items` into a plain zip archive at `path`, each as the markdown
+/// file it exports to, frontmatter included, laid out like `.claude`.
+/// Unlike a bundle it has no manifest, so it's meant for people rather than
+/// for loading back. Returns how many items were written.
+pub fn write_zip(items: &[Item], path: &Path) -> Result<usize> {
+    if items.is_empty() {
+        return Err(eyre!("nothing to put in the archive"));
+    }
+    if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+        std::fs::create_dir_all(parent)?;
+    }
+
+    let mut zip = ZipWriter::new(File::create(path)?);
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    for item in items {
+        zip.start_file(archive_path(item), options)?;
+        zip.write_all(item_document(item).as_bytes())?;
+    }
+    zip.finish()?;
+    Ok(items.len())
+}
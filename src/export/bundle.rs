@@ -0,0 +1,295 @@
+use super::gist::item_document;
+use crate::db::{unused_name, CollectionStore, ConflictMode, ItemStore, LinkStore, LoadSummary};
+use crate::import::{existing_for, item_from_markdown, same_definition, ImportConflict};
+use crate::models::{Category, Item};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Result};
+use rusqlite::Connection;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::{Read, Write};
+use std::path::Path;
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Version of the bundle layout, bumped on incompatible changes
+pub const BUNDLE_FORMAT: u32 = 1;
+
+/// File extension of bundles
+pub const BUNDLE_EXTENSION: &str = "grimoire";
+
+const MANIFEST: &str = "manifest.json";
+
+/// Largest file read out of a bundle. Items are markdown, so anything past
+/// this is a malformed or hostile archive rather than a long prompt.
+const MAX_ENTRY_SIZE: u64 = 8 * 1024 * 1024;
+
+/// A selection of items packed into one file for sharing: a zip archive
+/// with each item as the markdown file it exports to, and a manifest of
+/// what the files don't carry (tags, collections, and links)
+pub struct Bundle {
+    manifest: Manifest,
+    /// Text of each markdown file, by path in the archive
+    documents: HashMap<String, String>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Manifest {
+    format: u32,
+    created_at: DateTime<Utc>,
+    items: Vec<BundleEntry>,
+}
+
+#[derive(Serialize, Deserialize)]
+struct BundleEntry {
+    name: String,
+    category: Category,
+    /// Path of the item's markdown file in the archive
+    file: String,
+    #[serde(default)]
+    tags: Vec<String>,
+    /// Paths of the collections the item is in
+    #[serde(default)]
+    collections: Vec<String>,
+    /// Names of the skills and commands the item links to
+    #[serde(default)]
+    links: Vec<String>,
+}
+
+impl Bundle {
+    /// Pack `items` into a bundle at `path`, replacing any file there.
+    /// Returns how many items were written.
+    pub fn write(conn: &Connection, items: &[Item], path: &Path) -> Result<usize> {
+        let links = LinkStore::new(conn);
+        let collections = CollectionStore::new(conn);
+
+        let mut entries = Vec::new();
+        for item in items {
+            let (item_links, item_collections) = match item.id {
+                Some(id) => (
+                    links
+                        .targets(id)?
+                        .into_iter()
+                        .map(|link| link.name)
+                        .collect(),
+                    collections.for_item(id)?,
+                ),
+                None => (Vec::new(), Vec::new()),
+            };
+            entries.push(BundleEntry {
+                name: item.name.clone(),
+                category: item.category,
+                file: archive_path(item),
+                tags: item.tags_vec(),
+                collections: item_collections,
+                links: item_links,
+            });
+        }
+        let manifest = Manifest {
+            format: BUNDLE_FORMAT,
+            created_at: Utc::now(),
+            items: entries,
+        };
+
+        if let Some(parent) = path.parent().filter(|p| !p.as_os_str().is_empty()) {
+            std::fs::create_dir_all(parent)?;
+        }
+        let mut zip = ZipWriter::new(File::create(path)?);
+        let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        zip.start_file(MANIFEST, options)?;
+        zip.write_all(serde_json::to_string_pretty(&manifest)?.as_bytes())?;
+        for (item, entry) in items.iter().zip(&manifest.items) {
+            zip.start_file(entry.file.as_str(), options)?;
+            zip.write_all(item_document(item).as_bytes())?;
+        }
+        zip.finish()?;
+
+        Ok(manifest.items.len())
+    }
+
+    /// Read a bundle made by `write`
+    pub fn open(path: &Path) -> Result<Self> {
+        let file =
+            File::open(path).map_err(|e| eyre!("could not open {}: {}", path.display(), e))?;
+        let mut archive = ZipArchive::new(file)
+            .map_err(|e| eyre!("{} is not a grimoire bundle: {}", path.display(), e))?;
+
+        let manifest: Manifest = {
+            let file = archive
+                .by_name(MANIFEST)
+                .map_err(|_| eyre!("{} has no {}", path.display(), MANIFEST))?;
+            serde_json::from_str(&read_entry(file, MANIFEST)?)?
+        };
+        if manifest.format > BUNDLE_FORMAT {
+            return Err(eyre!(
+                "Bundle format {} is newer than this grimoire supports ({})",
+                manifest.format,
+                BUNDLE_FORMAT
+            ));
+        }
+
+        let mut documents = HashMap::new();
+        for entry in &manifest.items {
+            // Missing files are reported per item when loading
+            if let Ok(file) = archive.by_name(&entry.file) {
+                documents.insert(entry.file.clone(), read_entry(file, &entry.file)?);
+            }
+        }
+
+        Ok(Self {
+            manifest,
+            documents,
+        })
+    }
+
+    /// The bundle's items whose name is already taken by a different item
+    /// in the library. Ones that can't be loaded, such as a name in the
+    /// trash, are left to `load_into` to report.
+    pub fn conflicts(&self, store: &ItemStore) -> Result<Vec<ImportConflict>> {
+        let mut conflicts = Vec::new();
+        for item in self.items() {
+            let Ok(mut item) = item else {
+                continue;
+            };
+            if let Ok(Some(existing)) = existing_for(store, &mut item) {
+                if !same_definition(&existing, &item) {
+                    conflicts.push(ImportConflict {
+                        existing,
+                        incoming: item,
+                    });
+                }
+            }
+        }
+        Ok(conflicts)
+    }
+
+    /// Add the bundle's items to the database in one transaction. Each item
+    /// whose name is taken is handled according to `mode` for that name;
+    /// ones identical to the library's are skipped. Overwritten items keep
+    /// their history. A name in the trash or of another category is a
+    /// failure, as in the other imports.
+    pub fn load_into(
+        &self,
+        conn: &Connection,
+        mode: impl Fn(&str) -> ConflictMode,
+    ) -> Result<LoadSummary> {
+        let tx = conn.unchecked_transaction()?;
+        let store = ItemStore::new(&tx);
+        let collections = CollectionStore::new(&tx);
+        let mut summary = LoadSummary::default();
+        // Links are resolved once every item is in, following renames
+        let mut written: Vec<(i64, &BundleEntry)> = Vec::new();
+        let mut renamed: HashMap<&str, String> = HashMap::new();
+
+        for (entry, parsed) in self.manifest.items.iter().zip(self.items()) {
+            let mut item = match parsed {
+                Ok(item) => item,
+                Err(e) => {
+                    summary.failures.push((entry.name.clone(), e.to_string()));
+                    continue;
+                }
+            };
+
+            let existing = match existing_for(&store, &mut item) {
+                Ok(existing) => existing,
+                Err(e) => {
+                    summary.failures.push((entry.name.clone(), e.to_string()));
+                    continue;
+                }
+            };
+            let id = match (existing, mode(&item.name)) {
+                (None, _) => {
+                    summary.added += 1;
+                    store.insert(&item)?
+                }
+                (Some(existing), _) if same_definition(&existing, &item) => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                (Some(_), ConflictMode::Skip) => {
+                    summary.skipped += 1;
+                    continue;
+                }
+                (Some(existing), ConflictMode::Overwrite) => {
+                    item.id = existing.id;
+                    store.update(&item, Some("Imported from bundle"))?;
+                    summary.overwritten += 1;
+                    existing
+                        .id
+                        .ok_or_else(|| eyre!("Item '{}' has no id", item.name))?
+                }
+                (Some(_), ConflictMode::Duplicate) => {
+                    item.name = unused_name(&store, &item.name)?;
+                    renamed.insert(entry.name.as_str(), item.name.clone());
+                    summary.duplicated += 1;
+                    store.insert(&item)?
+                }
+            };
+            collections.edit_items(&[id], &entry.collections, &[])?;
+            written.push((id, entry));
+        }
+
+        let links = LinkStore::new(&tx);
+        for (id, entry) in written {
+            let mut target_ids = Vec::new();
+            for name in &entry.links {
+                let name = renamed.get(name.as_str()).unwrap_or(name);
+                if let Some(target_id) = store.get_by_name(name)?.and_then(|t| t.id) {
+                    target_ids.push(target_id);
+                }
+            }
+            links.set(id, &target_ids)?;
+        }
+
+        tx.commit()?;
+        Ok(summary)
+    }
+
+    /// The bundle's items parsed from their files, in manifest order
+    fn items(&self) -> impl Iterator<Item = Result<Item>> + '_ {
+        self.manifest.items.iter().map(|entry| {
+            let text = self
+                .documents
+                .get(&entry.file)
+                .ok_or_else(|| eyre!("{} is missing from the bundle", entry.file))?;
+            let mut item = item_from_markdown(entry.category, &entry.name, text)?;
+            if !entry.tags.is_empty() {
+                item.tags = Some(entry.tags.join(", "));
+            }
+            Ok(item)
+        })
+    }
+}
+
+/// The text of an archive entry, refusing one larger than `MAX_ENTRY_SIZE`
+/// whatever size its header claims
+fn read_entry(file: impl Read, name: &str) -> Result<String> {
+    let mut text = String::new();
+    file.take(MAX_ENTRY_SIZE + 1).read_to_string(&mut text)?;
+    if text.len() as u64 > MAX_ENTRY_SIZE {
+        return Err(eyre!(
+            "{} is larger than {} MB",
+            name,
+            MAX_ENTRY_SIZE / (1024 * 1024)
+        ));
+    }
+    Ok(text)
+}
+
+/// Where an item's file sits in the archive, following the `.claude`
+/// layout. Separators in the name become `-` and a name of only dots is
+/// replaced, so no entry lands outside its category's directory when the
+/// archive is extracted.
+pub(super) fn archive_path(item: &Item) -> String {
+    let mut name = item.name.replace(['/', '\\'], "-");
+    if name.trim_matches('.').is_empty() {
+        name = name.replace('.', "_");
+    }
+    match item.category {
+        Category::Agent => format!("agents/{}.md", name),
+        Category::Command => format!("commands/{}.md", name),
+        Category::Skill => format!("skills/{}/SKILL.md", name),
+        Category::Prompt => format!("prompts/{}.md", name),
+    }
+}
@@ -1,147 +1,145 @@
+use super::{check_name, expand_home, ExportSummary, FrontmatterKeys};
 use crate::models::{Category, Item};
 use color_eyre::eyre::{eyre, Result};
 use std::fs;
 use std::path::{Path, PathBuf};
+use tracing::{debug, warn};
 
 pub struct ClaudeExporter {
     base_path: PathBuf,
+    keys: FrontmatterKeys,
 }
 
 impl ClaudeExporter {
     pub fn new(base_path: impl AsRef<Path>) -> Self {
-        let path = base_path.as_ref();
-        // Expand ~ to home directory
-        let expanded = if path.starts_with("~") {
-            if let Some(home) = dirs::home_dir() {
-                home.join(path.strip_prefix("~").unwrap_or(path))
-            } else {
-                path.to_path_buf()
-            }
-        } else {
-            path.to_path_buf()
-        };
-
         Self {
-            base_path: expanded,
+            base_path: expand_home(base_path),
+            keys: FrontmatterKeys::default(),
         }
     }
 
-    pub fn export(&self, item: &Item) -> Result<PathBuf> {
-        match item.category {
-            Category::Agent => self.export_agent(item),
-            Category::Command => self.export_command(item),
-            Category::Skill => self.export_skill(item),
-            Category::Prompt => Err(eyre!("Prompts cannot be exported (copy-only)")),
-        }
-    }
-
-    fn export_agent(&self, item: &Item) -> Result<PathBuf> {
-        let dir = self.base_path.join("agents");
-        fs::create_dir_all(&dir)?;
-
-        let file_path = dir.join(format!("{}.md", item.name));
-        let content = self.format_agent(item);
-
-        fs::write(&file_path, content)?;
-        Ok(file_path)
-    }
-
-    fn export_command(&self, item: &Item) -> Result<PathBuf> {
-        let dir = self.base_path.join("commands");
-        fs::create_dir_all(&dir)?;
-
-        let file_path = dir.join(format!("{}.md", item.name));
-        let content = self.format_command(item);
-
-        fs::write(&file_path, content)?;
-        Ok(file_path)
+    /// Write frontmatter with `keys` renamed or left out
+    pub fn with_keys(mut self, keys: FrontmatterKeys) -> Self {
+        self.keys = keys;
+        self
     }
 
-    fn export_skill(&self, item: &Item) -> Result<PathBuf> {
-        let dir = self.base_path.join("skills").join(&item.name);
-        fs::create_dir_all(&dir)?;
-
-        let file_path = dir.join("SKILL.md");
-        let content = self.format_skill(item);
-
+    pub fn export(&self, item: &Item) -> Result<PathBuf> {
+        let (file_path, content) = self.render(item)?;
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
         fs::write(&file_path, content)?;
+        debug!(item = %item.name, path = %file_path.display(), "exported item");
         Ok(file_path)
     }
 
-    fn format_agent(&self, item: &Item) -> String {
-        let mut frontmatter = vec![format!("name: {}", item.name)];
-
-        if let Some(ref desc) = item.description {
-            frontmatter.push(format!("description: {}", desc));
+    /// Destination path and file content for the item, without writing it
+    pub fn render(&self, item: &Item) -> Result<(PathBuf, String)> {
+        check_name(item)?;
+        match item.category {
+            Category::Agent => Ok((
+                self.base_path
+                    .join("agents")
+                    .join(format!("{}.md", item.name)),
+                self.format_agent(item),
+            )),
+            Category::Command => Ok((
+                self.base_path
+                    .join("commands")
+                    .join(format!("{}.md", item.name)),
+                self.format_command(item),
+            )),
+            Category::Skill => Ok((
+                self.base_path
+                    .join("skills")
+                    .join(&item.name)
+                    .join("SKILL.md"),
+                self.format_skill(item),
+            )),
+            Category::Prompt => Err(eyre!("Prompts cannot be exported (copy-only)")),
         }
+    }
 
-        if let Some(ref tools) = item.tools {
-            frontmatter.push(format!("tools: {}", tools));
+    /// Category and name of the item that exports to `path`, the reverse
+    /// of `render`
+    pub fn item_at(&self, path: &Path) -> Option<(Category, String)> {
+        let relative = path.strip_prefix(&self.base_path).ok()?;
+        let parts: Vec<&str> = relative.iter().filter_map(|part| part.to_str()).collect();
+        match parts.as_slice() {
+            ["agents", file] => Some((Category::Agent, file.strip_suffix(".md")?.to_string())),
+            ["commands", file] => Some((Category::Command, file.strip_suffix(".md")?.to_string())),
+            ["skills", name, "SKILL.md"] => Some((Category::Skill, name.to_string())),
+            _ => None,
         }
+    }
 
-        if let Some(ref model) = item.model {
-            frontmatter.push(format!("model: {}", model));
-        }
+    /// Number of items whose exported file exists with different content
+    pub fn count_changed_on_disk(&self, items: &[Item]) -> usize {
+        items
+            .iter()
+            .filter_map(|item| self.render(item).ok())
+            .filter(|(path, content)| {
+                fs::read_to_string(path).is_ok_and(|on_disk| on_disk != *content)
+            })
+            .count()
+    }
 
-        if let Some(ref perm) = item.permission_mode {
-            frontmatter.push(format!("permissionMode: {}", perm));
+    /// Export every exportable item, skipping prompts
+    pub fn export_all(&self, items: &[Item]) -> ExportSummary {
+        let mut summary = ExportSummary::default();
+
+        for item in items.iter().filter(|i| i.category != Category::Prompt) {
+            match self.export(item) {
+                Ok(path) => summary.written.push((item.name.clone(), path)),
+                Err(e) => {
+                    warn!(item = %item.name, error = %e, "export failed");
+                    summary.failures.push((item.name.clone(), e.to_string()))
+                }
+            }
         }
 
-        if let Some(ref skills) = item.skills {
-            frontmatter.push(format!("skills: {}", skills));
-        }
+        summary
+    }
 
-        format!("---\n{}\n---\n\n{}", frontmatter.join("\n"), item.content)
+    fn format_agent(&self, item: &Item) -> String {
+        let frontmatter = fields([
+            ("name", Some(&item.name)),
+            ("description", item.description.as_ref()),
+            ("tools", item.tools.as_ref()),
+            ("model", item.model.as_ref()),
+            ("permissionMode", item.permission_mode.as_ref()),
+            ("skills", item.skills.as_ref()),
+        ]);
+        self.keys.document(frontmatter, &item.content)
     }
 
     fn format_command(&self, item: &Item) -> String {
-        let mut frontmatter = Vec::new();
-
-        if let Some(ref desc) = item.description {
-            frontmatter.push(format!("description: {}", desc));
-        }
-
-        if let Some(ref tools) = item.allowed_tools {
-            frontmatter.push(format!("allowed-tools: {}", tools));
-        }
-
-        if let Some(ref hint) = item.argument_hint {
-            frontmatter.push(format!("argument-hint: {}", hint));
-        }
-
-        if let Some(ref model) = item.model {
-            frontmatter.push(format!("model: {}", model));
-        }
-
-        if frontmatter.is_empty() {
-            item.content.clone()
-        } else {
-            format!("---\n{}\n---\n\n{}", frontmatter.join("\n"), item.content)
-        }
+        let frontmatter = fields([
+            ("description", item.description.as_ref()),
+            ("allowed-tools", item.allowed_tools.as_ref()),
+            ("argument-hint", item.argument_hint.as_ref()),
+            ("model", item.model.as_ref()),
+        ]);
+        self.keys.document(frontmatter, &item.content)
     }
 
     fn format_skill(&self, item: &Item) -> String {
-        let mut frontmatter = vec![format!("name: {}", item.name)];
-
-        if let Some(ref desc) = item.description {
-            frontmatter.push(format!("description: {}", desc));
-        }
-
-        if let Some(ref tools) = item.allowed_tools {
-            frontmatter.push(format!("allowed-tools: {}", tools));
-        }
-
-        format!("---\n{}\n---\n\n{}", frontmatter.join("\n"), item.content)
+        let frontmatter = fields([
+            ("name", Some(&item.name)),
+            ("description", item.description.as_ref()),
+            ("allowed-tools", item.allowed_tools.as_ref()),
+        ]);
+        self.keys.document(frontmatter, &item.content)
     }
 }
 
-// Helper to get home directory
-mod dirs {
-    use std::path::PathBuf;
-
-    pub fn home_dir() -> Option<PathBuf> {
-        std::env::var_os("HOME")
-            .or_else(|| std::env::var_os("USERPROFILE"))
-            .map(PathBuf::from)
-    }
+/// The frontmatter fields that have a value, in order
+fn fields<const N: usize>(
+    fields: [(&'static str, Option<&String>); N],
+) -> Vec<(&'static str, String)> {
+    fields
+        .into_iter()
+        .filter_map(|(key, value)| Some((key, value?.clone())))
+        .collect()
 }
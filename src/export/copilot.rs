@@ -0,0 +1,90 @@
+use super::{check_name, FrontmatterKeys};
+use crate::models::Item;
+use color_eyre::eyre::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Exports items as GitHub Copilot custom instructions
+pub struct CopilotExporter {
+    github_dir: PathBuf,
+    keys: FrontmatterKeys,
+}
+
+impl CopilotExporter {
+    /// `project_root` is the directory containing (or receiving) `.github/`
+    pub fn new(project_root: impl AsRef<Path>) -> Self {
+        Self {
+            github_dir: project_root.as_ref().join(".github"),
+            keys: FrontmatterKeys::default(),
+        }
+    }
+
+    /// Write frontmatter with `keys` renamed or left out
+    pub fn with_keys(mut self, keys: FrontmatterKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    /// Write the item as `.github/instructions/<name>.instructions.md`
+    pub fn export_instructions(&self, item: &Item) -> Result<PathBuf> {
+        let (file_path, content) = self.render_instructions(item)?;
+        fs::create_dir_all(self.github_dir.join("instructions"))?;
+        fs::write(&file_path, content)?;
+        Ok(file_path)
+    }
+
+    /// Destination path and file content for the instructions file
+    pub fn render_instructions(&self, item: &Item) -> Result<(PathBuf, String)> {
+        check_name(item)?;
+        let file_path = self
+            .github_dir
+            .join("instructions")
+            .join(format!("{}.instructions.md", item.name));
+        Ok((file_path, self.format_instructions(item)))
+    }
+
+    /// Insert or replace the item's section in `.github/copilot-instructions.md`.
+    /// Each item lives between grimoire markers so hand-written content in
+    /// the file is left untouched.
+    pub fn export_repo_instructions(&self, item: &Item) -> Result<PathBuf> {
+        fs::create_dir_all(&self.github_dir)?;
+
+        let file_path = self.github_dir.join("copilot-instructions.md");
+        let existing = fs::read_to_string(&file_path).unwrap_or_default();
+
+        let start_marker = format!("<!-- grimoire:{} -->", item.name);
+        let end_marker = format!("<!-- /grimoire:{} -->", item.name);
+        let section = format!(
+            "{}\n## {}\n\n{}\n{}",
+            start_marker,
+            item.name,
+            item.content.trim_end(),
+            end_marker
+        );
+
+        let updated = match (existing.find(&start_marker), existing.find(&end_marker)) {
+            (Some(start), Some(end)) if end > start => format!(
+                "{}{}{}",
+                &existing[..start],
+                section,
+                &existing[end + end_marker.len()..]
+            ),
+            _ if existing.trim().is_empty() => format!("{}\n", section),
+            _ => format!("{}\n\n{}\n", existing.trim_end(), section),
+        };
+
+        fs::write(&file_path, updated)?;
+        Ok(file_path)
+    }
+
+    fn format_instructions(&self, item: &Item) -> String {
+        let mut frontmatter = Vec::new();
+
+        if let Some(ref desc) = item.description {
+            frontmatter.push(("description", desc.replace('\n', " ")));
+        }
+        frontmatter.push(("applyTo", "\"**\"".to_string()));
+
+        self.keys.document(frontmatter, &item.content)
+    }
+}
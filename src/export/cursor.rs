@@ -0,0 +1,65 @@
+use super::{check_name, FrontmatterKeys};
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Exports prompts and agents as Cursor project rules (`.cursor/rules/*.mdc`)
+pub struct CursorExporter {
+    base_path: PathBuf,
+    keys: FrontmatterKeys,
+}
+
+impl CursorExporter {
+    /// `project_root` is the directory containing (or receiving) `.cursor/`
+    pub fn new(project_root: impl AsRef<Path>) -> Self {
+        Self {
+            base_path: project_root.as_ref().join(".cursor").join("rules"),
+            keys: FrontmatterKeys::default(),
+        }
+    }
+
+    /// Write frontmatter with `keys` renamed or left out
+    pub fn with_keys(mut self, keys: FrontmatterKeys) -> Self {
+        self.keys = keys;
+        self
+    }
+
+    pub fn export(&self, item: &Item) -> Result<PathBuf> {
+        let (file_path, content) = self.render(item)?;
+        fs::create_dir_all(&self.base_path)?;
+        fs::write(&file_path, content)?;
+        Ok(file_path)
+    }
+
+    /// Destination path and file content for the item, without writing it
+    pub fn render(&self, item: &Item) -> Result<(PathBuf, String)> {
+        check_name(item)?;
+        match item.category {
+            Category::Prompt | Category::Agent => Ok((
+                self.base_path.join(format!("{}.mdc", item.name)),
+                self.format_rule(item),
+            )),
+            _ => Err(eyre!(
+                "Cursor rules only support prompts and agents, not {}",
+                item.category.display_name().to_lowercase()
+            )),
+        }
+    }
+
+    fn format_rule(&self, item: &Item) -> String {
+        let description = item
+            .description
+            .as_deref()
+            .map(|d| d.replace('\n', " "))
+            .unwrap_or_default();
+
+        let frontmatter = vec![
+            ("description", description),
+            ("globs", String::new()),
+            ("alwaysApply", "false".to_string()),
+        ];
+
+        self.keys.document(frontmatter, &item.content)
+    }
+}
@@ -0,0 +1,35 @@
+use crate::models::Item;
+use color_eyre::eyre::Result;
+
+/// Data formats an item can be written in with all its fields, for feeding
+/// it to other tooling. The fields are named as in JSON dumps and the API.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DataFormat {
+    Json,
+    Yaml,
+}
+
+impl DataFormat {
+    pub fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "json" => Some(Self::Json),
+            "yaml" => Some(Self::Yaml),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            Self::Json => "JSON",
+            Self::Yaml => "YAML",
+        }
+    }
+
+    /// The item in this format, ending with a line break
+    pub fn render(&self, item: &Item) -> Result<String> {
+        Ok(match self {
+            Self::Json => serde_json::to_string_pretty(item)? + "\n",
+            Self::Yaml => serde_norway::to_string(item)?,
+        })
+    }
+}
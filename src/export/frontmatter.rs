@@ -0,0 +1,123 @@
+use crate::models::Item;
+use std::collections::{HashMap, HashSet};
+
+/// Targets whose frontmatter keys can be changed in Settings: the name the
+/// changes are saved under, the label shown, and the keys it writes
+pub const FRONTMATTER_TARGETS: [(&str, &str, &[&str]); 3] = [
+    (
+        "claude",
+        "Claude",
+        &[
+            "name",
+            "description",
+            "tools",
+            "model",
+            "permissionMode",
+            "skills",
+            "allowed-tools",
+            "argument-hint",
+        ],
+    ),
+    ("cursor", "Cursor", &["description", "globs", "alwaysApply"]),
+    ("copilot", "Copilot", &["description", "applyTo"]),
+];
+
+/// Changes to the frontmatter an exporter writes, for tools that expect
+/// other key names than grimoire's (`allowedTools` for `allowed-tools`) or
+/// choke on some keys. Written as `allowed-tools=allowedTools, -model`:
+/// `key=name` renames a key, `-key` leaves it out.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FrontmatterKeys {
+    renamed: HashMap<String, String>,
+    omitted: HashSet<String>,
+}
+
+impl FrontmatterKeys {
+    /// Parse the changes for `target` (a name from `FRONTMATTER_TARGETS`),
+    /// rejecting keys the target doesn't write
+    pub fn parse(target: &str, spec: &str) -> Result<Self, String> {
+        let known = FRONTMATTER_TARGETS
+            .iter()
+            .find(|(name, _, _)| *name == target)
+            .map_or(&[][..], |(_, _, keys)| *keys);
+        let check = |key: &str| {
+            if known.contains(&key) {
+                Ok(key.to_string())
+            } else {
+                Err(format!(
+                    "unknown key '{}' (expected one of {})",
+                    key,
+                    known.join(", ")
+                ))
+            }
+        };
+
+        let mut keys = Self::default();
+        for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+            if let Some(key) = entry.strip_prefix('-') {
+                keys.omitted.insert(check(key.trim())?);
+            } else if let Some((key, name)) = entry.split_once('=') {
+                let name = name.trim();
+                if name.is_empty() || name.contains(|c: char| c == ':' || c.is_whitespace()) {
+                    return Err(format!("'{}' can't be used as a key", name));
+                }
+                keys.renamed.insert(check(key.trim())?, name.to_string());
+            } else {
+                return Err(format!("'{}' should be key=name or -key", entry));
+            }
+        }
+        Ok(keys)
+    }
+
+    /// grimoire's name for a key read from a file the target wrote,
+    /// undoing renames
+    pub fn original_key<'a>(&'a self, key: &'a str) -> &'a str {
+        self.renamed
+            .iter()
+            .find(|(_, name)| name.as_str() == key)
+            .map_or(key, |(original, _)| original.as_str())
+    }
+
+    /// Give `item`, read back from a file, the fields of `existing` that
+    /// the target leaves out, so a round trip doesn't clear them
+    pub fn keep_omitted(&self, item: &mut Item, existing: &Item) {
+        for key in &self.omitted {
+            match key.as_str() {
+                "name" => item.name = existing.name.clone(),
+                "description" => item.description = existing.description.clone(),
+                "tools" => item.tools = existing.tools.clone(),
+                "model" => item.model = existing.model.clone(),
+                "permissionMode" => item.permission_mode = existing.permission_mode.clone(),
+                "skills" => item.skills = existing.skills.clone(),
+                "allowed-tools" => item.allowed_tools = existing.allowed_tools.clone(),
+                "argument-hint" => item.argument_hint = existing.argument_hint.clone(),
+                // The other targets' keys aren't item fields
+                _ => {}
+            }
+        }
+    }
+
+    /// The file for `content` under frontmatter made of `fields`, given
+    /// with grimoire's key names. Without any fields left it is the content
+    /// alone.
+    pub fn document(&self, fields: Vec<(&str, String)>, content: &str) -> String {
+        let lines: Vec<String> = fields
+            .into_iter()
+            .filter(|(key, _)| !self.omitted.contains(*key))
+            .map(|(key, value)| {
+                let key = self.renamed.get(key).map_or(key, String::as_str);
+                if value.is_empty() {
+                    format!("{}:", key)
+                } else {
+                    format!("{}: {}", key, value)
+                }
+            })
+            .collect();
+
+        if lines.is_empty() {
+            content.to_string()
+        } else {
+            format!("---\n{}\n---\n\n{}", lines.join("\n"), content)
+        }
+    }
+}
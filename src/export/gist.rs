@@ -0,0 +1,101 @@
+use super::ClaudeExporter;
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use serde::Deserialize;
+use std::time::Duration;
+
+const DEFAULT_API_URL: &str = "https://api.github.com";
+
+/// Environment variables read when no GitHub token is set in Settings
+const TOKEN_ENV_VARS: [&str; 2] = ["GITHUB_TOKEN", "GH_TOKEN"];
+
+#[derive(Deserialize)]
+struct CreatedGist {
+    html_url: String,
+}
+
+/// The configured token, or one from the environment
+pub fn github_token(configured: &str) -> Option<String> {
+    let configured = configured.trim();
+    if !configured.is_empty() {
+        return Some(configured.to_string());
+    }
+    TOKEN_ENV_VARS
+        .iter()
+        .filter_map(|var| std::env::var(var).ok())
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty())
+}
+
+/// File name and content an item is shared as
+pub fn gist_file(item: &Item) -> (String, String) {
+    (format!("{}.md", item.name), item_document(item))
+}
+
+/// An item as a markdown file of its own: the file the Claude exporter
+/// writes, frontmatter included. Prompts get a small frontmatter of their
+/// own so importing the file brings them back as prompts.
+pub fn item_document(item: &Item) -> String {
+    match item.category {
+        Category::Prompt => {
+            let mut frontmatter = vec![
+                format!("name: {}", item.name),
+                "category: prompt".to_string(),
+            ];
+            if let Some(ref desc) = item.description {
+                frontmatter.push(format!("description: {}", desc));
+            }
+            if let Some(ref tags) = item.tags {
+                frontmatter.push(format!("tags: {}", tags));
+            }
+            format!("---\n{}\n---\n\n{}", frontmatter.join("\n"), item.content)
+        }
+        _ => ClaudeExporter::new("")
+            .render(item)
+            .map(|(_, content)| content)
+            .unwrap_or_else(|_| item.content.clone()),
+    }
+}
+
+/// Post an item as a new secret gist, returning the gist's URL. The API
+/// address can be changed with `GITHUB_API_URL` for GitHub Enterprise.
+pub fn create_gist(token: &str, item: &Item) -> Result<String> {
+    let (file_name, content) = gist_file(item);
+    let description = match item.description {
+        Some(ref desc) => format!("{}: {}", item.name, desc),
+        None => format!("{} ({})", item.name, item.category.as_str()),
+    };
+    let body = serde_json::json!({
+        "description": description,
+        "public": false,
+        "files": { file_name: { "content": content } },
+    });
+    let api_url = std::env::var("GITHUB_API_URL").unwrap_or_else(|_| DEFAULT_API_URL.to_string());
+
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(30))
+        .user_agent(concat!("grimoire/", env!("CARGO_PKG_VERSION")))
+        .build()?;
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(async {
+        let response = client
+            .post(format!("{}/gists", api_url.trim_end_matches('/')))
+            .bearer_auth(token)
+            .header("Accept", "application/vnd.github+json")
+            .json(&body)
+            .send()
+            .await?;
+        let status = response.status();
+        if status == reqwest::StatusCode::UNAUTHORIZED || status == reqwest::StatusCode::FORBIDDEN {
+            return Err(eyre!(
+                "GitHub refused the token ({}); it needs the gist scope",
+                status
+            ));
+        }
+        if !status.is_success() {
+            return Err(eyre!("GitHub returned {}", status));
+        }
+        let gist: CreatedGist = response.json().await?;
+        Ok(gist.html_url)
+    })
+}
@@ -0,0 +1,63 @@
+use color_eyre::eyre::{eyre, Result};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Mutex;
+
+/// Held while committing, so commits made from background threads don't
+/// race for the repository's index
+static COMMIT_LOCK: Mutex<()> = Mutex::new(());
+
+/// Stage the files under `dir` an export wrote or removed, `paths`, and
+/// commit them with `message`. Returns the short hash of the new commit,
+/// or `None` when `dir` isn't inside a git repository or none of the files
+/// changed. Nothing else in the repository is staged or committed, not
+/// even other files under `dir`.
+pub fn commit_exports(dir: &Path, paths: &[PathBuf], message: &str) -> Result<Option<String>> {
+    let _lock = COMMIT_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+    if !dir.is_dir() || git(dir, &["rev-parse", "--is-inside-work-tree"]).is_err() {
+        return Ok(None);
+    }
+
+    let mut staged = Vec::new();
+    for path in paths.iter().filter(|path| path.starts_with(dir)) {
+        let path = path.to_string_lossy();
+        if Path::new(path.as_ref()).exists() {
+            git(dir, &["add", "--", &path])?;
+        } else if git(dir, &["ls-files", "--error-unmatch", "--", &path]).is_ok() {
+            git(dir, &["rm", "--cached", "--quiet", "--", &path])?;
+        } else {
+            // Removed before it was ever committed
+            continue;
+        }
+        staged.push(path.to_string());
+    }
+    if staged.is_empty() {
+        return Ok(None);
+    }
+
+    // Exits with 1 when one of the files is staged
+    let unchanged = Command::new("git")
+        .current_dir(dir)
+        .args(["diff", "--cached", "--quiet", "--"])
+        .args(&staged)
+        .status()?
+        .success();
+    if unchanged {
+        return Ok(None);
+    }
+
+    let mut args = vec!["commit", "--quiet", "-m", message, "--"];
+    args.extend(staged.iter().map(String::as_str));
+    git(dir, &args)?;
+    git(dir, &["rev-parse", "--short", "HEAD"]).map(Some)
+}
+
+/// Run git in `dir`, returning its trimmed output or its error message
+fn git(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = Command::new("git").current_dir(dir).args(args).output()?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!("git {} failed: {}", args[0], stderr.trim()));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
@@ -0,0 +1,60 @@
+use crate::db::{ItemStore, SortOrder};
+use color_eyre::eyre::Result;
+use rusqlite::Connection;
+use std::io::Write;
+
+/// Columns of the inventory, in order
+const COLUMNS: [&str; 7] = [
+    "name",
+    "category",
+    "tags",
+    "description",
+    "created",
+    "updated",
+    "archived",
+];
+
+/// Write every item outside the trash, archived ones included, as CSV rows
+/// sorted by name, for auditing the library in a spreadsheet. Content is
+/// left out. Returns the number of items written.
+pub fn write_inventory(conn: &Connection, out: impl Write) -> Result<usize> {
+    let store = ItemStore::new(conn);
+    let archived = store.list_archived(SortOrder::Name)?;
+    let mut items: Vec<_> = store
+        .list_all()?
+        .into_iter()
+        .map(|item| (item, false))
+        .chain(archived.into_iter().map(|item| (item, true)))
+        .collect();
+    items.sort_by_key(|(item, _)| item.name.to_lowercase());
+
+    let date = |date: Option<chrono::DateTime<chrono::Utc>>| {
+        date.map(|d| d.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    };
+    let mut writer = csv::Writer::from_writer(out);
+    writer.write_record(COLUMNS)?;
+    for (item, archived) in &items {
+        writer.write_record([
+            &*cell(&item.name),
+            item.category.as_str(),
+            &*cell(item.tags.as_deref().unwrap_or_default()),
+            &*cell(item.description.as_deref().unwrap_or_default()),
+            &date(item.created_at),
+            &date(item.updated_at),
+            if *archived { "yes" } else { "no" },
+        ])?;
+    }
+    writer.flush()?;
+    Ok(items.len())
+}
+
+/// `text` as a cell a spreadsheet shows as text: one starting with a
+/// formula character gets a leading `'` so it isn't evaluated on open
+fn cell(text: &str) -> std::borrow::Cow<'_, str> {
+    if text.starts_with(['=', '+', '-', '@', '\t', '\r']) {
+        format!("'{}", text).into()
+    } else {
+        text.into()
+    }
+}
@@ -1,3 +1,461 @@
+mod agents_md;
+mod archive;
+mod bundle;
 mod claude;
+mod copilot;
+mod cursor;
+mod data;
+mod frontmatter;
+mod gist;
+mod git;
+mod inventory;
+mod profile;
+mod sync;
+mod template;
+mod watch;
 
+pub use agents_md::AgentsMdExporter;
+pub use archive::{write_zip, ZIP_FILE_NAME};
+pub use bundle::{Bundle, BUNDLE_EXTENSION};
 pub use claude::ClaudeExporter;
+pub use copilot::CopilotExporter;
+pub use cursor::CursorExporter;
+pub use data::DataFormat;
+pub use frontmatter::{FrontmatterKeys, FRONTMATTER_TARGETS};
+pub use gist::{create_gist, github_token, item_document};
+pub use git::commit_exports;
+pub use inventory::write_inventory;
+pub use profile::{format_export_profiles, parse_export_profiles, ExportProfile};
+pub use sync::{apply_sync, find_drift, pull_into, Drift, SyncAction};
+pub use watch::ExportWatcher;
+
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use template::{export_templates, ExportTemplate};
+use tracing::{info, warn};
+
+/// Settings the exporters are built from
+#[derive(Debug, Clone, Default)]
+pub struct ExportOptions {
+    /// Where Claude Code exports go, e.g. `~/.claude`
+    pub claude_path: String,
+    /// Directory the project-scoped targets write into, instead of the
+    /// current one
+    pub project_root: Option<String>,
+    /// Frontmatter changes by target, named as in `FRONTMATTER_TARGETS`
+    pub frontmatter: HashMap<String, FrontmatterKeys>,
+}
+
+impl ExportOptions {
+    pub fn new(claude_path: impl Into<String>) -> Self {
+        Self {
+            claude_path: claude_path.into(),
+            project_root: None,
+            frontmatter: HashMap::new(),
+        }
+    }
+
+    /// Directory the project-scoped targets write into
+    fn project_root(&self) -> Result<PathBuf> {
+        match self.project_root {
+            Some(ref root) => Ok(expand_home(root)),
+            None => Ok(std::env::current_dir()?),
+        }
+    }
+
+    /// `relative` under the project directory, for showing where files go
+    pub fn project_destination(&self, relative: &str) -> String {
+        match self.project_root {
+            Some(ref root) => format!("{}/{}", root.trim_end_matches('/'), relative),
+            None => format!("./{}", relative),
+        }
+    }
+
+    /// Frontmatter changes for `target`, named as in `FRONTMATTER_TARGETS`
+    pub fn keys(&self, target: &str) -> FrontmatterKeys {
+        self.frontmatter.get(target).cloned().unwrap_or_default()
+    }
+
+    /// Exporter for the Claude Code export path
+    pub fn claude_exporter(&self) -> ClaudeExporter {
+        self.claude_exporter_at(&self.claude_path)
+    }
+
+    fn claude_exporter_at(&self, base_path: impl AsRef<Path>) -> ClaudeExporter {
+        ClaudeExporter::new(base_path).with_keys(self.keys("claude"))
+    }
+
+    fn cursor_exporter(&self) -> Result<CursorExporter> {
+        Ok(CursorExporter::new(self.project_root()?).with_keys(self.keys("cursor")))
+    }
+
+    fn copilot_exporter(&self) -> Result<CopilotExporter> {
+        Ok(CopilotExporter::new(self.project_root()?).with_keys(self.keys("copilot")))
+    }
+}
+
+/// Destination format for an export
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ExportTarget {
+    Claude,
+    ClaudeProject,
+    Cursor,
+    CopilotInstructions,
+    CopilotRepo,
+    AgentsMd,
+    /// The marked items (or the list) as markdown files in a zip archive
+    Zip,
+    /// A user template, by its position in `export_templates()`
+    Template(usize),
+}
+
+impl ExportTarget {
+    /// The built-in targets followed by the user's templates
+    pub fn all() -> &'static [ExportTarget] {
+        static ALL: OnceLock<Vec<ExportTarget>> = OnceLock::new();
+        ALL.get_or_init(|| {
+            let mut all = vec![
+                ExportTarget::Claude,
+                ExportTarget::ClaudeProject,
+                ExportTarget::Cursor,
+                ExportTarget::CopilotInstructions,
+                ExportTarget::CopilotRepo,
+                ExportTarget::AgentsMd,
+                ExportTarget::Zip,
+            ];
+            all.extend((0..export_templates().len()).map(ExportTarget::Template));
+            all
+        })
+    }
+
+    fn template(&self) -> Option<&'static ExportTemplate> {
+        match self {
+            ExportTarget::Template(index) => export_templates().get(*index),
+            _ => None,
+        }
+    }
+
+    pub fn display_name(&self) -> &'static str {
+        match self {
+            ExportTarget::Claude => "Claude Code",
+            ExportTarget::ClaudeProject => "Claude project",
+            ExportTarget::Cursor => "Cursor rules",
+            ExportTarget::CopilotInstructions => "Copilot file",
+            ExportTarget::CopilotRepo => "Copilot repo",
+            ExportTarget::AgentsMd => "AGENTS.md",
+            ExportTarget::Zip => "Zip archive",
+            ExportTarget::Template(_) => self.template().map_or("", |t| t.name.as_str()),
+        }
+    }
+
+    /// Stable identifier stored in the export manifest
+    pub fn key(&self) -> &'static str {
+        match self {
+            ExportTarget::Claude => "claude",
+            ExportTarget::ClaudeProject => "claude-project",
+            ExportTarget::Cursor => "cursor",
+            ExportTarget::CopilotInstructions => "copilot-instructions",
+            ExportTarget::CopilotRepo => "copilot-repo",
+            ExportTarget::AgentsMd => "agents-md",
+            ExportTarget::Zip => "zip",
+            ExportTarget::Template(_) => self.template().map_or("", |t| t.key.as_str()),
+        }
+    }
+
+    /// Target for a key stored in the export manifest
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::all()
+            .iter()
+            .copied()
+            .find(|target| target.key() == key)
+    }
+
+    /// Short description of where files end up
+    pub fn destination(&self, options: &ExportOptions) -> String {
+        match self {
+            ExportTarget::Claude => options.claude_path.clone(),
+            ExportTarget::ClaudeProject => options.project_destination(".claude/"),
+            ExportTarget::Cursor => options.project_destination(".cursor/rules/"),
+            ExportTarget::CopilotInstructions => {
+                options.project_destination(".github/instructions/")
+            }
+            ExportTarget::CopilotRepo => {
+                options.project_destination(".github/copilot-instructions.md")
+            }
+            ExportTarget::AgentsMd => {
+                format!("{} (whole list)", options.project_destination("AGENTS.md"))
+            }
+            ExportTarget::Zip => format!(
+                "{} (marked or list)",
+                options.project_destination(ZIP_FILE_NAME)
+            ),
+            ExportTarget::Template(_) => {
+                let destination = self
+                    .template()
+                    .map_or(String::new(), ExportTemplate::destination);
+                match destination.strip_prefix("./") {
+                    Some(relative) => options.project_destination(relative),
+                    None => destination,
+                }
+            }
+        }
+    }
+
+    pub fn supports(&self, category: Category) -> bool {
+        match self {
+            ExportTarget::Claude | ExportTarget::ClaudeProject => category != Category::Prompt,
+            ExportTarget::Cursor => matches!(category, Category::Prompt | Category::Agent),
+            ExportTarget::CopilotInstructions | ExportTarget::CopilotRepo | ExportTarget::Zip => {
+                true
+            }
+            ExportTarget::AgentsMd => matches!(category, Category::Agent | Category::Command),
+            ExportTarget::Template(_) => self.template().is_some_and(|t| t.supports(category)),
+        }
+    }
+
+    /// Targets that combine several items into a single file
+    pub fn is_combined(&self) -> bool {
+        matches!(self, ExportTarget::AgentsMd | ExportTarget::Zip)
+    }
+
+    /// Export a single item. Project-scoped targets write into the options'
+    /// project directory (the current one by default); Claude Code's go to
+    /// the configured path.
+    pub fn export(&self, item: &Item, options: &ExportOptions) -> Result<PathBuf> {
+        let result = self.write(item, options);
+        match result {
+            Ok(ref path) => {
+                info!(target = self.key(), item = %item.name, path = %path.display(), "exported")
+            }
+            Err(ref e) => {
+                warn!(target = self.key(), item = %item.name, error = %e, "export failed")
+            }
+        }
+        result
+    }
+
+    fn write(&self, item: &Item, options: &ExportOptions) -> Result<PathBuf> {
+        match self {
+            ExportTarget::Claude => options.claude_exporter().export(item),
+            ExportTarget::ClaudeProject => options
+                .claude_exporter_at(options.project_root()?.join(".claude"))
+                .export(item),
+            ExportTarget::Cursor => options.cursor_exporter()?.export(item),
+            ExportTarget::CopilotInstructions => {
+                options.copilot_exporter()?.export_instructions(item)
+            }
+            ExportTarget::CopilotRepo => options.copilot_exporter()?.export_repo_instructions(item),
+            ExportTarget::AgentsMd | ExportTarget::Zip => {
+                self.export_combined(std::slice::from_ref(item), options)
+            }
+            ExportTarget::Template(_) => self
+                .template_or_err()?
+                .export(item, &options.project_root()?),
+        }
+    }
+
+    /// Write an item in this target's format to `dest` instead of where the
+    /// target keeps it. `dest` may start with `~`; when it is a directory
+    /// (existing, or ending in `/`) the file keeps its usual name, in a
+    /// directory of its own for a skill. Directories on the way are created.
+    pub fn export_to(&self, item: &Item, options: &ExportOptions, dest: &str) -> Result<PathBuf> {
+        let (path, content) = self.render_to(item, options, dest)?;
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, content)?;
+        info!(target = self.key(), item = %item.name, path = %path.display(), "exported to path");
+        Ok(path)
+    }
+
+    /// The file `export_to` would write for `dest`, and its content
+    pub fn render_to(
+        &self,
+        item: &Item,
+        options: &ExportOptions,
+        dest: &str,
+    ) -> Result<(PathBuf, String)> {
+        let Some((usual_path, content)) = self.render(item, options)? else {
+            return Err(eyre!(
+                "{} can't be written to a path of its own",
+                self.display_name()
+            ));
+        };
+        let dest = dest.trim();
+        if dest.is_empty() {
+            return Err(eyre!("no destination given"));
+        }
+
+        let mut path = expand_home(dest);
+        if dest.ends_with(['/', std::path::MAIN_SEPARATOR]) || path.is_dir() {
+            let file_name = usual_path.file_name().unwrap_or_default();
+            if file_name == "SKILL.md" {
+                path.push(&item.name);
+            }
+            path.push(file_name);
+        }
+        Ok((path, content))
+    }
+
+    /// Where `export_to` suggests writing an item: the path the target
+    /// would use, relative to the current directory, e.g. `./agents/foo.md`
+    pub fn suggested_path(&self, item: &Item) -> String {
+        let Ok(Some((path, _))) = self.render(item, &ExportOptions::new(".")) else {
+            return format!("./{}.md", item.name);
+        };
+        let relative = std::env::current_dir()
+            .ok()
+            .and_then(|cwd| path.strip_prefix(cwd).ok().map(Path::to_path_buf))
+            .unwrap_or(path);
+        if relative.is_absolute() || relative.starts_with(".") {
+            relative.display().to_string()
+        } else {
+            format!("./{}", relative.display())
+        }
+    }
+
+    /// Destination path and content for targets that own the whole file.
+    /// Section-based targets (which leave the rest of the file alone) and
+    /// combined targets return `None`.
+    pub fn render(
+        &self,
+        item: &Item,
+        options: &ExportOptions,
+    ) -> Result<Option<(PathBuf, String)>> {
+        let rendered = match self {
+            ExportTarget::Claude => options.claude_exporter().render(item)?,
+            ExportTarget::ClaudeProject => options
+                .claude_exporter_at(options.project_root()?.join(".claude"))
+                .render(item)?,
+            ExportTarget::Cursor => options.cursor_exporter()?.render(item)?,
+            ExportTarget::CopilotInstructions => {
+                options.copilot_exporter()?.render_instructions(item)?
+            }
+            ExportTarget::Template(_) => self
+                .template_or_err()?
+                .render(item, &options.project_root()?)?,
+            ExportTarget::CopilotRepo | ExportTarget::AgentsMd | ExportTarget::Zip => {
+                return Ok(None)
+            }
+        };
+        Ok(Some(rendered))
+    }
+
+    fn template_or_err(&self) -> Result<&'static ExportTemplate> {
+        self.template()
+            .ok_or_else(|| eyre!("export template is no longer loaded"))
+    }
+
+    /// Export several items into one file (combined targets only)
+    pub fn export_combined(&self, items: &[Item], options: &ExportOptions) -> Result<PathBuf> {
+        match self {
+            ExportTarget::AgentsMd => AgentsMdExporter::new(options.project_root()?).export(items),
+            ExportTarget::Zip => {
+                let path = options.project_root()?.join(ZIP_FILE_NAME);
+                write_zip(items, &path)?;
+                Ok(path)
+            }
+            _ => Err(eyre!("{} exports one item at a time", self.display_name())),
+        }
+    }
+}
+
+/// Result of exporting several items in one go
+#[derive(Debug, Default)]
+pub struct ExportSummary {
+    /// Item name and destination of each file written
+    pub written: Vec<(String, PathBuf)>,
+    pub failures: Vec<(String, String)>,
+}
+
+impl ExportSummary {
+    /// One-line summary suitable for the status bar
+    pub fn describe(&self) -> String {
+        let mut text = format!("Exported {} items", self.written.len());
+        if let Some((name, error)) = self.failures.first() {
+            text.push_str(&format!(
+                ", {} failed ({}: {})",
+                self.failures.len(),
+                name,
+                error
+            ));
+        }
+        text
+    }
+}
+
+/// Move an exported file from `old` to `new`, with `write` putting the item
+/// at `new`. A skill's directory is renamed along with its SKILL.md so the
+/// files next to it come too, and renamed back if the write fails. Anything
+/// else is written first and the old file only removed once that worked.
+pub fn move_export(
+    old: &Path,
+    new: &Path,
+    write: impl FnOnce() -> Result<PathBuf>,
+) -> Result<PathBuf> {
+    let is_skill = |path: &Path| path.file_name().is_some_and(|name| name == "SKILL.md");
+
+    if is_skill(old) && is_skill(new) {
+        if let (Some(old_dir), Some(new_dir)) = (old.parent(), new.parent()) {
+            if !new_dir.exists() {
+                if let Some(parent) = new_dir.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::rename(old_dir, new_dir)?;
+                return write().inspect_err(|_| {
+                    if let Err(e) = fs::rename(new_dir, old_dir) {
+                        warn!("Could not move {} back: {}", new_dir.display(), e);
+                    }
+                });
+            }
+        }
+    }
+
+    let written = write()?;
+    if written != old {
+        if let Err(e) = fs::remove_file(old) {
+            warn!("Could not remove old export {}: {}", old.display(), e);
+        } else if is_skill(old) {
+            // Only succeeds when nothing else was in the skill's directory
+            if let Some(dir) = old.parent() {
+                let _ = fs::remove_dir(dir);
+            }
+        }
+    }
+    Ok(written)
+}
+
+/// Refuse an item whose name would lead out of the export directory, e.g.
+/// one saved before names were checked
+fn check_name(item: &Item) -> Result<()> {
+    match Item::name_error(&item.name) {
+        Some(error) => Err(eyre!("'{}': {}", item.name, error)),
+        None => Ok(()),
+    }
+}
+
+/// Expand a leading `~` to the user's home directory
+pub fn expand_home(path: impl AsRef<Path>) -> PathBuf {
+    let path = path.as_ref();
+    if path.starts_with("~") {
+        if let Some(home) = dirs::home_dir() {
+            return home.join(path.strip_prefix("~").unwrap_or(path));
+        }
+    }
+    path.to_path_buf()
+}
+
+// Helper to get home directory
+mod dirs {
+    use std::path::PathBuf;
+
+    pub fn home_dir() -> Option<PathBuf> {
+        std::env::var_os("HOME")
+            .or_else(|| std::env::var_os("USERPROFILE"))
+            .map(PathBuf::from)
+    }
+}
@@ -0,0 +1,83 @@
+use super::{ExportOptions, ExportTarget};
+
+/// A named place to export to: a target's format written somewhere other
+/// than its default, such as a work project's `.claude/` directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ExportProfile {
+    pub name: String,
+    pub target: ExportTarget,
+    /// The export path for Claude Code, the project directory for the
+    /// other targets; may start with `~`
+    pub path: String,
+}
+
+impl ExportProfile {
+    /// Key the profile's exports are recorded under in the export manifest
+    pub fn key(&self) -> String {
+        format!("profile:{}", self.name)
+    }
+
+    /// `options` with the profile's path in place of the default one
+    pub fn options(&self, options: &ExportOptions) -> ExportOptions {
+        let mut options = options.clone();
+        if self.target == ExportTarget::Claude {
+            options.claude_path = self.path.clone();
+        } else {
+            options.project_root = Some(self.path.clone());
+        }
+        options
+    }
+}
+
+/// Parse profiles written as `name=target path; name=target path`, where
+/// `target` is a target's key such as `claude-project`. A name given twice
+/// keeps its last profile. Returns an error naming the first entry that
+/// doesn't parse.
+pub fn parse_export_profiles(spec: &str) -> Result<Vec<ExportProfile>, String> {
+    let mut profiles: Vec<ExportProfile> = Vec::new();
+    for entry in spec.split(';').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, rest) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' is not name=target path", entry))?;
+        let name = name.trim();
+        if name.is_empty() {
+            return Err(format!("'{}' has no name", entry));
+        }
+        let (key, path) = rest
+            .trim()
+            .split_once(char::is_whitespace)
+            .unwrap_or((rest, ""));
+        let target = ExportTarget::from_key(key.trim()).ok_or_else(|| {
+            format!(
+                "unknown target '{}' (expected one of {})",
+                key.trim(),
+                ExportTarget::all()
+                    .iter()
+                    .map(ExportTarget::key)
+                    .collect::<Vec<_>>()
+                    .join(", ")
+            )
+        })?;
+        let path = path.trim();
+        if path.is_empty() {
+            return Err(format!("profile '{}' has no path", name));
+        }
+
+        profiles.retain(|p| p.name != name);
+        profiles.push(ExportProfile {
+            name: name.to_string(),
+            target,
+            path: path.to_string(),
+        });
+    }
+    Ok(profiles)
+}
+
+/// Write profiles back in the form `parse_export_profiles` reads
+pub fn format_export_profiles(profiles: &[ExportProfile]) -> String {
+    profiles
+        .iter()
+        .map(|p| format!("{}={} {}", p.name, p.target.key(), p.path))
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,266 @@
+use super::ExportOptions;
+use crate::db::{ExportStore, ItemStore};
+use crate::import::{item_from_export, ClaudeImporter};
+use crate::models::{Category, Item};
+use chrono::{DateTime, Utc};
+use color_eyre::eyre::{eyre, Result};
+use rusqlite::Connection;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// How an item and its file in the export path have come apart
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DriftKind {
+    /// Both exist with different content
+    Changed,
+    /// The item was exported but its file is gone
+    MissingOnDisk,
+    /// A file with no item of that name in the library
+    OnlyOnDisk,
+}
+
+/// What to do about a drifted item
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SyncAction {
+    /// Write the library's version to disk
+    Push,
+    /// Take the file on disk into the library
+    Pull,
+    Skip,
+}
+
+impl SyncAction {
+    pub fn label(&self) -> &'static str {
+        match self {
+            SyncAction::Push => "push →",
+            SyncAction::Pull => "← pull",
+            SyncAction::Skip => "skip",
+        }
+    }
+}
+
+/// One item (or file) that differs between the library and the export path
+pub struct Drift {
+    pub kind: DriftKind,
+    pub category: Category,
+    pub name: String,
+    pub path: PathBuf,
+    /// The library's item; `None` for files only on disk
+    pub item: Option<Item>,
+    /// File content, empty when the file is missing
+    pub on_disk: String,
+    /// What grimoire would write, empty for files only on disk
+    pub rendered: String,
+    /// Whether the file was modified after the item was last saved
+    pub newer_on_disk: bool,
+    /// Chosen action, which starts out as the newer side winning
+    pub action: SyncAction,
+}
+
+impl Drift {
+    /// Actions that make sense for this kind of drift
+    pub fn actions(&self) -> &'static [SyncAction] {
+        match self.kind {
+            DriftKind::Changed => &[SyncAction::Push, SyncAction::Pull, SyncAction::Skip],
+            DriftKind::MissingOnDisk => &[SyncAction::Push, SyncAction::Skip],
+            DriftKind::OnlyOnDisk => &[SyncAction::Pull, SyncAction::Skip],
+        }
+    }
+
+    /// Pick `action` if it applies to this drift
+    pub fn set_action(&mut self, action: SyncAction) {
+        if self.actions().contains(&action) {
+            self.action = action;
+        }
+    }
+
+    pub fn cycle_action(&mut self) {
+        let actions = self.actions();
+        let index = actions.iter().position(|a| *a == self.action).unwrap_or(0);
+        self.action = actions[(index + 1) % actions.len()];
+    }
+
+    pub fn describe(&self) -> &'static str {
+        match self.kind {
+            DriftKind::Changed if self.newer_on_disk => "file is newer",
+            DriftKind::Changed => "item is newer",
+            DriftKind::MissingOnDisk => "file deleted",
+            DriftKind::OnlyOnDisk => "not in library",
+        }
+    }
+}
+
+/// Result of applying a sync
+#[derive(Debug, Default)]
+pub struct SyncSummary {
+    /// Items written to disk
+    pub pushed: Vec<Item>,
+    /// Files the pushes wrote
+    pub written: Vec<PathBuf>,
+    pub pulled: usize,
+    pub skipped: usize,
+    pub failures: Vec<(String, String)>,
+}
+
+impl SyncSummary {
+    /// One-line summary suitable for the status bar
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "Synced: {} pushed, {} pulled, {} skipped",
+            self.pushed.len(),
+            self.pulled,
+            self.skipped
+        );
+        if let Some((name, error)) = self.failures.first() {
+            text.push_str(&format!(
+                ", {} failed ({}: {})",
+                self.failures.len(),
+                name,
+                error
+            ));
+        }
+        text
+    }
+}
+
+/// Compare the library's agents, commands, and skills with the files in
+/// the export path, both ways. Archived items and prompts are left out.
+pub fn find_drift(conn: &Connection, options: &ExportOptions) -> Result<Vec<Drift>> {
+    let exporter = options.claude_exporter();
+    let store = ItemStore::new(conn);
+    let exported = ExportStore::new(conn).exported_ids("claude")?;
+    let mut drifts = Vec::new();
+
+    let mut items = store.list_all()?;
+    items.retain(|item| item.category != Category::Prompt);
+    items.sort_by_key(|item| (item.category.as_str(), item.name.to_lowercase()));
+    for item in items {
+        let (path, rendered) = exporter.render(&item)?;
+        let (kind, on_disk) = match fs::read_to_string(&path) {
+            Ok(on_disk) if on_disk == rendered => continue,
+            Ok(on_disk) => (DriftKind::Changed, on_disk),
+            // Items never exported aren't expected on disk
+            Err(_) if item.id.is_some_and(|id| exported.contains(&id)) => {
+                (DriftKind::MissingOnDisk, String::new())
+            }
+            Err(_) => continue,
+        };
+        let newer_on_disk = kind == DriftKind::Changed
+            && modified_at(&path)
+                .zip(item.updated_at)
+                .is_some_and(|(file, item)| file > item);
+        drifts.push(Drift {
+            kind,
+            category: item.category,
+            name: item.name.clone(),
+            path,
+            item: Some(item),
+            on_disk,
+            rendered,
+            newer_on_disk,
+            action: if newer_on_disk {
+                SyncAction::Pull
+            } else {
+                SyncAction::Push
+            },
+        });
+    }
+
+    for (path, parsed) in ClaudeImporter::new(&options.claude_path).scan() {
+        // Unreadable files are the import's business
+        let Ok(parsed) = parsed else {
+            continue;
+        };
+        if store.get_by_name(&parsed.name)?.is_some() {
+            continue;
+        }
+        drifts.push(Drift {
+            kind: DriftKind::OnlyOnDisk,
+            category: parsed.category,
+            name: parsed.name,
+            on_disk: fs::read_to_string(&path)?,
+            path,
+            item: None,
+            rendered: String::new(),
+            newer_on_disk: true,
+            action: SyncAction::Pull,
+        });
+    }
+
+    Ok(drifts)
+}
+
+/// Carry out the chosen action of each drift
+pub fn apply_sync(conn: &Connection, options: &ExportOptions, drifts: &[Drift]) -> SyncSummary {
+    let exporter = options.claude_exporter();
+    let store = ItemStore::new(conn);
+    let exports = ExportStore::new(conn);
+    let mut summary = SyncSummary::default();
+
+    for drift in drifts {
+        let result = match (drift.action, &drift.item) {
+            (SyncAction::Skip, _) => {
+                summary.skipped += 1;
+                continue;
+            }
+            (SyncAction::Push, Some(item)) => exporter
+                .export(item)
+                .and_then(|path| exports.record(item, "claude", &path).map(|_| path))
+                .map(|path| {
+                    summary.pushed.push(item.clone());
+                    summary.written.push(path);
+                }),
+            (SyncAction::Push, None) => Err(eyre!("not in the library")),
+            (SyncAction::Pull, existing) => pull(&store, options, existing.as_ref(), drift)
+                .and_then(|item| exports.record(&item, "claude", &drift.path))
+                .map(|_| summary.pulled += 1),
+        };
+        if let Err(e) = result {
+            summary.failures.push((drift.name.clone(), e.to_string()));
+        }
+    }
+
+    summary
+}
+
+/// Take the drift's file into the library: a new version of `existing`,
+/// or a new item. Returns the item as stored.
+fn pull(
+    store: &ItemStore,
+    options: &ExportOptions,
+    existing: Option<&Item>,
+    drift: &Drift,
+) -> Result<Item> {
+    let mut item = item_from_export(
+        drift.category,
+        &drift.name,
+        &drift.on_disk,
+        &options.keys("claude"),
+        existing,
+    )?;
+    let Some(existing) = existing else {
+        let id = store.insert(&item)?;
+        item.id = Some(id);
+        return Ok(item);
+    };
+
+    pull_into(store, existing, item)
+}
+
+/// Replace `existing` with `item` read from its exported file, as a new
+/// version. The name is kept: renames go through grimoire, which moves
+/// the file along.
+pub fn pull_into(store: &ItemStore, existing: &Item, mut item: Item) -> Result<Item> {
+    item.name = existing.name.clone();
+    item.id = existing.id;
+    if item.tags.is_none() {
+        item.tags = existing.tags.clone();
+    }
+    store.update(&item, Some("Edited outside grimoire"))?;
+    Ok(item)
+}
+
+fn modified_at(path: &Path) -> Option<DateTime<Utc>> {
+    let modified = fs::metadata(path).and_then(|meta| meta.modified()).ok()?;
+    Some(modified.into())
+}
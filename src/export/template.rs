@@ -0,0 +1,200 @@
+use super::{check_name, expand_home};
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use std::error::Error;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::OnceLock;
+use tera::{Context, Tera};
+use tracing::{debug, warn};
+
+/// An export format defined by the user as a Tera template in the
+/// `templates` directory of the config dir. The template starts with a
+/// comment naming the target and where files go:
+///
+/// ```text
+/// {#
+/// name: Windsurf rules
+/// path: .windsurf/rules/{{ name }}.md
+/// categories: prompt, agent
+/// #}
+/// {{ content }}
+/// ```
+///
+/// `path` is a template too, relative to the project directory (usually the
+/// current one) unless it starts with `/` or `~`. Without `categories` every
+/// category is exported.
+#[derive(Debug)]
+pub struct ExportTemplate {
+    /// Key stored in the export manifest, `template:<file stem>`
+    pub key: String,
+    pub name: String,
+    pub path: String,
+    categories: Vec<Category>,
+    body: String,
+}
+
+/// The templates in the config dir, read once per run and sorted by file
+/// name. Templates that can't be read or lack a `path` are left out and
+/// logged.
+pub fn export_templates() -> &'static [ExportTemplate] {
+    static TEMPLATES: OnceLock<Vec<ExportTemplate>> = OnceLock::new();
+    TEMPLATES.get_or_init(|| {
+        let Some(dir) = templates_dir() else {
+            return Vec::new();
+        };
+        let Ok(entries) = fs::read_dir(&dir) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = entries
+            .flatten()
+            .map(|entry| entry.path())
+            .filter(|path| path.extension().is_some_and(|ext| ext == "tera"))
+            .collect();
+        paths.sort();
+
+        paths
+            .iter()
+            .filter_map(|path| match ExportTemplate::load(path) {
+                Ok(template) => Some(template),
+                Err(e) => {
+                    warn!(path = %path.display(), error = %e, "skipped export template");
+                    None
+                }
+            })
+            .collect()
+    })
+}
+
+/// Where export templates are read from, e.g. `~/.config/grimoire/templates`
+fn templates_dir() -> Option<PathBuf> {
+    directories::ProjectDirs::from("", "", "grimoire")
+        .map(|dirs| dirs.config_dir().join("templates"))
+}
+
+impl ExportTemplate {
+    fn load(path: &Path) -> Result<Self> {
+        let text = fs::read_to_string(path)?;
+        let stem = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let (header, body) = text
+            .trim_start()
+            .strip_prefix("{#")
+            .and_then(|rest| rest.split_once("#}"))
+            .ok_or_else(|| eyre!("no {{# ... #}} header naming the target's path"))?;
+        let field = |key: &str| {
+            header.lines().find_map(|line| {
+                let (k, v) = line.split_once(':')?;
+                (k.trim() == key).then(|| v.trim().to_string())
+            })
+        };
+
+        let path = field("path")
+            .filter(|path| !path.is_empty())
+            .ok_or_else(|| eyre!("the header has no path"))?;
+        let categories = field("categories")
+            .map(|list| {
+                list.split(',')
+                    .map(str::trim)
+                    .filter(|c| !c.is_empty())
+                    .map(|c| Category::from_str(c.trim_end_matches('s')))
+                    .collect()
+            })
+            .unwrap_or_default();
+        let template = Self {
+            key: format!("template:{}", stem),
+            name: field("name").filter(|n| !n.is_empty()).unwrap_or(stem),
+            path,
+            categories,
+            // The line break closing the header isn't part of the output
+            body: body.strip_prefix('\n').unwrap_or(body).to_string(),
+        };
+
+        // Report syntax errors now rather than on every export
+        let mut tera = Tera::default();
+        tera.add_raw_template("path", &template.path)
+            .and_then(|_| tera.add_raw_template("body", &template.body))
+            .map_err(|e| eyre!(describe(&e)))?;
+        Ok(template)
+    }
+
+    /// The fixed start of `path`, e.g. `./.windsurf/rules/`
+    pub fn destination(&self) -> String {
+        let fixed = self.path.split("{{").next().unwrap_or_default();
+        if fixed.starts_with(['/', '~']) || fixed.starts_with("./") || fixed.starts_with("../") {
+            fixed.to_string()
+        } else {
+            format!("./{}", fixed)
+        }
+    }
+
+    pub fn supports(&self, category: Category) -> bool {
+        self.categories.is_empty() || self.categories.contains(&category)
+    }
+
+    /// Destination path and file content for the item, without writing it.
+    /// A relative `path` is taken from `project_root`.
+    pub fn render(&self, item: &Item, project_root: &Path) -> Result<(PathBuf, String)> {
+        if !self.supports(item.category) {
+            return Err(eyre!(
+                "{} doesn't export {}",
+                self.name,
+                item.category.display_name().to_lowercase()
+            ));
+        }
+        check_name(item)?;
+        let context = item_context(item)?;
+        let render = |template: &str| {
+            Tera::one_off(template, &context, false).map_err(|e| eyre!(describe(&e)))
+        };
+
+        let path = expand_home(render(&self.path)?.trim());
+        let path = if path.is_absolute() {
+            path
+        } else {
+            project_root.join(path)
+        };
+        Ok((path, render(&self.body)?))
+    }
+
+    pub fn export(&self, item: &Item, project_root: &Path) -> Result<PathBuf> {
+        let (file_path, content) = self.render(item, project_root)?;
+        if let Some(dir) = file_path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&file_path, content)?;
+        debug!(item = %item.name, path = %file_path.display(), "exported item");
+        Ok(file_path)
+    }
+}
+
+/// Variables a template sees: the item's fields as named in JSON exports,
+/// with `tags` also split into `tag_list`
+fn item_context(item: &Item) -> Result<Context> {
+    let mut context = Context::from_serialize(item).map_err(|e| eyre!(describe(&e)))?;
+    let tags: Vec<&str> = item
+        .tags
+        .as_deref()
+        .unwrap_or_default()
+        .split(',')
+        .map(str::trim)
+        .filter(|tag| !tag.is_empty())
+        .collect();
+    context.insert("tag_list", &tags);
+    Ok(context)
+}
+
+/// A Tera error with its causes, which hold the useful part
+fn describe(error: &tera::Error) -> String {
+    let mut text = error.to_string();
+    let mut source = error.source();
+    while let Some(cause) = source {
+        text.push_str(": ");
+        text.push_str(&cause.to_string());
+        source = cause.source();
+    }
+    text
+}
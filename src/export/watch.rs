@@ -0,0 +1,46 @@
+use color_eyre::eyre::Result;
+use notify::{Event, EventKind, RecommendedWatcher, RecursiveMode, Watcher};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver};
+
+/// Watches an export directory for markdown files changed by something
+/// other than grimoire, such as an editor opened on `~/.claude`
+pub struct ExportWatcher {
+    // Stops watching when dropped
+    _watcher: RecommendedWatcher,
+    events: Receiver<notify::Result<Event>>,
+    dir: PathBuf,
+}
+
+impl ExportWatcher {
+    pub fn new(dir: &Path) -> Result<Self> {
+        let (tx, events) = mpsc::channel();
+        let mut watcher = notify::recommended_watcher(tx)?;
+        watcher.watch(dir, RecursiveMode::Recursive)?;
+        Ok(Self {
+            _watcher: watcher,
+            events,
+            dir: dir.to_path_buf(),
+        })
+    }
+
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Markdown files written or created since the last call, each once
+    pub fn changed_files(&self) -> Vec<PathBuf> {
+        let mut files: Vec<PathBuf> = Vec::new();
+        for event in self.events.try_iter().flatten() {
+            if !matches!(event.kind, EventKind::Create(_) | EventKind::Modify(_)) {
+                continue;
+            }
+            for path in event.paths {
+                if path.extension().is_some_and(|ext| ext == "md") && !files.contains(&path) {
+                    files.push(path);
+                }
+            }
+        }
+        files
+    }
+}
@@ -0,0 +1,109 @@
+use super::{item_from_markdown, upsert, ImportSummary};
+use crate::db::{ConflictMode, ItemStore};
+use crate::export::expand_home;
+use crate::models::{Category, Item};
+use color_eyre::eyre::Result;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Imports agents, commands, and skills from a `.claude/` directory
+/// (the same layout `ClaudeExporter` writes).
+pub struct ClaudeImporter {
+    base_path: PathBuf,
+}
+
+impl ClaudeImporter {
+    pub fn new(base_path: impl AsRef<Path>) -> Self {
+        Self {
+            base_path: expand_home(base_path),
+        }
+    }
+
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Scan the directory and create the matching items, settling ones that
+    /// differ from the library's according to `mode`
+    pub fn import(&self, store: &ItemStore, mode: ConflictMode) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+
+        for (path, parsed) in self.scan() {
+            match parsed.and_then(|item| upsert(store, item, mode)) {
+                Ok(outcome) => summary.record(outcome),
+                Err(e) => summary.failures.push((path, e.to_string())),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Parse every agent, command, and skill file under the base path
+    pub fn scan(&self) -> Vec<(PathBuf, Result<Item>)> {
+        let mut results = Vec::new();
+
+        for path in markdown_files(&self.base_path.join("agents")) {
+            let parsed = parse_file(Category::Agent, &path, file_stem(&path));
+            results.push((path, parsed));
+        }
+
+        for path in markdown_files(&self.base_path.join("commands")) {
+            let parsed = parse_file(Category::Command, &path, file_stem(&path));
+            results.push((path, parsed));
+        }
+
+        for dir in subdirectories(&self.base_path.join("skills")) {
+            let path = dir.join("SKILL.md");
+            if path.is_file() {
+                let fallback = dir
+                    .file_name()
+                    .map(|n| n.to_string_lossy().to_string())
+                    .unwrap_or_default();
+                let parsed = parse_file(Category::Skill, &path, fallback);
+                results.push((path, parsed));
+            }
+        }
+
+        results
+    }
+}
+
+fn parse_file(category: Category, path: &Path, fallback_name: String) -> Result<Item> {
+    let text = fs::read_to_string(path)?;
+    item_from_markdown(category, &fallback_name, &text)
+}
+
+fn file_stem(path: &Path) -> String {
+    path.file_stem()
+        .map(|s| s.to_string_lossy().to_string())
+        .unwrap_or_default()
+}
+
+/// Sorted list of `*.md` files directly inside `dir` (empty if it doesn't exist)
+fn markdown_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|p| p.is_file() && p.extension().is_some_and(|ext| ext == "md"))
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
+
+fn subdirectories(dir: &Path) -> Vec<PathBuf> {
+    let mut dirs: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|p| p.is_dir())
+                .collect()
+        })
+        .unwrap_or_default();
+    dirs.sort();
+    dirs
+}
@@ -0,0 +1,184 @@
+use super::{item_from_markdown, upsert, ImportSummary};
+use crate::db::{ConflictMode, ItemStore};
+use crate::export::expand_home;
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use serde::de::IgnoredAny;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Imports Continue's prompts as grimoire prompts: the files in
+/// `.continue/prompts/` (`.prompt` files with a YAML preamble and markdown
+/// files with frontmatter) and the prompts listed in `config.yaml`
+/// (`prompts`) or the older `config.json` (`customCommands`). Continue's
+/// `{{{ input }}}` placeholders are kept as written.
+pub struct ContinueImporter {
+    base_path: PathBuf,
+}
+
+/// A prompt defined inline in Continue's config
+#[derive(Debug, Deserialize)]
+struct ConfigPrompt {
+    name: String,
+    #[serde(default)]
+    description: Option<String>,
+    prompt: String,
+    #[serde(default)]
+    tags: Vec<String>,
+}
+
+/// An entry of the config's prompt lists; entries that aren't inline
+/// prompts (such as `uses:` references to hub blocks) can't be imported
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ConfigEntry {
+    Prompt(ConfigPrompt),
+    Other(IgnoredAny),
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, rename_all = "camelCase")]
+struct Config {
+    prompts: Vec<ConfigEntry>,
+    custom_commands: Vec<ConfigEntry>,
+}
+
+impl ContinueImporter {
+    /// `path` is a `.continue` directory, or a project containing one
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = expand_home(path);
+        let nested = path.join(".continue");
+        Self {
+            base_path: if nested.is_dir() { nested } else { path },
+        }
+    }
+
+    pub fn base_path(&self) -> &Path {
+        &self.base_path
+    }
+
+    /// Scan the directory and create the matching items, settling ones that
+    /// differ from the library's according to `mode`
+    pub fn import(&self, store: &ItemStore, mode: ConflictMode) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+
+        for (path, parsed) in self.scan() {
+            match parsed.and_then(|item| upsert(store, item, mode)) {
+                Ok(outcome) => summary.record(outcome),
+                Err(e) => summary.failures.push((path, e.to_string())),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Parse every prompt file and every prompt in the config
+    pub fn scan(&self) -> Vec<(PathBuf, Result<Item>)> {
+        let mut results = Vec::new();
+
+        for path in prompt_files(&self.base_path.join("prompts")) {
+            let fallback = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let is_prompt = path.extension().is_some_and(|ext| ext == "prompt");
+            let parsed = fs::read_to_string(&path)
+                .map_err(Into::into)
+                .and_then(|text| {
+                    let text = if is_prompt { preamble(&text) } else { text };
+                    item_from_markdown(Category::Prompt, &fallback, &text)
+                });
+            results.push((path, parsed));
+        }
+
+        for file in ["config.yaml", "config.json"] {
+            let path = self.base_path.join(file);
+            if !path.is_file() {
+                continue;
+            }
+            match read_config(&path) {
+                Ok(config) => results.extend(
+                    config
+                        .prompts
+                        .into_iter()
+                        .chain(config.custom_commands)
+                        .filter_map(|entry| match entry {
+                            ConfigEntry::Prompt(prompt) => Some(prompt),
+                            ConfigEntry::Other(_) => None,
+                        })
+                        .map(|prompt| (path.clone(), item_from_config(prompt))),
+                ),
+                Err(e) => results.push((path, Err(e))),
+            }
+        }
+
+        results
+    }
+}
+
+/// `.prompt` files put their YAML preamble before a `---` line without one
+/// opening it; give it the opening line so it reads as frontmatter. Only a
+/// leading block made of YAML key/value pairs counts as a preamble, so a
+/// prompt that merely contains a `---` rule is left alone
+fn preamble(text: &str) -> String {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+    let opened = text.lines().next().map(str::trim_end) == Some("---");
+    let block = text
+        .lines()
+        .position(|line| line.trim_end() == "---")
+        .map(|end| text.lines().take(end).collect::<Vec<_>>().join("\n"));
+    let is_preamble = block.is_some_and(|block| {
+        serde_norway::from_str::<serde_norway::Mapping>(&block).is_ok_and(|map| !map.is_empty())
+    });
+    if !opened && is_preamble {
+        format!("---\n{}", text)
+    } else {
+        text.to_string()
+    }
+}
+
+fn read_config(path: &Path) -> Result<Config> {
+    let text = fs::read_to_string(path)?;
+    if path.extension().is_some_and(|ext| ext == "json") {
+        serde_json::from_str(&text).map_err(|e| eyre!("invalid config: {}", e))
+    } else {
+        serde_norway::from_str(&text).map_err(|e| eyre!("invalid config: {}", e))
+    }
+}
+
+fn item_from_config(prompt: ConfigPrompt) -> Result<Item> {
+    let mut item = Item::new(
+        prompt.name.trim().to_string(),
+        Category::Prompt,
+        prompt.prompt.trim().to_string(),
+    );
+    item.description = prompt.description.filter(|d| !d.trim().is_empty());
+    if !prompt.tags.is_empty() {
+        item.tags = Some(prompt.tags.join(", "));
+    }
+
+    item.validate()
+        .map_err(|errors| eyre!("{}: {}", item.name, errors.join(", ")))?;
+
+    Ok(item)
+}
+
+/// Sorted list of `*.prompt` and `*.md` files directly inside `dir`
+fn prompt_files(dir: &Path) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = fs::read_dir(dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|p| {
+                    p.is_file()
+                        && p.extension()
+                            .is_some_and(|ext| ext == "prompt" || ext == "md")
+                })
+                .collect()
+        })
+        .unwrap_or_default();
+    files.sort();
+    files
+}
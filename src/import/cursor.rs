@@ -0,0 +1,145 @@
+use super::{apply_field, parse_frontmatter, upsert, ImportSummary};
+use crate::db::{ConflictMode, ItemStore};
+use crate::export::expand_home;
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Imports Cursor project rules: the `.cursor/rules/*.mdc` files (nested
+/// rule directories included) and a legacy `.cursorrules` file. A rule the
+/// agent applies on its own judgement, with a description and neither
+/// `globs` nor `alwaysApply`, becomes an agent; every other rule becomes a
+/// prompt.
+pub struct CursorImporter {
+    project_root: PathBuf,
+}
+
+impl CursorImporter {
+    /// `project_root` is the directory containing `.cursor/`
+    pub fn new(project_root: impl AsRef<Path>) -> Self {
+        Self {
+            project_root: expand_home(project_root),
+        }
+    }
+
+    pub fn project_root(&self) -> &Path {
+        &self.project_root
+    }
+
+    /// Scan the project and create the matching items, settling ones that
+    /// differ from the library's according to `mode`
+    pub fn import(&self, store: &ItemStore, mode: ConflictMode) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+
+        for (path, parsed) in self.scan() {
+            match parsed.and_then(|item| upsert(store, item, mode)) {
+                Ok(outcome) => summary.record(outcome),
+                Err(e) => summary.failures.push((path, e.to_string())),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Parse every rule file in the project
+    pub fn scan(&self) -> Vec<(PathBuf, Result<Item>)> {
+        let rules = self.project_root.join(".cursor").join("rules");
+        let mut files = Vec::new();
+        rule_files_under(&rules, &mut files);
+
+        let mut results: Vec<(PathBuf, Result<Item>)> = files
+            .into_iter()
+            .map(|path| {
+                let name = rule_name(&rules, &path);
+                let parsed = fs::read_to_string(&path)
+                    .map_err(Into::into)
+                    .and_then(|text| item_from_rule(&name, &text));
+                (path, parsed)
+            })
+            .collect();
+
+        let legacy = self.project_root.join(".cursorrules");
+        if legacy.is_file() {
+            let parsed = fs::read_to_string(&legacy)
+                .map_err(Into::into)
+                .and_then(|text| item_from_rule(&self.legacy_name(), &text));
+            results.push((legacy, parsed));
+        }
+        results
+    }
+
+    /// Name for the `.cursorrules` file, after its project, e.g.
+    /// `webapp-cursorrules`
+    fn legacy_name(&self) -> String {
+        let root = fs::canonicalize(&self.project_root).unwrap_or(self.project_root.clone());
+        match root.file_name() {
+            Some(project) => format!("{}-cursorrules", project.to_string_lossy()),
+            None => "cursorrules".to_string(),
+        }
+    }
+}
+
+/// Build an item from a rule file. Cursor's own keys pick the category and
+/// are not kept: grimoire has no field for `globs`, and the Cursor exporter
+/// writes `alwaysApply: false` for every item.
+fn item_from_rule(name: &str, text: &str) -> Result<Item> {
+    let (fields, body) = parse_frontmatter(text);
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.trim())
+            .filter(|v| !v.is_empty())
+    };
+
+    let agent_requested = field("description").is_some()
+        && field("globs").is_none()
+        && field("alwaysApply") != Some("true");
+    let category = if agent_requested {
+        Category::Agent
+    } else {
+        Category::Prompt
+    };
+
+    let mut item = Item::new(name.to_string(), category, body);
+    for (key, value) in fields {
+        apply_field(&mut item, &key, value);
+    }
+
+    item.validate()
+        .map_err(|errors| eyre!("{}: {}", item.name, errors.join(", ")))?;
+
+    Ok(item)
+}
+
+/// Name for a rule file: its path under `rules` without the extension,
+/// with directories joined by dashes so nested rules sharing a file name
+/// stay apart, e.g. `frontend/style.mdc` becomes `frontend-style`
+fn rule_name(rules: &Path, path: &Path) -> String {
+    path.strip_prefix(rules)
+        .unwrap_or(path)
+        .with_extension("")
+        .components()
+        .map(|part| part.as_os_str().to_string_lossy())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Every `*.mdc` file under `dir`, in sorted order
+fn rule_files_under(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        // Symlinks aren't followed, so a link back up the tree can't loop
+        let is_dir = fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_dir());
+        if is_dir {
+            rule_files_under(&path, files);
+        } else if path.extension().is_some_and(|ext| ext == "mdc") {
+            files.push(path);
+        }
+    }
+}
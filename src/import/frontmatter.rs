@@ -0,0 +1,83 @@
+/// Split a markdown document into its YAML frontmatter fields and body.
+///
+/// Only the flat `key: value` subset written by the exporters is supported.
+/// Block lists (`- item` lines under a key) are joined with ", " so they map
+/// onto grimoire's comma-separated fields. Documents without frontmatter
+/// return no fields and the full text as body.
+pub fn parse_frontmatter(text: &str) -> (Vec<(String, String)>, String) {
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    let mut lines = text.lines();
+    if lines.next().map(str::trim_end) != Some("---") {
+        return (Vec::new(), text.trim().to_string());
+    }
+
+    let mut fields: Vec<(String, String)> = Vec::new();
+    let mut consumed = 0;
+    let mut closed = false;
+
+    for line in lines.by_ref() {
+        consumed += 1;
+        if line.trim_end() == "---" {
+            closed = true;
+            break;
+        }
+
+        let trimmed = line.trim();
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+
+        // Continuation of a block list for the previous key
+        if let Some(entry) = trimmed.strip_prefix("- ") {
+            if let Some((_, value)) = fields.last_mut() {
+                if !value.is_empty() {
+                    value.push_str(", ");
+                }
+                value.push_str(unquote(entry.trim()));
+            }
+            continue;
+        }
+
+        if let Some((key, value)) = trimmed.split_once(':') {
+            fields.push((key.trim().to_string(), parse_value(value.trim())));
+        }
+    }
+
+    if !closed {
+        return (Vec::new(), text.trim().to_string());
+    }
+
+    let body: Vec<&str> = text.lines().skip(consumed + 1).collect();
+    let body = body.join("\n").trim().to_string();
+
+    (fields, body)
+}
+
+/// Parse a scalar or inline list (`[a, b]`) value into grimoire's flat form
+fn parse_value(value: &str) -> String {
+    match value
+        .strip_prefix('[')
+        .and_then(|rest| rest.strip_suffix(']'))
+    {
+        Some(inner) => inner
+            .split(',')
+            .map(|entry| unquote(entry.trim()))
+            .filter(|entry| !entry.is_empty())
+            .collect::<Vec<_>>()
+            .join(", "),
+        None => unquote(value).to_string(),
+    }
+}
+
+fn unquote(value: &str) -> &str {
+    let bytes = value.as_bytes();
+    if bytes.len() >= 2
+        && ((bytes[0] == b'"' && bytes[bytes.len() - 1] == b'"')
+            || (bytes[0] == b'\'' && bytes[bytes.len() - 1] == b'\''))
+    {
+        &value[1..value.len() - 1]
+    } else {
+        value
+    }
+}
@@ -0,0 +1,328 @@
+mod claude;
+mod continue_dev;
+mod cursor;
+mod frontmatter;
+mod repo;
+mod url;
+
+pub use claude::ClaudeImporter;
+pub use continue_dev::ContinueImporter;
+pub use cursor::CursorImporter;
+pub use frontmatter::parse_frontmatter;
+pub use repo::RepoImporter;
+pub use url::UrlImporter;
+
+use crate::db::{unused_name, ConflictMode, ItemStore};
+use crate::export::FrontmatterKeys;
+use crate::models::{Category, Item};
+use color_eyre::eyre::{bail, eyre, Result};
+use std::path::PathBuf;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImportOutcome {
+    Created,
+    Updated,
+    Unchanged,
+    /// The library's item was kept over a different imported one
+    Kept,
+    /// The imported item was added under another name
+    Renamed,
+}
+
+#[derive(Debug, Default)]
+pub struct ImportSummary {
+    pub created: usize,
+    pub updated: usize,
+    pub unchanged: usize,
+    pub kept: usize,
+    pub renamed: usize,
+    pub failures: Vec<(PathBuf, String)>,
+    /// Items left for the user to settle, by `import_new`
+    pub conflicts: Vec<ImportConflict>,
+}
+
+/// An imported item whose name is taken by a different item in the library
+#[derive(Debug)]
+pub struct ImportConflict {
+    pub existing: Item,
+    pub incoming: Item,
+}
+
+impl ImportSummary {
+    pub fn record(&mut self, outcome: ImportOutcome) {
+        match outcome {
+            ImportOutcome::Created => self.created += 1,
+            ImportOutcome::Updated => self.updated += 1,
+            ImportOutcome::Unchanged => self.unchanged += 1,
+            ImportOutcome::Kept => self.kept += 1,
+            ImportOutcome::Renamed => self.renamed += 1,
+        }
+    }
+
+    /// One-line summary suitable for the status bar
+    pub fn describe(&self) -> String {
+        let mut text = format!(
+            "Imported: {} new, {} updated, {} unchanged",
+            self.created, self.updated, self.unchanged
+        );
+        if self.kept > 0 {
+            text.push_str(&format!(", {} kept yours", self.kept));
+        }
+        if self.renamed > 0 {
+            text.push_str(&format!(", {} renamed", self.renamed));
+        }
+        if !self.failures.is_empty() {
+            text.push_str(&format!(", {} failed", self.failures.len()));
+        }
+        text
+    }
+}
+
+/// Build an item from a markdown document with optional frontmatter.
+/// `fallback_name` is used when the frontmatter has no `name` key.
+pub fn item_from_markdown(category: Category, fallback_name: &str, text: &str) -> Result<Item> {
+    item_from_export(
+        category,
+        fallback_name,
+        text,
+        &FrontmatterKeys::default(),
+        None,
+    )
+}
+
+/// Like `item_from_markdown`, for a file exported with the `keys` changes:
+/// renamed keys are read under grimoire's names, and fields the target
+/// leaves out are taken from `existing`, the item the file was exported
+/// from
+pub fn item_from_export(
+    category: Category,
+    fallback_name: &str,
+    text: &str,
+    keys: &FrontmatterKeys,
+    existing: Option<&Item>,
+) -> Result<Item> {
+    let (fields, body) = parse_frontmatter(text);
+
+    let mut item = Item::new(fallback_name.to_string(), category, body);
+    for (key, value) in fields {
+        apply_field(&mut item, keys.original_key(&key), value);
+    }
+    if let Some(existing) = existing {
+        keys.keep_omitted(&mut item, existing);
+    }
+
+    item.validate()
+        .map_err(|errors| eyre!("{}: {}", item.name, errors.join(", ")))?;
+
+    Ok(item)
+}
+
+/// Build an unvalidated draft from generated markdown. Unlike imports, the
+/// category comes from a `category` frontmatter key when present.
+pub fn draft_from_markdown(default_category: Category, text: &str) -> Item {
+    let (fields, body) = parse_frontmatter(strip_code_fence(text));
+
+    let mut item = Item::new(String::new(), default_category, body);
+    for (key, value) in fields {
+        if key == "category" {
+            item.category = Category::from_str(value.trim());
+        } else {
+            apply_field(&mut item, &key, value);
+        }
+    }
+    item
+}
+
+/// Build an unvalidated draft from pasted text. Without a `category` key the
+/// category is guessed from the frontmatter keys Claude Code uses for each
+/// kind of file, falling back to `default_category` for plain text.
+pub fn draft_from_paste(default_category: Category, text: &str) -> Item {
+    let (fields, _) = parse_frontmatter(strip_code_fence(text));
+    let has = |keys: &[&str]| fields.iter().any(|(key, _)| keys.contains(&key.as_str()));
+
+    let category = if has(&["argument-hint", "argumentHint", "argument_hint"]) {
+        Category::Command
+    } else if has(&[
+        "tools",
+        "skills",
+        "permissionMode",
+        "permission-mode",
+        "permission_mode",
+    ]) {
+        Category::Agent
+    } else if has(&["name"]) && has(&["description"]) {
+        // Agents and skills both have these; only agents pick a model
+        if has(&["model"]) {
+            Category::Agent
+        } else {
+            Category::Skill
+        }
+    } else if has(&["allowed-tools", "allowedTools", "allowed_tools", "model"]) {
+        Category::Command
+    } else {
+        default_category
+    };
+    draft_from_markdown(category, text)
+}
+
+/// Remove a ```markdown fence wrapped around the whole document
+fn strip_code_fence(text: &str) -> &str {
+    let trimmed = text.trim();
+    match (trimmed.find('\n'), trimmed.strip_suffix("```")) {
+        (Some(first_line_end), Some(without_end)) if trimmed.starts_with("```") => {
+            without_end[first_line_end..].trim()
+        }
+        _ => trimmed,
+    }
+}
+
+/// Map a frontmatter key onto the matching item field. Unknown keys are ignored.
+fn apply_field(item: &mut Item, key: &str, value: String) {
+    let value = value.trim().to_string();
+    if value.is_empty() {
+        return;
+    }
+
+    match key {
+        "name" => item.name = value,
+        "description" => item.description = Some(value),
+        "tools" => item.tools = Some(value),
+        "model" => item.model = Some(value),
+        "permissionMode" | "permission-mode" | "permission_mode" => {
+            item.permission_mode = Some(value)
+        }
+        "skills" => item.skills = Some(value),
+        "allowed-tools" | "allowedTools" | "allowed_tools" => item.allowed_tools = Some(value),
+        "argument-hint" | "argumentHint" | "argument_hint" => item.argument_hint = Some(value),
+        "tags" => item.tags = Some(value),
+        _ => {}
+    }
+}
+
+/// Insert the item, or settle it with `mode` when the existing item with the
+/// same name has a different definition. Existing tags are kept when the
+/// import has none.
+pub fn upsert(store: &ItemStore, mut item: Item, mode: ConflictMode) -> Result<ImportOutcome> {
+    let Some(existing) = existing_for(store, &mut item)? else {
+        store.insert(&item)?;
+        return Ok(ImportOutcome::Created);
+    };
+
+    if same_definition(&existing, &item) {
+        return Ok(ImportOutcome::Unchanged);
+    }
+
+    let conflict = ImportConflict {
+        existing,
+        incoming: item,
+    };
+    resolve(store, conflict, mode)
+}
+
+/// Insert the scanned items that are new and count the unchanged ones.
+/// Items that differ from the library's item of the same name are left in
+/// the summary's `conflicts`, to be settled one by one with `resolve`.
+pub fn import_new(
+    store: &ItemStore,
+    scanned: Vec<(PathBuf, Result<Item>)>,
+) -> Result<ImportSummary> {
+    let mut summary = ImportSummary::default();
+
+    for (path, parsed) in scanned {
+        let mut item = match parsed {
+            Ok(item) => item,
+            Err(e) => {
+                summary.failures.push((path, e.to_string()));
+                continue;
+            }
+        };
+        let existing = match existing_for(store, &mut item) {
+            Ok(existing) => existing,
+            Err(e) => {
+                summary.failures.push((path, e.to_string()));
+                continue;
+            }
+        };
+        match existing {
+            None => match store.insert(&item) {
+                Ok(_) => summary.record(ImportOutcome::Created),
+                Err(e) => summary.failures.push((path, e.to_string())),
+            },
+            Some(existing) if same_definition(&existing, &item) => {
+                summary.record(ImportOutcome::Unchanged);
+            }
+            Some(existing) => summary.conflicts.push(ImportConflict {
+                existing,
+                incoming: item,
+            }),
+        }
+    }
+
+    Ok(summary)
+}
+
+/// Settle a conflict: `Skip` keeps the library's item, `Overwrite` replaces
+/// it with the imported one (its history is kept), and `Duplicate` adds
+/// the imported one under an unused name
+pub fn resolve(
+    store: &ItemStore,
+    conflict: ImportConflict,
+    mode: ConflictMode,
+) -> Result<ImportOutcome> {
+    let mut item = conflict.incoming;
+    match mode {
+        ConflictMode::Skip => Ok(ImportOutcome::Kept),
+        ConflictMode::Overwrite => {
+            item.id = conflict.existing.id;
+            store.update(&item, Some("Imported"))?;
+            Ok(ImportOutcome::Updated)
+        }
+        ConflictMode::Duplicate => {
+            item.name = unused_name(store, &item.name)?;
+            store.insert(&item)?;
+            Ok(ImportOutcome::Renamed)
+        }
+    }
+}
+
+/// The library's item with the imported item's name, if any. The imported
+/// item takes its tags when it has none. An item in the trash is an error
+/// rather than something to update out of sight; it has to be restored or
+/// purged first. So is an item of another category: names are unique across
+/// categories, and importing over it would change what it is.
+pub fn existing_for(store: &ItemStore, item: &mut Item) -> Result<Option<Item>> {
+    let existing = store.get_by_name(&item.name)?;
+    if let Some(ref existing) = existing {
+        if store.is_trashed(existing.id.unwrap_or_default())? {
+            bail!(
+                "'{}' is in the trash; restore or delete it to import",
+                item.name
+            );
+        }
+        if existing.category != item.category {
+            bail!(
+                "'{}' is already a {}; rename one of them to import",
+                item.name,
+                existing.category.as_str()
+            );
+        }
+        if item.tags.is_none() {
+            item.tags = existing.tags.clone();
+        }
+    }
+    Ok(existing)
+}
+
+/// Whether two items have the same definition and tags
+pub fn same_definition(a: &Item, b: &Item) -> bool {
+    a.category == b.category
+        && a.description == b.description
+        && a.content == b.content
+        && a.model == b.model
+        && a.tools == b.tools
+        && a.allowed_tools == b.allowed_tools
+        && a.argument_hint == b.argument_hint
+        && a.permission_mode == b.permission_mode
+        && a.skills == b.skills
+        && a.tags == b.tags
+}
@@ -0,0 +1,207 @@
+use super::{item_from_markdown, parse_frontmatter, upsert, ImportSummary};
+use crate::db::{ConflictMode, Database, ItemStore};
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Imports agents, commands, and skills from a git repository, such as a
+/// community collection. The repository is cloned into the data directory
+/// and updated from its remote on later imports.
+pub struct RepoImporter {
+    url: String,
+    checkout: PathBuf,
+}
+
+impl RepoImporter {
+    /// An importer for `url`; `owner/repo` is read as a GitHub repository
+    pub fn new(url: &str) -> Result<Self> {
+        let url = url.trim().trim_end_matches('/');
+        if url.is_empty() {
+            return Err(eyre!("repository URL is empty"));
+        }
+        let url = if is_github_shorthand(url) {
+            format!("https://github.com/{}", url)
+        } else {
+            url.to_string()
+        };
+        let checkout = Database::data_dir()?
+            .join("repos")
+            .join(checkout_name(&url));
+        Ok(Self { url, checkout })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Clone the repository, or bring an earlier clone up to date with the
+    /// remote's default branch
+    pub fn fetch(&self) -> Result<()> {
+        if self.checkout.join(".git").is_dir() {
+            git(
+                &self.checkout,
+                &["fetch", "--quiet", "--depth", "1", "origin"],
+            )?;
+            git(
+                &self.checkout,
+                &["reset", "--quiet", "--hard", "FETCH_HEAD"],
+            )?;
+            return Ok(());
+        }
+
+        if let Some(parent) = self.checkout.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        // A clone that failed halfway would otherwise stay in the way
+        if self.checkout.exists() {
+            fs::remove_dir_all(&self.checkout)?;
+        }
+        let checkout = self.checkout.to_string_lossy().to_string();
+        // `--` keeps a URL starting with `-` from being read as an option
+        git(
+            Path::new("."),
+            &[
+                "clone", "--quiet", "--depth", "1", "--", &self.url, &checkout,
+            ],
+        )
+    }
+
+    /// Create the items found in the fetched repository, settling ones that
+    /// differ from the library's according to `mode`
+    pub fn import(&self, store: &ItemStore, mode: ConflictMode) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+
+        for (path, parsed) in self.scan() {
+            match parsed.and_then(|item| upsert(store, item, mode)) {
+                Ok(outcome) => summary.record(outcome),
+                Err(e) => summary.failures.push((path, e.to_string())),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Parse the markdown files with frontmatter anywhere in the repository
+    /// that sit in an `agents/` or `commands/` directory, or are a skill's
+    /// `SKILL.md`. Paths are given relative to the repository.
+    pub fn scan(&self) -> Vec<(PathBuf, Result<Item>)> {
+        let mut files = Vec::new();
+        markdown_files_under(&self.checkout, &mut files);
+
+        let mut results = Vec::new();
+        for path in files {
+            let Some((category, fallback_name)) = self.classify(&path) else {
+                continue;
+            };
+            let text = match fs::read_to_string(&path) {
+                Ok(text) => text,
+                Err(e) => {
+                    results.push((path, Err(e.into())));
+                    continue;
+                }
+            };
+            // READMEs and other docs next to the definitions have none
+            if parse_frontmatter(&text).0.is_empty() {
+                continue;
+            }
+            let parsed = item_from_markdown(category, &fallback_name, &text);
+            results.push((path, parsed));
+        }
+
+        results
+            .into_iter()
+            .map(|(path, parsed)| {
+                let path = path
+                    .strip_prefix(&self.checkout)
+                    .map(Path::to_path_buf)
+                    .unwrap_or(path);
+                (path, parsed)
+            })
+            .collect()
+    }
+
+    /// Category of a file from where it sits, and the name to use when its
+    /// frontmatter has none
+    fn classify(&self, path: &Path) -> Option<(Category, String)> {
+        let relative = path.strip_prefix(&self.checkout).ok()?;
+        let dirs: Vec<String> = relative
+            .parent()?
+            .components()
+            .map(|c| c.as_os_str().to_string_lossy().to_lowercase())
+            .collect();
+        let name = |path: &Path| path.file_stem().map(|s| s.to_string_lossy().to_string());
+
+        if relative.file_name()? == "SKILL.md" {
+            return Some((Category::Skill, name(relative.parent()?)?));
+        }
+        if dirs.iter().any(|dir| dir == "agents") {
+            return Some((Category::Agent, name(relative)?));
+        }
+        if dirs.iter().any(|dir| dir == "commands") {
+            return Some((Category::Command, name(relative)?));
+        }
+        None
+    }
+}
+
+/// `owner/repo`, as opposed to a URL or a local path
+fn is_github_shorthand(url: &str) -> bool {
+    let mut parts = url.split('/');
+    let valid = |part: Option<&str>| {
+        part.is_some_and(|p| {
+            !p.is_empty()
+                && !p.starts_with('.')
+                && p.chars()
+                    .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+        })
+    };
+    valid(parts.next()) && valid(parts.next()) && parts.next().is_none()
+}
+
+/// Directory name for a repository's clone, e.g. `github.com-owner-repo`
+fn checkout_name(url: &str) -> String {
+    let url = url.split_once("://").map_or(url, |(_, rest)| rest);
+    let url = url.trim_end_matches(".git");
+    url.split(|c: char| !c.is_ascii_alphanumeric() && c != '.' && c != '_')
+        .filter(|part| !part.is_empty() && *part != "." && *part != "..")
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+/// Every `*.md` file under `dir`, in sorted order, skipping `.git`
+fn markdown_files_under(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    let mut paths: Vec<PathBuf> = entries.flatten().map(|entry| entry.path()).collect();
+    paths.sort();
+    for path in paths {
+        // Symlinks aren't followed, so a link back up the tree can't loop
+        let is_dir = fs::symlink_metadata(&path).is_ok_and(|meta| meta.is_dir());
+        if is_dir {
+            if path.file_name().is_some_and(|name| name != ".git") {
+                markdown_files_under(&path, files);
+            }
+        } else if path.extension().is_some_and(|ext| ext == "md") {
+            files.push(path);
+        }
+    }
+}
+
+/// Run git in `dir`, failing with its error output
+fn git(dir: &Path, args: &[&str]) -> Result<()> {
+    let output = Command::new("git")
+        .current_dir(dir)
+        .args(args)
+        // Fail instead of asking for credentials under the TUI
+        .env("GIT_TERMINAL_PROMPT", "0")
+        .output()
+        .map_err(|e| eyre!("could not run git: {}", e))?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(eyre!("git {} failed: {}", args[0], stderr.trim()));
+    }
+    Ok(())
+}
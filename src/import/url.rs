@@ -0,0 +1,178 @@
+use super::{draft_from_paste, upsert, ImportSummary};
+use crate::db::{ConflictMode, ItemStore};
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use serde::Deserialize;
+use std::collections::BTreeMap;
+use std::path::PathBuf;
+use std::time::Duration;
+
+/// Imports items shared by URL: a raw markdown file, a file on GitHub, or
+/// every file of a gist
+pub struct UrlImporter {
+    url: String,
+    /// File name and text of each document fetched
+    documents: Vec<(String, String)>,
+}
+
+#[derive(Deserialize)]
+struct Gist {
+    files: BTreeMap<String, GistFile>,
+}
+
+#[derive(Deserialize)]
+struct GistFile {
+    filename: String,
+    #[serde(default)]
+    content: String,
+    #[serde(default)]
+    truncated: bool,
+    raw_url: String,
+}
+
+impl UrlImporter {
+    pub fn new(url: &str) -> Result<Self> {
+        let url = url.trim();
+        if !url.starts_with("https://") && !url.starts_with("http://") {
+            return Err(eyre!("'{}' is not an http(s) URL", url));
+        }
+        Ok(Self {
+            url: url.to_string(),
+            documents: Vec::new(),
+        })
+    }
+
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Download the documents behind the URL
+    pub fn fetch(&mut self) -> Result<()> {
+        let client = reqwest::Client::builder()
+            .timeout(Duration::from_secs(30))
+            // GitHub's API turns away requests without one
+            .user_agent(concat!("grimoire/", env!("CARGO_PKG_VERSION")))
+            .build()?;
+        let rt = tokio::runtime::Runtime::new()?;
+        self.documents = rt.block_on(async {
+            match gist_id(&self.url) {
+                Some(id) => fetch_gist(&client, id).await,
+                None => {
+                    let url = raw_github_url(&self.url).unwrap_or_else(|| self.url.clone());
+                    let text = fetch_text(&client, &url).await?;
+                    Ok(vec![(file_name(&url), text)])
+                }
+            }
+        })?;
+        Ok(())
+    }
+
+    /// Create an item from each document, settling ones that differ from
+    /// the library's according to `mode`
+    pub fn import(&self, store: &ItemStore, mode: ConflictMode) -> Result<ImportSummary> {
+        let mut summary = ImportSummary::default();
+
+        for (path, parsed) in self.scan() {
+            match parsed.and_then(|item| upsert(store, item, mode)) {
+                Ok(outcome) => summary.record(outcome),
+                Err(e) => summary.failures.push((path, e.to_string())),
+            }
+        }
+
+        Ok(summary)
+    }
+
+    /// Parse each document. The category comes from the frontmatter, as
+    /// for a paste, and the name from the file when the frontmatter has
+    /// none.
+    pub fn scan(&self) -> Vec<(PathBuf, Result<Item>)> {
+        self.documents
+            .iter()
+            .map(|(file, text)| {
+                let mut item = draft_from_paste(Category::Prompt, text);
+                if item.name.trim().is_empty() {
+                    item.name = file
+                        .rsplit_once('.')
+                        .map_or(file.as_str(), |(stem, _)| stem)
+                        .to_string();
+                }
+                let parsed = item
+                    .validate()
+                    .map(|_| item.clone())
+                    .map_err(|errors| eyre!("{}: {}", item.name, errors.join(", ")));
+                (PathBuf::from(file), parsed)
+            })
+            .collect()
+    }
+}
+
+/// Id of a gist from its page URL, e.g. `https://gist.github.com/user/abc123`
+fn gist_id(url: &str) -> Option<&str> {
+    let path = url
+        .strip_prefix("https://gist.github.com/")
+        .or_else(|| url.strip_prefix("http://gist.github.com/"))?;
+    let path = path.split(['#', '?']).next()?.trim_end_matches('/');
+    let id = path.rsplit('/').next()?;
+    (!id.is_empty() && id.chars().all(|c| c.is_ascii_alphanumeric())).then_some(id)
+}
+
+/// Raw URL for a file shown on github.com,
+/// e.g. `https://github.com/owner/repo/blob/main/agents/reviewer.md`
+fn raw_github_url(url: &str) -> Option<String> {
+    let path = url.strip_prefix("https://github.com/")?;
+    let (repo, file) = path.split_once("/blob/")?;
+    Some(format!(
+        "https://raw.githubusercontent.com/{}/{}",
+        repo,
+        file.split(['#', '?']).next()?
+    ))
+}
+
+fn file_name(url: &str) -> String {
+    url.split(['#', '?'])
+        .next()
+        .unwrap_or(url)
+        .trim_end_matches('/')
+        .rsplit('/')
+        .next()
+        .unwrap_or_default()
+        .to_string()
+}
+
+async fn fetch_text(client: &reqwest::Client, url: &str) -> Result<String> {
+    let response = client.get(url).send().await?;
+    let status = response.status();
+    if !status.is_success() {
+        return Err(eyre!("{} returned {}", url, status));
+    }
+    let is_html = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok())
+        .is_some_and(|value| value.starts_with("text/html"));
+    if is_html {
+        return Err(eyre!("{} is a web page; use the link to the raw file", url));
+    }
+    Ok(response.text().await?)
+}
+
+/// Every file of a gist, in file name order
+async fn fetch_gist(client: &reqwest::Client, id: &str) -> Result<Vec<(String, String)>> {
+    let url = format!("https://api.github.com/gists/{}", id);
+    let gist: Gist = serde_json::from_str(&fetch_text(client, &url).await?)?;
+
+    let mut documents = Vec::new();
+    for file in gist.files.into_values() {
+        // The API cuts large files short; the raw URL has all of it
+        let content = if file.truncated {
+            fetch_text(client, &file.raw_url).await?
+        } else {
+            file.content
+        };
+        documents.push((file.filename, content));
+    }
+    if documents.is_empty() {
+        return Err(eyre!("gist {} has no files", id));
+    }
+    Ok(documents)
+}
@@ -1,5 +1,5 @@
-use super::{LlmClient, LlmRequest, LlmResponse};
-use color_eyre::eyre::{eyre, Result};
+use super::{ApiError, LlmClient, LlmRequest, LlmResponse, Usage};
+use color_eyre::eyre::Result;
 use serde::{Deserialize, Serialize};
 
 pub struct AnthropicClient {
@@ -9,11 +9,11 @@ pub struct AnthropicClient {
 }
 
 impl AnthropicClient {
-    pub fn new(api_key: &str, model: &str) -> Self {
+    pub fn new(api_key: &str, model: &str, client: reqwest::Client) -> Self {
         Self {
             api_key: api_key.to_string(),
             model: model.to_string(),
-            client: reqwest::Client::new(),
+            client,
         }
     }
 }
@@ -35,6 +35,13 @@ struct Message {
 #[derive(Deserialize)]
 struct AnthropicResponse {
     content: Vec<ContentBlock>,
+    usage: Option<AnthropicUsage>,
+}
+
+#[derive(Deserialize)]
+struct AnthropicUsage {
+    input_tokens: u64,
+    output_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -45,14 +52,26 @@ struct ContentBlock {
 #[async_trait::async_trait]
 impl LlmClient for AnthropicClient {
     async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
+        let mut messages: Vec<Message> = request
+            .history
+            .iter()
+            .flat_map(|turn| turn.messages())
+            .map(|(role, content)| Message {
+                role: role.to_string(),
+                content,
+            })
+            .collect();
+
+        messages.push(Message {
+            role: "user".to_string(),
+            content: request.user_message,
+        });
+
         let body = AnthropicRequest {
             model: self.model.clone(),
             max_tokens: request.max_tokens,
             system: request.system_prompt,
-            messages: vec![Message {
-                role: "user".to_string(),
-                content: request.user_message,
-            }],
+            messages,
         };
 
         let response = self
@@ -66,14 +85,8 @@ impl LlmClient for AnthropicClient {
             .await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(eyre!(
-                "Anthropic API error {} (model: {}): {}",
-                status,
-                self.model,
-                error_text
-            ));
+            let detail = format!("model: {}", self.model);
+            return Err(ApiError::read("Anthropic", detail, response).await.into());
         }
 
         let api_response: AnthropicResponse = response.json().await?;
@@ -84,7 +97,12 @@ impl LlmClient for AnthropicClient {
             .and_then(|block| block.text.clone())
             .unwrap_or_default();
 
-        Ok(LlmResponse { content })
+        let usage = api_response.usage.map(|u| Usage {
+            input_tokens: u.input_tokens,
+            output_tokens: u.output_tokens,
+        });
+
+        Ok(LlmResponse { content, usage })
     }
 
     fn is_configured(&self) -> bool {
@@ -1,23 +1,126 @@
 mod anthropic;
+mod ollama;
 mod openai;
+mod pricing;
 
 pub use anthropic::AnthropicClient;
-pub use openai::OpenAIClient;
+pub use ollama::{OllamaClient, DEFAULT_BASE_URL as OLLAMA_DEFAULT_URL};
+pub use openai::{OpenAIClient, DEFAULT_BASE_URL as OPENAI_DEFAULT_URL};
+pub use pricing::{estimate_cost, estimate_tokens};
 
-use color_eyre::eyre::Result;
+use color_eyre::eyre::{eyre, Report, Result};
+use std::fmt;
+use std::sync::mpsc;
+use std::thread::JoinHandle;
+use std::time::{Duration, Instant};
+use tokio::sync::oneshot;
+use tracing::{debug, info, warn};
 
 #[derive(Debug, Clone)]
 pub struct LlmRequest {
     pub system_prompt: String,
+    /// Earlier turns of the conversation, oldest first
+    pub history: Vec<ChatTurn>,
     pub user_message: String,
     pub max_tokens: u32,
 }
 
+/// One user message and the reply it got
+#[derive(Debug, Clone)]
+pub struct ChatTurn {
+    pub user: String,
+    pub assistant: String,
+}
+
+impl ChatTurn {
+    /// The turn as (role, content) pairs, in the roles all providers share
+    pub fn messages(&self) -> [(&'static str, String); 2] {
+        [
+            ("user", self.user.clone()),
+            ("assistant", self.assistant.clone()),
+        ]
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct LlmResponse {
     pub content: String,
+    pub usage: Option<Usage>,
 }
 
+/// Token counts reported by the provider for one call
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Usage {
+    pub input_tokens: u64,
+    pub output_tokens: u64,
+}
+
+/// Non-success HTTP response from a provider
+#[derive(Debug)]
+pub struct ApiError {
+    pub provider: &'static str,
+    pub status: reqwest::StatusCode,
+    /// Model and endpoint of the request, e.g. `model: gpt-4o`
+    pub detail: String,
+    /// The provider's explanation, taken out of its JSON error body
+    pub message: String,
+    /// Wait asked for in the response's `retry-after` headers
+    pub retry_after: Option<Duration>,
+}
+
+impl ApiError {
+    /// Read a failed response into an error
+    pub async fn read(provider: &'static str, detail: String, response: reqwest::Response) -> Self {
+        let status = response.status();
+        let retry_after = retry_after(response.headers());
+        let body = response.text().await.unwrap_or_default();
+        Self {
+            provider,
+            status,
+            detail,
+            message: error_message(&body),
+            retry_after,
+        }
+    }
+
+    /// Rate limits and overload (Anthropic's 529, 503 elsewhere): the
+    /// request was fine, but the provider can't take it right now
+    pub fn is_rate_limited(&self) -> bool {
+        self.status == reqwest::StatusCode::TOO_MANY_REQUESTS
+            || self.status == reqwest::StatusCode::SERVICE_UNAVAILABLE
+            || self.status.as_u16() == 529
+    }
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if !self.is_rate_limited() {
+            return write!(
+                f,
+                "{} API error {} ({}): {}",
+                self.provider, self.status, self.detail, self.message
+            );
+        }
+
+        let reason = if self.status == reqwest::StatusCode::TOO_MANY_REQUESTS {
+            "is rate limiting requests"
+        } else {
+            "is overloaded"
+        };
+        write!(
+            f,
+            "{} {} ({}): {}",
+            self.provider, reason, self.detail, self.message
+        )?;
+        if let Some(wait) = self.retry_after {
+            write!(f, " (retry after {}s)", wait.as_secs().max(1))?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for ApiError {}
+
 #[async_trait::async_trait]
 pub trait LlmClient: Send + Sync {
     async fn complete(&self, request: LlmRequest) -> Result<LlmResponse>;
@@ -25,15 +128,210 @@ pub trait LlmClient: Send + Sync {
     fn is_configured(&self) -> bool;
 }
 
-/// Helper to create a client based on provider
-pub fn get_client(provider: &str, api_key: &str, model: &str) -> Option<Box<dyn LlmClient>> {
-    let api_key = api_key.trim();
-    if api_key.is_empty() {
-        return None;
+/// Provider connection settings, as configured in Settings
+#[derive(Debug, Clone)]
+pub struct LlmConfig {
+    pub provider: String,
+    pub api_key: String,
+    pub model: String,
+    pub base_url: String,
+    pub timeout_secs: u64,
+    pub max_retries: u32,
+}
+
+impl Default for LlmConfig {
+    fn default() -> Self {
+        Self {
+            provider: String::new(),
+            api_key: String::new(),
+            model: String::new(),
+            base_url: String::new(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS,
+            max_retries: DEFAULT_MAX_RETRIES,
+        }
     }
+}
+
+pub const DEFAULT_TIMEOUT_SECS: u64 = 60;
+pub const DEFAULT_MAX_RETRIES: u32 = 2;
+
+/// HTTP client shared by the providers, with the configured request timeout
+fn http_client(timeout_secs: u64) -> reqwest::Client {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(timeout_secs.max(1)))
+        .build()
+        .unwrap_or_default()
+}
+
+/// Whether a failed call is worth retrying: network failures, timeouts,
+/// rate limits, and server errors
+fn is_transient(error: &Report) -> bool {
+    error.chain().any(|cause| {
+        if let Some(e) = cause.downcast_ref::<reqwest::Error>() {
+            return e.is_timeout() || e.is_connect() || e.is_request();
+        }
+        if let Some(e) = cause.downcast_ref::<ApiError>() {
+            return e.status == reqwest::StatusCode::TOO_MANY_REQUESTS
+                || e.status.is_server_error();
+        }
+        false
+    })
+}
+
+/// Exponential backoff: 1s, 2s, 4s, ... capped at 30s
+fn backoff(attempt: u32) -> Duration {
+    Duration::from_secs((1u64 << attempt.min(5)).min(30))
+}
 
+/// Longest wait a provider may ask for before an automatic retry; longer
+/// ones are reported so the user can decide
+const MAX_RETRY_WAIT: Duration = Duration::from_secs(30);
+
+/// `retry-after-ms` (OpenAI) or `retry-after` in seconds; HTTP dates are
+/// ignored
+fn retry_after(headers: &reqwest::header::HeaderMap) -> Option<Duration> {
+    let header = |name: &str| {
+        headers
+            .get(name)
+            .and_then(|value| value.to_str().ok())
+            .and_then(|value| value.trim().parse::<f64>().ok())
+            .filter(|value| value.is_finite() && *value >= 0.0)
+    };
+    // Values too large for a `Duration` are ignored
+    header("retry-after-ms")
+        .and_then(|ms| Duration::try_from_secs_f64(ms / 1000.0).ok())
+        .or_else(|| header("retry-after").and_then(|s| Duration::try_from_secs_f64(s).ok()))
+}
+
+/// The readable part of an error body: `error.message` (Anthropic, OpenAI),
+/// `error` (Ollama), or the body itself
+fn error_message(body: &str) -> String {
+    let json: Option<serde_json::Value> = serde_json::from_str(body).ok();
+    let message = json.as_ref().and_then(|json| {
+        json.pointer("/error/message")
+            .or_else(|| json.get("error"))
+            .or_else(|| json.get("message"))
+            .and_then(serde_json::Value::as_str)
+    });
+    match message {
+        Some(message) => message.trim().to_string(),
+        None if body.trim().is_empty() => "no details given".to_string(),
+        None => body.trim().chars().take(300).collect(),
+    }
+}
+
+/// The provider error behind a failed call, if it got a response
+fn api_error(error: &Report) -> Option<&ApiError> {
+    error
+        .chain()
+        .find_map(|cause| cause.downcast_ref::<ApiError>())
+}
+
+/// A failed completion as sent back from a background thread
+#[derive(Debug, Clone)]
+pub struct CompletionError {
+    pub message: String,
+    /// The provider was rate limiting or overloaded
+    pub rate_limited: bool,
+    /// How long the provider asked to wait, when it said
+    pub retry_after: Option<Duration>,
+}
+
+impl CompletionError {
+    fn from_report(error: &Report) -> Self {
+        let api = api_error(error);
+        Self {
+            message: format!("{:#}", error),
+            rate_limited: api.is_some_and(ApiError::is_rate_limited),
+            retry_after: api.and_then(|e| e.retry_after),
+        }
+    }
+}
+
+impl From<&str> for CompletionError {
+    fn from(message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            rate_limited: false,
+            retry_after: None,
+        }
+    }
+}
+
+impl fmt::Display for CompletionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Where the API key used for a provider comes from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeySource {
+    Settings,
+    Env(&'static str),
+    Missing,
+}
+
+/// Environment variable holding the provider's API key
+pub fn key_env_var(provider: &str) -> Option<&'static str> {
     match provider.to_lowercase().as_str() {
-        "openai" => Some(Box::new(OpenAIClient::new(api_key))),
+        "anthropic" => Some("ANTHROPIC_API_KEY"),
+        "openai" => Some("OPENAI_API_KEY"),
+        _ => None,
+    }
+}
+
+/// The key stored in settings wins; the provider's environment variable is
+/// the fallback so keys never have to be written to the database.
+pub fn resolve_api_key(provider: &str, stored: &str) -> (String, KeySource) {
+    let stored = stored.trim();
+    if !stored.is_empty() {
+        return (stored.to_string(), KeySource::Settings);
+    }
+
+    if let Some(var) = key_env_var(provider) {
+        if let Ok(value) = std::env::var(var) {
+            let value = value.trim();
+            if !value.is_empty() {
+                return (value.to_string(), KeySource::Env(var));
+            }
+        }
+    }
+
+    (String::new(), KeySource::Missing)
+}
+
+/// Helper to create a client based on provider
+pub fn get_client(config: &LlmConfig) -> Option<Box<dyn LlmClient>> {
+    let (api_key, _) = resolve_api_key(&config.provider, &config.api_key);
+    let api_key = api_key.as_str();
+    let model = config.model.trim();
+
+    match config.provider.to_lowercase().as_str() {
+        // Local models need no API key
+        "ollama" => {
+            let model = if model.is_empty() { "llama3.2" } else { model };
+            Some(Box::new(OllamaClient::new(
+                config.base_url.trim(),
+                model,
+                http_client(config.timeout_secs),
+            )))
+        }
+        "openai" => {
+            // A custom endpoint may not need a key; api.openai.com always does
+            let base_url = config.base_url.trim();
+            if api_key.is_empty() && base_url.is_empty() {
+                return None;
+            }
+            let model = if model.is_empty() { "gpt-4o" } else { model };
+            Some(Box::new(OpenAIClient::new(
+                api_key,
+                model,
+                base_url,
+                http_client(config.timeout_secs),
+            )))
+        }
+        _ if api_key.is_empty() => None,
         _ => {
             // Default to Anthropic
             let model = if model.is_empty() {
@@ -41,22 +339,161 @@ pub fn get_client(provider: &str, api_key: &str, model: &str) -> Option<Box<dyn
             } else {
                 model
             };
-            Some(Box::new(AnthropicClient::new(api_key, model)))
+            Some(Box::new(AnthropicClient::new(
+                api_key,
+                model,
+                http_client(config.timeout_secs),
+            )))
         }
     }
 }
 
+/// Send the smallest useful request, without retries, to check the
+/// provider settings. Returns the reply and how long it took.
+pub fn test_connection(config: &LlmConfig) -> Result<(LlmResponse, Duration)> {
+    let config = LlmConfig {
+        max_retries: 0,
+        ..config.clone()
+    };
+    let request = LlmRequest {
+        system_prompt: "Reply with the single word OK.".to_string(),
+        history: Vec::new(),
+        user_message: "Connection test".to_string(),
+        max_tokens: 5,
+    };
+
+    let started = Instant::now();
+    let response = complete_sync(&config, request)?;
+    Ok((response, started.elapsed()))
+}
+
 /// Synchronous LLM completion using blocking tokio runtime
-pub fn complete_sync(
-    provider: &str,
-    api_key: &str,
-    model: &str,
+pub fn complete_sync(config: &LlmConfig, request: LlmRequest) -> Result<LlmResponse> {
+    let client = get_client(config).ok_or_else(no_client_error)?;
+
+    let rt = tokio::runtime::Runtime::new()?;
+    rt.block_on(complete_with_retries(client.as_ref(), config, request))
+}
+
+/// Handle to a completion running on a background thread
+pub struct BackgroundCompletion {
+    cancel: oneshot::Sender<()>,
+    thread: JoinHandle<()>,
+}
+
+impl BackgroundCompletion {
+    /// Run the completion on a new thread; the result (errors as text) is
+    /// sent to `tx` unless it is cancelled first
+    pub fn spawn(
+        config: LlmConfig,
+        request: LlmRequest,
+        tx: mpsc::Sender<Result<LlmResponse, CompletionError>>,
+    ) -> Self {
+        let (cancel, cancelled) = oneshot::channel();
+        let thread = std::thread::spawn(move || {
+            let result = complete_cancellable(&config, request, cancelled);
+            if let Some(result) = result {
+                let _ = tx.send(result.map_err(|e| CompletionError::from_report(&e)));
+            }
+        });
+        Self { cancel, thread }
+    }
+
+    /// Abort the request in flight and wait for the thread to finish
+    pub fn cancel(self) {
+        let _ = self.cancel.send(());
+        let _ = self.thread.join();
+    }
+}
+
+/// Like `complete_sync`, but gives up when `cancelled` fires, dropping the
+/// request in flight. Returns None when cancelled.
+fn complete_cancellable(
+    config: &LlmConfig,
+    request: LlmRequest,
+    cancelled: oneshot::Receiver<()>,
+) -> Option<Result<LlmResponse>> {
+    let Some(client) = get_client(config) else {
+        return Some(Err(no_client_error()));
+    };
+    let rt = match tokio::runtime::Runtime::new() {
+        Ok(rt) => rt,
+        Err(e) => return Some(Err(e.into())),
+    };
+
+    rt.block_on(async {
+        tokio::select! {
+            result = complete_with_retries(client.as_ref(), config, request) => Some(result),
+            _ = cancelled => None,
+        }
+    })
+}
+
+async fn complete_with_retries(
+    client: &dyn LlmClient,
+    config: &LlmConfig,
     request: LlmRequest,
 ) -> Result<LlmResponse> {
-    let client = get_client(provider, api_key, model).ok_or_else(|| {
-        color_eyre::eyre::eyre!("No LLM API key configured. Go to Settings (s) to add one.")
-    })?;
+    // Sizes only; prompts and replies stay out of the log
+    debug!(
+        provider = %config.provider,
+        model = %config.model,
+        max_tokens = request.max_tokens,
+        history_turns = request.history.len(),
+        prompt_chars = request.system_prompt.len() + request.user_message.len(),
+        "sending AI request"
+    );
+    let started = Instant::now();
+    let mut attempt = 0;
+    loop {
+        match client.complete(request.clone()).await {
+            Err(e)
+                if attempt < config.max_retries
+                    && is_transient(&e)
+                    && api_error(&e)
+                        .and_then(|api| api.retry_after)
+                        .is_none_or(|wait| wait <= MAX_RETRY_WAIT) =>
+            {
+                // Wait at least as long as the provider asked
+                let wait = api_error(&e)
+                    .and_then(|api| api.retry_after)
+                    .map_or(backoff(attempt), |wait| wait.max(backoff(attempt)));
+                warn!(
+                    attempt,
+                    wait_ms = wait.as_millis() as u64,
+                    error = %e,
+                    "AI request failed, retrying"
+                );
+                tokio::time::sleep(wait).await;
+                attempt += 1;
+            }
+            Ok(response) => {
+                let usage = response.usage.unwrap_or_default();
+                info!(
+                    provider = %config.provider,
+                    model = %config.model,
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    input_tokens = usage.input_tokens,
+                    output_tokens = usage.output_tokens,
+                    reply_chars = response.content.len(),
+                    "AI request done"
+                );
+                return Ok(response);
+            }
+            Err(e) => {
+                warn!(
+                    provider = %config.provider,
+                    model = %config.model,
+                    elapsed_ms = started.elapsed().as_millis() as u64,
+                    error = %e,
+                    "AI request failed"
+                );
+                return Err(e);
+            }
+        }
+    }
+}
 
-    let rt = tokio::runtime::Runtime::new()?;
-    rt.block_on(client.complete(request))
+fn no_client_error() -> Report {
+    eyre!("No LLM API key configured. Go to Settings (s) to add one or set the provider's API key environment variable.")
 }
@@ -0,0 +1,118 @@
+use super::{ApiError, LlmClient, LlmRequest, LlmResponse, Usage};
+use color_eyre::eyre::{Result, WrapErr};
+use serde::{Deserialize, Serialize};
+
+pub const DEFAULT_BASE_URL: &str = "http://localhost:11434";
+
+/// Local models served by Ollama's chat API
+pub struct OllamaClient {
+    base_url: String,
+    model: String,
+    client: reqwest::Client,
+}
+
+impl OllamaClient {
+    pub fn new(base_url: &str, model: &str, client: reqwest::Client) -> Self {
+        let base_url = if base_url.is_empty() {
+            DEFAULT_BASE_URL
+        } else {
+            base_url
+        };
+
+        Self {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            model: model.to_string(),
+            client,
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct OllamaRequest {
+    model: String,
+    messages: Vec<Message>,
+    stream: bool,
+    options: Options,
+}
+
+#[derive(Serialize)]
+struct Options {
+    num_predict: u32,
+}
+
+#[derive(Serialize, Deserialize)]
+struct Message {
+    role: String,
+    content: String,
+}
+
+#[derive(Deserialize)]
+struct OllamaResponse {
+    message: Message,
+    prompt_eval_count: Option<u64>,
+    eval_count: Option<u64>,
+}
+
+#[async_trait::async_trait]
+impl LlmClient for OllamaClient {
+    async fn complete(&self, request: LlmRequest) -> Result<LlmResponse> {
+        let mut messages = vec![Message {
+            role: "system".to_string(),
+            content: request.system_prompt,
+        }];
+
+        messages.extend(request.history.iter().flat_map(|turn| turn.messages()).map(
+            |(role, content)| Message {
+                role: role.to_string(),
+                content,
+            },
+        ));
+
+        messages.push(Message {
+            role: "user".to_string(),
+            content: request.user_message,
+        });
+
+        let body = OllamaRequest {
+            model: self.model.clone(),
+            messages,
+            stream: false,
+            options: Options {
+                num_predict: request.max_tokens,
+            },
+        };
+
+        let response = self
+            .client
+            .post(format!("{}/api/chat", self.base_url))
+            .header("content-type", "application/json")
+            .json(&body)
+            .send()
+            .await
+            .wrap_err_with(|| format!("Could not reach Ollama at {}", self.base_url))?;
+
+        if !response.status().is_success() {
+            let detail = format!("model: {}", self.model);
+            return Err(ApiError::read("Ollama", detail, response).await.into());
+        }
+
+        let api_response: OllamaResponse = response.json().await?;
+
+        let usage = match (api_response.prompt_eval_count, api_response.eval_count) {
+            (None, None) => None,
+            (input, output) => Some(Usage {
+                input_tokens: input.unwrap_or(0),
+                output_tokens: output.unwrap_or(0),
+            }),
+        };
+
+        Ok(LlmResponse {
+            content: api_response.message.content,
+            usage,
+        })
+    }
+
+    fn is_configured(&self) -> bool {
+        !self.model.is_empty()
+    }
+}
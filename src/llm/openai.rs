@@ -1,28 +1,31 @@
-use super::{LlmClient, LlmRequest, LlmResponse};
-use color_eyre::eyre::{eyre, Result};
+use super::{ApiError, LlmClient, LlmRequest, LlmResponse, Usage};
+use color_eyre::eyre::Result;
 use serde::{Deserialize, Serialize};
 
+pub const DEFAULT_BASE_URL: &str = "https://api.openai.com/v1";
+
 pub struct OpenAIClient {
     api_key: String,
     model: String,
+    base_url: String,
     client: reqwest::Client,
 }
 
 impl OpenAIClient {
-    pub fn new(api_key: &str) -> Self {
-        Self {
-            api_key: api_key.to_string(),
-            model: "gpt-4o".to_string(),
-            client: reqwest::Client::new(),
-        }
-    }
+    /// `base_url` points at any OpenAI-compatible API (OpenRouter, vLLM,
+    /// LM Studio); empty uses api.openai.com
+    pub fn new(api_key: &str, model: &str, base_url: &str, client: reqwest::Client) -> Self {
+        let base_url = if base_url.is_empty() {
+            DEFAULT_BASE_URL
+        } else {
+            base_url
+        };
 
-    #[allow(dead_code)]
-    pub fn with_model(api_key: &str, model: &str) -> Self {
         Self {
             api_key: api_key.to_string(),
             model: model.to_string(),
-            client: reqwest::Client::new(),
+            base_url: base_url.trim_end_matches('/').to_string(),
+            client,
         }
     }
 }
@@ -43,6 +46,13 @@ struct Message {
 #[derive(Deserialize)]
 struct OpenAIResponse {
     choices: Vec<Choice>,
+    usage: Option<OpenAIUsage>,
+}
+
+#[derive(Deserialize)]
+struct OpenAIUsage {
+    prompt_tokens: u64,
+    completion_tokens: u64,
 }
 
 #[derive(Deserialize)]
@@ -63,6 +73,13 @@ impl LlmClient for OpenAIClient {
             content: request.system_prompt,
         }];
 
+        messages.extend(request.history.iter().flat_map(|turn| turn.messages()).map(
+            |(role, content)| Message {
+                role: role.to_string(),
+                content,
+            },
+        ));
+
         messages.push(Message {
             role: "user".to_string(),
             content: request.user_message,
@@ -74,19 +91,21 @@ impl LlmClient for OpenAIClient {
             messages,
         };
 
-        let response = self
+        let mut http_request = self
             .client
-            .post("https://api.openai.com/v1/chat/completions")
-            .header("Authorization", format!("Bearer {}", self.api_key))
-            .header("content-type", "application/json")
-            .json(&body)
-            .send()
-            .await?;
+            .post(format!("{}/chat/completions", self.base_url))
+            .header("content-type", "application/json");
+
+        // Local OpenAI-compatible servers often run without a key
+        if !self.api_key.is_empty() {
+            http_request = http_request.header("Authorization", format!("Bearer {}", self.api_key));
+        }
+
+        let response = http_request.json(&body).send().await?;
 
         if !response.status().is_success() {
-            let status = response.status();
-            let error_text = response.text().await.unwrap_or_default();
-            return Err(eyre!("OpenAI API error {}: {}", status, error_text));
+            let detail = format!("model: {}, {}", self.model, self.base_url);
+            return Err(ApiError::read("OpenAI", detail, response).await.into());
         }
 
         let api_response: OpenAIResponse = response.json().await?;
@@ -97,10 +116,15 @@ impl LlmClient for OpenAIClient {
             .and_then(|choice| choice.message.content.clone())
             .unwrap_or_default();
 
-        Ok(LlmResponse { content })
+        let usage = api_response.usage.map(|u| Usage {
+            input_tokens: u.prompt_tokens,
+            output_tokens: u.completion_tokens,
+        });
+
+        Ok(LlmResponse { content, usage })
     }
 
     fn is_configured(&self) -> bool {
-        !self.api_key.is_empty()
+        !self.api_key.is_empty() || self.base_url != DEFAULT_BASE_URL
     }
 }
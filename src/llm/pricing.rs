@@ -0,0 +1,55 @@
+use super::Usage;
+
+/// USD per million input and output tokens, matched by model name prefix.
+/// More specific prefixes come first.
+const PRICES: &[(&str, f64, f64)] = &[
+    ("claude-opus-4", 15.0, 75.0),
+    ("claude-sonnet-4", 3.0, 15.0),
+    ("claude-3-7-sonnet", 3.0, 15.0),
+    ("claude-3-5-sonnet", 3.0, 15.0),
+    ("claude-3-5-haiku", 0.8, 4.0),
+    ("claude-3-haiku", 0.25, 1.25),
+    ("gpt-4o-mini", 0.15, 0.6),
+    ("gpt-4o", 2.5, 10.0),
+    ("gpt-4.1-nano", 0.1, 0.4),
+    ("gpt-4.1-mini", 0.4, 1.6),
+    ("gpt-4.1", 2.0, 8.0),
+    ("o4-mini", 1.1, 4.4),
+    ("o3-mini", 1.1, 4.4),
+];
+
+/// Estimated cost of a call in USD. Local models are free; unknown models
+/// (custom endpoints, new releases) have no estimate.
+pub fn estimate_cost(provider: &str, model: &str, usage: Usage) -> Option<f64> {
+    if provider.eq_ignore_ascii_case("ollama") {
+        return Some(0.0);
+    }
+
+    PRICES
+        .iter()
+        .find(|(prefix, _, _)| model.starts_with(prefix))
+        .map(|(_, input, output)| {
+            (usage.input_tokens as f64 * input + usage.output_tokens as f64 * output) / 1_000_000.0
+        })
+}
+
+/// Rough token count of `text`, close to what BPE tokenizers like tiktoken
+/// produce for English and code: about four letters or digits per token,
+/// one per punctuation mark, and one per non-ASCII letter (CJK and the like
+/// rarely merge). Whitespace joins the following word.
+pub fn estimate_tokens(text: &str) -> usize {
+    let mut tokens = 0;
+    let mut run: usize = 0;
+    for c in text.chars() {
+        if c.is_ascii_alphanumeric() {
+            run += 1;
+            continue;
+        }
+        tokens += run.div_ceil(4);
+        run = 0;
+        if !c.is_whitespace() {
+            tokens += 1;
+        }
+    }
+    tokens + run.div_ceil(4)
+}
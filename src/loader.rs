@@ -0,0 +1,183 @@
+use crate::app::{SpecialList, RECENT_COPIES_LIMIT};
+use crate::db::{
+    CollectionStore, Database, ExportStore, Filter, ItemStore, SearchHit, SortOrder, SyncStatus,
+};
+use crate::models::{Category, Item};
+use color_eyre::eyre::Result;
+use rusqlite::Connection;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc::{self, Receiver, Sender};
+
+/// Which items the main list shows
+#[derive(Debug, Clone)]
+pub struct ListQuery {
+    pub special_list: Option<SpecialList>,
+    pub category: Option<Category>,
+    pub tags: Vec<String>,
+    pub tag_match_all: bool,
+    pub collection: Option<String>,
+    pub sort_order: SortOrder,
+    /// Rows of Recent to load, a whole number of pages
+    pub recent_limit: usize,
+    pub stale_months: u32,
+}
+
+/// The main list with the counts and sets the sidebar and columns show,
+/// read together so they agree with each other
+#[derive(Debug)]
+pub struct Snapshot {
+    pub items: Vec<Item>,
+    pub has_more_items: bool,
+    pub category_counts: Vec<(Category, usize)>,
+    pub trash_count: usize,
+    pub stale_count: usize,
+    pub copies_count: usize,
+    pub favorites: HashSet<i64>,
+    pub archived: HashSet<i64>,
+    pub use_counts: HashMap<i64, i64>,
+    pub tags: Vec<(String, usize)>,
+    pub collections: Vec<(String, usize)>,
+    pub sync_status: HashMap<i64, SyncStatus>,
+}
+
+impl Snapshot {
+    pub fn load(conn: &Connection, query: &ListQuery) -> Result<Self> {
+        let store = ItemStore::new(conn);
+        let sort = query.sort_order;
+
+        let mut has_more_items = false;
+        let items = match (query.special_list, &query.category, query.tags.as_slice()) {
+            (None, None, []) if query.collection.is_some() => {
+                let path = query.collection.as_deref().unwrap_or_default();
+                store.list_by_collection(path, sort)?
+            }
+            (Some(SpecialList::Favorites), _, _) => store.list_favorites(sort)?,
+            (Some(SpecialList::Copies), _, _) => store.list_recently_copied(RECENT_COPIES_LIMIT)?,
+            (Some(SpecialList::Archived), _, _) => store.list_archived(sort)?,
+            (Some(SpecialList::Stale), _, _) => store.list_stale(query.stale_months)?,
+            (Some(SpecialList::Trash), _, _) => store.list_trash()?,
+            (None, Some(cat), _) => store.list_by_category(*cat, sort)?,
+            (None, None, []) => {
+                let items = store.list_recent(sort, None, query.recent_limit)?;
+                has_more_items = items.len() == query.recent_limit;
+                items
+            }
+            (None, None, tags) => store.list_by_tags(tags, query.tag_match_all, sort)?,
+        };
+
+        Ok(Self {
+            has_more_items,
+            category_counts: store.count_by_category()?,
+            trash_count: store.count_trash()?,
+            stale_count: store.count_stale(query.stale_months)?,
+            copies_count: store.count_copied()?.min(RECENT_COPIES_LIMIT),
+            favorites: store.favorite_ids()?,
+            archived: store.archived_ids()?,
+            use_counts: store.use_counts()?,
+            tags: store.get_tags_with_counts()?,
+            collections: CollectionStore::new(conn).list_with_counts()?,
+            sync_status: ExportStore::new(conn).statuses(&items)?,
+            items,
+        })
+    }
+}
+
+enum Request {
+    Refresh(u64, ListQuery),
+    Search(u64, String, Vec<Filter>, bool),
+}
+
+/// A result from the loader, tagged with the number of the request it
+/// answers. Errors come back as text.
+pub enum Loaded {
+    Snapshot(u64, Result<Box<Snapshot>, String>),
+    Search(u64, Result<Vec<SearchHit>, String>),
+}
+
+/// Reads lists and search results on a thread of its own, with its own
+/// connection, so a large library doesn't hold up drawing and typing.
+/// Requests are numbered; when several are waiting only the newest of each
+/// kind is run.
+pub struct DataLoader {
+    requests: Sender<Request>,
+    results: Receiver<Loaded>,
+    last_request: u64,
+}
+
+impl DataLoader {
+    pub fn spawn(vault: &str) -> Result<Self> {
+        let db = Database::open(vault)?;
+        let (requests, incoming) = mpsc::channel();
+        let (outgoing, results) = mpsc::channel();
+        std::thread::spawn(move || serve(db, incoming, outgoing));
+
+        Ok(Self {
+            requests,
+            results,
+            last_request: 0,
+        })
+    }
+
+    /// Ask for a snapshot of `query`, returning the request's number
+    pub fn refresh(&mut self, query: ListQuery) -> u64 {
+        let id = self.next_id();
+        let _ = self.requests.send(Request::Refresh(id, query));
+        id
+    }
+
+    /// Ask for the matches of `query` within `scope`, returning the
+    /// request's number
+    pub fn search(&mut self, query: &str, scope: Vec<Filter>, include_archived: bool) -> u64 {
+        let id = self.next_id();
+        let _ = self.requests.send(Request::Search(
+            id,
+            query.to_string(),
+            scope,
+            include_archived,
+        ));
+        id
+    }
+
+    /// Number for a new request, higher than every earlier one
+    fn next_id(&mut self) -> u64 {
+        self.last_request += 1;
+        self.last_request
+    }
+
+    /// Results that have arrived, oldest first
+    pub fn try_recv(&self) -> Option<Loaded> {
+        self.results.try_recv().ok()
+    }
+}
+
+fn serve(db: Database, incoming: Receiver<Request>, outgoing: Sender<Loaded>) {
+    while let Ok(first) = incoming.recv() {
+        let (mut refresh, mut search) = (None, None);
+        for request in std::iter::once(first).chain(incoming.try_iter()) {
+            match request {
+                Request::Refresh(..) => refresh = Some(request),
+                Request::Search(..) => search = Some(request),
+            }
+        }
+
+        for request in [refresh, search].into_iter().flatten() {
+            let loaded = match request {
+                Request::Refresh(id, query) => Loaded::Snapshot(
+                    id,
+                    Snapshot::load(&db.conn, &query)
+                        .map(Box::new)
+                        .map_err(|e| e.to_string()),
+                ),
+                Request::Search(id, query, scope, include_archived) => Loaded::Search(
+                    id,
+                    ItemStore::new(&db.conn)
+                        .search(&query, &scope, include_archived)
+                        .map_err(|e| e.to_string()),
+                ),
+            };
+            if outgoing.send(loaded).is_err() {
+                return;
+            }
+        }
+    }
+}
@@ -0,0 +1,51 @@
+use crate::db::Database;
+use color_eyre::eyre::Result;
+use std::path::PathBuf;
+use tracing::level_filters::LevelFilter;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_subscriber::filter::Targets;
+use tracing_subscriber::prelude::*;
+
+/// Daily log files kept before the oldest is removed
+const LOG_FILES_KEPT: usize = 7;
+
+/// Directory of the log files, `logs/` in the data directory
+fn log_dir() -> Result<PathBuf> {
+    Ok(Database::data_dir()?.join("logs"))
+}
+
+/// Record tracing events in a daily `grimoire.<date>.log`. Grimoire's own
+/// events are kept from info up, or from debug up with `debug`; libraries
+/// only get to log warnings. The returned guard flushes the file when it
+/// is dropped, so it has to live until the program exits.
+pub fn init(debug: bool) -> Result<WorkerGuard> {
+    let dir = log_dir()?;
+    std::fs::create_dir_all(&dir)?;
+    let appender = RollingFileAppender::builder()
+        .rotation(Rotation::DAILY)
+        .filename_prefix("grimoire")
+        .filename_suffix("log")
+        .max_log_files(LOG_FILES_KEPT)
+        .build(dir)?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let level = if debug {
+        LevelFilter::DEBUG
+    } else {
+        LevelFilter::INFO
+    };
+    let filter = Targets::new()
+        .with_target("grimoire", level)
+        .with_default(LevelFilter::WARN);
+    tracing_subscriber::registry()
+        .with(
+            tracing_subscriber::fmt::layer()
+                .with_writer(writer)
+                .with_ansi(false),
+        )
+        .with(filter)
+        .try_init()?;
+
+    Ok(guard)
+}
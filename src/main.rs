@@ -1,28 +1,118 @@
 mod app;
+mod cli;
 mod db;
 mod export;
+mod import;
 mod llm;
+mod loader;
+mod logging;
 mod models;
+mod server;
 mod ui;
 
-use app::App;
-use color_eyre::eyre::Result;
+use app::{App, PickOutput};
+use clap::{CommandFactory, Parser};
+use clap_complete::CompleteEnv;
+use cli::Cli;
+use color_eyre::eyre::{bail, Result};
 use crossterm::event::{DisableBracketedPaste, EnableBracketedPaste};
 use crossterm::execute;
-use std::io::stdout;
+use crossterm::terminal::{
+    disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen,
+};
+use db::{Vaults, DEFAULT_VAULT};
+use ratatui::prelude::CrosstermBackend;
+use ratatui::Terminal;
+use std::io::{stderr, stdout};
+use std::process::ExitCode;
 
-fn main() -> Result<()> {
+fn main() -> Result<ExitCode> {
     color_eyre::install()?;
+    // Answers tab completion requests from the script `grimoire completions` prints
+    CompleteEnv::with_factory(Cli::command).complete();
+
+    let cli = Cli::parse();
+    // The log only helps with debugging; grimoire works the same without it
+    let _log_guard = logging::init(cli.debug).ok();
+    tracing::debug!(version = env!("CARGO_PKG_VERSION"), "starting");
+
+    let result = run(cli);
+    if let Err(ref e) = result {
+        tracing::error!("{:#}", e);
+    }
+    result
+}
+
+fn run(cli: Cli) -> Result<ExitCode> {
+    // A mistyped name would otherwise open a new, empty vault
+    if let Some(ref name) = cli.vault {
+        Vaults::validate(name)?;
+        if name != DEFAULT_VAULT && !cli.create_vault && !Vaults::exists(name) {
+            bail!(
+                "No vault named '{}' (there are: {}); add --create-vault to create it",
+                name,
+                Vaults::list()?.join(", ")
+            );
+        }
+    }
+    let vault = cli
+        .vault
+        .or_else(Vaults::last_used)
+        .unwrap_or_else(|| DEFAULT_VAULT.to_string());
+    Vaults::validate(&vault)?;
+    if let Some(command) = cli.command {
+        return cli::run(command, &vault).map(|()| ExitCode::SUCCESS);
+    }
+    if cli.pick {
+        let output = if cli.print_name {
+            PickOutput::Name
+        } else {
+            PickOutput::Content
+        };
+        return pick(&vault, output);
+    }
 
     // Enable bracketed paste mode so pasted text comes as a single event
     execute!(stdout(), EnableBracketedPaste)?;
 
     let terminal = ratatui::init();
-    let app_result = App::new()?.run(terminal);
+    let app_result = App::new(&vault)?.run(terminal);
     ratatui::restore();
 
     // Disable bracketed paste mode
     let _ = execute!(stdout(), DisableBracketedPaste);
 
-    app_result
+    app_result.map(|_| ExitCode::SUCCESS)
+}
+
+/// Run the TUI as a picker. It draws on stderr so that stdout carries only
+/// the chosen item, e.g. `prompt=$(grimoire --pick)`. Quitting without
+/// picking exits with status 1.
+fn pick(vault: &str, output: PickOutput) -> Result<ExitCode> {
+    let mut app = App::new(vault)?;
+    app.pick = Some(output);
+
+    let hook = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        let _ = execute!(stderr(), LeaveAlternateScreen);
+        let _ = disable_raw_mode();
+        hook(info);
+    }));
+    enable_raw_mode()?;
+    execute!(stderr(), EnterAlternateScreen, EnableBracketedPaste)?;
+
+    let picked = Terminal::new(CrosstermBackend::new(stderr()))
+        .map_err(Into::into)
+        .and_then(|terminal| app.run(terminal));
+
+    let _ = execute!(stderr(), DisableBracketedPaste, LeaveAlternateScreen);
+    let _ = disable_raw_mode();
+
+    match picked? {
+        Some(text) => {
+            println!("{}", text.trim_end_matches('\n'));
+            Ok(ExitCode::SUCCESS)
+        }
+        None => Ok(ExitCode::FAILURE),
+    }
 }
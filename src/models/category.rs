@@ -1,6 +1,7 @@
 use serde::{Deserialize, Serialize};
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Category {
     Prompt,
     Agent,
@@ -46,8 +47,52 @@ impl Category {
         ]
     }
 
+    /// Content a new item of this category starts with, unless a custom
+    /// skeleton is set in settings
+    pub fn default_skeleton(&self) -> &'static str {
+        match self {
+            Category::Prompt => "",
+            Category::Agent => {
+                "You are a ... Describe the agent's role and expertise.
+
+## Responsibilities
+
+-
+
+## Approach
+
+1.
+
+## Output
+
+Describe what the agent returns when it is done.
+"
+            }
+            Category::Skill => {
+                "## When to use
+
+Describe the tasks this skill helps with.
+
+## Instructions
+
+1.
+
+## Examples
+
+"
+            }
+            Category::Command => {
+                "Describe what to do with: $ARGUMENTS
+
+## Steps
+
+1.
+"
+            }
+        }
+    }
+
     /// Returns the required fields for this category
-    #[allow(dead_code)]
     pub fn required_fields(&self) -> &'static [&'static str] {
         match self {
             Category::Prompt => &["name", "content"],
@@ -57,19 +102,19 @@ impl Category {
         }
     }
 
-    /// Returns the optional fields for this category
-    #[allow(dead_code)]
+    /// Returns the optional fields for this category, in the order the edit
+    /// form shows them
     pub fn optional_fields(&self) -> &'static [&'static str] {
         match self {
-            Category::Prompt => &["description", "tags"],
-            Category::Agent => &["model", "tools", "permission_mode", "skills", "tags"],
-            Category::Skill => &["allowed_tools", "tags"],
+            Category::Prompt => &["tags", "description"],
+            Category::Agent => &["tags", "model", "tools", "permission_mode", "skills"],
+            Category::Skill => &["tags", "allowed_tools"],
             Category::Command => &[
-                "description",
+                "tags",
+                "model",
                 "allowed_tools",
                 "argument_hint",
-                "model",
-                "tags",
+                "description",
             ],
         }
     }
@@ -2,6 +2,7 @@ use super::Category;
 use chrono::{DateTime, NaiveDateTime, Utc};
 use rusqlite::Row;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 
 /// Parse SQLite datetime format (YYYY-MM-DD HH:MM:SS) to DateTime<Utc>
 fn parse_sqlite_datetime(s: &str) -> Option<DateTime<Utc>> {
@@ -11,7 +12,9 @@ fn parse_sqlite_datetime(s: &str) -> Option<DateTime<Utc>> {
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Item {
+    #[serde(skip)]
     pub id: Option<i64>,
     pub name: String,
     pub category: Category,
@@ -80,6 +83,20 @@ impl Item {
         })
     }
 
+    /// Why `name` can't name an item, if it can't. Names become file and
+    /// directory names on export, so they can't be or contain a path.
+    pub fn name_error(name: &str) -> Option<&'static str> {
+        if name.contains(['/', '\\', '\0']) {
+            Some("Name can't contain /, \\ or NUL")
+        } else if name.contains("..") || name.trim() == "." {
+            Some("Name can't be . or contain ..")
+        } else if std::path::Path::new(name).is_absolute() {
+            Some("Name can't be a path")
+        } else {
+            None
+        }
+    }
+
     /// Validate the item based on its category requirements
     pub fn validate(&self) -> Result<(), Vec<String>> {
         let mut errors = Vec::new();
@@ -87,6 +104,8 @@ impl Item {
         // Check required fields based on category
         if self.name.trim().is_empty() {
             errors.push("Name is required".to_string());
+        } else if let Some(error) = Self::name_error(&self.name) {
+            errors.push(error.to_string());
         }
 
         if self.content.trim().is_empty() {
@@ -95,15 +114,14 @@ impl Item {
 
         // Category-specific validation
         match self.category {
-            Category::Agent | Category::Skill => {
+            Category::Agent | Category::Skill
                 if self
                     .description
                     .as_ref()
                     .map(|s| s.trim().is_empty())
-                    .unwrap_or(true)
-                {
-                    errors.push("Description is required for this category".to_string());
-                }
+                    .unwrap_or(true) =>
+            {
+                errors.push("Description is required for this category".to_string());
             }
             _ => {}
         }
@@ -115,18 +133,45 @@ impl Item {
         }
     }
 
-    /// Get tags as a vector
-    #[allow(dead_code)]
+    /// Get tags as a vector: lowercased, without `#`, and without duplicates
     pub fn tags_vec(&self) -> Vec<String> {
-        self.tags
-            .as_ref()
-            .map(|t| {
-                t.split(',')
-                    .map(|s| s.trim().to_string())
-                    .filter(|s| !s.is_empty())
-                    .collect()
-            })
-            .unwrap_or_default()
+        let mut tags: Vec<String> = Vec::new();
+        for tag in self.tags.as_deref().unwrap_or_default().split(',') {
+            let tag = tag.trim().trim_start_matches('#').trim().to_lowercase();
+            if !tag.is_empty() && !tags.contains(&tag) {
+                tags.push(tag);
+            }
+        }
+        tags
+    }
+
+    /// SHA-256 of the fields that end up in exported files, used to detect
+    /// items modified since their last export
+    pub fn content_hash(&self) -> String {
+        let fields = [
+            Some(self.name.as_str()),
+            Some(self.category.as_str()),
+            self.description.as_deref(),
+            Some(self.content.as_str()),
+            self.model.as_deref(),
+            self.tools.as_deref(),
+            self.allowed_tools.as_deref(),
+            self.argument_hint.as_deref(),
+            self.permission_mode.as_deref(),
+            self.skills.as_deref(),
+        ];
+
+        let mut hasher = Sha256::new();
+        for field in fields {
+            hasher.update(field.unwrap_or_default().as_bytes());
+            hasher.update([0]);
+        }
+
+        hasher
+            .finalize()
+            .iter()
+            .map(|b| format!("{:02x}", b))
+            .collect()
     }
 
     /// Format the updated_at time as a relative string
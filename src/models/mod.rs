@@ -1,5 +1,9 @@
 mod category;
 mod item;
+mod template;
 
 pub use category::Category;
 pub use item::Item;
+pub use template::{
+    fill_template, format_variable_presets, parse_variable_presets, template_variables,
+};
@@ -0,0 +1,100 @@
+use std::collections::HashMap;
+
+/// Name inside a `{{ name }}` placeholder, if it is one. Names are letters,
+/// digits, `_`, `-`, and `.`, so other uses of double braces are left alone.
+fn placeholder_name(inner: &str) -> Option<&str> {
+    let name = inner.trim();
+    let valid = !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_alphanumeric() || matches!(c, '_' | '-' | '.'));
+    valid.then_some(name)
+}
+
+/// Each placeholder in `content` as `(start, end, name)`, with byte offsets
+/// covering the braces
+fn placeholders(content: &str) -> Vec<(usize, usize, &str)> {
+    let mut found = Vec::new();
+    let mut from = 0;
+    while let Some(open) = content[from..].find("{{").map(|i| from + i) {
+        let Some(close) = content[open + 2..].find("}}").map(|i| open + 2 + i) else {
+            break;
+        };
+        match placeholder_name(&content[open + 2..close]) {
+            Some(name) => {
+                found.push((open, close + 2, name));
+                from = close + 2;
+            }
+            None => from = open + 2,
+        }
+    }
+    found
+}
+
+/// Variable names used in `content`, in order of first use
+pub fn template_variables(content: &str) -> Vec<String> {
+    let mut names: Vec<String> = Vec::new();
+    for (_, _, name) in placeholders(content) {
+        if !names.iter().any(|n| n == name) {
+            names.push(name.to_string());
+        }
+    }
+    names
+}
+
+/// Replace each placeholder that has a non-empty value; the rest stay as
+/// they are
+pub fn fill_template(content: &str, values: &HashMap<String, String>) -> String {
+    let mut filled = String::with_capacity(content.len());
+    let mut last = 0;
+    for (start, end, name) in placeholders(content) {
+        if let Some(value) = values.get(name).filter(|v| !v.is_empty()) {
+            filled.push_str(&content[last..start]);
+            filled.push_str(value);
+            last = end;
+        }
+    }
+    filled.push_str(&content[last..]);
+    filled
+}
+
+/// Parse saved variable values written as `name=value; name=value`, where
+/// `\;` and `\\` in a value stand for `;` and `\`. A name given twice keeps
+/// its last value. Returns an error naming the first entry that isn't a
+/// valid `name=value` pair.
+pub fn parse_variable_presets(spec: &str) -> Result<Vec<(String, String)>, String> {
+    let mut entries = vec![String::new()];
+    let mut chars = spec.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' if matches!(chars.peek(), Some(';' | '\\')) => {
+                entries.last_mut().unwrap().extend(chars.next());
+            }
+            ';' => entries.push(String::new()),
+            c => entries.last_mut().unwrap().push(c),
+        }
+    }
+
+    let mut presets: Vec<(String, String)> = Vec::new();
+    for entry in entries.iter().map(|e| e.trim()).filter(|e| !e.is_empty()) {
+        let (name, value) = entry
+            .split_once('=')
+            .ok_or_else(|| format!("'{}' is not name=value", entry))?;
+        let name = placeholder_name(name).ok_or_else(|| format!("bad name '{}'", name.trim()))?;
+        presets.retain(|(n, _)| n != name);
+        presets.push((name.to_string(), value.trim().to_string()));
+    }
+    Ok(presets)
+}
+
+/// Write presets back in the form `parse_variable_presets` reads
+pub fn format_variable_presets(presets: &[(String, String)]) -> String {
+    presets
+        .iter()
+        .map(|(name, value)| {
+            let value = value.replace('\\', "\\\\").replace(';', "\\;");
+            format!("{}={}", name, value)
+        })
+        .collect::<Vec<_>>()
+        .join("; ")
+}
@@ -0,0 +1,290 @@
+use crate::db::{
+    normalize_collection_path, Database, ItemStore, SortOrder, SNIPPET_END, SNIPPET_START,
+};
+use crate::models::{Category, Item};
+use color_eyre::eyre::{eyre, Result};
+use serde::Serialize;
+use serde_json::json;
+use std::io::Read;
+use tiny_http::{Header, Method, Request, Response, Server};
+
+/// Largest request body accepted when creating an item
+const MAX_BODY_BYTES: u64 = 1024 * 1024;
+
+/// Small JSON API over the library for editor plugins and scripts, served
+/// on localhost only. Reading is open to anything on the machine; creating
+/// items needs the token the server was started with. Requests naming
+/// another host are refused, so a web page can't reach the API through
+/// DNS rebinding.
+pub struct ApiServer {
+    db: Database,
+    token: Option<String>,
+    port: u16,
+}
+
+#[derive(Serialize)]
+struct ApiItem<'a> {
+    id: Option<i64>,
+    #[serde(flatten)]
+    item: &'a Item,
+}
+
+#[derive(Serialize)]
+struct ApiHit<'a> {
+    #[serde(flatten)]
+    item: ApiItem<'a>,
+    snippet: String,
+}
+
+/// Response status and JSON body
+type Reply = (u16, serde_json::Value);
+
+impl ApiServer {
+    pub fn new(db: Database, token: Option<String>, port: u16) -> Self {
+        let token = token
+            .map(|t| t.trim().to_string())
+            .filter(|t| !t.is_empty());
+        Self { db, token, port }
+    }
+
+    pub fn accepts_writes(&self) -> bool {
+        self.token.is_some()
+    }
+
+    /// Answer requests on `127.0.0.1:port` until the process is stopped
+    pub fn run(&self) -> Result<()> {
+        let port = self.port;
+        let server = Server::http(("127.0.0.1", port))
+            .map_err(|e| eyre!("could not listen on port {}: {}", port, e))?;
+
+        for mut request in server.incoming_requests() {
+            let (status, body) = match self.handle(&mut request) {
+                Ok(reply) => reply,
+                Err(e) => (500, json!({ "error": e.to_string() })),
+            };
+            let response = Response::from_string(body.to_string())
+                .with_status_code(status)
+                .with_header(
+                    Header::from_bytes("Content-Type", "application/json")
+                        .expect("static header is valid"),
+                );
+            // The client may have gone away; that's no reason to stop
+            let _ = request.respond(response);
+        }
+        Ok(())
+    }
+
+    fn handle(&self, request: &mut Request) -> Result<Reply> {
+        if !self.host_allowed(request) {
+            return Ok((403, json!({ "error": "unexpected Host header" })));
+        }
+
+        let url = request.url().to_string();
+        let (path, query) = url.split_once('?').unwrap_or((&url, ""));
+        let path = path.trim_end_matches('/');
+        let store = ItemStore::new(&self.db.conn);
+
+        match (request.method(), path) {
+            (Method::Get, "/items") => self.list(&store, query),
+            (Method::Get, "/search") => self.search(&store, query),
+            (Method::Post, "/items") => self.create(&store, request),
+            (Method::Get, _) if path.starts_with("/items/") => {
+                let name = decode(&path["/items/".len()..]);
+                let item = match store.get_by_name(&name)? {
+                    Some(item) if !store.is_trashed(item.id.unwrap_or_default())? => Some(item),
+                    _ => None,
+                };
+                Ok(match item {
+                    Some(item) => (
+                        200,
+                        json!(ApiItem {
+                            id: item.id,
+                            item: &item
+                        }),
+                    ),
+                    None => not_found(&format!("no item named '{}'", name)),
+                })
+            }
+            (_, "/items" | "/search") => Ok((405, json!({ "error": "method not allowed" }))),
+            _ => Ok(not_found("no such endpoint")),
+        }
+    }
+
+    /// Whether the request's `Host` is this server, `127.0.0.1:<port>` or
+    /// `localhost:<port>`
+    fn host_allowed(&self, request: &Request) -> bool {
+        let allowed = [
+            format!("127.0.0.1:{}", self.port),
+            format!("localhost:{}", self.port),
+        ];
+        request.headers().iter().any(|header| {
+            header.field.equiv("Host")
+                && allowed
+                    .iter()
+                    .any(|host| header.value.as_str().eq_ignore_ascii_case(host))
+        })
+    }
+
+    /// `GET /items`, optionally narrowed with `category`, `tag`, and
+    /// `collection` parameters
+    fn list(&self, store: &ItemStore, query: &str) -> Result<Reply> {
+        let params = parse_query(query);
+        let param = |key: &str| {
+            params
+                .iter()
+                .find(|(k, _)| k == key)
+                .map(|(_, v)| v.as_str())
+        };
+
+        let mut items = if let Some(collection) = param("collection") {
+            store.list_by_collection(&normalize_collection_path(collection), SortOrder::Name)?
+        } else if let Some(tag) = param("tag") {
+            store.list_by_tags(&[tag.to_string()], false, SortOrder::Name)?
+        } else {
+            store.list_all()?
+        };
+        if let Some(category) = param("category") {
+            let category = Category::from_str(category);
+            items.retain(|item| item.category == category);
+        }
+
+        let items: Vec<ApiItem> = items
+            .iter()
+            .map(|item| ApiItem { id: item.id, item })
+            .collect();
+        Ok((200, json!(items)))
+    }
+
+    /// `GET /search?q=...`, with the full-text query syntax of the TUI
+    fn search(&self, store: &ItemStore, query: &str) -> Result<Reply> {
+        let Some((_, q)) = parse_query(query).into_iter().find(|(k, _)| k == "q") else {
+            return Ok((400, json!({ "error": "missing q parameter" })));
+        };
+        let hits = match store.search(&q, &[], false) {
+            Ok(hits) => hits,
+            // Almost always a malformed query
+            Err(e) => return Ok((400, json!({ "error": e.to_string() }))),
+        };
+
+        let hits: Vec<ApiHit> = hits
+            .iter()
+            .map(|hit| ApiHit {
+                item: ApiItem {
+                    id: hit.item.id,
+                    item: &hit.item,
+                },
+                snippet: hit.snippet.replace([SNIPPET_START, SNIPPET_END], ""),
+            })
+            .collect();
+        Ok((200, json!(hits)))
+    }
+
+    /// `POST /items` with an item as JSON; needs `Authorization: Bearer <token>`
+    fn create(&self, store: &ItemStore, request: &mut Request) -> Result<Reply> {
+        let Some(ref token) = self.token else {
+            return Ok((
+                403,
+                json!({ "error": "the server is read-only; start it with a token to allow writes" }),
+            ));
+        };
+        let authorized = request.headers().iter().any(|header| {
+            header.field.equiv("Authorization")
+                && header
+                    .value
+                    .as_str()
+                    .strip_prefix("Bearer ")
+                    .is_some_and(|given| same_token(given, token))
+        });
+        if !authorized {
+            return Ok((401, json!({ "error": "missing or wrong bearer token" })));
+        }
+
+        let mut body = String::new();
+        request
+            .as_reader()
+            .take(MAX_BODY_BYTES + 1)
+            .read_to_string(&mut body)?;
+        if body.len() as u64 > MAX_BODY_BYTES {
+            return Ok((
+                413,
+                json!({ "error": format!("item is larger than {} bytes", MAX_BODY_BYTES) }),
+            ));
+        }
+        let mut item: Item = match serde_json::from_str(&body) {
+            Ok(item) => item,
+            Err(e) => return Ok((400, json!({ "error": format!("invalid item: {}", e) }))),
+        };
+        item.name = item.name.trim().to_string();
+        if let Err(errors) = item.validate() {
+            return Ok((400, json!({ "error": errors.join(", ") })));
+        }
+        if store.get_by_name(&item.name)?.is_some() {
+            return Ok((
+                409,
+                json!({ "error": format!("an item named '{}' already exists", item.name) }),
+            ));
+        }
+
+        let id = store.insert(&item)?;
+        let item = store
+            .get(id)?
+            .ok_or_else(|| eyre!("item {} vanished after insert", id))?;
+        Ok((
+            201,
+            json!(ApiItem {
+                id: item.id,
+                item: &item
+            }),
+        ))
+    }
+}
+
+/// Compare tokens in time that doesn't depend on where they differ
+fn same_token(given: &str, token: &str) -> bool {
+    given.len() == token.len()
+        && given
+            .bytes()
+            .zip(token.bytes())
+            .fold(0u8, |diff, (a, b)| diff | (a ^ b))
+            == 0
+}
+
+fn not_found(message: &str) -> Reply {
+    (404, json!({ "error": message }))
+}
+
+/// Decoded `key=value` pairs of a query string
+fn parse_query(query: &str) -> Vec<(String, String)> {
+    query
+        .split('&')
+        .filter(|pair| !pair.is_empty())
+        .map(|pair| {
+            let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+            (decode(key), decode(value))
+        })
+        .collect()
+}
+
+/// Undo URL percent-encoding, with `+` as a space
+fn decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let hex = bytes
+            .get(i + 1..i + 3)
+            .and_then(|hex| std::str::from_utf8(hex).ok())
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (bytes[i], hex) {
+            (b'%', Some(byte)) => {
+                decoded.push(byte);
+                i += 3;
+                continue;
+            }
+            (b'+', _) => decoded.push(b' '),
+            (byte, _) => decoded.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&decoded).to_string()
+}
@@ -1,3 +1,7 @@
+use crate::db::{ActionTarget, UserAction};
+use crate::import::parse_frontmatter;
+use crate::llm::{ChatTurn, CompletionError, Usage};
+use crate::models::Category;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -5,6 +9,14 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph, Wrap},
     Frame,
 };
+use similar::{ChangeTag, DiffOp, TextDiff};
+use std::time::{Duration, Instant};
+
+/// Wait before retrying a rate-limited call when the provider doesn't say
+const DEFAULT_RETRY_DELAY: Duration = Duration::from_secs(10);
+
+/// Longest wait offered before a retry, whatever the provider asks for
+const MAX_RETRY_DELAY: Duration = Duration::from_secs(60 * 60);
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum AiAction {
@@ -12,6 +24,13 @@ pub enum AiAction {
     MakeConcise,
     AddExamples,
     CustomRequest,
+    SuggestTags,
+    GenerateDescription,
+    GenerateItem,
+    /// Restructure the item for another category
+    ConvertTo(Category),
+    /// One of the user's own actions, by index in `AiPopupState::user_actions`
+    User(usize),
 }
 
 impl AiAction {
@@ -21,6 +40,13 @@ impl AiAction {
             AiAction::MakeConcise,
             AiAction::AddExamples,
             AiAction::CustomRequest,
+            AiAction::SuggestTags,
+            AiAction::GenerateDescription,
+            AiAction::GenerateItem,
+            AiAction::ConvertTo(Category::Agent),
+            AiAction::ConvertTo(Category::Skill),
+            AiAction::ConvertTo(Category::Command),
+            AiAction::ConvertTo(Category::Prompt),
         ]
     }
 
@@ -30,6 +56,15 @@ impl AiAction {
             AiAction::MakeConcise => "Make it more concise",
             AiAction::AddExamples => "Add examples",
             AiAction::CustomRequest => "Custom request...",
+            AiAction::SuggestTags => "Suggest tags",
+            AiAction::GenerateDescription => "Write a one-line description",
+            AiAction::GenerateItem => "Generate new item from description...",
+            AiAction::ConvertTo(Category::Agent) => "Convert to Agent",
+            AiAction::ConvertTo(Category::Skill) => "Convert to Skill",
+            AiAction::ConvertTo(Category::Command) => "Convert to Command",
+            AiAction::ConvertTo(Category::Prompt) => "Convert to Prompt",
+            // Labelled by `AiPopupState::label`
+            AiAction::User(_) => "",
         }
     }
 
@@ -51,19 +86,296 @@ impl AiAction {
                  practical and relevant. Return only the enhanced prompt with examples, no explanations."
             }
             AiAction::CustomRequest => "",
+            AiAction::SuggestTags => {
+                "You organize a library of prompts, agents, skills, and commands. Suggest 3 to 5 \
+                 short, lowercase tags for the following item that describe its topic, language, \
+                 or purpose. Prefer existing tags when they fit. Return only the tags, \
+                 comma-separated, no explanations."
+            }
+            AiAction::GenerateDescription => {
+                "You write descriptions for Claude Code prompts, agents, skills, and commands. \
+                 Summarize what the following item does in one sentence of at most 25 words, \
+                 written so Claude can tell when to use it. Return only the sentence, no quotes \
+                 and no explanations."
+            }
+            AiAction::GenerateItem => {
+                "You write items for a library of Claude Code prompts, agents, skills, and \
+                 slash commands. From the user's description, write one complete item as a \
+                 markdown document with YAML frontmatter. Frontmatter keys: name (kebab-case), \
+                 category (prompt, agent, skill, or command), description (one line), tags \
+                 (comma-separated), and where relevant tools (comma-separated Claude Code tool \
+                 names such as Read, Grep, Glob, Edit, Bash), model (sonnet, opus, or haiku), \
+                 and argument-hint (commands only). The body after the frontmatter is the full \
+                 prompt or instructions. Return only the markdown document, no code fences."
+            }
+            AiAction::ConvertTo(Category::Agent) => {
+                "You convert prompts into Claude Code subagents. Rewrite the following item as \
+                 an agent: a system prompt written in the second person that defines the \
+                 agent's role, when it should be used, and how it works. Return a markdown \
+                 document with YAML frontmatter keys description (one line saying when to use \
+                 the agent), tools (comma-separated Claude Code tool names such as Read, Grep, \
+                 Glob, Edit, Bash, only those the agent needs), and model (sonnet, opus, or \
+                 haiku). Return only the markdown document, no code fences."
+            }
+            AiAction::ConvertTo(Category::Skill) => {
+                "You convert prompts into Claude Code skills. Rewrite the following item as a \
+                 skill: step-by-step instructions Claude follows when the skill applies. Return \
+                 a markdown document with YAML frontmatter keys description (one line saying \
+                 what the skill does and when to use it) and allowed-tools (comma-separated \
+                 Claude Code tool names, only those the skill needs). Return only the markdown \
+                 document, no code fences."
+            }
+            AiAction::ConvertTo(Category::Command) => {
+                "You convert prompts into Claude Code slash commands. Rewrite the following item \
+                 as a command prompt that uses $ARGUMENTS where the user's input belongs. \
+                 Return a markdown document with YAML frontmatter keys description (one line), \
+                 argument-hint (for example [file] or [issue-number]), and allowed-tools \
+                 (comma-separated Claude Code tool names, only when needed). Return only the \
+                 markdown document, no code fences."
+            }
+            AiAction::ConvertTo(Category::Prompt) => {
+                "You convert Claude Code agents, skills, and commands into plain prompts. \
+                 Rewrite the following item as a standalone prompt without frontmatter. \
+                 Return only the prompt, no explanations."
+            }
+            // Looked up by `AiPopupState::system_prompt`
+            AiAction::User(_) => "",
+        }
+    }
+
+    /// Actions that take free-text input from the user
+    pub fn needs_input(&self) -> bool {
+        matches!(self, AiAction::CustomRequest | AiAction::GenerateItem)
+    }
+
+    /// Short, cheap answers that run on the light model when one is set
+    pub fn is_lightweight(&self) -> bool {
+        matches!(self, AiAction::SuggestTags | AiAction::GenerateDescription)
+    }
+
+    /// Actions whose result is a rewrite of the content, reviewed hunk by hunk
+    pub fn rewrites_content(&self) -> bool {
+        matches!(
+            self,
+            AiAction::ImprovePrompt
+                | AiAction::MakeConcise
+                | AiAction::AddExamples
+                | AiAction::CustomRequest
+        )
+    }
+}
+
+/// Original content vs. AI rewrite, split into hunks that are accepted or
+/// rejected individually
+pub struct ResultDiff {
+    segments: Vec<Segment>,
+    /// Index into `segments` of the hunk under the cursor
+    pub selected: usize,
+}
+
+enum Segment {
+    Unchanged(Vec<String>),
+    Hunk {
+        removed: Vec<String>,
+        added: Vec<String>,
+        accepted: bool,
+    },
+}
+
+impl ResultDiff {
+    pub fn new(original: &str, rewritten: &str) -> Self {
+        let diff = TextDiff::from_lines(original, rewritten);
+        let mut segments: Vec<Segment> = Vec::new();
+
+        for op in diff.ops() {
+            let mut removed = Vec::new();
+            let mut added = Vec::new();
+            let mut unchanged = Vec::new();
+            for change in diff.iter_changes(op) {
+                let line = change.value().to_string();
+                match change.tag() {
+                    ChangeTag::Equal => unchanged.push(line),
+                    ChangeTag::Delete => removed.push(line),
+                    ChangeTag::Insert => added.push(line),
+                }
+            }
+
+            match (op, segments.last_mut()) {
+                (DiffOp::Equal { .. }, _) => segments.push(Segment::Unchanged(unchanged)),
+                // Adjacent delete and insert ops form a single hunk
+                (
+                    _,
+                    Some(Segment::Hunk {
+                        removed: prev_removed,
+                        added: prev_added,
+                        ..
+                    }),
+                ) => {
+                    prev_removed.extend(removed);
+                    prev_added.extend(added);
+                }
+                _ => segments.push(Segment::Hunk {
+                    removed,
+                    added,
+                    accepted: true,
+                }),
+            }
+        }
+
+        let selected = segments
+            .iter()
+            .position(|s| matches!(s, Segment::Hunk { .. }))
+            .unwrap_or(0);
+
+        Self { segments, selected }
+    }
+
+    fn hunk_indices(&self) -> Vec<usize> {
+        self.segments
+            .iter()
+            .enumerate()
+            .filter(|(_, s)| matches!(s, Segment::Hunk { .. }))
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    pub fn hunk_count(&self) -> usize {
+        self.hunk_indices().len()
+    }
+
+    pub fn accepted_count(&self) -> usize {
+        self.segments
+            .iter()
+            .filter(|s| matches!(s, Segment::Hunk { accepted: true, .. }))
+            .count()
+    }
+
+    pub fn select_next(&mut self) {
+        if let Some(&next) = self.hunk_indices().iter().find(|&&i| i > self.selected) {
+            self.selected = next;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if let Some(&prev) = self
+            .hunk_indices()
+            .iter()
+            .rev()
+            .find(|&&i| i < self.selected)
+        {
+            self.selected = prev;
         }
     }
+
+    /// Accept or reject the selected hunk, then move to the next one
+    pub fn decide(&mut self, accept: bool) {
+        if let Some(Segment::Hunk { accepted, .. }) = self.segments.get_mut(self.selected) {
+            *accepted = accept;
+        }
+        self.select_next();
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(Segment::Hunk { accepted, .. }) = self.segments.get_mut(self.selected) {
+            *accepted = !*accepted;
+        }
+    }
+
+    /// The original content with the accepted hunks applied
+    pub fn merged(&self) -> String {
+        self.segments
+            .iter()
+            .flat_map(|segment| match segment {
+                Segment::Unchanged(lines) => lines.iter(),
+                Segment::Hunk {
+                    removed,
+                    added,
+                    accepted,
+                } => {
+                    if *accepted {
+                        added.iter()
+                    } else {
+                        removed.iter()
+                    }
+                }
+            })
+            .map(String::as_str)
+            .collect()
+    }
+}
+
+/// A user action as the document edited in `$EDITOR`: the label and
+/// target as frontmatter, the system prompt as the body
+pub fn action_document(action: &UserAction) -> String {
+    format!(
+        "---\n# target: content, description, or tags\nlabel: {}\ntarget: {}\n---\n\n{}",
+        action.label,
+        action.target.key(),
+        action.system_prompt
+    )
+}
+
+/// Read back a document written by `action_document`, keeping `id`
+pub fn parse_action_document(text: &str, id: Option<i64>) -> Result<UserAction, String> {
+    let (fields, body) = parse_frontmatter(text);
+    let field = |key: &str| {
+        fields
+            .iter()
+            .find(|(k, _)| k == key)
+            .map(|(_, v)| v.trim().to_string())
+            .unwrap_or_default()
+    };
+
+    let label = field("label");
+    if label.is_empty() {
+        return Err("the action has no label".to_string());
+    }
+    if body.trim().is_empty() {
+        return Err(format!("'{}' has no prompt", label));
+    }
+    let target = match field("target") {
+        key if key.is_empty() => ActionTarget::Content,
+        key => ActionTarget::from_key(&key).ok_or_else(|| {
+            format!(
+                "unknown target '{}' (expected content, description, or tags)",
+                key
+            )
+        })?,
+    };
+
+    Ok(UserAction {
+        id,
+        label,
+        system_prompt: body.trim().to_string(),
+        target,
+    })
 }
 
 #[derive(Default)]
 pub struct AiPopupState {
     pub selected_action: usize,
+    /// The user's own actions, listed after the built-in ones
+    pub user_actions: Vec<UserAction>,
     pub custom_input: String,
     pub cursor_pos: usize,
     pub is_loading: bool,
     pub loading_tick: usize,
     pub result: Option<String>,
     pub error: Option<String>,
+    /// Tokens and estimated cost (USD) of the last call
+    pub usage: Option<(Usage, Option<f64>)>,
+    /// Hunk review of `result` for actions that rewrite the content
+    pub diff: Option<ResultDiff>,
+    /// Finished turns, so follow-ups continue the same conversation
+    pub conversation: Vec<ChatTurn>,
+    /// User message of the request in flight
+    pub pending_message: Option<String>,
+    /// Typing a follow-up instruction into `custom_input`
+    pub refining: bool,
+    /// Wait before the retry offered after a rate limit or overload
+    pub retry_delay: Option<Duration>,
+    /// When the scheduled retry is sent
+    pub retry_at: Option<Instant>,
 }
 
 impl AiPopupState {
@@ -82,21 +394,124 @@ impl AiPopupState {
         }
     }
 
+    /// The built-in actions followed by the user's
+    pub fn actions(&self) -> Vec<AiAction> {
+        let mut actions = AiAction::all().to_vec();
+        actions.extend((0..self.user_actions.len()).map(AiAction::User));
+        actions
+    }
+
     pub fn select_next(&mut self) {
-        self.selected_action = (self.selected_action + 1) % AiAction::all().len();
+        self.selected_action = (self.selected_action + 1) % self.actions().len();
+        self.retry_delay = None;
     }
 
     pub fn select_prev(&mut self) {
-        let len = AiAction::all().len();
+        let len = self.actions().len();
         self.selected_action = (self.selected_action + len - 1) % len;
+        self.retry_delay = None;
+    }
+
+    /// Show a failed call's error, offering a delayed retry when the
+    /// provider was rate limiting or overloaded
+    pub fn fail(&mut self, error: CompletionError) {
+        self.retry_delay = error.rate_limited.then(|| {
+            error
+                .retry_after
+                .unwrap_or(DEFAULT_RETRY_DELAY)
+                .min(MAX_RETRY_DELAY)
+        });
+        self.error = Some(error.message);
+        self.is_loading = false;
+    }
+
+    /// Send the retry once the offered delay has passed
+    pub fn schedule_retry(&mut self) {
+        self.retry_at = self
+            .retry_delay
+            .and_then(|delay| Instant::now().checked_add(delay));
+    }
+
+    pub fn retry_due(&self) -> bool {
+        self.retry_at.is_some_and(|at| Instant::now() >= at)
+    }
+
+    /// Whole seconds until the scheduled retry, rounded up
+    fn retry_countdown(&self) -> Option<u64> {
+        self.retry_at.map(|at| {
+            let left = at.saturating_duration_since(Instant::now());
+            left.as_secs() + u64::from(left.subsec_nanos() > 0)
+        })
     }
 
     pub fn selected_action(&self) -> AiAction {
-        AiAction::all()[self.selected_action]
+        self.actions()
+            .get(self.selected_action)
+            .copied()
+            .unwrap_or(AiAction::ImprovePrompt)
+    }
+
+    pub fn user_action(&self, action: AiAction) -> Option<&UserAction> {
+        match action {
+            AiAction::User(index) => self.user_actions.get(index),
+            _ => None,
+        }
+    }
+
+    pub fn label<'a>(&'a self, action: &AiAction) -> &'a str {
+        self.user_action(*action)
+            .map_or(action.label(), |user| user.label.as_str())
+    }
+
+    pub fn system_prompt(&self, action: AiAction) -> &str {
+        self.user_action(action)
+            .map_or(action.system_prompt(), |user| user.system_prompt.as_str())
+    }
+
+    /// The built-in action whose request and result handling a user action
+    /// shares: a rewrite for the content, tags and description otherwise
+    pub fn handled_as(&self, action: AiAction) -> AiAction {
+        match self.user_action(action).map(|user| user.target) {
+            Some(ActionTarget::Content) => AiAction::ImprovePrompt,
+            Some(ActionTarget::Description) => AiAction::GenerateDescription,
+            Some(ActionTarget::Tags) => AiAction::SuggestTags,
+            None => action,
+        }
+    }
+
+    /// Replace the user actions, selecting the one with `selected_id`
+    pub fn set_user_actions(&mut self, actions: Vec<UserAction>, selected_id: Option<i64>) {
+        self.user_actions = actions;
+        let builtin = AiAction::all().len();
+        self.selected_action = match selected_id {
+            Some(id) => self
+                .user_actions
+                .iter()
+                .position(|action| action.id == Some(id))
+                .map_or(0, |index| builtin + index),
+            None => self
+                .selected_action
+                .min(builtin + self.user_actions.len() - 1),
+        };
     }
 
-    pub fn is_custom(&self) -> bool {
-        self.selected_action() == AiAction::CustomRequest
+    pub fn needs_input(&self) -> bool {
+        self.selected_action().needs_input()
+    }
+
+    pub fn select_action(&mut self, action: AiAction) {
+        self.selected_action = self
+            .actions()
+            .iter()
+            .position(|a| *a == action)
+            .unwrap_or(0);
+    }
+
+    /// Focus the input for a follow-up instruction on the current result
+    pub fn start_refining(&mut self) {
+        self.refining = true;
+        self.custom_input.clear();
+        self.cursor_pos = 0;
     }
 
     pub fn insert_char(&mut self, c: char) {
@@ -112,12 +527,21 @@ impl AiPopupState {
     }
 
     pub fn clear(&mut self) {
-        *self = Self::default();
+        let user_actions = std::mem::take(&mut self.user_actions);
+        *self = Self {
+            user_actions,
+            ..Self::default()
+        };
     }
 }
 
 pub fn draw(frame: &mut Frame, state: &AiPopupState, content_preview: &str, has_llm: bool) {
-    let area = centered_rect(50, 60, frame.area());
+    // Reviewing a diff needs more room than picking an action
+    let area = if state.diff.is_some() {
+        centered_rect(80, 80, frame.area())
+    } else {
+        centered_rect(50, 60, frame.area())
+    };
 
     // Clear the area behind the popup
     frame.render_widget(Clear, area);
@@ -139,11 +563,11 @@ pub fn draw(frame: &mut Frame, state: &AiPopupState, content_preview: &str, has_
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(3), // Header
-            Constraint::Length(6), // Actions
-            Constraint::Length(3), // Custom input (if selected)
-            Constraint::Min(3),    // Preview/Result
-            Constraint::Length(1), // Status bar
+            Constraint::Length(3),                                // Header
+            Constraint::Length(state.actions().len() as u16 + 1), // Actions
+            Constraint::Length(3),                                // Custom input (if selected)
+            Constraint::Min(3),                                   // Preview/Result
+            Constraint::Length(1),                                // Status bar
         ])
         .split(inner);
 
@@ -158,13 +582,17 @@ pub fn draw(frame: &mut Frame, state: &AiPopupState, content_preview: &str, has_
     // Actions
     draw_actions(frame, chunks[1], state);
 
-    // Custom input
-    if state.is_custom() {
+    // Custom input, or the follow-up instruction when refining a result
+    if state.needs_input() || state.refining {
         draw_custom_input(frame, chunks[2], state);
     }
 
     // Result or loading indicator
-    draw_result(frame, chunks[3], state, content_preview);
+    if let Some(ref diff) = state.diff {
+        draw_diff(frame, chunks[3], state, diff);
+    } else {
+        draw_result(frame, chunks[3], state, content_preview);
+    }
 
     // Status bar
     draw_status_bar(frame, chunks[4], state);
@@ -201,7 +629,7 @@ fn draw_no_llm_warning(frame: &mut Frame, area: Rect) {
         .alignment(ratatui::layout::Alignment::Center);
     frame.render_widget(message, chunks[2]);
 
-    let hint = Paragraph::new("Go to Settings (s) to add an Anthropic or OpenAI API key")
+    let hint = Paragraph::new("Go to Settings (s) to add an API key, set ANTHROPIC_API_KEY / OPENAI_API_KEY, or pick Ollama")
         .style(Style::default().fg(Color::DarkGray))
         .alignment(ratatui::layout::Alignment::Center)
         .wrap(Wrap { trim: true });
@@ -217,7 +645,7 @@ fn draw_no_llm_warning(frame: &mut Frame, area: Rect) {
 fn draw_actions(frame: &mut Frame, area: Rect, state: &AiPopupState) {
     let mut lines = Vec::new();
 
-    for (i, action) in AiAction::all().iter().enumerate() {
+    for (i, action) in state.actions().iter().enumerate() {
         let is_selected = i == state.selected_action;
         let prefix = if is_selected { "> " } else { "  " };
 
@@ -229,7 +657,14 @@ fn draw_actions(frame: &mut Frame, area: Rect, state: &AiPopupState) {
             Style::default()
         };
 
-        lines.push(Line::styled(format!("{}{}", prefix, action.label()), style));
+        let mut line = Line::styled(format!("{}{}", prefix, state.label(action)), style);
+        if let Some(user) = state.user_action(*action) {
+            line.push_span(Span::styled(
+                format!("  → {}", user.target.key()),
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        lines.push(line);
     }
 
     let paragraph = Paragraph::new(lines);
@@ -237,9 +672,14 @@ fn draw_actions(frame: &mut Frame, area: Rect, state: &AiPopupState) {
 }
 
 fn draw_custom_input(frame: &mut Frame, area: Rect, state: &AiPopupState) {
-    let block = Block::default()
+    let mut block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::DarkGray));
+    if state.refining {
+        block = block
+            .title(" Follow-up ")
+            .border_style(Style::default().fg(Color::Magenta));
+    }
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -266,6 +706,10 @@ fn draw_custom_input(frame: &mut Frame, area: Rect, state: &AiPopupState) {
 fn draw_result(frame: &mut Frame, area: Rect, state: &AiPopupState, content_preview: &str) {
     let title = if state.is_loading {
         format!(" {} Processing... ", state.loading_spinner())
+    } else if let Some(seconds) = state.retry_countdown() {
+        format!(" Retrying in {}s ", seconds)
+    } else if let (Some(_), Some((usage, cost))) = (&state.result, state.usage) {
+        format!(" Preview · {} ", format_usage(usage, cost))
     } else {
         " Preview ".to_string()
     };
@@ -273,11 +717,13 @@ fn draw_result(frame: &mut Frame, area: Rect, state: &AiPopupState, content_prev
     let block = Block::default()
         .title(title)
         .borders(Borders::ALL)
-        .border_style(Style::default().fg(if state.is_loading {
-            Color::Yellow
-        } else {
-            Color::DarkGray
-        }));
+        .border_style(
+            Style::default().fg(if state.is_loading || state.retry_at.is_some() {
+                Color::Yellow
+            } else {
+                Color::DarkGray
+            }),
+        );
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -285,9 +731,28 @@ fn draw_result(frame: &mut Frame, area: Rect, state: &AiPopupState, content_prev
     let content = if state.is_loading {
         Paragraph::new("Waiting for AI response...").style(Style::default().fg(Color::Yellow))
     } else if let Some(ref error) = state.error {
-        Paragraph::new(error.as_str())
-            .style(Style::default().fg(Color::Red))
-            .wrap(Wrap { trim: true })
+        let mut lines = vec![Line::styled(
+            error.as_str(),
+            Style::default().fg(if state.retry_delay.is_some() {
+                Color::Yellow
+            } else {
+                Color::Red
+            }),
+        )];
+        if let Some(seconds) = state.retry_countdown() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                format!("Sending the request again in {}s...", seconds),
+                Style::default().fg(Color::DarkGray),
+            ));
+        } else if state.retry_delay.is_some() {
+            lines.push(Line::raw(""));
+            lines.push(Line::styled(
+                "The request was fine; the provider just can't take it right now.",
+                Style::default().fg(Color::DarkGray),
+            ));
+        }
+        Paragraph::new(lines).wrap(Wrap { trim: true })
     } else if let Some(ref result) = state.result {
         Paragraph::new(result.as_str())
             .style(Style::default().fg(Color::Green))
@@ -307,13 +772,154 @@ fn draw_result(frame: &mut Frame, area: Rect, state: &AiPopupState, content_prev
     frame.render_widget(content, inner);
 }
 
+fn draw_diff(frame: &mut Frame, area: Rect, state: &AiPopupState, diff: &ResultDiff) {
+    let mut title = format!(
+        " Changes · {}/{} accepted ",
+        diff.accepted_count(),
+        diff.hunk_count()
+    );
+    if let Some((usage, cost)) = state.usage {
+        title = format!("{}· {} ", title, format_usage(usage, cost));
+    }
+
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    if diff.hunk_count() == 0 {
+        let message =
+            Paragraph::new("No changes suggested").style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(message, inner);
+        return;
+    }
+
+    let dim = Style::default().fg(Color::DarkGray);
+    let mut lines = Vec::new();
+    let mut selected_line = 0;
+    let mut hunk_number = 0;
+
+    for (i, segment) in diff.segments.iter().enumerate() {
+        match segment {
+            Segment::Unchanged(text) => {
+                // Only show a few lines of context around each hunk
+                if text.len() > 6 {
+                    lines.extend(text[..3].iter().map(|l| context_line(l, dim)));
+                    lines.push(Line::styled("  ⋯", dim));
+                    lines.extend(text[text.len() - 3..].iter().map(|l| context_line(l, dim)));
+                } else {
+                    lines.extend(text.iter().map(|l| context_line(l, dim)));
+                }
+            }
+            Segment::Hunk {
+                removed,
+                added,
+                accepted,
+            } => {
+                hunk_number += 1;
+                let is_selected = i == diff.selected;
+                if is_selected {
+                    selected_line = lines.len();
+                }
+
+                let marker = if *accepted { "[x]" } else { "[ ]" };
+                let mut header_style = Style::default().fg(Color::Cyan);
+                if is_selected {
+                    header_style = header_style.add_modifier(Modifier::BOLD | Modifier::REVERSED);
+                }
+                lines.push(Line::styled(
+                    format!("{} hunk {}", marker, hunk_number),
+                    header_style,
+                ));
+
+                // Rejected hunks dim the side that will not be applied
+                let (removed_style, added_style) = if *accepted {
+                    (
+                        Style::default().fg(Color::Red),
+                        Style::default().fg(Color::Green),
+                    )
+                } else {
+                    (Style::default().fg(Color::Red), dim)
+                };
+                lines.extend(removed.iter().map(|l| {
+                    Line::styled(format!("-{}", l.trim_end_matches('\n')), removed_style)
+                }));
+                lines.extend(
+                    added.iter().map(|l| {
+                        Line::styled(format!("+{}", l.trim_end_matches('\n')), added_style)
+                    }),
+                );
+            }
+        }
+    }
+
+    // Keep the selected hunk in view
+    let scroll = selected_line.saturating_sub(inner.height as usize / 3) as u16;
+    frame.render_widget(Paragraph::new(lines).scroll((scroll, 0)), inner);
+}
+
+fn context_line(text: &str, style: Style) -> Line<'static> {
+    Line::styled(format!(" {}", text.trim_end_matches('\n')), style)
+}
+
+/// "1200 in / 340 out tokens · $0.0087"; the cost is omitted when unknown
+pub fn format_usage(usage: Usage, cost: Option<f64>) -> String {
+    let tokens = format!(
+        "{} in / {} out tokens",
+        usage.input_tokens, usage.output_tokens
+    );
+    match cost {
+        Some(cost) => format!("{} · ${:.4}", tokens, cost),
+        None => tokens,
+    }
+}
+
 fn draw_status_bar(frame: &mut Frame, area: Rect, state: &AiPopupState) {
+    let retry_label = state
+        .retry_delay
+        .map(|delay| format!("retry in {}s", delay.as_secs().max(1)))
+        .unwrap_or_default();
     let shortcuts = if state.is_loading {
         vec![("", "Processing...")]
+    } else if state.retry_at.is_some() {
+        vec![("r ", "retry now"), ("ESC ", "cancel retry")]
+    } else if state.retry_delay.is_some() {
+        vec![
+            ("Enter ", retry_label.as_str()),
+            ("r ", "retry now"),
+            ("j/k ", "select"),
+            ("ESC ", "close"),
+        ]
+    } else if state.refining {
+        vec![("Enter ", "send"), ("ESC ", "back")]
+    } else if state.diff.is_some() {
+        vec![
+            ("j/k ", "hunk"),
+            ("y/n ", "accept/reject"),
+            ("Space ", "toggle"),
+            ("Tab ", "follow-up"),
+            ("Enter ", "apply"),
+            ("ESC ", "cancel"),
+        ]
     } else if state.result.is_some() {
-        vec![("Enter ", "apply"), ("ESC ", "cancel")]
+        vec![
+            ("Tab ", "follow-up"),
+            ("Enter ", "apply"),
+            ("ESC ", "cancel"),
+        ]
     } else {
-        vec![("j/k ", "select"), ("Enter ", "run"), ("ESC ", "close")]
+        let mut shortcuts = vec![("j/k ", "select"), ("Enter ", "run")];
+        if !state.needs_input() {
+            shortcuts.push(("n ", "new"));
+        }
+        if state.user_action(state.selected_action()).is_some() {
+            shortcuts.push(("e ", "edit"));
+        }
+        shortcuts.push(("ESC ", "close"));
+        shortcuts
     };
 
     let spans: Vec<Span> = shortcuts
@@ -1,3 +1,4 @@
+use crate::export::ExportTarget;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -6,7 +7,29 @@ use ratatui::{
     Frame,
 };
 
+/// What confirming a `ConfirmDialog` does
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfirmAction {
+    /// Trash or purge the selected or marked items
+    Delete,
+    EmptyTrash,
+    /// Leave the edit or settings screen without saving
+    DiscardChanges,
+    /// Open the generated item in place of the edits
+    ReplaceEdits,
+    Quit,
+    ExportAll,
+    DeleteTag,
+    DeleteCollection,
+    ExportSkills,
+    RestoreDatabase,
+    /// Write an export over the file already at its path
+    Overwrite,
+    Share,
+}
+
 pub struct ConfirmDialog {
+    pub action: ConfirmAction,
     pub title: String,
     pub message: String,
     pub confirm_label: String,
@@ -17,16 +40,71 @@ pub struct ConfirmDialog {
 impl ConfirmDialog {
     pub fn delete(item_name: &str) -> Self {
         Self {
+            action: ConfirmAction::Delete,
             title: " Confirm Delete ".to_string(),
-            message: format!("Are you sure you want to delete '{}'?", item_name),
+            message: format!("Move '{}' to the trash?", item_name),
             confirm_label: "Delete".to_string(),
             cancel_label: "Cancel".to_string(),
             selected: false, // Default to cancel
         }
     }
 
+    pub fn delete_forever(item_name: &str) -> Self {
+        Self {
+            action: ConfirmAction::Delete,
+            title: " Delete Forever ".to_string(),
+            message: format!(
+                "Permanently delete '{}' and its history? This cannot be undone.",
+                item_name
+            ),
+            confirm_label: "Delete".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn delete_many(count: usize) -> Self {
+        Self {
+            action: ConfirmAction::Delete,
+            title: " Confirm Delete ".to_string(),
+            message: format!("Move {} marked item(s) to the trash?", count),
+            confirm_label: "Delete".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn delete_many_forever(count: usize) -> Self {
+        Self {
+            action: ConfirmAction::Delete,
+            title: " Delete Forever ".to_string(),
+            message: format!(
+                "Permanently delete {} marked item(s) and their history? This cannot be undone.",
+                count
+            ),
+            confirm_label: "Delete".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn empty_trash(count: usize) -> Self {
+        Self {
+            action: ConfirmAction::EmptyTrash,
+            title: " Empty Trash ".to_string(),
+            message: format!(
+                "Permanently delete {} item(s) in the trash? This cannot be undone.",
+                count
+            ),
+            confirm_label: "Empty".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
     pub fn discard_changes() -> Self {
         Self {
+            action: ConfirmAction::DiscardChanges,
             title: " Unsaved Changes ".to_string(),
             message: "You have unsaved changes. Discard them?".to_string(),
             confirm_label: "Discard".to_string(),
@@ -35,11 +113,351 @@ impl ConfirmDialog {
         }
     }
 
+    pub fn replace_edits() -> Self {
+        Self {
+            action: ConfirmAction::ReplaceEdits,
+            title: " Replace Edits ".to_string(),
+            message: "Replace your unsaved edits with the generated item?".to_string(),
+            confirm_label: "Replace".to_string(),
+            cancel_label: "Keep Editing".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn quit_during_ai() -> Self {
+        Self {
+            action: ConfirmAction::Quit,
+            title: " Quit ".to_string(),
+            message: "AI request in progress. Quit anyway?".to_string(),
+            confirm_label: "Quit".to_string(),
+            cancel_label: "Wait".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn export_all(count: usize, path: &str, changed: usize) -> Self {
+        let mut message = format!("Export {} items to {}?", count, path);
+        if changed > 0 {
+            message.push_str(&format!(
+                "\n{} file(s) changed on disk will be overwritten.",
+                changed
+            ));
+        }
+
+        Self {
+            action: ConfirmAction::ExportAll,
+            title: " Export All ".to_string(),
+            message,
+            confirm_label: "Export".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn delete_tag(tag: &str, count: usize) -> Self {
+        Self {
+            action: ConfirmAction::DeleteTag,
+            title: " Delete Tag ".to_string(),
+            message: format!("Remove #{} from {} item(s)?", tag, count),
+            confirm_label: "Delete".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn delete_collection(path: &str, count: usize) -> Self {
+        Self {
+            action: ConfirmAction::DeleteCollection,
+            title: " Delete Collection ".to_string(),
+            message: format!(
+                "Delete {} and the collections inside it?\n{} item(s) stay in the library.",
+                path, count
+            ),
+            confirm_label: "Delete".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    /// Offer the skills an exported agent uses that the target lacks
+    pub fn export_skills(agent: &str, skills: &[String], target: &str) -> Self {
+        let mut listed = skills
+            .iter()
+            .take(3)
+            .cloned()
+            .collect::<Vec<_>>()
+            .join(", ");
+        if skills.len() > 3 {
+            listed.push_str(&format!(" and {} more", skills.len() - 3));
+        }
+        Self {
+            action: ConfirmAction::ExportSkills,
+            title: " Export Skills ".to_string(),
+            message: format!(
+                "{} uses skills not exported to {}: {}.\nExport them too?",
+                agent, target, listed
+            ),
+            confirm_label: "Export".to_string(),
+            cancel_label: "Skip".to_string(),
+            selected: true,
+        }
+    }
+
+    pub fn restore_database(file_name: &str) -> Self {
+        Self {
+            action: ConfirmAction::RestoreDatabase,
+            title: " Restore Database ".to_string(),
+            message: format!(
+                "Replace this vault's items and settings\nwith {}?",
+                file_name
+            ),
+            confirm_label: "Restore".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn overwrite(path: &str) -> Self {
+        Self {
+            action: ConfirmAction::Overwrite,
+            title: " Overwrite File ".to_string(),
+            message: format!("{} already exists. Replace it?", path),
+            confirm_label: "Replace".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
+    pub fn share(item_name: &str) -> Self {
+        Self {
+            action: ConfirmAction::Share,
+            title: " Share ".to_string(),
+            message: format!(
+                "Publish '{}' as a secret gist?\nAnyone with the link can read it.",
+                item_name
+            ),
+            confirm_label: "Share".to_string(),
+            cancel_label: "Cancel".to_string(),
+            selected: false,
+        }
+    }
+
     pub fn toggle_selection(&mut self) {
         self.selected = !self.selected;
     }
 }
 
+/// What a value entered in an `InputDialog` is used for
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum InputAction {
+    RenameTag,
+    EditTags,
+    NewCollection,
+    EditCollections,
+    RenameCollection,
+    NewVault,
+    BackupDatabase,
+    RestoreDatabase,
+    ImportRepo,
+    ImportUrl,
+    ExportBundle,
+    ExportZip,
+    ExportToPath,
+    ImportBundle,
+}
+
+/// Single-line text prompt. `subject` is what the prompt acts on.
+pub struct InputDialog {
+    pub action: InputAction,
+    pub title: String,
+    pub subject: String,
+    pub value: String,
+    pub hint: String,
+}
+
+impl InputDialog {
+    pub fn rename_tag(tag: &str) -> Self {
+        Self {
+            action: InputAction::RenameTag,
+            title: " Rename Tag ".to_string(),
+            subject: tag.to_string(),
+            value: tag.to_string(),
+            hint: "Use an existing tag name to merge".to_string(),
+        }
+    }
+
+    pub fn edit_tags(count: usize) -> Self {
+        Self {
+            action: InputAction::EditTags,
+            title: " Tag Items ".to_string(),
+            subject: String::new(),
+            value: String::new(),
+            hint: format!("{} item(s): tag adds, -tag removes", count),
+        }
+    }
+
+    pub fn new_collection(parent: &str) -> Self {
+        Self {
+            action: InputAction::NewCollection,
+            title: " New Collection ".to_string(),
+            subject: String::new(),
+            value: parent.to_string(),
+            hint: "Use / to nest, e.g. Work/Rust tooling".to_string(),
+        }
+    }
+
+    pub fn edit_collections(count: usize) -> Self {
+        Self {
+            action: InputAction::EditCollections,
+            title: " Collect Items ".to_string(),
+            subject: String::new(),
+            value: String::new(),
+            hint: format!("{} item(s): Work/Rust adds, -Work removes", count),
+        }
+    }
+
+    pub fn rename_collection(path: &str) -> Self {
+        Self {
+            action: InputAction::RenameCollection,
+            title: " Rename Collection ".to_string(),
+            subject: path.to_string(),
+            value: path.to_string(),
+            hint: "Nested collections move along".to_string(),
+        }
+    }
+
+    pub fn new_vault() -> Self {
+        Self {
+            action: InputAction::NewVault,
+            title: " New Vault ".to_string(),
+            subject: String::new(),
+            value: String::new(),
+            hint: "Letters, digits, - and _".to_string(),
+        }
+    }
+
+    pub fn backup_database(default_path: &str) -> Self {
+        Self {
+            action: InputAction::BackupDatabase,
+            title: " Backup Database ".to_string(),
+            subject: String::new(),
+            value: default_path.to_string(),
+            hint: "Backup file".to_string(),
+        }
+    }
+
+    pub fn restore_database(default_path: &str) -> Self {
+        Self {
+            action: InputAction::RestoreDatabase,
+            title: " Restore Database ".to_string(),
+            subject: String::new(),
+            value: default_path.to_string(),
+            hint: "Replaces all items and settings".to_string(),
+        }
+    }
+
+    pub fn import_repo() -> Self {
+        Self {
+            action: InputAction::ImportRepo,
+            title: " Import from Repository ".to_string(),
+            subject: String::new(),
+            value: String::new(),
+            hint: "Git URL or GitHub owner/repo".to_string(),
+        }
+    }
+
+    pub fn import_url() -> Self {
+        Self {
+            action: InputAction::ImportUrl,
+            title: " Import from URL ".to_string(),
+            subject: String::new(),
+            value: String::new(),
+            hint: "Raw file, GitHub file, or gist URL".to_string(),
+        }
+    }
+
+    pub fn export_bundle(count: usize, default_path: &str) -> Self {
+        Self {
+            action: InputAction::ExportBundle,
+            title: " Export Bundle ".to_string(),
+            subject: String::new(),
+            value: default_path.to_string(),
+            hint: format!("{} item(s) into one file to share", count),
+        }
+    }
+
+    pub fn export_zip(count: usize, default_path: &str) -> Self {
+        Self {
+            action: InputAction::ExportZip,
+            title: " Export Zip ".to_string(),
+            subject: String::new(),
+            value: default_path.to_string(),
+            hint: format!("{} item(s) as markdown files in one archive", count),
+        }
+    }
+
+    /// `target` is the key of the export target whose format is written
+    pub fn export_to_path(target: &ExportTarget, default_path: &str) -> Self {
+        Self {
+            action: InputAction::ExportToPath,
+            title: " Export to Path ".to_string(),
+            subject: target.key().to_string(),
+            value: default_path.to_string(),
+            hint: format!("As {}; ~ and new directories work", target.display_name()),
+        }
+    }
+
+    pub fn import_bundle() -> Self {
+        Self {
+            action: InputAction::ImportBundle,
+            title: " Import Bundle ".to_string(),
+            subject: String::new(),
+            value: String::new(),
+            hint: "Path to a .grimoire file".to_string(),
+        }
+    }
+}
+
+pub fn draw_input(frame: &mut Frame, dialog: &InputDialog) {
+    let area = centered_rect_fixed(70, 5, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(dialog.title.as_str())
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1), // Input
+            Constraint::Length(1), // Spacer
+            Constraint::Length(1), // Hint
+        ])
+        .split(inner);
+
+    let input = Paragraph::new(Line::from(vec![
+        Span::raw(dialog.value.clone()),
+        Span::styled(" ", Style::default().bg(Color::White)),
+    ]));
+    frame.render_widget(input, chunks[0]);
+
+    let hint = Paragraph::new(Line::from(vec![
+        Span::styled(dialog.hint.clone(), Style::default().fg(Color::DarkGray)),
+        Span::raw("  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::styled(" ok  ", Style::default().fg(Color::DarkGray)),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::styled(" cancel", Style::default().fg(Color::DarkGray)),
+    ]));
+    frame.render_widget(hint, chunks[2]);
+}
+
 pub fn draw(frame: &mut Frame, dialog: &ConfirmDialog) {
     let area = centered_rect_fixed(50, 7, frame.area());
 
@@ -1,3 +1,9 @@
+use super::text_cursor::{
+    cursor_spans, display_width, next_boundary, pos_at_column, prev_boundary, split_at_cursor,
+};
+use super::vim::{VimMode, VimState};
+use super::{dropdown_area, list_offset, wrapped_rows};
+use crate::llm::estimate_tokens;
 pub use crate::models::{Category, Item};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
@@ -14,45 +20,90 @@ pub enum EditField {
     Tags,
     Model,
     Tools,
+    ArgumentHint,
+    PermissionMode,
+    Skills,
     Description,
     Content,
 }
 
 impl EditField {
-    pub fn next(&self, category: Category) -> Self {
-        match self {
-            EditField::Name => EditField::Category,
-            EditField::Category => EditField::Tags,
-            EditField::Tags => match category {
-                Category::Agent | Category::Command => EditField::Model,
-                Category::Skill => EditField::Tools,
-                Category::Prompt => EditField::Description,
-            },
-            EditField::Model => EditField::Tools,
-            EditField::Tools => EditField::Description,
-            EditField::Description => EditField::Content,
-            EditField::Content => EditField::Name,
+    /// The field for a name in `Category::required_fields` or
+    /// `optional_fields`
+    fn from_key(key: &str) -> Option<Self> {
+        match key {
+            "name" => Some(EditField::Name),
+            "tags" => Some(EditField::Tags),
+            "model" => Some(EditField::Model),
+            "tools" | "allowed_tools" => Some(EditField::Tools),
+            "argument_hint" => Some(EditField::ArgumentHint),
+            "permission_mode" => Some(EditField::PermissionMode),
+            "skills" => Some(EditField::Skills),
+            "description" => Some(EditField::Description),
+            "content" => Some(EditField::Content),
+            _ => None,
         }
     }
 
-    pub fn prev(&self, category: Category) -> Self {
+    /// Label drawn before a one-line field's value
+    fn label(&self) -> &'static str {
         match self {
-            EditField::Name => EditField::Content,
-            EditField::Category => EditField::Name,
-            EditField::Tags => EditField::Category,
-            EditField::Model => EditField::Tags,
-            EditField::Tools => match category {
-                Category::Agent | Category::Command => EditField::Model,
-                _ => EditField::Tags,
-            },
-            EditField::Description => match category {
-                Category::Agent | Category::Command => EditField::Tools,
-                Category::Skill => EditField::Tools,
-                Category::Prompt => EditField::Tags,
-            },
-            EditField::Content => EditField::Description,
+            EditField::Name => "Name:     ",
+            EditField::Category => "Category: ",
+            EditField::Tags => "Tags:     ",
+            EditField::Model => "Model:    ",
+            EditField::Tools => "Tools:    ",
+            EditField::ArgumentHint => "Arg hint: ",
+            EditField::PermissionMode => "Perms:    ",
+            EditField::Skills => "Skills:   ",
+            EditField::Description => "Description",
+            EditField::Content => "Content",
         }
     }
+
+    /// Whether the field has its own box below the form
+    pub fn is_multiline(&self) -> bool {
+        matches!(self, EditField::Description | EditField::Content)
+    }
+
+    /// The one-line fields of the form for `category`, top to bottom: name,
+    /// category, then the category's other fields in the order it lists
+    /// them
+    pub fn form_fields(category: Category) -> Vec<Self> {
+        let mut fields = vec![EditField::Name, EditField::Category];
+        for key in category
+            .required_fields()
+            .iter()
+            .chain(category.optional_fields())
+        {
+            if let Some(field) = Self::from_key(key) {
+                if !field.is_multiline() && !fields.contains(&field) {
+                    fields.push(field);
+                }
+            }
+        }
+        fields
+    }
+
+    /// Tab order for `category`: the form, then description and content
+    fn tab_order(category: Category) -> Vec<Self> {
+        let mut fields = Self::form_fields(category);
+        fields.extend([EditField::Description, EditField::Content]);
+        fields
+    }
+
+    pub fn next(&self, category: Category) -> Self {
+        let fields = Self::tab_order(category);
+        let index = fields.iter().position(|f| f == self).map_or(0, |i| i + 1);
+        fields[index % fields.len()]
+    }
+
+    pub fn prev(&self, category: Category) -> Self {
+        let fields = Self::tab_order(category);
+        let len = fields.len();
+        let index = fields.iter().position(|f| f == self).unwrap_or(0);
+        fields[(index + len - 1) % len]
+    }
 }
 
 pub struct EditState {
@@ -64,8 +115,41 @@ pub struct EditState {
     pub content_scroll: u16,
     pub show_category_dropdown: bool,
     pub category_dropdown_index: usize,
+    /// Change message being typed before saving; `Some` while the prompt is open
+    pub version_message: Option<String>,
+    /// Skills and commands this item depends on (id, name)
+    pub links: Vec<(i64, String)>,
+    /// Whether `links` was changed and must be saved
+    pub links_changed: bool,
+    /// Shown in the status bar until the next key press
+    pub notice: Option<String>,
+    /// Earlier field values, most recent last (`Ctrl+Z`)
+    undo_stack: Vec<FieldSnapshot>,
+    /// Undone field values, most recent last (`Ctrl+Y`)
+    redo_stack: Vec<FieldSnapshot>,
+    /// Field and kind of the edit in progress; keys continuing it share
+    /// one undo step
+    edit_run: Option<(EditField, EditRun)>,
+    /// Modal editing of the content field, when vim mode is on in settings
+    pub vim: VimState,
 }
 
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum EditRun {
+    Typing,
+    Deleting,
+}
+
+/// A field's value and cursor before an edit
+struct FieldSnapshot {
+    field: EditField,
+    value: String,
+    cursor_pos: usize,
+}
+
+/// Undo steps kept per edit session
+const UNDO_LIMIT: usize = 200;
+
 impl EditState {
     pub fn new_item() -> Self {
         Self {
@@ -77,6 +161,14 @@ impl EditState {
             content_scroll: 0,
             show_category_dropdown: false,
             category_dropdown_index: 0,
+            version_message: None,
+            links: Vec::new(),
+            links_changed: false,
+            notice: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            edit_run: None,
+            vim: VimState::default(),
         }
     }
 
@@ -95,6 +187,14 @@ impl EditState {
             content_scroll: 0,
             show_category_dropdown: false,
             category_dropdown_index: category_index,
+            version_message: None,
+            links: Vec::new(),
+            links_changed: false,
+            notice: None,
+            undo_stack: Vec::new(),
+            redo_stack: Vec::new(),
+            edit_run: None,
+            vim: VimState::default(),
         }
     }
 
@@ -122,7 +222,11 @@ impl EditState {
     }
 
     pub fn current_field_value(&self) -> &str {
-        match self.focused_field {
+        self.field_value(self.focused_field)
+    }
+
+    fn field_value(&self, field: EditField) -> &str {
+        match field {
             EditField::Name => &self.item.name,
             EditField::Category => self.item.category.as_str(),
             EditField::Tags => self.item.tags.as_deref().unwrap_or(""),
@@ -133,14 +237,21 @@ impl EditState {
                 .as_deref()
                 .or(self.item.allowed_tools.as_deref())
                 .unwrap_or(""),
+            EditField::ArgumentHint => self.item.argument_hint.as_deref().unwrap_or(""),
+            EditField::PermissionMode => self.item.permission_mode.as_deref().unwrap_or(""),
+            EditField::Skills => self.item.skills.as_deref().unwrap_or(""),
             EditField::Description => self.item.description.as_deref().unwrap_or(""),
             EditField::Content => &self.item.content,
         }
     }
 
     pub fn set_current_field(&mut self, value: String) {
+        self.set_field(self.focused_field, value);
+    }
+
+    fn set_field(&mut self, field: EditField, value: String) {
         self.has_changes = true;
-        match self.focused_field {
+        match field {
             EditField::Name => self.item.name = value,
             EditField::Category => self.item.category = Category::from_str(&value),
             EditField::Tags => self.item.tags = if value.is_empty() { None } else { Some(value) },
@@ -153,6 +264,15 @@ impl EditState {
                     _ => {}
                 }
             }
+            EditField::ArgumentHint => {
+                self.item.argument_hint = if value.is_empty() { None } else { Some(value) }
+            }
+            EditField::PermissionMode => {
+                self.item.permission_mode = if value.is_empty() { None } else { Some(value) }
+            }
+            EditField::Skills => {
+                self.item.skills = if value.is_empty() { None } else { Some(value) }
+            }
             EditField::Description => {
                 self.item.description = if value.is_empty() { None } else { Some(value) }
             }
@@ -160,7 +280,106 @@ impl EditState {
         }
     }
 
+    /// Replace a whole field (AI results, external editor) as one undo step
+    pub fn replace_field(&mut self, field: EditField, value: String) {
+        if self.field_value(field) == value {
+            return;
+        }
+        self.record_undo(field, None);
+        if field == self.focused_field {
+            self.cursor_pos = self.cursor_pos.min(value.chars().count());
+        }
+        self.set_field(field, value);
+    }
+
+    /// Save the field's current value before changing it. Characters typed
+    /// or deleted in a row without moving the cursor are one step.
+    fn record_undo(&mut self, field: EditField, run: Option<EditRun>) {
+        let continues = run.is_some_and(|run| self.edit_run == Some((field, run)));
+        self.edit_run = run.map(|run| (field, run));
+        if continues {
+            return;
+        }
+
+        self.undo_stack.push(FieldSnapshot {
+            field,
+            value: self.field_value(field).to_string(),
+            cursor_pos: self.cursor_pos,
+        });
+        if self.undo_stack.len() > UNDO_LIMIT {
+            self.undo_stack.remove(0);
+        }
+        self.redo_stack.retain(|s| s.field != field);
+    }
+
+    /// Undo the last change to the focused field
+    pub fn undo(&mut self) {
+        if !self.restore_snapshot(true) {
+            self.notice = Some("Nothing to undo".to_string());
+        }
+    }
+
+    /// Redo the last undone change to the focused field
+    pub fn redo(&mut self) {
+        if !self.restore_snapshot(false) {
+            self.notice = Some("Nothing to redo".to_string());
+        }
+    }
+
+    fn restore_snapshot(&mut self, undo: bool) -> bool {
+        let field = self.focused_field;
+        let current = FieldSnapshot {
+            field,
+            value: self.field_value(field).to_string(),
+            cursor_pos: self.cursor_pos,
+        };
+        let (from, to) = if undo {
+            (&mut self.undo_stack, &mut self.redo_stack)
+        } else {
+            (&mut self.redo_stack, &mut self.undo_stack)
+        };
+        let Some(index) = from.iter().rposition(|s| s.field == field) else {
+            return false;
+        };
+
+        let snapshot = from.remove(index);
+        to.push(current);
+        self.edit_run = None;
+        self.cursor_pos = snapshot.cursor_pos.min(snapshot.value.chars().count());
+        self.set_field(field, snapshot.value);
+        true
+    }
+
+    /// Move the cursor, ending the current undo step
+    pub fn set_cursor(&mut self, pos: usize) {
+        self.edit_run = None;
+        self.cursor_pos = pos.min(self.current_field_value().chars().count());
+    }
+
+    /// Remove chars `start..end` of the focused field as one undo step,
+    /// leaving the cursor at `start`, and return them
+    pub fn delete_range(&mut self, start: usize, end: usize) -> String {
+        let mut chars: Vec<char> = self.current_field_value().chars().collect();
+        let end = end.min(chars.len());
+        let start = start.min(end);
+        if start == end {
+            return String::new();
+        }
+
+        self.record_undo(self.focused_field, None);
+        let removed: String = chars.drain(start..end).collect();
+        self.cursor_pos = start;
+        self.set_current_field(chars.into_iter().collect());
+        removed
+    }
+
     pub fn insert_char(&mut self, c: char) {
+        self.record_undo(self.focused_field, Some(EditRun::Typing));
+        // Words are separate steps
+        if c.is_whitespace() {
+            self.edit_run = None;
+        }
+
         let field_value = self.current_field_value().to_string();
         let mut chars: Vec<char> = field_value.chars().collect();
 
@@ -175,10 +394,7 @@ impl EditState {
 
     pub fn insert_str(&mut self, s: &str) {
         // For multiline fields (Content, Description), keep newlines; for others, filter them
-        let is_multiline = matches!(
-            self.focused_field,
-            EditField::Content | EditField::Description
-        );
+        let is_multiline = self.focused_field.is_multiline();
         let clean: String = if is_multiline {
             s.chars()
                 .filter(|c| *c == '\n' || !c.is_control())
@@ -187,6 +403,9 @@ impl EditState {
             s.chars().filter(|c| !c.is_control()).collect()
         };
 
+        // A paste is its own undo step
+        self.record_undo(self.focused_field, None);
+
         let field_value = self.current_field_value().to_string();
         let mut chars: Vec<char> = field_value.chars().collect();
 
@@ -201,45 +420,56 @@ impl EditState {
         self.set_current_field(chars.into_iter().collect());
     }
 
+    /// Delete the grapheme before the cursor
     pub fn delete_char(&mut self) {
         let field_value = self.current_field_value().to_string();
         let mut chars: Vec<char> = field_value.chars().collect();
+        let cursor = self.cursor_pos.min(chars.len());
 
-        if self.cursor_pos > 0 && !chars.is_empty() {
-            chars.remove(self.cursor_pos - 1);
-            self.cursor_pos -= 1;
+        if cursor > 0 {
+            self.record_undo(self.focused_field, Some(EditRun::Deleting));
+            let start = prev_boundary(&field_value, cursor);
+            chars.drain(start..cursor);
+            self.cursor_pos = start;
             self.set_current_field(chars.into_iter().collect());
         }
     }
 
+    /// Delete the grapheme under the cursor
     pub fn delete_char_forward(&mut self) {
         let field_value = self.current_field_value().to_string();
         let mut chars: Vec<char> = field_value.chars().collect();
 
         if self.cursor_pos < chars.len() {
-            chars.remove(self.cursor_pos);
+            self.record_undo(self.focused_field, Some(EditRun::Deleting));
+            let end = next_boundary(&field_value, self.cursor_pos);
+            chars.drain(self.cursor_pos..end);
             self.set_current_field(chars.into_iter().collect());
         }
     }
 
     pub fn move_cursor_left(&mut self) {
-        self.cursor_pos = self.cursor_pos.saturating_sub(1);
+        self.edit_run = None;
+        self.cursor_pos = prev_boundary(self.current_field_value(), self.cursor_pos);
     }
 
     pub fn move_cursor_right(&mut self) {
-        let len = self.current_field_value().chars().count();
-        self.cursor_pos = (self.cursor_pos + 1).min(len);
+        self.edit_run = None;
+        self.cursor_pos = next_boundary(self.current_field_value(), self.cursor_pos);
     }
 
     pub fn move_cursor_start(&mut self) {
+        self.edit_run = None;
         self.cursor_pos = 0;
     }
 
     pub fn move_cursor_end(&mut self) {
+        self.edit_run = None;
         self.cursor_pos = self.current_field_value().chars().count();
     }
 
     pub fn move_cursor_up(&mut self) {
+        self.edit_run = None;
         let content = self.current_field_value();
         let chars: Vec<char> = content.chars().collect();
         let cursor = self.cursor_pos.min(chars.len());
@@ -254,7 +484,7 @@ impl EditState {
                 line_start = i + 1;
             }
         }
-        let column = cursor - line_start;
+        let column = display_width(&chars[line_start..cursor].iter().collect::<String>());
 
         // If we're on the first line, go to start
         if line_start == 0 {
@@ -273,14 +503,13 @@ impl EditState {
             }
         }
 
-        // Calculate the length of the previous line
-        let prev_line_len = line_start - 1 - prev_line_start;
-
-        // Move to the same column on the previous line, or end of line if shorter
-        self.cursor_pos = prev_line_start + column.min(prev_line_len);
+        // Move to the same screen column on the previous line, or end of line if shorter
+        let prev_line: String = chars[prev_line_start..line_start - 1].iter().collect();
+        self.cursor_pos = prev_line_start + pos_at_column(&prev_line, column);
     }
 
     pub fn move_cursor_down(&mut self) {
+        self.edit_run = None;
         let content = self.current_field_value();
         let chars: Vec<char> = content.chars().collect();
         let cursor = self.cursor_pos.min(chars.len());
@@ -295,7 +524,7 @@ impl EditState {
                 line_start = i + 1;
             }
         }
-        let column = cursor - line_start;
+        let column = display_width(&chars[line_start..cursor].iter().collect::<String>());
 
         // Find the start of the next line
         let mut next_line_start = None;
@@ -321,33 +550,33 @@ impl EditState {
             }
         }
 
-        // Calculate the length of the next line
-        let next_line_len = next_line_end - next_start;
-
-        // Move to the same column on the next line, or end of line if shorter
-        self.cursor_pos = next_start + column.min(next_line_len);
+        // Move to the same screen column on the next line, or end of line if shorter
+        let next_line: String = chars[next_start..next_line_end].iter().collect();
+        self.cursor_pos = next_start + pos_at_column(&next_line, column);
     }
 
     pub fn next_field(&mut self) {
+        self.edit_run = None;
         self.focused_field = self.focused_field.next(self.item.category);
         self.cursor_pos = self.current_field_value().chars().count();
     }
 
     pub fn prev_field(&mut self) {
+        self.edit_run = None;
         self.focused_field = self.focused_field.prev(self.item.category);
         self.cursor_pos = self.current_field_value().chars().count();
     }
 }
 
-pub fn draw(frame: &mut Frame, state: &EditState) {
+pub fn draw(frame: &mut Frame, state: &mut EditState, vim_mode: bool) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(1), // Title bar
-            Constraint::Length(7), // Form fields (top section)
-            Constraint::Length(6), // Description
-            Constraint::Min(0),    // Content
-            Constraint::Length(1), // Status bar
+            Constraint::Length(1),                                // Title bar
+            Constraint::Length(form_height(state.item.category)), // Form fields (top section)
+            Constraint::Length(6),                                // Description
+            Constraint::Min(0),                                   // Content
+            Constraint::Length(1),                                // Status bar
         ])
         .split(frame.area());
 
@@ -380,15 +609,19 @@ pub fn draw(frame: &mut Frame, state: &EditState) {
     draw_description_field(frame, chunks[2], state);
 
     // Content field
-    draw_content_field(frame, chunks[3], state);
+    draw_content_field(frame, chunks[3], state, vim_mode);
 
     // Status bar
-    draw_status_bar(frame, chunks[4], state);
+    draw_status_bar(frame, chunks[4], state, vim_mode);
 
     // Draw dropdown LAST so it appears on top of everything
     if state.show_category_dropdown {
         draw_category_dropdown(frame, category_field_rect, state);
     }
+
+    if let Some(ref message) = state.version_message {
+        draw_version_message_prompt(frame, message);
+    }
 }
 
 fn draw_form_fields(frame: &mut Frame, area: Rect, state: &EditState) -> Rect {
@@ -401,91 +634,62 @@ fn draw_form_fields(frame: &mut Frame, area: Rect, state: &EditState) -> Rect {
 
     let field_chunks = Layout::default()
         .direction(Direction::Vertical)
-        .constraints([
-            Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
-            Constraint::Length(1),
+        .constraints(vec![
+            Constraint::Length(1);
+            form_height(state.item.category) as usize - 2
         ])
         .split(inner);
 
-    // Name field
-    draw_field(
-        frame,
-        field_chunks[0],
-        "Name:     ",
-        &state.item.name,
-        state.focused_field == EditField::Name,
-        state.cursor_pos,
-    );
-
-    // Category field (with dropdown indicator)
-    let cat_display = format!("[{}] ▼", state.item.category.display_name());
-    draw_field(
-        frame,
-        field_chunks[1],
-        "Category: ",
-        &cat_display,
-        state.focused_field == EditField::Category,
-        0,
-    );
-
-    // Tags field
-    draw_field(
-        frame,
-        field_chunks[2],
-        "Tags:     ",
-        state.item.tags.as_deref().unwrap_or(""),
-        state.focused_field == EditField::Tags,
-        state.cursor_pos,
-    );
-
-    // Category-specific fields
-    match state.item.category {
-        Category::Agent | Category::Command => {
-            draw_field(
-                frame,
-                field_chunks[3],
-                "Model:    ",
-                state.item.model.as_deref().unwrap_or(""),
-                state.focused_field == EditField::Model,
-                state.cursor_pos,
-            );
-
-            let tools = state
-                .item
-                .tools
-                .as_deref()
-                .or(state.item.allowed_tools.as_deref())
-                .unwrap_or("");
-            draw_field(
-                frame,
-                field_chunks[4],
-                "Tools:    ",
-                tools,
-                state.focused_field == EditField::Tools,
-                state.cursor_pos,
-            );
-        }
-        Category::Skill => {
-            let tools = state.item.allowed_tools.as_deref().unwrap_or("");
+    let fields = EditField::form_fields(state.item.category);
+    for (field, area) in fields.iter().zip(field_chunks.iter()) {
+        let focused = state.focused_field == *field;
+        if *field == EditField::Category {
+            // Category field (with dropdown indicator)
+            let cat_display = format!("[{}] ▼", state.item.category.display_name());
+            draw_field(frame, *area, field.label(), &cat_display, focused, 0);
+        } else {
             draw_field(
                 frame,
-                field_chunks[3],
-                "Tools:    ",
-                tools,
-                state.focused_field == EditField::Tools,
+                *area,
+                field.label(),
+                state.field_value(*field),
+                focused,
                 state.cursor_pos,
             );
         }
-        Category::Prompt => {}
     }
 
+    // Links, picked in a popup, on the row after the last field
+    let links_row = fields.len();
+    let links = if state.links.is_empty() {
+        Span::styled("none (Ctrl+L to add)", Style::default().fg(Color::DarkGray))
+    } else {
+        Span::raw(
+            state
+                .links
+                .iter()
+                .map(|(_, name)| name.as_str())
+                .collect::<Vec<_>>()
+                .join(", "),
+        )
+    };
+    frame.render_widget(
+        Paragraph::new(Line::from(vec![
+            Span::styled("Links:    ", Style::default().fg(Color::Yellow)),
+            links,
+        ])),
+        field_chunks[links_row],
+    );
+
     // Return category field rect for dropdown positioning
     field_chunks[1]
 }
 
+/// Height of the form block: its fields, links, and the border
+fn form_height(category: Category) -> u16 {
+    EditField::form_fields(category).len() as u16 + 1 + 2
+}
+
 fn draw_field(
     frame: &mut Frame,
     area: Rect,
@@ -504,20 +708,9 @@ fn draw_field(
 
     let value_display = if focused {
         // Show cursor
-        let chars: Vec<char> = value.chars().collect();
-        let before: String = chars.iter().take(cursor).collect();
-        let cursor_char = chars.get(cursor).copied().unwrap_or(' ');
-        let after: String = chars.iter().skip(cursor + 1).collect();
-
-        vec![
-            label_span,
-            Span::raw(before),
-            Span::styled(
-                cursor_char.to_string(),
-                Style::default().bg(Color::White).fg(Color::Black),
-            ),
-            Span::raw(after),
-        ]
+        let mut spans = vec![label_span];
+        spans.extend(cursor_spans(value, cursor));
+        spans
     } else {
         vec![label_span, Span::styled(value, style)]
     };
@@ -535,9 +728,15 @@ fn draw_description_field(frame: &mut Frame, area: Rect, state: &EditState) {
         Color::DarkGray
     };
 
-    let required = match state.item.category {
-        Category::Agent | Category::Skill => " (required)",
-        _ => " (optional)",
+    let required = if state
+        .item
+        .category
+        .required_fields()
+        .contains(&"description")
+    {
+        " (required)"
+    } else {
+        " (optional)"
     };
 
     let block = Block::default()
@@ -560,7 +759,7 @@ fn draw_description_field(frame: &mut Frame, area: Rect, state: &EditState) {
     frame.render_widget(paragraph.wrap(Wrap { trim: false }), inner);
 }
 
-fn draw_content_field(frame: &mut Frame, area: Rect, state: &EditState) {
+fn draw_content_field(frame: &mut Frame, area: Rect, state: &mut EditState, vim_mode: bool) {
     let focused = state.focused_field == EditField::Content;
     let border_color = if focused {
         Color::Cyan
@@ -568,39 +767,135 @@ fn draw_content_field(frame: &mut Frame, area: Rect, state: &EditState) {
         Color::DarkGray
     };
 
-    let block = Block::default()
+    let content = &state.item.content;
+    let (cursor_line, cursor_col) = line_and_column(content, state.cursor_pos);
+
+    let mut block = Block::default()
         .title(" Content (required) ")
+        .title_bottom(Line::styled(
+            format!(
+                " {} words, ~{} tokens ",
+                content.split_whitespace().count(),
+                estimate_tokens(content)
+            ),
+            Style::default().fg(Color::DarkGray),
+        ))
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color));
+    if focused {
+        block = block.title_bottom(
+            Line::styled(
+                format!(" Ln {}, Col {} ", cursor_line + 1, cursor_col + 1),
+                Style::default().fg(Color::DarkGray),
+            )
+            .right_aligned(),
+        );
+    }
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
-    let content = &state.item.content;
-    let paragraph = if focused {
+    let line_count = content.split('\n').count();
+    let gutter_width = line_count.to_string().len() as u16 + 1;
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Length(gutter_width), Constraint::Min(0)])
+        .split(inner);
+    let (gutter_area, text_area) = (columns[0], columns[1]);
+
+    let text = if focused {
         // Show with cursor - render content with cursor character highlighted
-        let text = render_text_with_cursor(content, state.cursor_pos);
-        Paragraph::new(text)
+        let mut text = render_text_with_cursor(content, state.cursor_pos);
+
+        // Highlight the lines selected in vim's visual line mode
+        if let (true, VimMode::VisualLine { anchor }) = (vim_mode, state.vim.mode) {
+            let chars: Vec<char> = content.chars().collect();
+            let line_of = |pos: usize| {
+                chars[..pos.min(chars.len())]
+                    .iter()
+                    .filter(|c| **c == '\n')
+                    .count()
+            };
+            let (first, last) = (line_of(anchor), line_of(state.cursor_pos));
+            for line in text
+                .lines
+                .iter_mut()
+                .take(first.max(last) + 1)
+                .skip(first.min(last))
+            {
+                line.style = Style::default().bg(Color::Blue);
+            }
+        }
+        text
     } else {
-        Paragraph::new(content.as_str())
+        Text::from(
+            content
+                .split('\n')
+                .map(|line| Line::raw(line.to_string()))
+                .collect::<Vec<_>>(),
+        )
     };
 
+    // Number each line on its first row; wrapped rows get a blank gutter
+    let rows: Vec<usize> = text
+        .lines
+        .iter()
+        .map(|line| wrapped_rows(line.clone(), text_area.width))
+        .collect();
+    let mut gutter = Vec::new();
+    for (i, count) in rows.iter().enumerate() {
+        let style = if focused && i == cursor_line {
+            Style::default().fg(Color::Yellow)
+        } else {
+            Style::default().fg(Color::DarkGray)
+        };
+        gutter.push(Line::styled(
+            format!("{:>width$} ", i + 1, width = gutter_width as usize - 1),
+            style,
+        ));
+        gutter.extend(std::iter::repeat_n(Line::raw(""), count - 1));
+    }
+
+    // Keep the cursor's row in view
+    if focused {
+        let (before, at, _) = split_at_cursor(content, state.cursor_pos);
+        let line_before = before.rsplit('\n').next().unwrap_or_default();
+        let upto = format!("{}{}", line_before, at.trim_end_matches('\n'));
+        let cursor_row = rows[..cursor_line.min(rows.len())].iter().sum::<usize>()
+            + wrapped_rows(Line::raw(upto), text_area.width)
+            - 1;
+        let height = text_area.height as usize;
+        let scroll = state.content_scroll as usize;
+        if cursor_row < scroll {
+            state.content_scroll = cursor_row as u16;
+        } else if height > 0 && cursor_row >= scroll + height {
+            state.content_scroll = (cursor_row + 1 - height) as u16;
+        }
+    }
+
     frame.render_widget(
-        paragraph
+        Paragraph::new(gutter).scroll((state.content_scroll, 0)),
+        gutter_area,
+    );
+    frame.render_widget(
+        Paragraph::new(text)
             .wrap(Wrap { trim: false })
             .scroll((state.content_scroll, 0)),
-        inner,
+        text_area,
     );
 }
 
+/// Zero-based line and screen column of `cursor_pos` in `content`
+fn line_and_column(content: &str, cursor_pos: usize) -> (usize, usize) {
+    let (before, _, _) = split_at_cursor(content, cursor_pos);
+    let line = before.matches('\n').count();
+    let column = display_width(before.rsplit('\n').next().unwrap_or_default());
+    (line, column)
+}
+
 /// Render text with a cursor at the given position, preserving newlines naturally
 fn render_text_with_cursor(content: &str, cursor_pos: usize) -> Text<'static> {
-    let chars: Vec<char> = content.chars().collect();
-    let cursor_pos = cursor_pos.min(chars.len());
-
-    let before: String = chars.iter().take(cursor_pos).collect();
-    let cursor_char = chars.get(cursor_pos).copied().unwrap_or(' ');
-    let after: String = chars.iter().skip(cursor_pos + 1).collect();
+    let (before, cursor_char, after) = split_at_cursor(content, cursor_pos);
 
     let mut lines: Vec<Line<'static>> = Vec::new();
 
@@ -616,7 +911,7 @@ fn render_text_with_cursor(content: &str, cursor_pos: usize) -> Text<'static> {
             let mut spans = vec![Span::raw(line.to_string())];
 
             // If cursor is on a newline, show space cursor and start new line for after
-            if cursor_char == '\n' {
+            if cursor_char.ends_with('\n') {
                 spans.push(Span::styled(
                     " ".to_string(),
                     Style::default().bg(Color::White).fg(Color::Black),
@@ -670,14 +965,18 @@ fn render_multiline_with_cursor(content: &str, cursor_pos: usize) -> Vec<Line<'s
     text.lines.into_iter().collect()
 }
 
-fn draw_status_bar(frame: &mut Frame, area: Rect, state: &EditState) {
-    // Show dropdown-specific shortcuts when dropdown is open
-    if state.show_category_dropdown {
-        let shortcuts = [
-            ("j/k ", "navigate"),
-            ("Enter ", "select"),
-            ("ESC ", "close"),
-        ];
+fn draw_status_bar(frame: &mut Frame, area: Rect, state: &EditState, vim_mode: bool) {
+    // Show dropdown- or prompt-specific shortcuts while one is open
+    if state.show_category_dropdown || state.version_message.is_some() {
+        let shortcuts: &[(&str, &str)] = if state.show_category_dropdown {
+            &[
+                ("j/k ", "navigate"),
+                ("Enter ", "select"),
+                ("ESC ", "close"),
+            ]
+        } else {
+            &[("Enter ", "save"), ("ESC ", "back to editing")]
+        };
 
         let spans: Vec<Span> = shortcuts
             .iter()
@@ -707,7 +1006,11 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, state: &EditState) {
     {
         shortcuts.push(("C-a ", "ai-assist"));
     }
+    if state.focused_field == EditField::Content {
+        shortcuts.push(("C-e ", "$EDITOR"));
+    }
 
+    shortcuts.push(("C-l ", "links"));
     shortcuts.push(("Ctrl+S ", "save"));
     shortcuts.push(("ESC ", "cancel"));
 
@@ -715,7 +1018,7 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, state: &EditState) {
         shortcuts.push(("", "[unsaved]"));
     }
 
-    let spans: Vec<Span> = shortcuts
+    let mut spans: Vec<Span> = shortcuts
         .iter()
         .flat_map(|(key, action)| {
             if key.is_empty() {
@@ -735,19 +1038,39 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, state: &EditState) {
         })
         .collect();
 
+    if vim_mode && state.focused_field == EditField::Content {
+        spans.insert(
+            0,
+            Span::styled(
+                format!("-- {} -- ", state.vim.mode.label()),
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD),
+            ),
+        );
+    }
+
+    if let Some(ref notice) = state.notice {
+        spans.push(Span::styled(
+            format!(" {}", notice),
+            Style::default().fg(Color::Red),
+        ));
+    }
+
     let status = Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::Black));
 
     frame.render_widget(status, area);
 }
 
 fn draw_category_dropdown(frame: &mut Frame, anchor: Rect, state: &EditState) {
-    // Position dropdown below the category field
-    let dropdown_area = Rect {
-        x: anchor.x + 10, // After "Category: "
-        y: anchor.y + 1,
-        width: 15,
-        height: 6, // 4 items + 2 for border
-    };
+    // Position dropdown below the category field, after "Category: "
+    let dropdown_area = dropdown_area(
+        anchor.x + 10,
+        anchor.y + 1,
+        15,
+        Category::all().len() as u16 + 2,
+        frame.area(),
+    );
 
     // Clear the area behind dropdown
     frame.render_widget(Clear, dropdown_area);
@@ -779,6 +1102,35 @@ fn draw_category_dropdown(frame: &mut Frame, anchor: Rect, state: &EditState) {
         ));
     }
 
-    let paragraph = Paragraph::new(lines);
+    // Scroll when the screen is too short to show every category
+    let offset = list_offset(state.category_dropdown_index, inner.height);
+    let paragraph = Paragraph::new(lines).scroll((offset, 0));
     frame.render_widget(paragraph, inner);
 }
+
+fn draw_version_message_prompt(frame: &mut Frame, message: &str) {
+    let area = frame.area();
+    let width = area.width.saturating_sub(4).min(60);
+    let prompt_area = Rect {
+        x: area.x + (area.width.saturating_sub(width)) / 2,
+        y: area.y + area.height.saturating_sub(3) / 2,
+        width,
+        height: 3,
+    }
+    .clamp(area);
+
+    // Clear the area behind the prompt
+    frame.render_widget(Clear, prompt_area);
+
+    let block = Block::default()
+        .title(" Change message (optional) ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let line = Line::from(vec![
+        Span::raw(message.to_string()),
+        Span::styled(" ", Style::default().bg(Color::White)),
+    ]);
+
+    frame.render_widget(Paragraph::new(line).block(block), prompt_area);
+}
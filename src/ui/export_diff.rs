@@ -0,0 +1,205 @@
+use crate::export::{ExportProfile, ExportTarget};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use similar::{ChangeTag, TextDiff};
+use std::path::PathBuf;
+
+/// Pending export that would overwrite a file changed outside grimoire
+pub struct ExportDiffState {
+    pub target: ExportTarget,
+    /// Profile the export goes through, when one was picked
+    pub profile: Option<ExportProfile>,
+    pub path: PathBuf,
+    pub lines: Vec<DiffLine>,
+    pub scroll: u16,
+    /// Item whose exported file was just edited outside grimoire, when the
+    /// diff was opened for that rather than for an export
+    pub edited_item: Option<i64>,
+}
+
+pub enum DiffLine {
+    Hunk(String),
+    Context(String),
+    Removed(String),
+    Added(String),
+}
+
+impl ExportDiffState {
+    /// Diff the file on disk (`-`) against what grimoire would write (`+`)
+    pub fn new(target: ExportTarget, path: PathBuf, on_disk: &str, rendered: &str) -> Self {
+        Self {
+            target,
+            profile: None,
+            path,
+            lines: diff_lines(on_disk, rendered),
+            scroll: 0,
+            edited_item: None,
+        }
+    }
+
+    /// Diff an exported file edited outside grimoire against the item
+    pub fn external_edit(item_id: i64, path: PathBuf, on_disk: &str, rendered: &str) -> Self {
+        Self {
+            edited_item: Some(item_id),
+            ..Self::new(ExportTarget::Claude, path, on_disk, rendered)
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        if (self.scroll as usize) + 1 < self.lines.len() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &ExportDiffState) {
+    let area = centered_rect(80, 80, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let file_name = state
+        .path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+
+    let title = match state.edited_item {
+        Some(_) => format!(" {} changed outside grimoire ", file_name),
+        None => format!(" Overwrite {}? ", file_name),
+    };
+    let block = Block::default()
+        .title(title)
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Path and legend
+            Constraint::Min(1),    // Diff
+            Constraint::Length(1), // Footer with keybindings
+        ])
+        .split(inner);
+
+    let header = Paragraph::new(vec![
+        Line::from(Span::styled(
+            state.path.display().to_string(),
+            Style::default().fg(Color::White),
+        )),
+        Line::from(vec![
+            Span::styled("- on disk", Style::default().fg(Color::Red)),
+            Span::raw("  "),
+            Span::styled("+ grimoire", Style::default().fg(Color::Green)),
+        ]),
+    ]);
+    frame.render_widget(header, chunks[0]);
+
+    let lines = styled_lines(&state.lines);
+
+    frame.render_widget(Paragraph::new(lines).scroll((state.scroll, 0)), chunks[1]);
+
+    // Footer
+    let mut keys = vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(" scroll  "),
+    ];
+    if state.edited_item.is_some() {
+        keys.push(Span::styled("r", Style::default().fg(Color::Yellow)));
+        keys.push(Span::raw(" re-import  "));
+    }
+    keys.extend([
+        Span::styled("y", Style::default().fg(Color::Yellow)),
+        Span::raw(" overwrite  "),
+        Span::styled("n/ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(if state.edited_item.is_some() {
+            " leave it"
+        } else {
+            " cancel"
+        }),
+    ]);
+    let footer = Paragraph::new(Line::from(keys)).style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(footer, chunks[2]);
+}
+
+/// Unified line diff of `old` (`-`) against `new` (`+`), with @@ hunk
+/// headers and three lines of context
+pub fn diff_lines(old: &str, new: &str) -> Vec<DiffLine> {
+    let diff = TextDiff::from_lines(old, new);
+    let mut lines = Vec::new();
+
+    for group in diff.grouped_ops(3) {
+        let (first, last) = (&group[0], &group[group.len() - 1]);
+        lines.push(DiffLine::Hunk(format!(
+            "@@ -{},{} +{},{} @@",
+            first.old_range().start + 1,
+            last.old_range().end - first.old_range().start,
+            first.new_range().start + 1,
+            last.new_range().end - first.new_range().start,
+        )));
+
+        for op in &group {
+            for change in diff.iter_changes(op) {
+                let text = change.value().trim_end_matches('\n').to_string();
+                lines.push(match change.tag() {
+                    ChangeTag::Equal => DiffLine::Context(text),
+                    ChangeTag::Delete => DiffLine::Removed(text),
+                    ChangeTag::Insert => DiffLine::Added(text),
+                });
+            }
+        }
+    }
+
+    lines
+}
+
+pub fn styled_lines(lines: &[DiffLine]) -> Vec<Line<'static>> {
+    lines
+        .iter()
+        .map(|line| match line {
+            DiffLine::Hunk(text) => Line::styled(text.clone(), Style::default().fg(Color::Cyan)),
+            DiffLine::Context(text) => {
+                Line::styled(format!(" {}", text), Style::default().fg(Color::DarkGray))
+            }
+            DiffLine::Removed(text) => {
+                Line::styled(format!("-{}", text), Style::default().fg(Color::Red))
+            }
+            DiffLine::Added(text) => {
+                Line::styled(format!("+{}", text), Style::default().fg(Color::Green))
+            }
+        })
+        .collect()
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
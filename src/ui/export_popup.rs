@@ -0,0 +1,155 @@
+use crate::export::{ExportOptions, ExportProfile, ExportTarget};
+use crate::models::Item;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// The export targets, followed by the profiles from Settings
+#[derive(Default)]
+pub struct ExportPopupState {
+    pub selected: usize,
+    pub profiles: Vec<ExportProfile>,
+}
+
+impl ExportPopupState {
+    /// Offer `profiles` after the targets, keeping the selection when it
+    /// is still there
+    pub fn set_profiles(&mut self, profiles: Vec<ExportProfile>) {
+        self.profiles = profiles;
+        if self.selected >= self.len() {
+            self.selected = 0;
+        }
+    }
+
+    fn len(&self) -> usize {
+        ExportTarget::all().len() + self.profiles.len()
+    }
+
+    pub fn select_next(&mut self) {
+        self.selected = (self.selected + 1) % self.len();
+    }
+
+    pub fn select_prev(&mut self) {
+        let len = self.len();
+        self.selected = (self.selected + len - 1) % len;
+    }
+
+    /// The profile selected, if a profile rather than a target is
+    pub fn selected_profile(&self) -> Option<&ExportProfile> {
+        self.profiles
+            .get(self.selected.checked_sub(ExportTarget::all().len())?)
+    }
+
+    /// The selected target, or the selected profile's
+    pub fn selected_target(&self) -> ExportTarget {
+        match self.selected_profile() {
+            Some(profile) => profile.target,
+            None => ExportTarget::all()[self.selected],
+        }
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &ExportPopupState, item: &Item, options: &ExportOptions) {
+    let height = state.len() as u16 + 4;
+    let area = centered_rect_fixed(50, height, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Export: {} ", item.name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Targets
+            Constraint::Length(1), // Footer with keybindings
+        ])
+        .split(inner);
+
+    let mut lines = Vec::new();
+    let profiles = state.profiles.iter().map(|profile| {
+        (
+            profile.name.as_str(),
+            profile.target,
+            profile.options(options),
+        )
+    });
+    let rows = ExportTarget::all()
+        .iter()
+        .map(|target| (target.display_name(), *target, options.clone()))
+        .chain(profiles);
+    for (i, (name, target, options)) in rows.enumerate() {
+        let is_selected = i == state.selected;
+        let supported = target.supports(item.category);
+        let prefix = if is_selected { "> " } else { "  " };
+
+        let style = match (is_selected, supported) {
+            (true, true) => Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+            (true, false) => Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::BOLD),
+            (false, true) => Style::default(),
+            (false, false) => Style::default().fg(Color::DarkGray),
+        };
+
+        lines.push(Line::from(vec![
+            Span::styled(format!("{}{:14} ", prefix, name), style),
+            Span::styled(
+                target.destination(&options),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    }
+
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    // Footer
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(" select  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" export  "),
+        Span::styled("p", Style::default().fg(Color::Yellow)),
+        Span::raw(" to path  "),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(" cancel"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(footer, chunks[1]);
+}
+
+fn centered_rect_fixed(percent_x: u16, height: u16, r: Rect) -> Rect {
+    // Center vertically with fixed height
+    let vertical_padding = r.height.saturating_sub(height) / 2;
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(vertical_padding),
+            Constraint::Length(height),
+            Constraint::Min(0),
+        ])
+        .split(r);
+
+    // Center horizontally with percentage width
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
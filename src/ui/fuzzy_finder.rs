@@ -0,0 +1,335 @@
+use crate::models::Item;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Quick open by name (Ctrl+P): items whose name or tags contain the query
+/// as a subsequence, best matches first
+pub struct FuzzyFinderState {
+    pub query: String,
+    items: Vec<Item>,
+    pub matches: Vec<FuzzyMatch>,
+    pub selected: usize,
+}
+
+pub struct FuzzyMatch {
+    /// Index into the finder's items
+    pub index: usize,
+    pub score: i64,
+    /// Char positions of the matched letters in the item name
+    pub positions: Vec<usize>,
+}
+
+impl FuzzyFinderState {
+    pub fn new(items: Vec<Item>) -> Self {
+        let mut state = Self {
+            query: String::new(),
+            items,
+            matches: Vec::new(),
+            selected: 0,
+        };
+        state.update();
+        state
+    }
+
+    pub fn insert_char(&mut self, c: char) {
+        self.query.push(c);
+        self.update();
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        self.query.extend(s.chars().filter(|c| !c.is_control()));
+        self.update();
+    }
+
+    pub fn delete_char(&mut self) {
+        self.query.pop();
+        self.update();
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = (self.selected + 1) % self.matches.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.matches.is_empty() {
+            self.selected = (self.selected + self.matches.len() - 1) % self.matches.len();
+        }
+    }
+
+    pub fn selected_item(&self) -> Option<&Item> {
+        let m = self.matches.get(self.selected)?;
+        self.items.get(m.index)
+    }
+
+    /// Rescore every item against the query. Names count fully, tags at
+    /// half weight; ties keep the items' original (recent first) order.
+    fn update(&mut self) {
+        let query: String = self.query.chars().filter(|c| !c.is_whitespace()).collect();
+
+        self.matches = self
+            .items
+            .iter()
+            .enumerate()
+            .filter_map(|(index, item)| {
+                if query.is_empty() {
+                    return Some(FuzzyMatch {
+                        index,
+                        score: 0,
+                        positions: Vec::new(),
+                    });
+                }
+
+                let by_name = fuzzy_match(&query, &item.name);
+                let by_tags = fuzzy_match(&query, &item.tags_vec().join(" "))
+                    .map(|(score, _)| (score / 2, Vec::new()));
+                let (score, positions) = match (by_name, by_tags) {
+                    (Some(name), Some(tags)) if tags.0 > name.0 => tags,
+                    (Some(name), _) => name,
+                    (None, tags) => tags?,
+                };
+                Some(FuzzyMatch {
+                    index,
+                    score,
+                    positions,
+                })
+            })
+            .collect();
+
+        self.matches.sort_by_key(|m| std::cmp::Reverse(m.score));
+        self.selected = 0;
+    }
+}
+
+/// Score `query` as a case-insensitive subsequence of `text`, with the
+/// positions of the matched chars. Runs of consecutive letters and letters
+/// at the start of a word score higher; skipped chars cost a little. Each
+/// place the first query letter occurs is tried as a start.
+pub fn fuzzy_match(query: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let query: Vec<char> = query.chars().flat_map(char::to_lowercase).collect();
+    let text: Vec<char> = text.chars().collect();
+    let lower: Vec<char> = text
+        .iter()
+        .map(|c| c.to_lowercase().next().unwrap_or(*c))
+        .collect();
+    let first = *query.first()?;
+
+    let mut best: Option<(i64, Vec<usize>)> = None;
+    for start in (0..lower.len()).filter(|i| lower[*i] == first) {
+        let mut positions = vec![start];
+        let mut pos = start + 1;
+        for q in &query[1..] {
+            match lower[pos..].iter().position(|c| c == q) {
+                Some(offset) => {
+                    positions.push(pos + offset);
+                    pos += offset + 1;
+                }
+                None => break,
+            }
+        }
+        if positions.len() < query.len() {
+            // Later starts only have fewer chars left to match
+            break;
+        }
+
+        let score = score_positions(&text, &positions);
+        if best.as_ref().is_none_or(|(b, _)| score > *b) {
+            best = Some((score, positions));
+        }
+    }
+    best
+}
+
+fn score_positions(text: &[char], positions: &[usize]) -> i64 {
+    let mut score = 0;
+    for (i, &pos) in positions.iter().enumerate() {
+        score += 16;
+
+        let word_start = pos == 0
+            || !text[pos - 1].is_alphanumeric()
+            || (text[pos - 1].is_lowercase() && text[pos].is_uppercase());
+        if word_start {
+            score += 10;
+        }
+
+        match i.checked_sub(1).map(|prev| positions[prev]) {
+            Some(prev) if pos == prev + 1 => score += 15,
+            Some(prev) => score -= (pos - prev - 1).min(10) as i64,
+            // Matches further into the text rank a little lower
+            None => score -= pos.min(10) as i64,
+        }
+    }
+    score
+}
+
+pub fn draw(frame: &mut Frame, state: &FuzzyFinderState) {
+    let area = centered_rect(80, 70, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Find Item ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let rows = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Query
+            Constraint::Min(1),    // Results and preview
+            Constraint::Length(1), // Footer with keybindings
+        ])
+        .split(inner);
+
+    let input = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("> ", Style::default().fg(Color::Yellow)),
+            Span::raw(state.query.clone()),
+            Span::styled("█", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("  {}/{}", state.matches.len(), state.items.len()),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+        Line::raw(""),
+    ]);
+    frame.render_widget(input, rows[0]);
+
+    let columns = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([Constraint::Percentage(40), Constraint::Percentage(60)])
+        .split(rows[1]);
+
+    draw_results(frame, columns[0], state);
+    draw_preview(frame, columns[1], state.selected_item());
+
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("↑/↓ C-n/C-p", Style::default().fg(Color::Yellow)),
+        Span::raw(" select  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" open  "),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(" close"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(footer, rows[2]);
+}
+
+fn draw_results(frame: &mut Frame, area: Rect, state: &FuzzyFinderState) {
+    let height = area.height as usize;
+    // Keep the selection in view
+    let offset = state.selected.saturating_sub(height.saturating_sub(1));
+
+    let lines: Vec<Line> = state
+        .matches
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .take(height)
+        .map(|(i, m)| {
+            let item = &state.items[m.index];
+            let is_selected = i == state.selected;
+            let base = if is_selected {
+                Style::default().bg(Color::DarkGray)
+            } else {
+                Style::default()
+            };
+
+            let mut spans = vec![Span::styled(if is_selected { "> " } else { "  " }, base)];
+            for (pos, c) in item.name.chars().enumerate() {
+                let style = if m.positions.contains(&pos) {
+                    base.fg(Color::Yellow).add_modifier(Modifier::BOLD)
+                } else {
+                    base
+                };
+                spans.push(Span::styled(c.to_string(), style));
+            }
+            spans.push(Span::styled(
+                format!("  {}", item.category.as_str()),
+                base.fg(Color::DarkGray),
+            ));
+            Line::from(spans)
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), area);
+}
+
+fn draw_preview(frame: &mut Frame, area: Rect, item: Option<&Item>) {
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let Some(item) = item else {
+        frame.render_widget(
+            Paragraph::new("No matches")
+                .style(Style::default().fg(Color::DarkGray))
+                .block(block),
+            area,
+        );
+        return;
+    };
+
+    let mut lines = Vec::new();
+    if !item.tags_vec().is_empty() {
+        let tags: Vec<String> = item.tags_vec().iter().map(|t| format!("#{}", t)).collect();
+        lines.push(Line::styled(
+            tags.join(" "),
+            Style::default().fg(Color::Cyan),
+        ));
+    }
+    if let Some(description) = item.description.as_deref().filter(|d| !d.is_empty()) {
+        lines.push(Line::styled(
+            description.to_string(),
+            Style::default()
+                .fg(Color::Gray)
+                .add_modifier(Modifier::ITALIC),
+        ));
+    }
+    if !lines.is_empty() {
+        lines.push(Line::raw(""));
+    }
+    lines.extend(
+        item.content
+            .lines()
+            .take(area.height as usize)
+            .map(|line| Line::raw(line.to_string())),
+    );
+
+    frame.render_widget(
+        Paragraph::new(lines)
+            .block(block)
+            .wrap(Wrap { trim: false }),
+        area,
+    );
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
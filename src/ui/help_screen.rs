@@ -86,20 +86,57 @@ fn get_help_content() -> Vec<Line<'static>> {
                 ("G", "Go to bottom"),
                 ("Ctrl+d", "Page down"),
                 ("Ctrl+u", "Page up"),
+                ("'", "Jump to the item whose name starts with what you type"),
             ],
         ),
         (
             "ACTIONS",
             vec![
-                ("Enter", "View selected item"),
+                ("Enter", "View selected item (copy again in Recent copies)"),
                 ("e", "Edit selected item"),
                 ("n", "Create new item"),
+                ("N", "Generate new item with AI from a description"),
+                ("P", "New item from the clipboard"),
                 ("c / yy", "Copy content to clipboard"),
-                ("dd", "Delete item (with confirmation)"),
-                ("x", "Export to .claude/ directory"),
+                ("yc", "Copy as exported, with frontmatter"),
+                ("yn / yp", "Copy name / last exported path"),
+                ("yj / ya", "Copy the whole item as JSON / YAML"),
+                ("dd", "Move item to trash (with confirmation)"),
+                ("u", "Undo delete / restore from trash"),
+                ("E", "Empty trash (in Trash)"),
+                ("K", "Keep item, off the Stale list (in Stale)"),
+                ("f", "Toggle favorite"),
+                ("A", "Archive / unarchive item"),
+                ("p", "Toggle preview pane"),
+                ("o", "Sort by updated / created / name / category"),
+                ("< / >", "Narrow / widen sidebar"),
+                ("r", "Rename/merge focused tag, rename collection"),
+                ("Space", "Add / remove focused tag in filter"),
+                ("a", "Tag filter: match any / all"),
+                ("dd", "Delete focused tag or collection (sidebar)"),
+                ("x", "Export (Claude Code, Cursor, Copilot, AGENTS.md)"),
+                ("X", "Export all items in the current list"),
+                ("Space", "Mark item for bulk actions"),
+                ("v", "Start / mark a range of items"),
+                ("t", "Add / remove tags on marked items"),
+                ("C", "Add / remove marked items in collections"),
+                ("I", "Import from .claude/ directory"),
+                ("R", "Import from a git repository"),
+                ("U", "Import from a URL or gist"),
+                ("S", "Share as a GitHub gist (URL copied)"),
+                ("B", "Export list or marked items as a bundle"),
+                ("O", "Import a .grimoire bundle"),
+                (
+                    "m / t / b",
+                    "On an import conflict: keep mine / take theirs / keep both",
+                ),
+                ("D", "Two-way sync with the export path"),
+                ("V", "Switch vault"),
                 ("/", "Open search"),
+                ("Ctrl+P", "Fuzzy find an item by name or tag"),
                 ("s", "Open settings"),
                 ("?", "Show this help"),
+                ("M", "Show recent messages and errors"),
                 ("q / ESC", "Quit / Back"),
             ],
         ),
@@ -119,7 +156,11 @@ fn get_help_content() -> Vec<Line<'static>> {
                 ("Tab", "Next field"),
                 ("Shift+Tab", "Previous field"),
                 ("Ctrl+S", "Save"),
+                ("Ctrl+Z / Y", "Undo / redo in the field"),
+                ("Ctrl+E", "Edit content in $EDITOR"),
+                ("Ctrl+L", "Link skills and commands"),
                 ("a", "AI assistant (in content field)"),
+                ("Esc / i", "Vim mode: normal / insert"),
                 ("ESC", "Cancel"),
             ],
         ),
@@ -129,6 +170,8 @@ fn get_help_content() -> Vec<Line<'static>> {
                 ("j / k", "Navigate results"),
                 ("Enter", "Select result"),
                 ("c", "Copy selected item"),
+                ("Ctrl+A", "Include archived items"),
+                ("Ctrl+F", "Only the current category or tags"),
                 ("ESC", "Close search"),
             ],
         ),
@@ -138,11 +181,32 @@ fn get_help_content() -> Vec<Line<'static>> {
                 ("j / k", "Scroll content"),
                 ("e", "Edit item"),
                 ("c / yy", "Copy content"),
+                ("yc", "Copy as exported, with frontmatter"),
+                ("yn / yp", "Copy name / last exported path"),
+                ("yj / ya", "Copy the whole item as JSON / YAML"),
                 ("x", "Export item"),
+                ("f", "Toggle favorite"),
+                ("A", "Archive / unarchive item"),
+                ("m", "Toggle rendered markdown"),
+                ("/", "Find in content"),
+                ("n / N", "Next / previous match"),
                 ("a", "AI assistant"),
                 ("ESC / q", "Back to list"),
             ],
         ),
+        (
+            "SETTINGS",
+            vec![
+                ("Tab", "Next field"),
+                ("Ctrl+S", "Save"),
+                ("Ctrl+B", "Back up the database"),
+                ("Ctrl+R", "Restore the database from a backup"),
+                ("Ctrl+T", "Test the LLM connection"),
+                ("Space", "Toggle auto-export / vim mode"),
+                ("Up/Down", "Frontmatter target on the Keys line"),
+                ("ESC", "Back"),
+            ],
+        ),
     ];
 
     let mut lines = Vec::new();
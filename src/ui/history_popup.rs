@@ -1,10 +1,11 @@
 use crate::db::ItemVersion;
+use crate::models::Item;
 use chrono::{NaiveDateTime, Utc};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
     text::{Line, Span},
-    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph},
+    widgets::{Block, Borders, Clear, List, ListItem, ListState, Paragraph, Wrap},
     Frame,
 };
 
@@ -12,6 +13,9 @@ pub struct HistoryState {
     pub versions: Vec<ItemVersion>,
     pub list_state: ListState,
     pub item_name: String,
+    /// Snapshot of the selected version, shown in the preview pane
+    pub preview: Option<Item>,
+    pub preview_scroll: u16,
 }
 
 impl HistoryState {
@@ -24,6 +28,8 @@ impl HistoryState {
             versions,
             list_state,
             item_name,
+            preview: None,
+            preview_scroll: 0,
         }
     }
 
@@ -33,6 +39,19 @@ impl HistoryState {
             .and_then(|i| self.versions.get(i))
     }
 
+    pub fn set_preview(&mut self, item: Option<Item>) {
+        self.preview = item;
+        self.preview_scroll = 0;
+    }
+
+    pub fn scroll_preview_down(&mut self) {
+        self.preview_scroll = self.preview_scroll.saturating_add(1);
+    }
+
+    pub fn scroll_preview_up(&mut self) {
+        self.preview_scroll = self.preview_scroll.saturating_sub(1);
+    }
+
     pub fn select_next(&mut self) {
         if self.versions.is_empty() {
             return;
@@ -69,8 +88,8 @@ impl HistoryState {
 }
 
 pub fn draw(frame: &mut Frame, state: &mut HistoryState) {
-    let popup_height = (state.versions.len() as u16 + 5).clamp(7, 15);
-    let area = centered_rect_fixed(50, popup_height, frame.area());
+    let popup_height = frame.area().height.saturating_sub(4).clamp(7, 30);
+    let area = centered_rect_fixed(80, popup_height, frame.area());
 
     // Clear the area behind the popup
     frame.render_widget(Clear, area);
@@ -86,11 +105,19 @@ pub fn draw(frame: &mut Frame, state: &mut HistoryState) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Min(1),    // Version list
+            Constraint::Min(1),    // Version list and preview
             Constraint::Length(1), // Footer with keybindings
         ])
         .split(inner);
 
+    let panes = Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage(40), // Version list
+            Constraint::Percentage(60), // Preview of the selected version
+        ])
+        .split(chunks[0]);
+
     // Version list
     let items: Vec<ListItem> = state
         .versions
@@ -102,7 +129,14 @@ pub fn draw(frame: &mut Frame, state: &mut HistoryState) {
             } else {
                 format!("v{}  {}", v.version, formatted_date)
             };
-            ListItem::new(Line::from(label))
+            let mut spans = vec![Span::raw(label)];
+            if let Some(ref message) = v.message {
+                spans.push(Span::styled(
+                    format!("  – {}", message),
+                    Style::default().fg(Color::Gray),
+                ));
+            }
+            ListItem::new(Line::from(spans))
         })
         .collect();
 
@@ -115,7 +149,9 @@ pub fn draw(frame: &mut Frame, state: &mut HistoryState) {
         )
         .highlight_symbol("> ");
 
-    frame.render_stateful_widget(list, chunks[0], &mut state.list_state);
+    frame.render_stateful_widget(list, panes[0], &mut state.list_state);
+
+    draw_preview(frame, panes[1], state);
 
     // Footer
     let footer = Paragraph::new(Line::from(vec![
@@ -123,6 +159,12 @@ pub fn draw(frame: &mut Frame, state: &mut HistoryState) {
         Span::raw(" view  "),
         Span::styled("r", Style::default().fg(Color::Yellow)),
         Span::raw(" restore  "),
+        Span::styled("d", Style::default().fg(Color::Yellow)),
+        Span::raw(" diff  "),
+        Span::styled("a", Style::default().fg(Color::Yellow)),
+        Span::raw(" summarize changes  "),
+        Span::styled("J/K", Style::default().fg(Color::Yellow)),
+        Span::raw(" scroll preview  "),
         Span::styled("ESC", Style::default().fg(Color::Yellow)),
         Span::raw(" close"),
     ]))
@@ -131,6 +173,39 @@ pub fn draw(frame: &mut Frame, state: &mut HistoryState) {
     frame.render_widget(footer, chunks[1]);
 }
 
+fn draw_preview(frame: &mut Frame, area: Rect, state: &HistoryState) {
+    let block = Block::default()
+        .borders(Borders::LEFT)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(ref item) = state.preview else {
+        return;
+    };
+
+    let mut lines = vec![Line::styled(
+        format!("v{}  {}", item.version, item.name),
+        Style::default()
+            .fg(Color::Cyan)
+            .add_modifier(Modifier::BOLD),
+    )];
+    if let Some(ref desc) = item.description {
+        lines.push(Line::styled(
+            desc.clone(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    lines.push(Line::raw(""));
+    lines.extend(item.content.lines().map(|l| Line::raw(l.to_string())));
+
+    let paragraph = Paragraph::new(lines)
+        .wrap(Wrap { trim: false })
+        .scroll((state.preview_scroll, 0));
+    frame.render_widget(paragraph, inner);
+}
+
 fn format_datetime(s: &str) -> String {
     // Parse SQLite datetime format and format nicely
     if let Ok(dt) = NaiveDateTime::parse_from_str(s, "%Y-%m-%d %H:%M:%S") {
@@ -0,0 +1,191 @@
+use super::export_diff::{diff_lines, styled_lines, DiffLine};
+use crate::db::ConflictMode;
+use crate::export::item_document;
+use crate::import::{ImportConflict, ImportSummary};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Imported items whose names are taken by different items in the library,
+/// shown one at a time with a diff until each has been settled
+pub struct ImportConflictState {
+    /// Where the items come from: a directory, repository, or bundle
+    pub source: String,
+    pub conflicts: Vec<ImportConflict>,
+    /// Choices made so far, one for each conflict from the first
+    pub decisions: Vec<ConflictMode>,
+    /// What the import did with the items that didn't conflict; empty for
+    /// a bundle, which is loaded once every conflict is settled
+    pub summary: ImportSummary,
+    /// Diff of the current conflict
+    pub lines: Vec<DiffLine>,
+    pub scroll: u16,
+}
+
+impl ImportConflictState {
+    pub fn new(source: String, conflicts: Vec<ImportConflict>, summary: ImportSummary) -> Self {
+        let mut state = Self {
+            source,
+            conflicts,
+            decisions: Vec::new(),
+            summary,
+            lines: Vec::new(),
+            scroll: 0,
+        };
+        state.show_current();
+        state
+    }
+
+    pub fn current(&self) -> Option<&ImportConflict> {
+        self.conflicts.get(self.decisions.len())
+    }
+
+    pub fn is_settled(&self) -> bool {
+        self.decisions.len() >= self.conflicts.len()
+    }
+
+    /// Settle the current conflict and move on to the next
+    pub fn decide(&mut self, mode: ConflictMode) {
+        if !self.is_settled() {
+            self.decisions.push(mode);
+            self.show_current();
+        }
+    }
+
+    /// Settle the current conflict and every one after it the same way
+    pub fn decide_rest(&mut self, mode: ConflictMode) {
+        while !self.is_settled() {
+            self.decisions.push(mode);
+        }
+    }
+
+    /// The conflicts paired with the choices made for them
+    pub fn into_decisions(self) -> (String, ImportSummary, Vec<(ImportConflict, ConflictMode)>) {
+        let decided = self.conflicts.into_iter().zip(self.decisions).collect();
+        (self.source, self.summary, decided)
+    }
+
+    pub fn scroll_down(&mut self) {
+        if (self.scroll as usize) + 1 < self.lines.len() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    /// Diff the library's item (`-`) against the imported one (`+`), as the
+    /// files they export to so that changed fields show too
+    fn show_current(&mut self) {
+        self.scroll = 0;
+        self.lines = self.current().map_or_else(Vec::new, |conflict| {
+            diff_lines(
+                &item_document(&conflict.existing),
+                &item_document(&conflict.incoming),
+            )
+        });
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &ImportConflictState) {
+    let Some(conflict) = state.current() else {
+        return;
+    };
+    let area = centered_rect(80, 80, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " Import conflict {}/{}: {} ",
+            state.decisions.len() + 1,
+            state.conflicts.len(),
+            conflict.incoming.name
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Yellow));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(2), // Source and legend
+            Constraint::Min(1),    // Diff
+            Constraint::Length(1), // Footer with keybindings
+        ])
+        .split(inner);
+
+    let header = Paragraph::new(vec![
+        Line::from(Span::styled(
+            format!("From {}", state.source),
+            Style::default().fg(Color::White),
+        )),
+        Line::from(vec![
+            Span::styled("- yours", Style::default().fg(Color::Red)),
+            Span::raw("  "),
+            Span::styled("+ imported", Style::default().fg(Color::Green)),
+        ]),
+    ]);
+    frame.render_widget(header, chunks[0]);
+
+    let lines = if state.lines.is_empty() {
+        // Only the category differs, which the files don't show
+        vec![Line::styled(
+            format!(
+                "Yours is {}, the imported one {}",
+                conflict.existing.category.display_name().to_lowercase(),
+                conflict.incoming.category.display_name().to_lowercase()
+            ),
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        styled_lines(&state.lines)
+    };
+    frame.render_widget(Paragraph::new(lines).scroll((state.scroll, 0)), chunks[1]);
+
+    let key = |text| Span::styled(text, Style::default().fg(Color::Yellow));
+    let footer = Paragraph::new(Line::from(vec![
+        key("m"),
+        Span::raw(" keep mine  "),
+        key("t"),
+        Span::raw(" take theirs  "),
+        key("b"),
+        Span::raw(" keep both  "),
+        key("M/T/B"),
+        Span::raw(" same for the rest  "),
+        key("j/k"),
+        Span::raw(" scroll  "),
+        key("ESC"),
+        Span::raw(" stop"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(footer, chunks[2]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
@@ -0,0 +1,153 @@
+use crate::models::{Category, Item};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+use std::collections::HashSet;
+
+/// Checklist of the skills and commands an item can depend on
+pub struct LinkPickerState {
+    pub candidates: Vec<Item>,
+    pub checked: HashSet<i64>,
+    pub selected: usize,
+}
+
+impl LinkPickerState {
+    pub fn new(candidates: Vec<Item>, linked: &[(i64, String)]) -> Self {
+        Self {
+            candidates,
+            checked: linked.iter().map(|(id, _)| *id).collect(),
+            selected: 0,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.candidates.is_empty() {
+            self.selected = (self.selected + 1) % self.candidates.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.candidates.is_empty() {
+            let len = self.candidates.len();
+            self.selected = (self.selected + len - 1) % len;
+        }
+    }
+
+    pub fn toggle(&mut self) {
+        if let Some(id) = self.candidates.get(self.selected).and_then(|i| i.id) {
+            if !self.checked.remove(&id) {
+                self.checked.insert(id);
+            }
+        }
+    }
+
+    /// Checked items in list order
+    pub fn links(&self) -> Vec<&Item> {
+        self.candidates
+            .iter()
+            .filter(|item| item.id.is_some_and(|id| self.checked.contains(&id)))
+            .collect()
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &LinkPickerState) {
+    let area = centered_rect(60, 70, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Links ({} selected) ", state.checked.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Candidates
+            Constraint::Length(1), // Footer with keybindings
+        ])
+        .split(inner);
+
+    let lines: Vec<Line> = if state.candidates.is_empty() {
+        vec![Line::styled(
+            "No skills or commands to link yet",
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        state
+            .candidates
+            .iter()
+            .enumerate()
+            .map(|(i, item)| {
+                let is_selected = i == state.selected;
+                let checked = item.id.is_some_and(|id| state.checked.contains(&id));
+                let style = if is_selected {
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default()
+                };
+                let kind = match item.category {
+                    Category::Command => "command",
+                    _ => "skill",
+                };
+
+                Line::from(vec![
+                    Span::styled(if is_selected { "> " } else { "  " }, style),
+                    Span::styled(if checked { "[x] " } else { "[ ] " }, style),
+                    Span::styled(item.name.clone(), style),
+                    Span::styled(format!("  {}", kind), Style::default().fg(Color::DarkGray)),
+                ])
+            })
+            .collect()
+    };
+
+    // Keep the selection visible in long lists
+    let visible = chunks[0].height as usize;
+    let scroll = state.selected.saturating_sub(visible.saturating_sub(1));
+    frame.render_widget(Paragraph::new(lines).scroll((scroll as u16, 0)), chunks[0]);
+
+    // Footer
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(" select  "),
+        Span::styled("Space", Style::default().fg(Color::Yellow)),
+        Span::raw(" toggle  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" done  "),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(" cancel"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(footer, chunks[1]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
@@ -0,0 +1,123 @@
+/// Columns of the main item table, as listed in the `list_columns` setting
+pub const DEFAULT_LIST_COLUMNS: &str = "name,category,version,sync,tags,updated";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ListColumn {
+    Name,
+    Category,
+    Version,
+    Sync,
+    Tags,
+    Updated,
+    Size,
+    Usage,
+}
+
+impl ListColumn {
+    pub fn all() -> &'static [ListColumn] {
+        &[
+            ListColumn::Name,
+            ListColumn::Category,
+            ListColumn::Version,
+            ListColumn::Sync,
+            ListColumn::Tags,
+            ListColumn::Updated,
+            ListColumn::Size,
+            ListColumn::Usage,
+        ]
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ListColumn::Name => "name",
+            ListColumn::Category => "category",
+            ListColumn::Version => "version",
+            ListColumn::Sync => "sync",
+            ListColumn::Tags => "tags",
+            ListColumn::Updated => "updated",
+            ListColumn::Size => "size",
+            ListColumn::Usage => "usage",
+        }
+    }
+
+    pub fn header(&self) -> &'static str {
+        match self {
+            ListColumn::Name => "NAME",
+            ListColumn::Category => "CATEGORY",
+            ListColumn::Version => "VER",
+            ListColumn::Sync => "SYNC",
+            ListColumn::Tags => "TAGS",
+            ListColumn::Updated => "UPDATED",
+            ListColumn::Size => "SIZE",
+            ListColumn::Usage => "USED",
+        }
+    }
+
+    /// Width used when the setting doesn't give one. The name column takes
+    /// whatever is left, so its width is a minimum.
+    pub fn default_width(&self) -> u16 {
+        match self {
+            ListColumn::Name => 15,
+            ListColumn::Category => 10,
+            ListColumn::Version => 4,
+            ListColumn::Sync => 4,
+            ListColumn::Tags => 15,
+            ListColumn::Updated => 12,
+            ListColumn::Size => 8,
+            ListColumn::Usage => 5,
+        }
+    }
+
+    fn from_str(s: &str) -> Option<Self> {
+        Self::all().iter().copied().find(|c| c.as_str() == s)
+    }
+}
+
+/// Parse a `list_columns` setting such as `name,tags:20,updated`: column
+/// names in display order, each with an optional `:width`. Returns an error
+/// naming the first entry that isn't a column or has a bad width.
+pub fn parse_list_columns(spec: &str) -> Result<Vec<(ListColumn, u16)>, String> {
+    let mut columns = Vec::new();
+    for entry in spec.split(',').map(str::trim).filter(|e| !e.is_empty()) {
+        let (name, width) = match entry.split_once(':') {
+            Some((name, width)) => (name.trim(), Some(width.trim())),
+            None => (entry, None),
+        };
+        let column = ListColumn::from_str(&name.to_lowercase())
+            .ok_or_else(|| format!("unknown column '{}'", name))?;
+        let width = match width {
+            Some(width) => width
+                .parse::<u16>()
+                .ok()
+                .filter(|w| (1..=200).contains(w))
+                .ok_or_else(|| format!("bad width for '{}'", name))?,
+            None => column.default_width(),
+        };
+        if !columns.iter().any(|(c, _)| *c == column) {
+            columns.push((column, width));
+        }
+    }
+    if columns.is_empty() {
+        return Err("no columns given".to_string());
+    }
+    Ok(columns)
+}
+
+/// Columns to draw for a setting, falling back to the defaults when it
+/// doesn't parse
+pub fn list_columns(spec: &str) -> Vec<(ListColumn, u16)> {
+    parse_list_columns(spec)
+        .or_else(|_| parse_list_columns(DEFAULT_LIST_COLUMNS))
+        .unwrap_or_default()
+}
+
+/// Content size in bytes, shortened for the size column
+pub fn format_size(bytes: usize) -> String {
+    if bytes < 1024 {
+        format!("{} B", bytes)
+    } else if bytes < 1024 * 1024 {
+        format!("{:.1} KB", bytes as f64 / 1024.0)
+    } else {
+        format!("{:.1} MB", bytes as f64 / (1024.0 * 1024.0))
+    }
+}
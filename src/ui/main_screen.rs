@@ -1,10 +1,17 @@
-use crate::app::{App, Focus};
+use super::list_columns::{format_size, list_columns, ListColumn};
+use super::{markdown, message_log, COPY_MENU};
+use crate::app::{
+    App, Focus, SpecialList, SIDEBAR_ARCHIVED_INDEX, SIDEBAR_CATEGORIES_START,
+    SIDEBAR_COLLECTIONS_START, SIDEBAR_COPIES_INDEX, SIDEBAR_FAVORITES_INDEX, SIDEBAR_STALE_INDEX,
+    SIDEBAR_TRASH_INDEX,
+};
+use crate::db::{SyncStatus, DEFAULT_VAULT};
 use crate::models::Category;
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style, Stylize},
-    text::{Line, Span},
-    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState},
+    text::{Line, Span, Text},
+    widgets::{Block, Borders, Cell, Paragraph, Row, Table, TableState, Wrap},
     Frame,
 };
 
@@ -23,28 +30,117 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         ])
         .split(frame.area());
 
-    draw_title_bar(frame, chunks[0]);
+    draw_title_bar(frame, chunks[0], &app.vault);
     draw_main_content(frame, chunks[1], app);
     draw_status_bar(frame, chunks[2], app);
 }
 
-fn draw_title_bar(frame: &mut Frame, area: Rect) {
-    let title = Paragraph::new(Line::from(vec![
-        Span::styled(" GRIMOIRE ", Style::default().fg(Color::Cyan).bold()),
-        Span::raw("                                                        "),
-        Span::styled("[?] Help", Style::default().fg(Color::DarkGray)),
-    ]));
+fn draw_title_bar(frame: &mut Frame, area: Rect, vault: &str) {
+    let mut spans = vec![Span::styled(
+        " GRIMOIRE ",
+        Style::default().fg(Color::Cyan).bold(),
+    )];
+    if vault != DEFAULT_VAULT {
+        spans.push(Span::styled(
+            format!("[{}]", vault),
+            Style::default().fg(Color::Magenta),
+        ));
+    }
+    spans.push(Span::raw(
+        "                                                        ",
+    ));
+    spans.push(Span::styled(
+        "[?] Help",
+        Style::default().fg(Color::DarkGray),
+    ));
+    let title = Paragraph::new(Line::from(spans));
     frame.render_widget(title, area);
 }
 
 fn draw_main_content(frame: &mut Frame, area: Rect, app: &mut App) {
     let chunks = Layout::default()
         .direction(Direction::Horizontal)
-        .constraints([Constraint::Length(20), Constraint::Min(0)])
+        .constraints([Constraint::Length(app.sidebar_width), Constraint::Min(0)])
         .split(area);
 
     draw_sidebar(frame, chunks[0], app);
-    draw_item_list(frame, chunks[1], app);
+
+    if app.show_preview {
+        let panes = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(chunks[1]);
+        draw_item_list(frame, panes[0], app);
+        draw_preview(frame, panes[1], app);
+    } else {
+        draw_item_list(frame, chunks[1], app);
+    }
+}
+
+/// Description and the start of the content of the selected item
+fn draw_preview(frame: &mut Frame, area: Rect, app: &App) {
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray))
+        .title(" Preview ");
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let Some(item) = app.selected_item() else {
+        return;
+    };
+
+    let description = item.description.as_deref().unwrap_or("No description");
+    let description_height = if inner.width == 0 {
+        1
+    } else {
+        (description.chars().count() as u16)
+            .div_ceil(inner.width)
+            .clamp(1, 4)
+    };
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(description_height), // Description
+            Constraint::Length(1),                  // Separator
+            Constraint::Min(0),                     // Content
+        ])
+        .split(inner);
+
+    let description =
+        Paragraph::new(description)
+            .wrap(Wrap { trim: true })
+            .style(Style::default().fg(if item.description.is_some() {
+                Color::White
+            } else {
+                Color::DarkGray
+            }));
+    frame.render_widget(description, chunks[0]);
+
+    frame.render_widget(
+        Paragraph::new("─".repeat(chunks[1].width as usize))
+            .style(Style::default().fg(Color::DarkGray)),
+        chunks[1],
+    );
+
+    // Only the lines that fit; follows the view screen's markdown setting
+    let height = chunks[2].height as usize;
+    let content = if app.view_state.render_markdown {
+        let mut lines = markdown::render(&item.content, chunks[2].width);
+        lines.truncate(height);
+        Text::from(lines)
+    } else {
+        Text::from(
+            item.content
+                .lines()
+                .take(height)
+                .collect::<Vec<_>>()
+                .join("\n"),
+        )
+    };
+    frame.render_widget(Paragraph::new(content), chunks[2]);
 }
 
 fn draw_sidebar(frame: &mut Frame, area: Rect, app: &App) {
@@ -68,7 +164,10 @@ fn draw_sidebar(frame: &mut Frame, area: Rect, app: &App) {
 
     // Recent Items (index 0)
     let is_recent_selected = app.sidebar_index == 0 && is_focused;
-    let is_recent_active = app.selected_category.is_none() && app.selected_tag.is_none();
+    let is_recent_active = app.selected_category.is_none()
+        && app.selected_tags.is_empty()
+        && app.selected_collection.is_none()
+        && app.special_list.is_none();
     let recent_prefix = if is_recent_active { "> " } else { "  " };
     let recent_style = if is_recent_selected {
         SELECTED_STYLE
@@ -82,10 +181,24 @@ fn draw_sidebar(frame: &mut Frame, area: Rect, app: &App) {
         recent_style,
     ));
 
-    // Categories section (indices 1-4)
+    lines.push(special_list_line(
+        app,
+        SpecialList::Favorites,
+        SIDEBAR_FAVORITES_INDEX,
+        app.favorites.len(),
+    ));
+
+    lines.push(special_list_line(
+        app,
+        SpecialList::Copies,
+        SIDEBAR_COPIES_INDEX,
+        app.copies_count,
+    ));
+
+    // Categories section
     for (i, category) in Category::all().iter().enumerate() {
         let count = app.get_category_count(*category);
-        let sidebar_index = i + 1; // Offset by 1 for Recent
+        let sidebar_index = SIDEBAR_CATEGORIES_START + i;
         let is_selected = app.sidebar_index == sidebar_index && is_focused;
         let is_active = app.selected_category == Some(*category);
 
@@ -103,15 +216,73 @@ fn draw_sidebar(frame: &mut Frame, area: Rect, app: &App) {
         lines.push(Line::styled(text, style));
     }
 
+    lines.push(special_list_line(
+        app,
+        SpecialList::Archived,
+        SIDEBAR_ARCHIVED_INDEX,
+        app.archived.len(),
+    ));
+
+    lines.push(special_list_line(
+        app,
+        SpecialList::Stale,
+        SIDEBAR_STALE_INDEX,
+        app.stale_count,
+    ));
+
+    lines.push(special_list_line(
+        app,
+        SpecialList::Trash,
+        SIDEBAR_TRASH_INDEX,
+        app.trash_count,
+    ));
+
+    // Collections, indented under their parents
+    lines.push(Line::raw(""));
+    lines.push(Line::styled(
+        " Collections",
+        Style::default().fg(Color::Yellow),
+    ));
+    for (i, (path, count)) in app.collections.iter().enumerate() {
+        let sidebar_index = SIDEBAR_COLLECTIONS_START + i;
+        let is_selected = app.sidebar_index == sidebar_index && is_focused;
+        let is_active = app.selected_collection.as_ref() == Some(path);
+
+        let prefix = if is_active { "> " } else { "  " };
+        let (depth, name) = match path.rsplit_once('/') {
+            Some((_, name)) => (path.matches('/').count(), name),
+            None => (0, path.as_str()),
+        };
+        let text = format!("{}{}{} ({})", prefix, "  ".repeat(depth), name, count);
+
+        let style = if is_selected {
+            SELECTED_STYLE
+        } else if is_active {
+            Style::default().fg(Color::Cyan)
+        } else {
+            Style::default()
+        };
+
+        lines.push(Line::styled(text, style));
+    }
+
     // Tags header
     lines.push(Line::raw(""));
-    lines.push(Line::styled(" Tags", Style::default().fg(Color::Yellow)));
+    let tags_header = match (app.selected_tags.len(), app.tag_match_all) {
+        (0 | 1, _) => " Tags".to_string(),
+        (_, true) => " Tags (all)".to_string(),
+        (_, false) => " Tags (any)".to_string(),
+    };
+    lines.push(Line::styled(
+        tags_header,
+        Style::default().fg(Color::Yellow),
+    ));
 
-    // Tags list (indices 5+)
+    // Tags list
     for (i, (tag, count)) in app.tags.iter().enumerate() {
-        let sidebar_index = 5 + i; // After Recent + 4 categories
+        let sidebar_index = app.tags_start() + i;
         let is_selected = app.sidebar_index == sidebar_index && is_focused;
-        let is_active = app.selected_tag.as_ref() == Some(tag);
+        let is_active = app.selected_tags.contains(tag);
 
         let prefix = if is_active { "> " } else { "  " };
         let text = format!("{}#{} ({})", prefix, tag, count);
@@ -128,11 +299,14 @@ fn draw_sidebar(frame: &mut Frame, area: Rect, app: &App) {
     }
 
     // Calculate scroll to keep selected item visible
-    let selected_line = if app.sidebar_index <= 4 {
+    let selected_line = if app.sidebar_index < SIDEBAR_COLLECTIONS_START {
         app.sidebar_index
-    } else {
-        // Account for empty line and "Tags" header between categories and tags
+    } else if app.sidebar_index < app.tags_start() {
+        // Account for the empty line and "Collections" header
         app.sidebar_index + 2
+    } else {
+        // ... and the empty line and "Tags" header after the collections
+        app.sidebar_index + 4
     };
 
     let visible_height = inner.height as usize;
@@ -146,6 +320,30 @@ fn draw_sidebar(frame: &mut Frame, area: Rect, app: &App) {
     frame.render_widget(paragraph, inner);
 }
 
+fn special_list_line(
+    app: &App,
+    list: SpecialList,
+    sidebar_index: usize,
+    count: usize,
+) -> Line<'static> {
+    let is_selected = app.sidebar_index == sidebar_index && app.focus == Focus::Sidebar;
+    let is_active = app.special_list == Some(list);
+
+    let prefix = if is_active { "> " } else { "  " };
+    let style = if is_selected {
+        SELECTED_STYLE
+    } else if is_active {
+        Style::default().fg(Color::Cyan)
+    } else {
+        Style::default()
+    };
+
+    Line::styled(
+        format!("{}{} ({})", prefix, list.display_name(), count),
+        style,
+    )
+}
+
 fn draw_item_list(frame: &mut Frame, area: Rect, app: &mut App) {
     let is_focused = app.focus == Focus::ItemList;
     let border_color = if is_focused {
@@ -154,35 +352,72 @@ fn draw_item_list(frame: &mut Frame, area: Rect, app: &mut App) {
         Color::DarkGray
     };
 
-    let title = match (&app.selected_category, &app.selected_tag) {
-        (Some(cat), _) => format!(" {} ", cat.display_name()),
-        (None, Some(tag)) => format!(" #{} ", tag),
-        (None, None) => " Recent Items ".to_string(),
+    let mut title = match (
+        app.special_list,
+        &app.selected_category,
+        app.selected_tags.as_slice(),
+    ) {
+        (None, None, []) if app.selected_collection.is_some() => {
+            format!(
+                " {} ",
+                app.selected_collection.as_deref().unwrap_or_default()
+            )
+        }
+        (Some(list), _, _) => format!(" {} ", list.display_name()),
+        (None, Some(cat), _) => format!(" {} ", cat.display_name()),
+        (None, None, []) => " Recent Items ".to_string(),
+        (None, None, tags) => {
+            let tags: Vec<String> = tags.iter().map(|tag| format!("#{}", tag)).collect();
+            let separator = if app.tag_match_all { " & " } else { " | " };
+            format!(" {} ", tags.join(separator))
+        }
     };
+    let marked = (0..app.items.len()).filter(|i| app.is_marked(*i)).count();
+    if marked > 0 {
+        title.push_str(&format!("({} marked) ", marked));
+    }
 
     let block = Block::default()
         .borders(Borders::ALL)
         .border_style(Style::default().fg(border_color))
-        .title(title);
+        .title(title)
+        .title_top(
+            Line::styled(
+                format!(
+                    " {}sort: {} ",
+                    if app.is_loading() { "loading…  " } else { "" },
+                    app.sort_order.as_str()
+                ),
+                Style::default().fg(Color::DarkGray),
+            )
+            .right_aligned(),
+        );
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
 
     if app.items.is_empty() {
-        let msg = Paragraph::new("No items found. Press 'n' to create one.")
-            .style(Style::default().fg(Color::DarkGray));
+        let text = match app.special_list {
+            Some(SpecialList::Trash) => "Trash is empty.",
+            Some(SpecialList::Favorites) => "No favorites yet. Press 'f' on an item to add it.",
+            Some(SpecialList::Archived) => "Nothing archived. Press 'A' on an item to archive it.",
+            Some(SpecialList::Copies) => "Nothing copied yet. Items copied with 'c' show up here.",
+            Some(SpecialList::Stale) => "Nothing stale. Every item was updated or copied recently.",
+            None => "No items found. Press 'n' to create one.",
+        };
+        let msg = Paragraph::new(text).style(Style::default().fg(Color::DarkGray));
         frame.render_widget(msg, inner);
         return;
     }
 
+    let columns = list_columns(&app.settings_state.list_columns);
+
     // Create header
-    let header = Row::new(vec![
-        Cell::from("NAME").style(HEADER_STYLE),
-        Cell::from("CATEGORY").style(HEADER_STYLE),
-        Cell::from("VER").style(HEADER_STYLE),
-        Cell::from("TAGS").style(HEADER_STYLE),
-        Cell::from("UPDATED").style(HEADER_STYLE),
-    ])
+    let header = Row::new(
+        columns
+            .iter()
+            .map(|(column, _)| Cell::from(column.header()).style(HEADER_STYLE)),
+    )
     .height(1);
 
     // Create rows
@@ -202,31 +437,58 @@ fn draw_item_list(frame: &mut Frame, area: Rect, app: &mut App) {
                 (Style::default(), Style::default().fg(Color::DarkGray))
             };
 
-            let tags = item.tags.clone().unwrap_or_default();
-            let tags_short = if tags.len() > 15 {
-                format!("{}...", &tags[..12])
-            } else {
-                tags
-            };
-
-            Row::new(vec![
-                Cell::from(item.name.clone()),
-                Cell::from(item.category.display_name()),
-                Cell::from(format!("v{}", item.version)).style(dim_style),
-                Cell::from(tags_short).style(dim_style),
-                Cell::from(item.updated_ago()).style(dim_style),
-            ])
-            .style(row_style)
+            let cells = columns.iter().map(|(column, width)| match column {
+                ListColumn::Name => {
+                    let mut name = Vec::new();
+                    if app.is_marked(i) {
+                        name.push(Span::styled("● ", dim_style.fg(Color::Magenta)));
+                    }
+                    if item.id.is_some_and(|id| app.favorites.contains(&id)) {
+                        name.push(Span::styled("★ ", dim_style.fg(Color::Yellow)));
+                    }
+                    name.push(Span::raw(item.name.clone()));
+                    Cell::from(Line::from(name))
+                }
+                ListColumn::Category => Cell::from(item.category.display_name()),
+                ListColumn::Version => Cell::from(format!("v{}", item.version)).style(dim_style),
+                ListColumn::Sync => {
+                    let sync = item
+                        .id
+                        .and_then(|id| app.sync_status.get(&id))
+                        .copied()
+                        .unwrap_or(SyncStatus::NeverExported);
+                    let sync_style = match sync {
+                        SyncStatus::Exported => dim_style.fg(Color::Green),
+                        SyncStatus::Modified => dim_style.fg(Color::Yellow),
+                        SyncStatus::NeverExported => dim_style,
+                    };
+                    Cell::from(format!(" {}", sync.symbol())).style(sync_style)
+                }
+                ListColumn::Tags => {
+                    let tags = item.tags.clone().unwrap_or_default();
+                    Cell::from(truncate(&tags, *width as usize)).style(dim_style)
+                }
+                ListColumn::Updated => Cell::from(item.updated_ago()).style(dim_style),
+                ListColumn::Size => Cell::from(format_size(item.content.len())).style(dim_style),
+                ListColumn::Usage => {
+                    let uses = item
+                        .id
+                        .and_then(|id| app.use_counts.get(&id))
+                        .copied()
+                        .unwrap_or(0);
+                    Cell::from(format!("{:>4}", uses)).style(dim_style)
+                }
+            });
+
+            Row::new(cells).style(row_style)
         })
         .collect();
 
-    let widths = [
-        Constraint::Min(15),
-        Constraint::Length(10),
-        Constraint::Length(4),
-        Constraint::Length(15),
-        Constraint::Length(12),
-    ];
+    // The name column takes the space the others leave
+    let widths = columns.iter().map(|(column, width)| match column {
+        ListColumn::Name => Constraint::Min(*width),
+        _ => Constraint::Length(*width),
+    });
 
     let table = Table::new(rows, widths)
         .header(header)
@@ -238,10 +500,20 @@ fn draw_item_list(frame: &mut Frame, area: Rect, app: &mut App) {
     frame.render_stateful_widget(table, inner, &mut state);
 }
 
+/// Shorten `text` to `width` chars, ending in "..." when cut
+fn truncate(text: &str, width: usize) -> String {
+    if text.chars().count() > width {
+        let kept: String = text.chars().take(width.saturating_sub(3)).collect();
+        format!("{}...", kept)
+    } else {
+        text.to_string()
+    }
+}
+
 fn draw_status_bar(frame: &mut Frame, area: Rect, app: &App) {
     // If there's a status message, show it instead of shortcuts
     if let Some(ref msg) = app.status_message {
-        let style = if msg.contains("failed") || msg.contains("Error") {
+        let style = if message_log::is_error(msg) {
             Style::default().fg(Color::Red).bg(Color::Black)
         } else {
             Style::default().fg(Color::Green).bg(Color::Black)
@@ -251,18 +523,101 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, app: &App) {
         return;
     }
 
-    let shortcuts = vec![
-        ("/ ", "search"),
-        ("n ", "new"),
-        ("e ", "edit"),
-        ("c ", "copy"),
-        ("dd ", "delete"),
-        ("x ", "export"),
-        ("Enter ", "view"),
-        ("s ", "settings"),
-        ("? ", "help"),
-        ("q ", "quit"),
-    ];
+    if let Some(ref query) = app.type_ahead {
+        let prefix = query.to_lowercase();
+        let found = app
+            .items
+            .iter()
+            .any(|item| item.name.to_lowercase().starts_with(&prefix));
+        let mut spans = vec![
+            Span::styled(" jump to: ", Style::default().fg(Color::Yellow)),
+            Span::raw(query.clone()),
+            Span::styled("█", Style::default().fg(Color::Yellow)),
+        ];
+        if !found {
+            spans.push(Span::styled("  no match", Style::default().fg(Color::Red)));
+        }
+        spans.push(Span::styled(
+            "  Enter view  ESC done",
+            Style::default().fg(Color::DarkGray),
+        ));
+        let prompt = Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::Black));
+        frame.render_widget(prompt, area);
+        return;
+    }
+
+    let shortcuts = if app.pending_key == Some('y') {
+        COPY_MENU.to_vec()
+    } else if !app.marked.is_empty() || app.mark_anchor.is_some() {
+        let mut shortcuts = vec![
+            ("Space ", "mark"),
+            ("v ", "range"),
+            ("dd ", "delete marked"),
+        ];
+        if !app.in_trash() {
+            shortcuts.extend([
+                ("t ", "tag marked"),
+                ("C ", "collect marked"),
+                ("X ", "export marked"),
+            ]);
+        }
+        shortcuts.push(("ESC ", "clear marks"));
+        shortcuts
+    } else if app.in_trash() {
+        vec![
+            ("u ", "restore"),
+            ("dd ", "delete forever"),
+            ("E ", "empty trash"),
+            ("Enter ", "view"),
+            ("ESC ", "back"),
+        ]
+    } else if app.special_list == Some(SpecialList::Copies) {
+        vec![
+            ("Enter ", "copy again"),
+            ("e ", "edit"),
+            ("dd ", "delete"),
+            ("ESC ", "back"),
+        ]
+    } else if app.special_list == Some(SpecialList::Stale) {
+        vec![
+            ("K ", "keep"),
+            ("A ", "archive"),
+            ("dd ", "delete"),
+            ("e ", "edit"),
+            ("Enter ", "view"),
+            ("ESC ", "back"),
+        ]
+    } else if app.special_list == Some(SpecialList::Archived) {
+        vec![
+            ("A ", "unarchive"),
+            ("e ", "edit"),
+            ("c ", "copy"),
+            ("dd ", "delete"),
+            ("Enter ", "view"),
+            ("ESC ", "back"),
+        ]
+    } else if app.pick.is_some() {
+        vec![
+            ("Enter ", "pick"),
+            ("/ ", "search"),
+            ("Ctrl+p ", "find"),
+            ("p ", "preview"),
+            ("q ", "cancel"),
+        ]
+    } else {
+        vec![
+            ("/ ", "search"),
+            ("n ", "new"),
+            ("e ", "edit"),
+            ("c ", "copy"),
+            ("dd ", "delete"),
+            ("x ", "export"),
+            ("Enter ", "view"),
+            ("s ", "settings"),
+            ("? ", "help"),
+            ("q ", "quit"),
+        ]
+    };
 
     let spans: Vec<Span> = shortcuts
         .iter()
@@ -0,0 +1,203 @@
+use ratatui::{
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+};
+
+/// Lay out markdown for reading: headings styled, list items indented with
+/// bullets, rules drawn across `width`, code blocks set apart, and inline
+/// emphasis, code, and links styled. Not a full CommonMark renderer; text
+/// it doesn't recognize is shown as written.
+pub fn render(text: &str, width: u16) -> Vec<Line<'static>> {
+    let mut lines = Vec::new();
+    let mut in_code_block = false;
+
+    for raw in text.lines() {
+        let trimmed = raw.trim_start();
+
+        if trimmed.starts_with("```") || trimmed.starts_with("~~~") {
+            in_code_block = !in_code_block;
+            lines.push(Line::styled(
+                "─".repeat(width.min(40) as usize),
+                Style::default().fg(Color::DarkGray),
+            ));
+            continue;
+        }
+
+        if in_code_block {
+            lines.push(Line::styled(
+                format!("  {}", raw),
+                Style::default().fg(Color::Yellow),
+            ));
+            continue;
+        }
+
+        if is_rule(trimmed) {
+            lines.push(Line::styled(
+                "─".repeat(width as usize),
+                Style::default().fg(Color::DarkGray),
+            ));
+            continue;
+        }
+
+        if let Some((level, heading)) = heading(trimmed) {
+            let mut style = Style::default()
+                .fg(Color::Cyan)
+                .add_modifier(Modifier::BOLD);
+            if level == 1 {
+                style = style.add_modifier(Modifier::UNDERLINED);
+            }
+            lines.push(Line::from(inline(heading, style)));
+            continue;
+        }
+
+        if let Some(quote) = trimmed.strip_prefix('>') {
+            let style = Style::default()
+                .fg(Color::DarkGray)
+                .add_modifier(Modifier::ITALIC);
+            let mut spans = vec![Span::styled("│ ", Style::default().fg(Color::DarkGray))];
+            spans.extend(inline(quote.trim_start(), style));
+            lines.push(Line::from(spans));
+            continue;
+        }
+
+        // Two spaces of source indentation per nesting level
+        let depth = (raw.len() - trimmed.len()) / 2;
+        if let Some((marker, item)) = list_item(trimmed) {
+            let mut spans = vec![Span::styled(
+                format!("{}{} ", "  ".repeat(depth + 1), marker),
+                Style::default().fg(Color::Yellow),
+            )];
+            spans.extend(inline(item, Style::default()));
+            lines.push(Line::from(spans));
+            continue;
+        }
+
+        lines.push(Line::from(inline(raw, Style::default())));
+    }
+
+    lines
+}
+
+fn is_rule(line: &str) -> bool {
+    let compact: String = line.chars().filter(|c| !c.is_whitespace()).collect();
+    compact.len() >= 3
+        && ['-', '*', '_']
+            .iter()
+            .any(|marker| compact.chars().all(|c| c == *marker))
+}
+
+fn heading(line: &str) -> Option<(usize, &str)> {
+    let level = line.chars().take_while(|c| *c == '#').count();
+    if (1..=6).contains(&level) && line[level..].starts_with(' ') {
+        Some((level, line[level..].trim()))
+    } else {
+        None
+    }
+}
+
+/// Bullet or number to show for a list item, and the item text
+fn list_item(line: &str) -> Option<(String, &str)> {
+    for bullet in ["- ", "* ", "+ "] {
+        if let Some(rest) = line.strip_prefix(bullet) {
+            // Task list checkboxes
+            if let Some(rest) = rest.strip_prefix("[ ] ") {
+                return Some(("☐".to_string(), rest));
+            }
+            if let Some(rest) = rest
+                .strip_prefix("[x] ")
+                .or_else(|| rest.strip_prefix("[X] "))
+            {
+                return Some(("☑".to_string(), rest));
+            }
+            return Some(("•".to_string(), rest));
+        }
+    }
+
+    let digits = line.chars().take_while(|c| c.is_ascii_digit()).count();
+    if digits > 0 {
+        if let Some(rest) = line[digits..].strip_prefix(". ") {
+            return Some((format!("{}.", &line[..digits]), rest));
+        }
+    }
+    None
+}
+
+/// Spans for `**bold**`, `*italic*` / `_italic_`, `` `code` ``, and
+/// `[text](url)` links, on top of `base`
+fn inline(text: &str, base: Style) -> Vec<Span<'static>> {
+    let mut spans = Vec::new();
+    let mut plain = String::new();
+    let mut rest = text;
+
+    while let Some(c) = rest.chars().next() {
+        // `_` inside a word (snake_case) is not emphasis
+        let word_boundary = plain.chars().last().is_none_or(|p| !p.is_alphanumeric());
+        let styled = match c {
+            '`' => delimited(rest, "`").map(|(inner, len)| {
+                (
+                    inner.to_string(),
+                    base.fg(Color::Yellow).bg(Color::Black),
+                    len,
+                )
+            }),
+            '_' if !word_boundary => None,
+            '*' | '_' if rest.starts_with("**") || rest.starts_with("__") => {
+                delimited(rest, &rest[..2])
+                    .map(|(inner, len)| (inner.to_string(), base.add_modifier(Modifier::BOLD), len))
+            }
+            '*' | '_' => delimited(rest, &rest[..1])
+                .filter(|(inner, _)| !inner.starts_with(' '))
+                .map(|(inner, len)| (inner.to_string(), base.add_modifier(Modifier::ITALIC), len)),
+            '[' => link(rest).map(|(label, len)| {
+                (
+                    label.to_string(),
+                    base.fg(Color::Blue).add_modifier(Modifier::UNDERLINED),
+                    len,
+                )
+            }),
+            _ => None,
+        };
+
+        match styled {
+            Some((content, style, len)) => {
+                if !plain.is_empty() {
+                    spans.push(Span::styled(std::mem::take(&mut plain), base));
+                }
+                spans.push(Span::styled(content, style));
+                rest = &rest[len..];
+            }
+            None => {
+                plain.push(c);
+                rest = &rest[c.len_utf8()..];
+            }
+        }
+    }
+
+    if !plain.is_empty() || spans.is_empty() {
+        spans.push(Span::styled(plain, base));
+    }
+    spans
+}
+
+/// Text between `delimiter` at the start of `text` and its next occurrence,
+/// with the number of bytes consumed
+fn delimited<'a>(text: &'a str, delimiter: &str) -> Option<(&'a str, usize)> {
+    let after = &text[delimiter.len()..];
+    let end = after.find(delimiter)?;
+    if end == 0 {
+        return None;
+    }
+    Some((&after[..end], delimiter.len() * 2 + end))
+}
+
+/// Label of a `[label](url)` link at the start of `text`, with the number
+/// of bytes consumed
+fn link(text: &str) -> Option<(&str, usize)> {
+    let close = text.find("](")?;
+    let label = &text[1..close];
+    if label.contains('[') {
+        return None;
+    }
+    let url_len = text[close + 2..].find(')')?;
+    Some((label, close + 2 + url_len + 1))
+}
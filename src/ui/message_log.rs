@@ -0,0 +1,161 @@
+use chrono::{DateTime, Local};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState},
+    Frame,
+};
+use std::collections::VecDeque;
+
+/// Messages kept for the log screen; older ones are dropped
+const MESSAGE_LOG_SIZE: usize = 200;
+
+/// Status messages and notices shown during the session, for review on
+/// the message log screen (`M`)
+#[derive(Default)]
+pub struct MessageLog {
+    entries: VecDeque<(DateTime<Local>, String)>,
+    /// Message last seen in each watched slot, so one shown across several
+    /// frames is logged once
+    seen: Vec<Option<String>>,
+    pub scroll: u16,
+    pub max_scroll: u16,
+}
+
+impl MessageLog {
+    /// Log the message in watched slot `slot` if it changed since last
+    /// time. Called every frame with the status bar and screen notices.
+    pub fn watch(&mut self, slot: usize, current: Option<&String>) {
+        if self.seen.len() <= slot {
+            self.seen.resize(slot + 1, None);
+        }
+        if self.seen[slot].as_ref() == current {
+            return;
+        }
+
+        self.seen[slot] = current.cloned();
+        if let Some(message) = current {
+            if is_error(message) {
+                tracing::warn!("{}", message);
+            } else {
+                tracing::debug!("{}", message);
+            }
+            if self.entries.len() == MESSAGE_LOG_SIZE {
+                self.entries.pop_front();
+            }
+            self.entries.push_back((Local::now(), message.clone()));
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        if self.scroll < self.max_scroll {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+
+    /// Open at the newest messages
+    pub fn scroll_to_end(&mut self) {
+        self.scroll = u16::MAX;
+    }
+}
+
+/// Messages that look like failures, shown in red
+pub fn is_error(message: &str) -> bool {
+    message.contains("failed") || message.contains("Error")
+}
+
+pub fn draw(frame: &mut Frame, log: &mut MessageLog) {
+    let area = centered_rect(80, 70, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Messages ({}) ", log.entries.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([Constraint::Min(0), Constraint::Length(1)])
+        .split(inner);
+
+    let lines: Vec<Line> = if log.entries.is_empty() {
+        vec![Line::styled(
+            "No messages yet.",
+            Style::default().fg(Color::DarkGray),
+        )]
+    } else {
+        log.entries
+            .iter()
+            .map(|(time, message)| {
+                let color = if is_error(message) {
+                    Color::Red
+                } else {
+                    Color::Green
+                };
+                Line::from(vec![
+                    Span::styled(
+                        format!("{} ", time.format("%H:%M:%S")),
+                        Style::default().fg(Color::DarkGray),
+                    ),
+                    Span::styled(message.clone(), Style::default().fg(color)),
+                ])
+            })
+            .collect()
+    };
+
+    log.max_scroll = lines.len().saturating_sub(chunks[0].height as usize) as u16;
+    log.scroll = log.scroll.min(log.max_scroll);
+
+    frame.render_widget(Paragraph::new(lines).scroll((log.scroll, 0)), chunks[0]);
+
+    // Scrollbar
+    if log.max_scroll > 0 {
+        let scrollbar = Scrollbar::new(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑"))
+            .end_symbol(Some("↓"));
+
+        let mut scrollbar_state =
+            ScrollbarState::new(log.max_scroll as usize).position(log.scroll as usize);
+
+        frame.render_stateful_widget(scrollbar, chunks[0], &mut scrollbar_state);
+    }
+
+    // Status bar
+    let status = Paragraph::new(Line::from(vec![
+        Span::styled("j/k ", Style::default().fg(Color::Yellow)),
+        Span::styled("scroll  ", Style::default().fg(Color::DarkGray)),
+        Span::styled("ESC/M ", Style::default().fg(Color::Yellow)),
+        Span::styled("close", Style::default().fg(Color::DarkGray)),
+    ]));
+    frame.render_widget(status, chunks[1]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
@@ -1,24 +1,55 @@
 mod ai_popup;
 mod dialog;
 mod edit_screen;
+mod export_diff;
+mod export_popup;
+mod fuzzy_finder;
 mod help_screen;
 mod history_popup;
+mod import_conflict;
+mod link_picker;
+mod list_columns;
 mod main_screen;
+mod markdown;
+mod message_log;
 mod search;
 mod settings_screen;
+mod sync_popup;
+mod template_form;
+mod text_cursor;
+mod vault_picker;
+mod version_diff;
 mod view_screen;
+mod vim;
 
-pub use ai_popup::AiPopupState;
-pub use dialog::ConfirmDialog;
+pub use ai_popup::{action_document, parse_action_document, AiAction, AiPopupState, ResultDiff};
+pub use dialog::{ConfirmAction, ConfirmDialog, InputAction, InputDialog};
 pub use edit_screen::{EditField, EditState};
+pub use export_diff::ExportDiffState;
+pub use export_popup::ExportPopupState;
+pub use fuzzy_finder::FuzzyFinderState;
 pub use help_screen::HelpState;
 pub use history_popup::HistoryState;
-pub use search::SearchState;
+pub use import_conflict::ImportConflictState;
+pub use link_picker::LinkPickerState;
+pub use list_columns::parse_list_columns;
+pub use message_log::MessageLog;
+pub use search::{SearchScope, SearchState};
 pub use settings_screen::{LlmProvider, SettingsField, SettingsState};
+pub use sync_popup::SyncState;
+pub use template_form::TemplateFormState;
+pub use vault_picker::VaultPickerState;
+pub use version_diff::{VersionDiffState, SUMMARY_PROMPT};
 pub use view_screen::ViewState;
+pub use vim::VimMode;
 
 use crate::app::{App, Screen};
-use ratatui::Frame;
+use ratatui::{
+    layout::Rect,
+    text::Line,
+    widgets::{Paragraph, Wrap},
+    Frame,
+};
 
 pub fn draw(frame: &mut Frame, app: &mut App) {
     // Draw the base screen
@@ -26,34 +57,133 @@ pub fn draw(frame: &mut Frame, app: &mut App) {
         Screen::Main => main_screen::draw(frame, app),
         Screen::View => {
             let item = app.selected_item().cloned();
-            view_screen::draw(frame, item.as_ref(), &mut app.view_state);
+            let is_favorite = item
+                .as_ref()
+                .and_then(|item| item.id)
+                .is_some_and(|id| app.favorites.contains(&id));
+            let copy_menu = app.pending_key == Some('y');
+            view_screen::draw(
+                frame,
+                item.as_ref(),
+                is_favorite,
+                copy_menu,
+                &mut app.view_state,
+            );
         }
-        Screen::Edit => edit_screen::draw(frame, &app.edit_state),
+        Screen::Edit => edit_screen::draw(frame, &mut app.edit_state, app.settings_state.vim_mode),
         Screen::Search => {
             main_screen::draw(frame, app);
-            search::draw(frame, &app.search_state);
+            search::draw(frame, &app.search_state, app.is_searching());
         }
         Screen::Settings => settings_screen::draw(frame, &app.settings_state),
         Screen::Help => {
             main_screen::draw(frame, app);
             help_screen::draw(frame, &mut app.help_state);
         }
+        Screen::Messages => {
+            main_screen::draw(frame, app);
+            message_log::draw(frame, &mut app.message_log);
+        }
     }
 
     // Draw overlays
-    if let Some(ref dialog) = app.confirm_dialog {
-        dialog::draw(frame, dialog);
+    if let Some(ref picker) = app.link_picker {
+        link_picker::draw(frame, picker);
+    }
+
+    if let Some(ref picker) = app.vault_picker {
+        vault_picker::draw(frame, picker);
+    }
+
+    if let Some(ref finder) = app.fuzzy_finder {
+        fuzzy_finder::draw(frame, finder);
+    }
+
+    if let Some(ref form) = app.template_form {
+        template_form::draw(frame, form);
     }
 
     if app.show_ai_popup {
         let content = app.edit_state.item.content.clone();
-        let has_llm = !app.settings_state.api_key.is_empty();
+        let has_llm = app.settings_state.has_llm();
         ai_popup::draw(frame, &app.ai_popup_state, &content, has_llm);
     }
 
+    if app.show_export_popup {
+        if let Some(item) = app.selected_item() {
+            export_popup::draw(
+                frame,
+                &app.export_popup_state,
+                item,
+                &app.settings_state.export_options(),
+            );
+        }
+    }
+
+    if let Some(ref diff) = app.export_diff {
+        export_diff::draw(frame, diff);
+    }
+
+    if let Some(ref conflicts) = app.import_conflicts {
+        import_conflict::draw(frame, conflicts);
+    }
+
+    if let Some(ref sync) = app.sync_state {
+        sync_popup::draw(frame, sync);
+    }
+
     if app.show_history_popup {
         if let Some(ref mut history_state) = app.history_state {
             history_popup::draw(frame, history_state);
         }
     }
+
+    if let Some(ref diff) = app.version_diff {
+        version_diff::draw(frame, diff);
+    }
+
+    // Dialogs go last so they stay on top of the popup that opened them
+    if let Some(ref dialog) = app.confirm_dialog {
+        dialog::draw(frame, dialog);
+    }
+
+    if let Some(ref dialog) = app.input_dialog {
+        dialog::draw_input(frame, dialog);
+    }
+}
+
+/// Second keys after `y`, shown in the status bar while it waits for one
+const COPY_MENU: &[(&str, &str)] = &[
+    ("y ", "content"),
+    ("c ", "as exported"),
+    ("n ", "name"),
+    ("p ", "export path"),
+    ("j ", "JSON"),
+    ("a ", "YAML"),
+    ("ESC ", "cancel"),
+];
+
+/// Area for a dropdown of `width` x `height` opening at (`x`, `y`), moved up
+/// or left to stay inside `screen` and shrunk when the screen is smaller
+fn dropdown_area(x: u16, y: u16, width: u16, height: u16, screen: Rect) -> Rect {
+    Rect {
+        x,
+        y,
+        width,
+        height,
+    }
+    .clamp(screen)
+}
+
+/// First row to show so `selected` stays visible in a list `visible` rows tall
+fn list_offset(selected: usize, visible: u16) -> u16 {
+    selected.saturating_sub((visible as usize).saturating_sub(1)) as u16
+}
+
+/// Rows a line takes when wrapped to `width`, at least one
+fn wrapped_rows(line: Line, width: u16) -> usize {
+    Paragraph::new(line)
+        .wrap(Wrap { trim: false })
+        .line_count(width)
+        .max(1)
 }
@@ -1,4 +1,6 @@
-use crate::models::Item;
+use super::text_cursor::{cursor_spans, insert_at, next_boundary, prev_boundary, remove_range};
+use crate::db::{Filter, SearchHit, SNIPPET_END, SNIPPET_START};
+use crate::models::{Category, Item};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -7,42 +9,82 @@ use ratatui::{
     Frame,
 };
 
+/// The list a scoped search stays within (toggled with Ctrl+F)
+#[derive(Debug, Clone)]
+pub enum SearchScope {
+    Category(Category),
+    Tags { tags: Vec<String>, match_all: bool },
+}
+
+impl SearchScope {
+    /// Name shown in the popup title, written as in the main list's title
+    pub fn label(&self) -> String {
+        match self {
+            SearchScope::Category(category) => category.display_name().to_string(),
+            SearchScope::Tags { tags, match_all } => {
+                let tags: Vec<String> = tags.iter().map(|tag| format!("#{}", tag)).collect();
+                tags.join(if *match_all { " & " } else { " | " })
+            }
+        }
+    }
+
+    pub fn filters(&self) -> Vec<Filter> {
+        match self {
+            SearchScope::Category(category) => {
+                vec![Filter::Category(category.as_str().to_string())]
+            }
+            SearchScope::Tags {
+                tags,
+                match_all: true,
+            } => tags
+                .iter()
+                .map(|tag| Filter::Tags(vec![tag.clone()]))
+                .collect(),
+            SearchScope::Tags { tags, .. } => vec![Filter::Tags(tags.clone())],
+        }
+    }
+}
+
 #[derive(Default)]
 pub struct SearchState {
     pub query: String,
     pub cursor_pos: usize,
-    pub results: Vec<Item>,
+    pub results: Vec<SearchHit>,
     pub selected_index: usize,
+    /// Also match archived items (toggled with Ctrl+A)
+    pub include_archived: bool,
+    /// What the search is limited to, when scoped to the main list's
+    /// category or tags
+    pub scope: Option<SearchScope>,
 }
 
 impl SearchState {
     pub fn insert_char(&mut self, c: char) {
-        self.query.insert(self.cursor_pos, c);
+        insert_at(&mut self.query, self.cursor_pos, c.encode_utf8(&mut [0; 4]));
         self.cursor_pos += 1;
     }
 
     pub fn insert_str(&mut self, s: &str) {
         // Filter out control characters for search
         let clean: String = s.chars().filter(|c| !c.is_control()).collect();
-        for (i, c) in clean.chars().enumerate() {
-            self.query.insert(self.cursor_pos + i, c);
-        }
+        insert_at(&mut self.query, self.cursor_pos, &clean);
         self.cursor_pos += clean.chars().count();
     }
 
     pub fn delete_char(&mut self) {
         if self.cursor_pos > 0 {
-            self.query.remove(self.cursor_pos - 1);
-            self.cursor_pos -= 1;
+            let start = prev_boundary(&self.query, self.cursor_pos);
+            remove_range(&mut self.query, start, self.cursor_pos);
+            self.cursor_pos = start;
         }
     }
 
     pub fn move_cursor_left(&mut self) {
-        self.cursor_pos = self.cursor_pos.saturating_sub(1);
+        self.cursor_pos = prev_boundary(&self.query, self.cursor_pos);
     }
 
     pub fn move_cursor_right(&mut self) {
-        self.cursor_pos = (self.cursor_pos + 1).min(self.query.len());
+        self.cursor_pos = next_boundary(&self.query, self.cursor_pos);
     }
 
     pub fn clear(&mut self) {
@@ -68,20 +110,34 @@ impl SearchState {
     }
 
     pub fn selected_item(&self) -> Option<&Item> {
-        self.results.get(self.selected_index)
+        self.results.get(self.selected_index).map(|hit| &hit.item)
     }
 }
 
-pub fn draw(frame: &mut Frame, state: &SearchState) {
+/// `searching` while results for the current query are still coming
+pub fn draw(frame: &mut Frame, state: &SearchState, searching: bool) {
     let area = centered_rect(70, 60, frame.area());
 
     // Clear the area behind the popup
     frame.render_widget(Clear, area);
 
-    let block = Block::default()
-        .title(" Search ")
+    let mut title = match state.scope {
+        Some(ref scope) => format!(" Search in {} ", scope.label()),
+        None => " Search ".to_string(),
+    };
+    if state.include_archived {
+        title.push_str("(incl. archived) ");
+    }
+
+    let mut block = Block::default()
+        .title(title)
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
+    if searching {
+        block = block.title_top(
+            Line::styled(" searching… ", Style::default().fg(Color::DarkGray)).right_aligned(),
+        );
+    }
 
     let inner = block.inner(area);
     frame.render_widget(block, area);
@@ -114,21 +170,9 @@ fn draw_search_input(frame: &mut Frame, area: Rect, state: &SearchState) {
     frame.render_widget(block, area);
 
     // Build query with cursor
-    let chars: Vec<char> = state.query.chars().collect();
-    let cursor = state.cursor_pos.min(chars.len());
-    let before: String = chars.iter().take(cursor).collect();
-    let cursor_char = chars.get(cursor).copied().unwrap_or(' ');
-    let after: String = chars.iter().skip(cursor + 1).collect();
-
-    let line = Line::from(vec![
-        Span::styled("/ ", Style::default().fg(Color::Yellow)),
-        Span::raw(before),
-        Span::styled(
-            cursor_char.to_string(),
-            Style::default().bg(Color::White).fg(Color::Black),
-        ),
-        Span::raw(after),
-    ]);
+    let mut spans = vec![Span::styled("/ ", Style::default().fg(Color::Yellow))];
+    spans.extend(cursor_spans(&state.query, state.cursor_pos));
+    let line = Line::from(spans);
 
     let paragraph = Paragraph::new(line);
     frame.render_widget(paragraph, inner);
@@ -137,7 +181,7 @@ fn draw_search_input(frame: &mut Frame, area: Rect, state: &SearchState) {
 fn draw_results(frame: &mut Frame, area: Rect, state: &SearchState) {
     if state.results.is_empty() {
         let msg = if state.query.is_empty() {
-            "Type to search..."
+            "Type to search: words, \"an exact phrase\", -excluded, this OR that"
         } else {
             "No results found"
         };
@@ -157,7 +201,7 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &SearchState) {
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
         ),
-        Cell::from("TAGS").style(
+        Cell::from("MATCH").style(
             Style::default()
                 .fg(Color::Yellow)
                 .add_modifier(Modifier::BOLD),
@@ -168,7 +212,7 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &SearchState) {
         .results
         .iter()
         .enumerate()
-        .map(|(i, item)| {
+        .map(|(i, hit)| {
             let style = if i == state.selected_index {
                 Style::default()
                     .bg(Color::DarkGray)
@@ -178,19 +222,18 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &SearchState) {
             };
 
             Row::new(vec![
-                Cell::from(item.name.clone()),
-                Cell::from(item.category.display_name()),
-                Cell::from(item.tags.clone().unwrap_or_default())
-                    .style(Style::default().fg(Color::DarkGray)),
+                Cell::from(hit.item.name.clone()),
+                Cell::from(hit.item.category.display_name()),
+                Cell::from(snippet_line(&hit.snippet)),
             ])
             .style(style)
         })
         .collect();
 
     let widths = [
-        Constraint::Min(20),
+        Constraint::Percentage(25),
         Constraint::Length(10),
-        Constraint::Min(15),
+        Constraint::Min(20),
     ];
 
     let table = Table::new(rows, widths)
@@ -203,11 +246,40 @@ fn draw_results(frame: &mut Frame, area: Rect, state: &SearchState) {
     frame.render_stateful_widget(table, area, &mut table_state);
 }
 
+/// Snippet on one line, with the matched terms highlighted
+fn snippet_line(snippet: &str) -> Line<'static> {
+    let text = snippet.split_whitespace().collect::<Vec<_>>().join(" ");
+    let mut spans = Vec::new();
+
+    for (i, part) in text.split(SNIPPET_START).enumerate() {
+        let (matched, rest) = match part.split_once(SNIPPET_END) {
+            Some((matched, rest)) if i > 0 => (matched, rest),
+            _ => ("", part),
+        };
+        if !matched.is_empty() {
+            spans.push(Span::styled(
+                matched.to_string(),
+                Style::default()
+                    .fg(Color::Yellow)
+                    .add_modifier(Modifier::BOLD),
+            ));
+        }
+        spans.push(Span::styled(
+            rest.to_string(),
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+
+    Line::from(spans)
+}
+
 fn draw_status_bar(frame: &mut Frame, area: Rect) {
     let shortcuts = [
         ("j/k ", "navigate"),
         ("Enter ", "select"),
         ("c ", "copy"),
+        ("C-a ", "archived"),
+        ("C-f ", "this list only"),
         ("ESC ", "close"),
     ];
 
@@ -1,4 +1,17 @@
-use crate::db::Database;
+use super::list_columns::{parse_list_columns, ListColumn, DEFAULT_LIST_COLUMNS};
+use super::message_log;
+use super::text_cursor::{cursor_spans, next_boundary, prev_boundary, remove_range};
+use super::{dropdown_area, list_offset};
+use crate::db::{UsageTotals, Vaults, DEFAULT_VAULT};
+use crate::export::{
+    expand_home, github_token, parse_export_profiles, ExportOptions, ExportProfile,
+    FrontmatterKeys, FRONTMATTER_TARGETS,
+};
+use crate::llm::{
+    key_env_var, resolve_api_key, KeySource, LlmConfig, DEFAULT_MAX_RETRIES, DEFAULT_TIMEOUT_SECS,
+    OLLAMA_DEFAULT_URL, OPENAI_DEFAULT_URL,
+};
+use crate::models::{parse_variable_presets, Category};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
@@ -6,29 +19,62 @@ use ratatui::{
     widgets::{Block, Borders, Clear, Paragraph},
     Frame,
 };
+use std::collections::HashMap;
+
+/// Months without an update or copy before an item shows under Stale
+pub const DEFAULT_STALE_MONTHS: u32 = 6;
 
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
 pub enum LlmProvider {
     #[default]
     Anthropic,
     OpenAI,
+    Ollama,
 }
 
 impl LlmProvider {
     pub fn all() -> &'static [LlmProvider] {
-        &[LlmProvider::Anthropic, LlmProvider::OpenAI]
+        &[
+            LlmProvider::Anthropic,
+            LlmProvider::OpenAI,
+            LlmProvider::Ollama,
+        ]
     }
 
     pub fn display_name(&self) -> &'static str {
         match self {
             LlmProvider::Anthropic => "Anthropic",
             LlmProvider::OpenAI => "OpenAI",
+            LlmProvider::Ollama => "Ollama",
+        }
+    }
+
+    pub fn default_model(&self) -> &'static str {
+        match self {
+            LlmProvider::Anthropic => "claude-sonnet-4-20250514",
+            LlmProvider::OpenAI => "gpt-4o",
+            LlmProvider::Ollama => "llama3.2",
+        }
+    }
+
+    /// Local providers run without an API key
+    pub fn needs_api_key(&self) -> bool {
+        *self != LlmProvider::Ollama
+    }
+
+    /// Providers whose endpoint can be changed, with their default URL
+    pub fn default_base_url(&self) -> Option<&'static str> {
+        match self {
+            LlmProvider::Anthropic => None,
+            LlmProvider::OpenAI => Some(OPENAI_DEFAULT_URL),
+            LlmProvider::Ollama => Some(OLLAMA_DEFAULT_URL),
         }
     }
 
     pub fn from_str(s: &str) -> Self {
         match s.to_lowercase().as_str() {
             "openai" => LlmProvider::OpenAI,
+            "ollama" => LlmProvider::Ollama,
             _ => LlmProvider::Anthropic,
         }
     }
@@ -39,7 +85,21 @@ pub enum SettingsField {
     Provider,
     ApiKey,
     Model,
+    LightModel,
+    BaseUrl,
+    Timeout,
+    Retries,
     ExportPath,
+    AutoExport,
+    GitCommit,
+    FrontmatterKeys,
+    ExportProfiles,
+    GithubToken,
+    VimMode,
+    ListColumns,
+    StaleMonths,
+    Variables,
+    Skeleton,
 }
 
 impl SettingsField {
@@ -47,17 +107,45 @@ impl SettingsField {
         match self {
             SettingsField::Provider => SettingsField::ApiKey,
             SettingsField::ApiKey => SettingsField::Model,
-            SettingsField::Model => SettingsField::ExportPath,
-            SettingsField::ExportPath => SettingsField::Provider,
+            SettingsField::Model => SettingsField::LightModel,
+            SettingsField::LightModel => SettingsField::BaseUrl,
+            SettingsField::BaseUrl => SettingsField::Timeout,
+            SettingsField::Timeout => SettingsField::Retries,
+            SettingsField::Retries => SettingsField::ExportPath,
+            SettingsField::ExportPath => SettingsField::AutoExport,
+            SettingsField::AutoExport => SettingsField::GitCommit,
+            SettingsField::GitCommit => SettingsField::FrontmatterKeys,
+            SettingsField::FrontmatterKeys => SettingsField::ExportProfiles,
+            SettingsField::ExportProfiles => SettingsField::GithubToken,
+            SettingsField::GithubToken => SettingsField::VimMode,
+            SettingsField::VimMode => SettingsField::ListColumns,
+            SettingsField::ListColumns => SettingsField::StaleMonths,
+            SettingsField::StaleMonths => SettingsField::Variables,
+            SettingsField::Variables => SettingsField::Skeleton,
+            SettingsField::Skeleton => SettingsField::Provider,
         }
     }
 
     pub fn prev(&self) -> Self {
         match self {
-            SettingsField::Provider => SettingsField::ExportPath,
+            SettingsField::Provider => SettingsField::Skeleton,
             SettingsField::ApiKey => SettingsField::Provider,
             SettingsField::Model => SettingsField::ApiKey,
-            SettingsField::ExportPath => SettingsField::Model,
+            SettingsField::LightModel => SettingsField::Model,
+            SettingsField::BaseUrl => SettingsField::LightModel,
+            SettingsField::Timeout => SettingsField::BaseUrl,
+            SettingsField::Retries => SettingsField::Timeout,
+            SettingsField::ExportPath => SettingsField::Retries,
+            SettingsField::AutoExport => SettingsField::ExportPath,
+            SettingsField::GitCommit => SettingsField::AutoExport,
+            SettingsField::FrontmatterKeys => SettingsField::GitCommit,
+            SettingsField::ExportProfiles => SettingsField::FrontmatterKeys,
+            SettingsField::GithubToken => SettingsField::ExportProfiles,
+            SettingsField::VimMode => SettingsField::GithubToken,
+            SettingsField::ListColumns => SettingsField::VimMode,
+            SettingsField::StaleMonths => SettingsField::ListColumns,
+            SettingsField::Variables => SettingsField::StaleMonths,
+            SettingsField::Skeleton => SettingsField::Variables,
         }
     }
 }
@@ -66,12 +154,51 @@ pub struct SettingsState {
     pub provider: LlmProvider,
     pub api_key: String,
     pub llm_model: String,
+    /// Cheaper model for tags, descriptions, and summaries; empty uses
+    /// `llm_model` for everything
+    pub light_model: String,
+    pub base_url: String,
+    pub timeout_secs: String,
+    pub max_retries: String,
     pub export_path: String,
+    pub auto_export: bool,
+    /// Commit the export path after each export when it is in a git repo
+    pub git_commit: bool,
+    /// Frontmatter changes by target name, e.g. `allowed-tools=allowedTools, -model`
+    pub frontmatter_keys: HashMap<String, String>,
+    /// Index in `FRONTMATTER_TARGETS` of the target the Keys field shows
+    pub frontmatter_target: usize,
+    /// Named export destinations, e.g. `work=claude-project ~/work/app`
+    pub export_profiles: String,
+    /// Token for sharing items as gists, kept like the API key
+    pub github_token: String,
+    /// Vim keys (normal / insert / visual line) in the content editor
+    pub vim_mode: bool,
+    /// Main table columns, e.g. `name,tags:20,updated`
+    pub list_columns: String,
+    /// Months without an update or copy before an item shows under Stale
+    pub stale_months: String,
+    /// Saved values for template variables, e.g. `project=grimoire; lang=Rust`
+    pub template_variables: String,
+    /// Custom content for new items, by category; the others use the
+    /// built-in skeleton
+    pub skeletons: HashMap<Category, String>,
+    /// Category whose skeleton the Skeleton field shows
+    pub skeleton_category: Category,
+    pub usage_totals: UsageTotals,
+    /// Whether the API key is kept in the OS keychain
+    pub keychain_available: bool,
     pub focused_field: SettingsField,
     pub cursor_pos: usize,
     pub has_changes: bool,
     pub show_provider_dropdown: bool,
     pub provider_dropdown_index: usize,
+    /// Result of the last backup or restore
+    pub notice: Option<String>,
+    /// Open vault, shown with its database path
+    pub vault: String,
+    /// Provider, API key, models, and base URL as last loaded or saved
+    pub saved_ai: Option<(LlmProvider, [String; 4])>,
 }
 
 impl Default for SettingsState {
@@ -80,12 +207,33 @@ impl Default for SettingsState {
             provider: LlmProvider::Anthropic,
             api_key: String::new(),
             llm_model: "claude-sonnet-4-20250514".to_string(),
+            light_model: String::new(),
+            base_url: String::new(),
+            timeout_secs: DEFAULT_TIMEOUT_SECS.to_string(),
+            max_retries: DEFAULT_MAX_RETRIES.to_string(),
             export_path: "~/.claude".to_string(),
+            auto_export: false,
+            git_commit: false,
+            frontmatter_keys: HashMap::new(),
+            frontmatter_target: 0,
+            export_profiles: String::new(),
+            github_token: String::new(),
+            vim_mode: false,
+            list_columns: DEFAULT_LIST_COLUMNS.to_string(),
+            stale_months: DEFAULT_STALE_MONTHS.to_string(),
+            template_variables: String::new(),
+            skeletons: HashMap::new(),
+            skeleton_category: Category::Skill,
+            usage_totals: UsageTotals::default(),
+            keychain_available: false,
             focused_field: SettingsField::Provider,
             cursor_pos: 0,
             has_changes: false,
             show_provider_dropdown: false,
             provider_dropdown_index: 0,
+            notice: None,
+            vault: DEFAULT_VAULT.to_string(),
+            saved_ai: None,
         }
     }
 }
@@ -96,22 +244,165 @@ impl SettingsState {
             SettingsField::Provider => self.provider.display_name(),
             SettingsField::ApiKey => &self.api_key,
             SettingsField::Model => &self.llm_model,
+            SettingsField::LightModel => &self.light_model,
+            SettingsField::BaseUrl => &self.base_url,
+            SettingsField::Timeout => &self.timeout_secs,
+            SettingsField::Retries => &self.max_retries,
             SettingsField::ExportPath => &self.export_path,
+            SettingsField::FrontmatterKeys => self
+                .frontmatter_keys
+                .get(self.frontmatter_target_name())
+                .map_or("", String::as_str),
+            SettingsField::ExportProfiles => &self.export_profiles,
+            SettingsField::GithubToken => &self.github_token,
+            SettingsField::ListColumns => &self.list_columns,
+            SettingsField::StaleMonths => &self.stale_months,
+            SettingsField::Variables => &self.template_variables,
+            SettingsField::AutoExport
+            | SettingsField::GitCommit
+            | SettingsField::VimMode
+            | SettingsField::Skeleton => "",
         }
     }
 
+    /// Whether the focused field takes typed text
+    fn is_text_field(&self) -> bool {
+        !matches!(
+            self.focused_field,
+            SettingsField::Provider
+                | SettingsField::AutoExport
+                | SettingsField::GitCommit
+                | SettingsField::VimMode
+                | SettingsField::Skeleton
+        )
+    }
+
+    fn is_numeric_field(&self) -> bool {
+        matches!(
+            self.focused_field,
+            SettingsField::Timeout | SettingsField::Retries | SettingsField::StaleMonths
+        )
+    }
+
+    pub fn toggle_auto_export(&mut self) {
+        self.auto_export = !self.auto_export;
+        self.has_changes = true;
+    }
+
+    pub fn toggle_git_commit(&mut self) {
+        self.git_commit = !self.git_commit;
+        self.has_changes = true;
+    }
+
+    pub fn toggle_vim_mode(&mut self) {
+        self.vim_mode = !self.vim_mode;
+        self.has_changes = true;
+    }
+
     fn set_current_field(&mut self, value: String) {
         self.has_changes = true;
         match self.focused_field {
             SettingsField::Provider => {} // Handled by dropdown
             SettingsField::ApiKey => self.api_key = value,
             SettingsField::Model => self.llm_model = value,
+            SettingsField::LightModel => self.light_model = value,
+            SettingsField::BaseUrl => self.base_url = value,
+            SettingsField::Timeout => self.timeout_secs = value,
+            SettingsField::Retries => self.max_retries = value,
             SettingsField::ExportPath => self.export_path = value,
+            SettingsField::FrontmatterKeys => {
+                let target = self.frontmatter_target_name().to_string();
+                self.frontmatter_keys.insert(target, value);
+            }
+            SettingsField::ExportProfiles => self.export_profiles = value,
+            SettingsField::GithubToken => self.github_token = value,
+            SettingsField::ListColumns => self.list_columns = value,
+            SettingsField::StaleMonths => self.stale_months = value,
+            SettingsField::Variables => self.template_variables = value,
+            SettingsField::AutoExport | SettingsField::GitCommit | SettingsField::VimMode => {} // Toggled with Space
+            SettingsField::Skeleton => {} // Edited in $EDITOR
         }
     }
 
+    /// Months after which untouched items count as stale
+    pub fn stale_months(&self) -> u32 {
+        self.stale_months
+            .parse()
+            .ok()
+            .filter(|months| *months > 0)
+            .unwrap_or(DEFAULT_STALE_MONTHS)
+    }
+
+    fn frontmatter_target_name(&self) -> &'static str {
+        FRONTMATTER_TARGETS[self.frontmatter_target].0
+    }
+
+    /// Show the next (or previous) target's frontmatter changes
+    pub fn cycle_frontmatter_target(&mut self, forward: bool) {
+        let len = FRONTMATTER_TARGETS.len();
+        self.frontmatter_target = if forward {
+            (self.frontmatter_target + 1) % len
+        } else {
+            (self.frontmatter_target + len - 1) % len
+        };
+        self.cursor_pos = self.current_field_value().chars().count();
+    }
+
+    /// Settings the exporters are built from. Frontmatter changes that
+    /// don't parse are left out; saving reports them.
+    pub fn export_options(&self) -> ExportOptions {
+        let mut options = ExportOptions::new(self.export_path.trim());
+        for (target, spec) in &self.frontmatter_keys {
+            if let Ok(keys) = FrontmatterKeys::parse(target, spec) {
+                options.frontmatter.insert(target.clone(), keys);
+            }
+        }
+        options
+    }
+
+    /// Profiles offered in the export popup. Ones that don't parse are left
+    /// out; saving reports them.
+    pub fn export_profiles(&self) -> Vec<ExportProfile> {
+        parse_export_profiles(&self.export_profiles).unwrap_or_default()
+    }
+
+    /// Content new items of `category` start with
+    pub fn skeleton(&self, category: Category) -> &str {
+        self.skeletons
+            .get(&category)
+            .map_or(category.default_skeleton(), String::as_str)
+    }
+
+    /// Set the skeleton for the selected category. An empty one, or one
+    /// matching the built-in skeleton, goes back to the built-in.
+    pub fn set_skeleton(&mut self, content: String) {
+        let category = self.skeleton_category;
+        if content.trim().is_empty() || content == category.default_skeleton() {
+            self.skeletons.remove(&category);
+        } else {
+            self.skeletons.insert(category, content);
+        }
+        self.has_changes = true;
+    }
+
+    /// Show the next (or previous) category's skeleton
+    pub fn cycle_skeleton_category(&mut self, forward: bool) {
+        let all = Category::all();
+        let len = all.len();
+        let index = all
+            .iter()
+            .position(|c| *c == self.skeleton_category)
+            .unwrap_or(0);
+        let index = if forward {
+            (index + 1) % len
+        } else {
+            (index + len - 1) % len
+        };
+        self.skeleton_category = all[index];
+    }
+
     pub fn insert_char(&mut self, c: char) {
-        if self.focused_field == SettingsField::Provider {
+        if !self.is_text_field() || (self.is_numeric_field() && !c.is_ascii_digit()) {
             return;
         }
         let field_value = self.current_field_value().to_string();
@@ -122,11 +413,14 @@ impl SettingsState {
     }
 
     pub fn insert_str(&mut self, s: &str) {
-        if self.focused_field == SettingsField::Provider {
+        if !self.is_text_field() {
             return;
         }
         // Filter out newlines and other control characters
-        let clean: String = s.chars().filter(|c| !c.is_control()).collect();
+        let clean: String = s
+            .chars()
+            .filter(|c| !c.is_control() && (!self.is_numeric_field() || c.is_ascii_digit()))
+            .collect();
         let field_value = self.current_field_value().to_string();
         let mut chars: Vec<char> = field_value.chars().collect();
         let insert_pos = self.cursor_pos.min(chars.len());
@@ -138,28 +432,47 @@ impl SettingsState {
     }
 
     pub fn delete_char(&mut self) {
-        if self.focused_field == SettingsField::Provider {
+        if !self.is_text_field() {
             return;
         }
         if self.cursor_pos > 0 {
-            let field_value = self.current_field_value().to_string();
-            let mut chars: Vec<char> = field_value.chars().collect();
-            chars.remove(self.cursor_pos - 1);
-            self.cursor_pos -= 1;
-            self.set_current_field(chars.into_iter().collect());
+            let mut value = self.current_field_value().to_string();
+            let start = prev_boundary(&value, self.cursor_pos);
+            remove_range(&mut value, start, self.cursor_pos);
+            self.cursor_pos = start;
+            self.set_current_field(value);
         }
     }
 
+    pub fn move_cursor_left(&mut self) {
+        self.cursor_pos = prev_boundary(self.current_field_value(), self.cursor_pos);
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        self.cursor_pos = next_boundary(self.current_field_value(), self.cursor_pos);
+    }
+
     pub fn next_field(&mut self) {
         self.focused_field = self.focused_field.next();
+        if self.is_hidden(self.focused_field) {
+            self.focused_field = self.focused_field.next();
+        }
         self.cursor_pos = self.current_field_value().chars().count();
     }
 
     pub fn prev_field(&mut self) {
         self.focused_field = self.focused_field.prev();
+        if self.is_hidden(self.focused_field) {
+            self.focused_field = self.focused_field.prev();
+        }
         self.cursor_pos = self.current_field_value().chars().count();
     }
 
+    /// Fields the current provider doesn't use are skipped when tabbing
+    fn is_hidden(&self, field: SettingsField) -> bool {
+        field == SettingsField::BaseUrl && self.provider.default_base_url().is_none()
+    }
+
     pub fn open_provider_dropdown(&mut self) {
         self.show_provider_dropdown = true;
         self.provider_dropdown_index = LlmProvider::all()
@@ -170,6 +483,15 @@ impl SettingsState {
 
     pub fn select_provider_from_dropdown(&mut self) {
         if let Some(provider) = LlmProvider::all().get(self.provider_dropdown_index) {
+            // Swap in the new provider's default unless a custom model was set
+            if self.llm_model.is_empty() || self.llm_model == self.provider.default_model() {
+                self.llm_model = provider.default_model().to_string();
+            }
+            // Endpoints and the light model are provider-specific
+            if *provider != self.provider {
+                self.base_url.clear();
+                self.light_model.clear();
+            }
             self.provider = *provider;
             self.has_changes = true;
         }
@@ -186,7 +508,151 @@ impl SettingsState {
         self.provider_dropdown_index = (self.provider_dropdown_index + max - 1) % max;
     }
 
+    /// Connection settings for the LLM clients
+    pub fn llm_config(&self) -> LlmConfig {
+        LlmConfig {
+            provider: self.provider.display_name().to_string(),
+            api_key: self.api_key.clone(),
+            model: self.llm_model.clone(),
+            base_url: self.base_url.clone(),
+            timeout_secs: self.timeout_secs.parse().unwrap_or(DEFAULT_TIMEOUT_SECS),
+            max_retries: self.max_retries.parse().unwrap_or(DEFAULT_MAX_RETRIES),
+        }
+    }
+
+    /// Connection settings for lightweight calls (tags, descriptions,
+    /// summaries), with the light model when one is set
+    pub fn light_llm_config(&self) -> LlmConfig {
+        let mut config = self.llm_config();
+        if !self.light_model.trim().is_empty() {
+            config.model = self.light_model.clone();
+        }
+        config
+    }
+
+    /// Where the current provider's API key comes from
+    pub fn key_source(&self) -> KeySource {
+        resolve_api_key(self.provider.display_name(), &self.api_key).1
+    }
+
+    pub fn has_llm(&self) -> bool {
+        !self.provider.needs_api_key()
+            || self.key_source() != KeySource::Missing
+            || (self.provider == LlmProvider::OpenAI && !self.base_url.is_empty())
+    }
+
+    fn ai_fields(&self) -> (LlmProvider, [String; 4]) {
+        (
+            self.provider,
+            [
+                &self.api_key,
+                &self.llm_model,
+                &self.light_model,
+                &self.base_url,
+            ]
+            .map(|value| value.trim().to_string()),
+        )
+    }
+
+    /// Remember the AI fields as saved, so only changing them asks for a key
+    pub fn mark_ai_saved(&mut self) {
+        self.saved_ai = Some(self.ai_fields());
+    }
+
+    /// The first problem with the entered settings, and the field to fix
+    pub fn validate(&self) -> Option<(SettingsField, String)> {
+        // Saving other settings doesn't need AI set up
+        let ai_changed = self.saved_ai.as_ref() != Some(&self.ai_fields());
+        if ai_changed && self.provider.needs_api_key() && !self.has_llm() {
+            let fallback = match key_env_var(self.provider.display_name()) {
+                Some(var) => format!(" or set ${}", var),
+                None => String::new(),
+            };
+            return Some((
+                SettingsField::ApiKey,
+                format!(
+                    "{} needs an API key: enter one{}",
+                    self.provider.display_name(),
+                    fallback
+                ),
+            ));
+        }
+
+        // Names like claude-sonnet-4-20250514, llama3.2:latest, org/model
+        for (field, model) in [
+            (SettingsField::Model, &self.llm_model),
+            (SettingsField::LightModel, &self.light_model),
+        ] {
+            let model = model.trim();
+            if let Some(c) = model
+                .chars()
+                .find(|c| !c.is_ascii_alphanumeric() && !"-_.:/@".contains(*c))
+            {
+                return Some((field, format!("model name '{}' contains '{}'", model, c)));
+            }
+        }
+
+        let base_url = self.base_url.trim();
+        if !base_url.is_empty()
+            && !base_url.starts_with("http://")
+            && !base_url.starts_with("https://")
+        {
+            return Some((
+                SettingsField::BaseUrl,
+                "base URL must start with http:// or https://".to_string(),
+            ));
+        }
+
+        if let Err(e) = check_export_path(self.export_path.trim()) {
+            return Some((SettingsField::ExportPath, e));
+        }
+
+        for (target, label, _) in FRONTMATTER_TARGETS {
+            let spec = self.frontmatter_keys.get(target).map_or("", String::as_str);
+            if let Err(e) = FrontmatterKeys::parse(target, spec) {
+                return Some((
+                    SettingsField::FrontmatterKeys,
+                    format!("{} keys: {}", label, e),
+                ));
+            }
+        }
+
+        if let Err(e) = parse_export_profiles(&self.export_profiles) {
+            return Some((SettingsField::ExportProfiles, format!("profiles: {}", e)));
+        }
+
+        if let Err(e) = parse_list_columns(&self.list_columns) {
+            return Some((SettingsField::ListColumns, format!("list columns: {}", e)));
+        }
+
+        if self
+            .stale_months
+            .parse::<u32>()
+            .map_or(true, |months| months == 0)
+        {
+            return Some((
+                SettingsField::StaleMonths,
+                "stale months must be a number above 0".to_string(),
+            ));
+        }
+
+        if let Err(e) = parse_variable_presets(&self.template_variables) {
+            return Some((SettingsField::Variables, format!("variables: {}", e)));
+        }
+
+        None
+    }
+
     /// Mask the API key for display
+    /// Where a secret saved in Settings is kept
+    fn saved_secret_source(&self) -> &'static str {
+        if self.keychain_available {
+            "from keychain"
+        } else {
+            "from database"
+        }
+    }
+
     pub fn mask_key(key: &str) -> String {
         if key.is_empty() {
             String::new()
@@ -198,6 +664,30 @@ impl SettingsState {
     }
 }
 
+/// Whether exports can be written to `path`: an existing directory, or one
+/// that can be created under its nearest existing parent
+fn check_export_path(path: &str) -> Result<(), String> {
+    if path.is_empty() {
+        return Err("export path is empty".to_string());
+    }
+
+    let expanded = expand_home(path);
+    let Some(existing) = expanded.ancestors().find(|p| p.exists()) else {
+        return Err(format!("export path {} has no existing parent", path));
+    };
+    if !existing.is_dir() {
+        return Err(format!("{} is a file, not a directory", existing.display()));
+    }
+    let readonly = existing
+        .metadata()
+        .map(|m| m.permissions().readonly())
+        .unwrap_or(true);
+    if readonly {
+        return Err(format!("{} is not writable", existing.display()));
+    }
+    Ok(())
+}
+
 pub fn draw(frame: &mut Frame, state: &SettingsState) {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
@@ -244,8 +734,12 @@ fn draw_content(frame: &mut Frame, area: Rect, state: &SettingsState) -> Rect {
     let chunks = Layout::default()
         .direction(Direction::Vertical)
         .constraints([
-            Constraint::Length(7), // LLM section
-            Constraint::Length(4), // Export section
+            Constraint::Length(9), // LLM section
+            Constraint::Length(7), // Export section
+            Constraint::Length(3), // Sharing section
+            Constraint::Length(3), // Editor section
+            Constraint::Length(4), // Display section
+            Constraint::Length(4), // Templates section
             Constraint::Length(4), // Data section
             Constraint::Min(0),    // Spacer
         ])
@@ -266,6 +760,90 @@ fn draw_content(frame: &mut Frame, area: Rect, state: &SettingsState) -> Rect {
             state.cursor_pos,
         )],
     );
+    draw_export_checkbox(
+        frame,
+        chunks[1],
+        1,
+        "Auto:     ",
+        state.auto_export,
+        state.focused_field == SettingsField::AutoExport,
+        " Export agents, skills, and commands on save",
+    );
+    draw_export_checkbox(
+        frame,
+        chunks[1],
+        2,
+        "Git:      ",
+        state.git_commit,
+        state.focused_field == SettingsField::GitCommit,
+        " Commit the export path after each export (when it is a git repo)",
+    );
+    draw_frontmatter_line(frame, chunks[1], state);
+    draw_profiles_line(frame, chunks[1], state);
+
+    draw_sharing_section(frame, chunks[2], state);
+
+    // Editor section
+    draw_editor_section(frame, chunks[3], state);
+
+    // Display section
+    let mut columns_line = text_field_line(
+        "Columns:  ",
+        &state.list_columns,
+        state.focused_field == SettingsField::ListColumns,
+        state.cursor_pos,
+    );
+    columns_line.push_span(Span::styled(
+        format!(
+            "  ({}; optional :width)",
+            ListColumn::all()
+                .iter()
+                .map(|c| c.as_str())
+                .collect::<Vec<_>>()
+                .join(" ")
+        ),
+        Style::default().fg(Color::DarkGray),
+    ));
+    let display_block = Block::default()
+        .title(" Display ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    let mut stale_line = text_field_line(
+        "Stale:    ",
+        &state.stale_months,
+        state.focused_field == SettingsField::StaleMonths,
+        state.cursor_pos,
+    );
+    stale_line.push_span(Span::styled(
+        " months without an update or copy",
+        Style::default().fg(Color::DarkGray),
+    ));
+    frame.render_widget(
+        Paragraph::new(vec![columns_line, stale_line]).block(display_block),
+        chunks[4],
+    );
+
+    // Templates section
+    let mut variables_line = text_field_line(
+        "Variables: ",
+        &state.template_variables,
+        state.focused_field == SettingsField::Variables,
+        state.cursor_pos,
+    );
+    if state.template_variables.is_empty() {
+        variables_line.push_span(Span::styled(
+            "  (name=value; ... fills {{name}} when copying)",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    let templates_block = Block::default()
+        .title(" Templates ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+    frame.render_widget(
+        Paragraph::new(vec![variables_line, skeleton_line(state)]).block(templates_block),
+        chunks[5],
+    );
 
     // Data section (read-only info)
     let data_block = Block::default()
@@ -273,16 +851,29 @@ fn draw_content(frame: &mut Frame, area: Rect, state: &SettingsState) -> Rect {
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::DarkGray));
 
-    let data_inner = data_block.inner(chunks[2]);
-    frame.render_widget(data_block, chunks[2]);
+    let data_inner = data_block.inner(chunks[6]);
+    frame.render_widget(data_block, chunks[6]);
 
-    let db_path = Database::db_path()
-        .map(|p| p.display().to_string())
+    let db_path = Vaults::path(&state.vault)
+        .map(|p| format!("{} ({})", p.display(), state.vault))
         .unwrap_or_else(|_| "unknown".to_string());
-    let data_info = Paragraph::new(vec![Line::from(vec![
-        Span::styled("Database: ", Style::default().fg(Color::Yellow)),
-        Span::styled(db_path, Style::default().fg(Color::DarkGray)),
-    ])]);
+    let usage = state.usage_totals;
+    let data_info = Paragraph::new(vec![
+        Line::from(vec![
+            Span::styled("Database: ", Style::default().fg(Color::Yellow)),
+            Span::styled(db_path, Style::default().fg(Color::DarkGray)),
+        ]),
+        Line::from(vec![
+            Span::styled("AI usage: ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!(
+                    "{} calls, {} in / {} out tokens, ~${:.2}",
+                    usage.calls, usage.input_tokens, usage.output_tokens, usage.cost_usd
+                ),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]),
+    ]);
     frame.render_widget(data_info, data_inner);
 
     // Return the LLM section area for dropdown positioning
@@ -320,72 +911,335 @@ fn draw_llm_section(frame: &mut Frame, area: Rect, state: &SettingsState) {
     let api_key_focused = state.focused_field == SettingsField::ApiKey;
     let masked_key = SettingsState::mask_key(&state.api_key);
     if api_key_focused {
-        let chars: Vec<char> = state.api_key.chars().collect();
-        let cursor_pos = state.cursor_pos.min(chars.len());
-        let before: String = chars.iter().take(cursor_pos).collect();
-        let cursor_char = chars.get(cursor_pos).copied().unwrap_or(' ');
-        let after: String = chars.iter().skip(cursor_pos + 1).collect();
-
+        lines.push(text_field_line(
+            "API Key:  ",
+            &state.api_key,
+            true,
+            state.cursor_pos,
+        ));
+    } else if !state.provider.needs_api_key() && state.api_key.is_empty() {
         lines.push(Line::from(vec![
-            Span::styled("API Key:  ", Style::default().fg(Color::Yellow)),
-            Span::raw(before),
-            Span::styled(
-                cursor_char.to_string(),
-                Style::default().bg(Color::White).fg(Color::Black),
-            ),
-            Span::raw(after),
+            Span::styled("API Key:  ", Style::default().fg(Color::DarkGray)),
+            Span::styled("(not needed)", Style::default().fg(Color::DarkGray)),
         ]));
     } else {
+        let source = match state.key_source() {
+            KeySource::Settings => state.saved_secret_source().to_string(),
+            KeySource::Env(var) => format!("from ${}", var),
+            KeySource::Missing => "not set".to_string(),
+        };
+        let source = secret_source(&masked_key, &source);
         lines.push(Line::from(vec![
             Span::styled("API Key:  ", Style::default().fg(Color::Yellow)),
             Span::raw(masked_key),
+            Span::styled(source, Style::default().fg(Color::DarkGray)),
         ]));
     }
 
-    // Model field (only show for Anthropic)
-    if state.provider == LlmProvider::Anthropic {
-        let model_focused = state.focused_field == SettingsField::Model;
-        if model_focused {
-            let chars: Vec<char> = state.llm_model.chars().collect();
-            let cursor_pos = state.cursor_pos.min(chars.len());
-            let before: String = chars.iter().take(cursor_pos).collect();
-            let cursor_char = chars.get(cursor_pos).copied().unwrap_or(' ');
-            let after: String = chars.iter().skip(cursor_pos + 1).collect();
+    // Model field
+    lines.push(text_field_line(
+        "Model:    ",
+        &state.llm_model,
+        state.focused_field == SettingsField::Model,
+        state.cursor_pos,
+    ));
+
+    // Light model field, for tags, descriptions, and summaries
+    if state.light_model.is_empty() && state.focused_field != SettingsField::LightModel {
+        lines.push(Line::from(vec![
+            Span::styled("Light:    ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                "same as Model (tags, descriptions, summaries)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]));
+    } else {
+        let mut light_line = text_field_line(
+            "Light:    ",
+            &state.light_model,
+            state.focused_field == SettingsField::LightModel,
+            state.cursor_pos,
+        );
+        light_line.push_span(Span::styled(
+            " (tags, descriptions, summaries)",
+            Style::default().fg(Color::DarkGray),
+        ));
+        lines.push(light_line);
+    }
 
+    // Base URL field (OpenAI-compatible endpoints and Ollama)
+    if let Some(default_url) = state.provider.default_base_url() {
+        let focused = state.focused_field == SettingsField::BaseUrl;
+        if state.base_url.is_empty() && !focused {
             lines.push(Line::from(vec![
-                Span::styled("Model:    ", Style::default().fg(Color::Yellow)),
-                Span::raw(before),
-                Span::styled(
-                    cursor_char.to_string(),
-                    Style::default().bg(Color::White).fg(Color::Black),
-                ),
-                Span::raw(after),
+                Span::styled("Base URL: ", Style::default().fg(Color::Yellow)),
+                Span::styled(default_url, Style::default().fg(Color::DarkGray)),
             ]));
         } else {
-            lines.push(Line::from(vec![
-                Span::styled("Model:    ", Style::default().fg(Color::Yellow)),
-                Span::raw(&state.llm_model),
-            ]));
+            lines.push(text_field_line(
+                "Base URL: ",
+                &state.base_url,
+                focused,
+                state.cursor_pos,
+            ));
         }
     } else {
-        // Show placeholder for OpenAI
         lines.push(Line::from(vec![
-            Span::styled("Model:    ", Style::default().fg(Color::DarkGray)),
-            Span::styled("(uses gpt-4o)", Style::default().fg(Color::DarkGray)),
+            Span::styled("Base URL: ", Style::default().fg(Color::DarkGray)),
+            Span::styled("(not used)", Style::default().fg(Color::DarkGray)),
         ]));
     }
 
+    // Request timeout and retries
+    let mut timeout_line = text_field_line(
+        "Timeout:  ",
+        &state.timeout_secs,
+        state.focused_field == SettingsField::Timeout,
+        state.cursor_pos,
+    );
+    timeout_line.push_span(Span::styled(
+        " seconds",
+        Style::default().fg(Color::DarkGray),
+    ));
+    lines.push(timeout_line);
+
+    let mut retries_line = text_field_line(
+        "Retries:  ",
+        &state.max_retries,
+        state.focused_field == SettingsField::Retries,
+        state.cursor_pos,
+    );
+    retries_line.push_span(Span::styled(
+        " (exponential backoff)",
+        Style::default().fg(Color::DarkGray),
+    ));
+    lines.push(retries_line);
+
     let paragraph = Paragraph::new(lines);
     frame.render_widget(paragraph, inner);
 }
 
-fn draw_provider_dropdown(frame: &mut Frame, anchor: Rect, state: &SettingsState) {
-    let dropdown_area = Rect {
-        x: anchor.x + 12,
-        y: anchor.y + 2,
-        width: 15,
-        height: 4,
+/// `source` in brackets, spaced from the masked secret when there is one
+fn secret_source(masked: &str, source: &str) -> String {
+    if masked.is_empty() {
+        format!("({})", source)
+    } else {
+        format!(" ({})", source)
+    }
+}
+
+fn draw_sharing_section(frame: &mut Frame, area: Rect, state: &SettingsState) {
+    let block = Block::default()
+        .title(" Sharing ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let line = if state.focused_field == SettingsField::GithubToken {
+        text_field_line("GitHub:   ", &state.github_token, true, state.cursor_pos)
+    } else {
+        let source = if !state.github_token.is_empty() {
+            state.saved_secret_source()
+        } else if github_token("").is_some() {
+            "from environment"
+        } else {
+            "not set"
+        };
+        let masked_token = SettingsState::mask_key(&state.github_token);
+        let source = secret_source(&masked_token, source);
+        Line::from(vec![
+            Span::styled("GitHub:   ", Style::default().fg(Color::Yellow)),
+            Span::raw(masked_token),
+            Span::styled(source, Style::default().fg(Color::DarkGray)),
+            Span::styled(
+                "  token with the gist scope, for S (share)",
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
     };
+    frame.render_widget(Paragraph::new(line), inner);
+}
+
+fn draw_editor_section(frame: &mut Frame, area: Rect, state: &SettingsState) {
+    let block = Block::default()
+        .title(" Editor ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::DarkGray));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let focused = state.focused_field == SettingsField::VimMode;
+    let checkbox = if state.vim_mode { "[x]" } else { "[ ]" };
+    let checkbox_style = if focused {
+        Style::default().bg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+
+    let line = Line::from(vec![
+        Span::styled("Vim:      ", Style::default().fg(Color::Yellow)),
+        Span::styled(checkbox, checkbox_style),
+        Span::styled(
+            " Vim keys in the content field (normal, insert, visual line)",
+            Style::default().fg(Color::DarkGray),
+        ),
+    ]);
+    frame.render_widget(Paragraph::new(line), inner);
+}
+
+/// A checkbox on line `row` (0 being the path) inside the export section's
+/// border
+fn draw_export_checkbox(
+    frame: &mut Frame,
+    section: Rect,
+    row: u16,
+    label: &'static str,
+    checked: bool,
+    focused: bool,
+    hint: &'static str,
+) {
+    let area = Rect {
+        x: section.x + 1,
+        y: section.y + 1 + row,
+        width: section.width.saturating_sub(2),
+        height: 1,
+    }
+    .intersection(section);
+
+    let checkbox = if checked { "[x]" } else { "[ ]" };
+    let checkbox_style = if focused {
+        Style::default().bg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+
+    let line = Line::from(vec![
+        Span::styled(label, Style::default().fg(Color::Yellow)),
+        Span::styled(checkbox, checkbox_style),
+        Span::styled(hint, Style::default().fg(Color::DarkGray)),
+    ]);
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// The Keys field on the export section's fourth line: the selected
+/// target and its frontmatter changes
+fn draw_frontmatter_line(frame: &mut Frame, section: Rect, state: &SettingsState) {
+    let area = Rect {
+        x: section.x + 1,
+        y: section.y + 4,
+        width: section.width.saturating_sub(2),
+        height: 1,
+    }
+    .intersection(section);
+
+    let focused = state.focused_field == SettingsField::FrontmatterKeys;
+    let (target, label, keys) = FRONTMATTER_TARGETS[state.frontmatter_target];
+    let value = state
+        .frontmatter_keys
+        .get(target)
+        .map_or("", String::as_str);
+    let mut line = text_field_line("Keys:     ", value, focused, state.cursor_pos);
+    line.spans.insert(
+        1,
+        Span::styled(format!("[{}] ", label), Style::default().fg(Color::Cyan)),
+    );
+    let hint = if focused {
+        format!(
+            "  ↑/↓ target; key=name renames, -key omits ({})",
+            keys.join(" ")
+        )
+    } else if value.is_empty() {
+        "  frontmatter as written by grimoire".to_string()
+    } else {
+        String::new()
+    };
+    line.push_span(Span::styled(hint, Style::default().fg(Color::DarkGray)));
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// The Profiles field on the export section's fifth line
+fn draw_profiles_line(frame: &mut Frame, section: Rect, state: &SettingsState) {
+    let area = Rect {
+        x: section.x + 1,
+        y: section.y + 5,
+        width: section.width.saturating_sub(2),
+        height: 1,
+    }
+    .intersection(section);
+
+    let focused = state.focused_field == SettingsField::ExportProfiles;
+    let mut line = text_field_line(
+        "Profiles: ",
+        &state.export_profiles,
+        focused,
+        state.cursor_pos,
+    );
+    if state.export_profiles.is_empty() {
+        line.push_span(Span::styled(
+            "  (name=target path; ... e.g. work=claude-project ~/work/app)",
+            Style::default().fg(Color::DarkGray),
+        ));
+    }
+    frame.render_widget(Paragraph::new(line), area);
+}
+
+/// The selected category's skeleton: whether it is custom, and its size
+fn skeleton_line(state: &SettingsState) -> Line<'static> {
+    let focused = state.focused_field == SettingsField::Skeleton;
+    let category_style = if focused {
+        Style::default().bg(Color::DarkGray)
+    } else {
+        Style::default()
+    };
+    let category = state.skeleton_category;
+    let skeleton = state.skeleton(category);
+    let summary = if skeleton.is_empty() {
+        "empty".to_string()
+    } else {
+        format!("{} lines", skeleton.lines().count())
+    };
+    let source = if state.skeletons.contains_key(&category) {
+        "custom"
+    } else {
+        "built-in"
+    };
+    let hint = if focused {
+        "  ←/→ category, Enter edit in $EDITOR"
+    } else {
+        "  content for new items"
+    };
+    Line::from(vec![
+        Span::styled("Skeleton:  ", Style::default().fg(Color::Yellow)),
+        Span::styled(format!("[{}]", category.display_name()), category_style),
+        Span::raw(format!(" {}, {}", source, summary)),
+        Span::styled(hint, Style::default().fg(Color::DarkGray)),
+    ])
+}
+
+/// A label followed by an editable value, with the cursor drawn when focused
+fn text_field_line<'a>(label: &'a str, value: &'a str, focused: bool, cursor: usize) -> Line<'a> {
+    let label_span = Span::styled(label, Style::default().fg(Color::Yellow));
+
+    if !focused {
+        return Line::from(vec![label_span, Span::raw(value)]);
+    }
+
+    let mut spans = vec![label_span];
+    spans.extend(cursor_spans(value, cursor));
+    Line::from(spans)
+}
+
+fn draw_provider_dropdown(frame: &mut Frame, anchor: Rect, state: &SettingsState) {
+    let dropdown_area = dropdown_area(
+        anchor.x + 12,
+        anchor.y + 2,
+        15,
+        LlmProvider::all().len() as u16 + 2,
+        frame.area(),
+    );
 
     frame.render_widget(Clear, dropdown_area);
 
@@ -410,7 +1264,8 @@ fn draw_provider_dropdown(frame: &mut Frame, anchor: Rect, state: &SettingsState
         ));
     }
 
-    let paragraph = Paragraph::new(lines);
+    let offset = list_offset(state.provider_dropdown_index, inner.height);
+    let paragraph = Paragraph::new(lines).scroll((offset, 0));
     frame.render_widget(paragraph, inner);
 }
 
@@ -425,27 +1280,7 @@ fn draw_section(frame: &mut Frame, area: Rect, title: &str, fields: &[(&str, &st
 
     let mut lines = Vec::new();
     for (label, value, focused, cursor) in fields {
-        let label_span = Span::styled(*label, Style::default().fg(Color::Yellow));
-
-        if *focused {
-            let chars: Vec<char> = value.chars().collect();
-            let cursor_pos = (*cursor).min(chars.len());
-            let before: String = chars.iter().take(cursor_pos).collect();
-            let cursor_char = chars.get(cursor_pos).copied().unwrap_or(' ');
-            let after: String = chars.iter().skip(cursor_pos + 1).collect();
-
-            lines.push(Line::from(vec![
-                label_span,
-                Span::raw(before),
-                Span::styled(
-                    cursor_char.to_string(),
-                    Style::default().bg(Color::White).fg(Color::Black),
-                ),
-                Span::raw(after),
-            ]));
-        } else {
-            lines.push(Line::from(vec![label_span, Span::raw(*value)]));
-        }
+        lines.push(text_field_line(label, value, *focused, *cursor));
     }
 
     let paragraph = Paragraph::new(lines);
@@ -457,6 +1292,9 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, state: &SettingsState) {
         ("Tab ", "next"),
         ("S-Tab ", "prev"),
         ("Ctrl+S ", "save"),
+        ("Ctrl+B ", "backup"),
+        ("Ctrl+R ", "restore"),
+        ("Ctrl+T ", "test connection"),
         ("ESC ", "back"),
     ];
 
@@ -464,7 +1302,7 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, state: &SettingsState) {
         shortcuts.push(("", "[unsaved]"));
     }
 
-    let spans: Vec<Span> = shortcuts
+    let mut spans: Vec<Span> = shortcuts
         .iter()
         .flat_map(|(key, action)| {
             if key.is_empty() {
@@ -484,6 +1322,19 @@ fn draw_status_bar(frame: &mut Frame, area: Rect, state: &SettingsState) {
         })
         .collect();
 
+    // The notice goes first so a full shortcut list can't push it off screen
+    if let Some(ref notice) = state.notice {
+        let color = if message_log::is_error(notice) {
+            Color::Red
+        } else {
+            Color::Green
+        };
+        spans.insert(
+            0,
+            Span::styled(format!(" {}  ", notice), Style::default().fg(color)),
+        );
+    }
+
     let status = Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::Black));
 
     frame.render_widget(status, area);
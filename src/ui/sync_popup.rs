@@ -0,0 +1,185 @@
+use super::export_diff::{diff_lines, styled_lines};
+use crate::export::{Drift, SyncAction};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+/// Items that differ between the library and the export path, each with
+/// the action to take on it
+pub struct SyncState {
+    pub drifts: Vec<Drift>,
+    pub selected: usize,
+    /// Scroll of the selected item's diff
+    pub scroll: u16,
+}
+
+impl SyncState {
+    pub fn new(drifts: Vec<Drift>) -> Self {
+        Self {
+            drifts,
+            selected: 0,
+            scroll: 0,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if self.selected + 1 < self.drifts.len() {
+            self.selected += 1;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if self.selected > 0 {
+            self.selected -= 1;
+            self.scroll = 0;
+        }
+    }
+
+    pub fn set_action(&mut self, action: SyncAction) {
+        if let Some(drift) = self.drifts.get_mut(self.selected) {
+            drift.set_action(action);
+        }
+    }
+
+    pub fn cycle_action(&mut self) {
+        if let Some(drift) = self.drifts.get_mut(self.selected) {
+            drift.cycle_action();
+        }
+    }
+
+    pub fn scroll_down(&mut self) {
+        self.scroll = self.scroll.saturating_add(1);
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &SyncState) {
+    let area = centered_rect(80, 80, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Sync with export path ({}) ", state.drifts.len()))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let list_height = (state.drifts.len() as u16).clamp(1, inner.height / 3);
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(list_height), // Drifted items
+            Constraint::Length(1),           // Legend
+            Constraint::Min(1),              // Diff of the selected item
+            Constraint::Length(1),           // Footer with keybindings
+        ])
+        .split(inner);
+
+    // Keep the selected row in view
+    let offset = state
+        .selected
+        .saturating_sub(list_height.saturating_sub(1) as usize);
+    let rows: Vec<Line> = state
+        .drifts
+        .iter()
+        .enumerate()
+        .skip(offset)
+        .map(|(i, drift)| {
+            let is_selected = i == state.selected;
+            let prefix = if is_selected { "> " } else { "  " };
+            let name_style = if is_selected {
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+            let action_color = match drift.action {
+                SyncAction::Push => Color::Green,
+                SyncAction::Pull => Color::Yellow,
+                SyncAction::Skip => Color::DarkGray,
+            };
+            Line::from(vec![
+                Span::styled(
+                    format!("{}{:8}", prefix, drift.action.label()),
+                    Style::default().fg(action_color),
+                ),
+                Span::styled(format!("{:8}", drift.category.as_str()), name_style),
+                Span::styled(format!("{:30}", drift.name), name_style),
+                Span::styled(drift.describe(), Style::default().fg(Color::DarkGray)),
+            ])
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(rows), chunks[0]);
+
+    let legend = Line::from(vec![
+        Span::styled("- on disk", Style::default().fg(Color::Red)),
+        Span::raw("  "),
+        Span::styled("+ grimoire", Style::default().fg(Color::Green)),
+    ]);
+    frame.render_widget(Paragraph::new(legend), chunks[1]);
+
+    if let Some(drift) = state.drifts.get(state.selected) {
+        let lines = styled_lines(&diff_lines(&drift.on_disk, &drift.rendered));
+        let mut text = vec![Line::styled(
+            drift.path.display().to_string(),
+            Style::default().fg(Color::White),
+        )];
+        text.extend(lines);
+        frame.render_widget(Paragraph::new(text).scroll((state.scroll, 0)), chunks[2]);
+    }
+
+    // Footer
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(" select  "),
+        Span::styled(">", Style::default().fg(Color::Yellow)),
+        Span::raw(" push  "),
+        Span::styled("<", Style::default().fg(Color::Yellow)),
+        Span::raw(" pull  "),
+        Span::styled("s", Style::default().fg(Color::Yellow)),
+        Span::raw(" skip  "),
+        Span::styled("Space", Style::default().fg(Color::Yellow)),
+        Span::raw(" cycle  "),
+        Span::styled("J/K", Style::default().fg(Color::Yellow)),
+        Span::raw(" scroll diff  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" apply  "),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(" cancel"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(footer, chunks[3]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
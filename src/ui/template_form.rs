@@ -0,0 +1,197 @@
+use super::text_cursor::{cursor_spans, insert_at, next_boundary, prev_boundary, remove_range};
+use crate::models::{fill_template, Item};
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+use std::collections::HashMap;
+
+/// Form for the `{{variables}}` of an item being copied
+pub struct TemplateFormState {
+    pub item: Item,
+    /// Variable names and the values typed so far
+    pub fields: Vec<(String, String)>,
+    pub selected: usize,
+    pub cursor_pos: usize,
+}
+
+impl TemplateFormState {
+    /// A form for `variables`, pre-filled from the saved `presets`
+    pub fn new(item: Item, variables: Vec<String>, presets: &[(String, String)]) -> Self {
+        let fields: Vec<(String, String)> = variables
+            .into_iter()
+            .map(|name| {
+                let value = presets
+                    .iter()
+                    .find(|(preset, _)| *preset == name)
+                    .map(|(_, value)| value.clone())
+                    .unwrap_or_default();
+                (name, value)
+            })
+            .collect();
+        let cursor_pos = fields.first().map_or(0, |(_, value)| value.chars().count());
+        Self {
+            item,
+            fields,
+            selected: 0,
+            cursor_pos,
+        }
+    }
+
+    fn value_mut(&mut self) -> Option<&mut String> {
+        self.fields.get_mut(self.selected).map(|(_, value)| value)
+    }
+
+    fn value(&self) -> &str {
+        self.fields
+            .get(self.selected)
+            .map_or("", |(_, value)| value.as_str())
+    }
+
+    pub fn insert_str(&mut self, s: &str) {
+        let clean: String = s.chars().filter(|c| !c.is_control()).collect();
+        let pos = self.cursor_pos;
+        if let Some(value) = self.value_mut() {
+            insert_at(value, pos, &clean);
+            self.cursor_pos += clean.chars().count();
+        }
+    }
+
+    pub fn delete_char(&mut self) {
+        let pos = self.cursor_pos;
+        let start = prev_boundary(self.value(), pos);
+        if let Some(value) = self.value_mut() {
+            remove_range(value, start, pos);
+            self.cursor_pos = start;
+        }
+    }
+
+    pub fn move_cursor_left(&mut self) {
+        self.cursor_pos = prev_boundary(self.value(), self.cursor_pos);
+    }
+
+    pub fn move_cursor_right(&mut self) {
+        self.cursor_pos = next_boundary(self.value(), self.cursor_pos);
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.fields.is_empty() {
+            self.selected = (self.selected + 1) % self.fields.len();
+            self.cursor_pos = self.value().chars().count();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.fields.is_empty() {
+            let len = self.fields.len();
+            self.selected = (self.selected + len - 1) % len;
+            self.cursor_pos = self.value().chars().count();
+        }
+    }
+
+    /// The item's content with the values filled in
+    pub fn filled(&self) -> String {
+        let values: HashMap<String, String> = self.fields.iter().cloned().collect();
+        fill_template(&self.item.content, &values)
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &TemplateFormState) {
+    let area = centered_rect(70, 70, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(" Fill Template: {} ", state.item.name))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(state.fields.len() as u16 + 1), // Variables
+            Constraint::Min(1),                                // Preview
+            Constraint::Length(1),                             // Footer
+        ])
+        .split(inner);
+
+    // One line per variable, labels padded to the longest name
+    let width = state
+        .fields
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    let lines: Vec<Line> = state
+        .fields
+        .iter()
+        .enumerate()
+        .map(|(i, (name, value))| {
+            let label = Span::styled(
+                format!("{:<width$}  ", name, width = width),
+                if i == state.selected {
+                    Style::default()
+                        .fg(Color::Cyan)
+                        .add_modifier(Modifier::BOLD)
+                } else {
+                    Style::default().fg(Color::Yellow)
+                },
+            );
+            let mut spans = vec![label];
+            if i == state.selected {
+                spans.extend(cursor_spans(value, state.cursor_pos));
+            } else {
+                spans.push(Span::raw(value.clone()));
+            }
+            Line::from(spans)
+        })
+        .collect();
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    let preview = Paragraph::new(state.filled())
+        .style(Style::default().fg(Color::DarkGray))
+        .wrap(Wrap { trim: false });
+    frame.render_widget(preview, chunks[1]);
+
+    // Footer
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("Tab", Style::default().fg(Color::Yellow)),
+        Span::raw(" next  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" copy  "),
+        Span::styled("Ctrl+S", Style::default().fg(Color::Yellow)),
+        Span::raw(" save as presets  "),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(" cancel"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(footer, chunks[2]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
@@ -0,0 +1,98 @@
+//! Cursor handling shared by the text fields. Cursor positions are char
+//! indices that stay on grapheme cluster boundaries, so an emoji or an
+//! accented letter built from several chars moves and deletes as one.
+
+use ratatui::{
+    style::{Color, Style},
+    text::Span,
+};
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::UnicodeWidthStr;
+
+/// Byte offset of the char at `pos`, or the end of `text`
+fn byte_offset(text: &str, pos: usize) -> usize {
+    text.char_indices()
+        .nth(pos)
+        .map_or(text.len(), |(offset, _)| offset)
+}
+
+/// Start of the grapheme before `pos`
+pub fn prev_boundary(text: &str, pos: usize) -> usize {
+    let mut boundary = 0;
+    let mut chars = 0;
+    for grapheme in text.graphemes(true) {
+        if chars >= pos {
+            break;
+        }
+        boundary = chars;
+        chars += grapheme.chars().count();
+    }
+    boundary
+}
+
+/// End of the grapheme at `pos`
+pub fn next_boundary(text: &str, pos: usize) -> usize {
+    let mut chars = 0;
+    for grapheme in text.graphemes(true) {
+        chars += grapheme.chars().count();
+        if chars > pos {
+            return chars;
+        }
+    }
+    chars
+}
+
+/// Insert `s` at char position `pos` (clamped to the end)
+pub fn insert_at(text: &mut String, pos: usize, s: &str) {
+    text.insert_str(byte_offset(text, pos), s);
+}
+
+/// Remove the chars in `start..end`
+pub fn remove_range(text: &mut String, start: usize, end: usize) {
+    let (start, end) = (byte_offset(text, start), byte_offset(text, end));
+    text.replace_range(start..end.max(start), "");
+}
+
+/// The text before the cursor, the grapheme under it (a space at the end of
+/// the text), and the rest
+pub fn split_at_cursor(text: &str, pos: usize) -> (&str, &str, &str) {
+    let offset = byte_offset(text, pos);
+    let (before, rest) = text.split_at(offset);
+    match rest.graphemes(true).next() {
+        Some(grapheme) => (before, grapheme, &rest[grapheme.len()..]),
+        None => (before, " ", ""),
+    }
+}
+
+/// Terminal columns `text` takes
+pub fn display_width(text: &str) -> usize {
+    text.width()
+}
+
+/// Char position in `line` of the grapheme at display column `column`, or
+/// the end of the line when it is shorter
+pub fn pos_at_column(line: &str, column: usize) -> usize {
+    let mut width = 0;
+    let mut chars = 0;
+    for grapheme in line.graphemes(true) {
+        width += grapheme.width();
+        if width > column {
+            break;
+        }
+        chars += grapheme.chars().count();
+    }
+    chars
+}
+
+/// A single-line value with the cursor drawn on the grapheme at `pos`
+pub fn cursor_spans(value: &str, pos: usize) -> Vec<Span<'static>> {
+    let (before, at, after) = split_at_cursor(value, pos);
+    vec![
+        Span::raw(before.to_string()),
+        Span::styled(
+            at.to_string(),
+            Style::default().bg(Color::White).fg(Color::Black),
+        ),
+        Span::raw(after.to_string()),
+    ]
+}
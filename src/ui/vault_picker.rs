@@ -0,0 +1,130 @@
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph},
+    Frame,
+};
+
+pub struct VaultPickerState {
+    pub vaults: Vec<String>,
+    pub current: String,
+    pub selected: usize,
+}
+
+impl VaultPickerState {
+    pub fn new(vaults: Vec<String>, current: &str) -> Self {
+        let selected = vaults.iter().position(|v| v == current).unwrap_or(0);
+        Self {
+            vaults,
+            current: current.to_string(),
+            selected,
+        }
+    }
+
+    pub fn select_next(&mut self) {
+        if !self.vaults.is_empty() {
+            self.selected = (self.selected + 1) % self.vaults.len();
+        }
+    }
+
+    pub fn select_prev(&mut self) {
+        if !self.vaults.is_empty() {
+            self.selected = (self.selected + self.vaults.len() - 1) % self.vaults.len();
+        }
+    }
+
+    pub fn selected_vault(&self) -> Option<&str> {
+        self.vaults.get(self.selected).map(|v| v.as_str())
+    }
+}
+
+pub fn draw(frame: &mut Frame, state: &VaultPickerState) {
+    let height = state.vaults.len() as u16 + 4;
+    let area = centered_rect_fixed(40, height, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(" Vaults ")
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Min(1),    // Vaults
+            Constraint::Length(1), // Footer with keybindings
+        ])
+        .split(inner);
+
+    let lines: Vec<Line> = state
+        .vaults
+        .iter()
+        .enumerate()
+        .map(|(i, vault)| {
+            let is_selected = i == state.selected;
+            let prefix = if is_selected { "> " } else { "  " };
+            let style = if is_selected {
+                Style::default()
+                    .fg(Color::Cyan)
+                    .add_modifier(Modifier::BOLD)
+            } else {
+                Style::default()
+            };
+
+            let mut spans = vec![Span::styled(format!("{}{}", prefix, vault), style)];
+            if *vault == state.current {
+                spans.push(Span::styled(
+                    " (open)",
+                    Style::default().fg(Color::DarkGray),
+                ));
+            }
+            Line::from(spans)
+        })
+        .collect();
+
+    frame.render_widget(Paragraph::new(lines), chunks[0]);
+
+    // Footer
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(" select  "),
+        Span::styled("Enter", Style::default().fg(Color::Yellow)),
+        Span::raw(" open  "),
+        Span::styled("n", Style::default().fg(Color::Yellow)),
+        Span::raw(" new  "),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(" cancel"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(footer, chunks[1]);
+}
+
+fn centered_rect_fixed(percent_x: u16, height: u16, r: Rect) -> Rect {
+    // Center vertically with fixed height
+    let vertical_padding = r.height.saturating_sub(height) / 2;
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(vertical_padding),
+            Constraint::Length(height),
+            Constraint::Min(0),
+        ])
+        .split(r);
+
+    // Center horizontally with percentage width
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
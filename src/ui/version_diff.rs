@@ -0,0 +1,208 @@
+use super::export_diff::{diff_lines, styled_lines, DiffLine};
+use crate::models::Item;
+use ratatui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Color, Style},
+    text::{Line, Span},
+    widgets::{Block, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+/// Instructions for summarizing the changes between two versions
+pub const SUMMARY_PROMPT: &str = "You review edits to a prompt or agent definition. Given an \
+     older and a newer version, summarize in 2-4 short sentences what changed and what \
+     effect it is likely to have on the model's behavior. Mention renamed or changed \
+     fields. Don't quote large passages. Return only the summary.";
+
+/// Changes between a past version of an item and its current version
+pub struct VersionDiffState {
+    pub item_name: String,
+    pub old_version: i64,
+    pub current_version: i64,
+    pub lines: Vec<DiffLine>,
+    pub scroll: u16,
+    /// The AI's account of the changes, or why there is none
+    pub summary: Option<String>,
+    pub summarizing: bool,
+    old_text: String,
+    current_text: String,
+}
+
+impl VersionDiffState {
+    pub fn new(old: &Item, current: &Item) -> Self {
+        let old_text = diff_text(old);
+        let current_text = diff_text(current);
+        Self {
+            item_name: current.name.clone(),
+            old_version: old.version,
+            current_version: current.version,
+            lines: diff_lines(&old_text, &current_text),
+            scroll: 0,
+            summary: None,
+            summarizing: false,
+            old_text,
+            current_text,
+        }
+    }
+
+    /// Message asking for a summary of the changes, with both versions
+    pub fn summary_request(&self) -> String {
+        format!(
+            "Older version (v{}):\n{}\n\nNewer version (v{}):\n{}",
+            self.old_version, self.old_text, self.current_version, self.current_text
+        )
+    }
+
+    pub fn scroll_down(&mut self) {
+        if (self.scroll as usize) + 1 < self.lines.len() {
+            self.scroll += 1;
+        }
+    }
+
+    pub fn scroll_up(&mut self) {
+        self.scroll = self.scroll.saturating_sub(1);
+    }
+}
+
+/// The item's fields followed by its content, so metadata changes show up
+/// in the diff alongside content changes
+fn diff_text(item: &Item) -> String {
+    let fields = [
+        ("name", Some(item.name.as_str())),
+        ("category", Some(item.category.as_str())),
+        ("description", item.description.as_deref()),
+        ("tags", item.tags.as_deref()),
+        ("model", item.model.as_deref()),
+        ("tools", item.tools.as_deref()),
+        ("allowed-tools", item.allowed_tools.as_deref()),
+        ("argument-hint", item.argument_hint.as_deref()),
+        ("permission-mode", item.permission_mode.as_deref()),
+        ("skills", item.skills.as_deref()),
+    ];
+
+    let mut text = String::new();
+    for (key, value) in fields {
+        if let Some(value) = value {
+            text.push_str(&format!("{}: {}\n", key, value.replace('\n', " ")));
+        }
+    }
+    text.push('\n');
+    text.push_str(&item.content);
+    if !item.content.ends_with('\n') {
+        text.push('\n');
+    }
+    text
+}
+
+pub fn draw(frame: &mut Frame, state: &VersionDiffState) {
+    let area = centered_rect(80, 80, frame.area());
+
+    // Clear the area behind the popup
+    frame.render_widget(Clear, area);
+
+    let block = Block::default()
+        .title(format!(
+            " {}: v{} → v{} ",
+            state.item_name, state.old_version, state.current_version
+        ))
+        .borders(Borders::ALL)
+        .border_style(Style::default().fg(Color::Cyan));
+
+    let inner = block.inner(area);
+    frame.render_widget(block, area);
+
+    let summary = if state.summarizing {
+        Some("Summarizing changes...")
+    } else {
+        state.summary.as_deref()
+    };
+    // Wrapped lines of the summary plus its borders, at most a third of
+    // the popup
+    let summary_height = summary.map_or(0, |text| {
+        let width = inner.width.max(1) as usize;
+        let lines: usize = text.lines().map(|line| line.len() / width + 1).sum();
+        (lines as u16 + 2).min(inner.height / 3)
+    });
+
+    let chunks = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Length(1),              // Legend
+            Constraint::Length(summary_height), // AI summary of the changes
+            Constraint::Min(1),                 // Diff
+            Constraint::Length(1),              // Footer with keybindings
+        ])
+        .split(inner);
+
+    let legend = Paragraph::new(Line::from(vec![
+        Span::styled(
+            format!("- v{}", state.old_version),
+            Style::default().fg(Color::Red),
+        ),
+        Span::raw("  "),
+        Span::styled(
+            format!("+ v{} (latest)", state.current_version),
+            Style::default().fg(Color::Green),
+        ),
+    ]));
+    frame.render_widget(legend, chunks[0]);
+
+    if let Some(text) = summary {
+        let color = if state.summarizing {
+            Color::DarkGray
+        } else {
+            Color::White
+        };
+        let summary = Paragraph::new(text)
+            .style(Style::default().fg(color))
+            .wrap(Wrap { trim: true })
+            .block(
+                Block::default()
+                    .title(" Summary ")
+                    .borders(Borders::TOP | Borders::BOTTOM)
+                    .border_style(Style::default().fg(Color::DarkGray)),
+            );
+        frame.render_widget(summary, chunks[1]);
+    }
+
+    if state.lines.is_empty() {
+        let message = Paragraph::new("No differences").style(Style::default().fg(Color::DarkGray));
+        frame.render_widget(message, chunks[2]);
+    } else {
+        let lines = styled_lines(&state.lines);
+        frame.render_widget(Paragraph::new(lines).scroll((state.scroll, 0)), chunks[2]);
+    }
+
+    // Footer
+    let footer = Paragraph::new(Line::from(vec![
+        Span::styled("j/k", Style::default().fg(Color::Yellow)),
+        Span::raw(" scroll  "),
+        Span::styled("a", Style::default().fg(Color::Yellow)),
+        Span::raw(" summarize with AI  "),
+        Span::styled("ESC", Style::default().fg(Color::Yellow)),
+        Span::raw(" back to history"),
+    ]))
+    .style(Style::default().fg(Color::DarkGray));
+
+    frame.render_widget(footer, chunks[3]);
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints([
+            Constraint::Percentage((100 - percent_y) / 2),
+            Constraint::Percentage(percent_y),
+            Constraint::Percentage((100 - percent_y) / 2),
+        ])
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints([
+            Constraint::Percentage((100 - percent_x) / 2),
+            Constraint::Percentage(percent_x),
+            Constraint::Percentage((100 - percent_x) / 2),
+        ])
+        .split(popup_layout[1])[1]
+}
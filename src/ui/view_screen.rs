@@ -1,8 +1,10 @@
+use super::{markdown, wrapped_rows, COPY_MENU};
+use crate::llm::estimate_tokens;
 use crate::models::{Category, Item};
 use ratatui::{
     layout::{Constraint, Direction, Layout, Rect},
     style::{Color, Modifier, Style},
-    text::{Line, Span},
+    text::{Line, Span, Text},
     widgets::{Block, Borders, Paragraph, Scrollbar, ScrollbarOrientation, ScrollbarState, Wrap},
     Frame,
 };
@@ -13,9 +15,58 @@ pub struct ViewState {
     pub max_scroll: u16,
     pub viewing_version: Option<i64>, // None means latest/current
     pub max_version: i64,             // Current/latest version number
+    pub links: Vec<String>,           // Names of the skills/commands this item uses
+    pub used_by: Vec<String>,         // Names of the items linking to this one
+    /// Show the content as rendered markdown instead of source (`m`)
+    pub render_markdown: bool,
+    /// Query being typed after `/`
+    pub find_input: Option<String>,
+    /// Query highlighted in the content, ignoring ASCII case
+    pub find_query: String,
+    /// Matches of `find_query` as (line, occurrence within the line),
+    /// filled in when the content is drawn
+    pub find_matches: Vec<(usize, usize)>,
+    pub find_current: usize,
+    /// Scroll to the current match on the next draw
+    pub find_jump: bool,
 }
 
-pub fn draw(frame: &mut Frame, item: Option<&Item>, view_state: &mut ViewState) {
+impl ViewState {
+    /// Start highlighting `query` and jump to its first match
+    pub fn find(&mut self, query: String) {
+        self.find_query = query;
+        self.find_current = 0;
+        self.find_jump = true;
+    }
+
+    /// Move to the next (or previous) match, wrapping around
+    pub fn find_step(&mut self, forward: bool) {
+        let count = self.find_matches.len();
+        if count == 0 {
+            return;
+        }
+        self.find_current = if forward {
+            (self.find_current + 1) % count
+        } else {
+            (self.find_current + count - 1) % count
+        };
+        self.find_jump = true;
+    }
+
+    pub fn clear_find(&mut self) {
+        self.find_query.clear();
+        self.find_matches.clear();
+        self.find_current = 0;
+    }
+}
+
+pub fn draw(
+    frame: &mut Frame,
+    item: Option<&Item>,
+    is_favorite: bool,
+    copy_menu: bool,
+    view_state: &mut ViewState,
+) {
     let item = match item {
         Some(item) => item,
         None => {
@@ -29,22 +80,26 @@ pub fn draw(frame: &mut Frame, item: Option<&Item>, view_state: &mut ViewState)
     let is_viewing_old = view_state.viewing_version.is_some()
         && view_state.viewing_version != Some(view_state.max_version);
 
+    // One extra metadata line each for links and reverse links
+    let metadata_height =
+        9 + u16::from(!view_state.links.is_empty()) + u16::from(!view_state.used_by.is_empty());
+
     let constraints = if is_viewing_old {
         vec![
-            Constraint::Length(1), // Title bar
-            Constraint::Length(1), // Version warning banner
-            Constraint::Length(9), // Metadata section
-            Constraint::Length(5), // Description section
-            Constraint::Min(0),    // Content section
-            Constraint::Length(1), // Status bar
+            Constraint::Length(1),               // Title bar
+            Constraint::Length(1),               // Version warning banner
+            Constraint::Length(metadata_height), // Metadata section
+            Constraint::Length(5),               // Description section
+            Constraint::Min(0),                  // Content section
+            Constraint::Length(1),               // Status bar
         ]
     } else {
         vec![
-            Constraint::Length(1), // Title bar
-            Constraint::Length(9), // Metadata section
-            Constraint::Length(5), // Description section
-            Constraint::Min(0),    // Content section
-            Constraint::Length(1), // Status bar
+            Constraint::Length(1),               // Title bar
+            Constraint::Length(metadata_height), // Metadata section
+            Constraint::Length(5),               // Description section
+            Constraint::Min(0),                  // Content section
+            Constraint::Length(1),               // Status bar
         ]
     };
 
@@ -62,6 +117,10 @@ pub fn draw(frame: &mut Frame, item: Option<&Item>, view_state: &mut ViewState)
                 .fg(Color::Cyan)
                 .add_modifier(Modifier::BOLD),
         ),
+        Span::styled(
+            if is_favorite { "★" } else { "" },
+            Style::default().fg(Color::Yellow),
+        ),
         Span::raw("                                                        "),
         Span::styled("[ESC] Back", Style::default().fg(Color::DarkGray)),
     ]));
@@ -101,7 +160,7 @@ pub fn draw(frame: &mut Frame, item: Option<&Item>, view_state: &mut ViewState)
     idx += 1;
 
     // Status bar
-    draw_status_bar(frame, chunks[idx], is_viewing_old);
+    draw_status_bar(frame, chunks[idx], is_viewing_old, copy_menu, view_state);
 }
 
 fn draw_metadata(frame: &mut Frame, area: Rect, item: &Item, view_state: &ViewState) {
@@ -187,6 +246,26 @@ fn draw_metadata(frame: &mut Frame, area: Rect, item: &Item, view_state: &ViewSt
         Category::Prompt => {}
     }
 
+    // Links between items
+    if !view_state.links.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("Links:       ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                view_state.links.join(", "),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]));
+    }
+    if !view_state.used_by.is_empty() {
+        lines.push(Line::from(vec![
+            Span::styled("Used by:     ", Style::default().fg(Color::Yellow)),
+            Span::styled(
+                view_state.used_by.join(", "),
+                Style::default().fg(Color::Cyan),
+            ),
+        ]));
+    }
+
     // Timestamps
     lines.push(Line::from(vec![
         Span::styled("Updated:     ", Style::default().fg(Color::Yellow)),
@@ -220,18 +299,79 @@ fn draw_description(frame: &mut Frame, area: Rect, item: &Item) {
 }
 
 fn draw_content(frame: &mut Frame, area: Rect, item: &Item, view_state: &mut ViewState) {
+    let title = if view_state.render_markdown {
+        " Content (rendered) "
+    } else {
+        " Content "
+    };
     let block = Block::default()
-        .title(" Content ")
+        .title(title)
+        .title_bottom(
+            Line::styled(
+                format!(
+                    " {} words, ~{} tokens ",
+                    item.content.split_whitespace().count(),
+                    estimate_tokens(&item.content)
+                ),
+                Style::default().fg(Color::DarkGray),
+            )
+            .right_aligned(),
+        )
         .borders(Borders::ALL)
         .border_style(Style::default().fg(Color::Cyan));
 
     let inner = block.inner(area);
 
-    // Calculate max scroll based on content height
-    let content_lines = item.content.lines().count() as u16;
-    view_state.max_scroll = content_lines.saturating_sub(inner.height);
+    let mut text = if view_state.render_markdown {
+        Text::from(markdown::render(&item.content, inner.width))
+    } else {
+        Text::from(item.content.clone())
+    };
+
+    // Scrolling counts screen rows, so long lines count once per row they
+    // wrap to
+    let rows: Vec<usize> = text
+        .lines
+        .iter()
+        .map(|line| wrapped_rows(line.clone(), inner.width))
+        .collect();
+    let content_rows = rows.iter().sum::<usize>().min(u16::MAX as usize) as u16;
+    view_state.max_scroll = content_rows.saturating_sub(inner.height);
+
+    if !view_state.find_query.is_empty() {
+        let query: Vec<char> = view_state.find_query.chars().collect();
+        let current = view_state.find_current;
+        let mut matches = Vec::new();
+        // Line and span of the current match
+        let mut current_span = None;
+        for (index, line) in text.lines.iter_mut().enumerate() {
+            let span = highlight_matches(line, &query, |occurrence| {
+                matches.push((index, occurrence));
+                matches.len() - 1 == current
+            });
+            if let Some(span) = span {
+                current_span = Some((index, span));
+            }
+        }
+        view_state.find_current = current.min(matches.len().saturating_sub(1));
+        view_state.find_matches = matches;
+
+        if std::mem::take(&mut view_state.find_jump) {
+            if let Some((index, span)) = current_span {
+                // The row the match wraps to: the rows of the lines above,
+                // then the last row of its line up to and including it
+                let up_to_match = Line::from(text.lines[index].spans[..=span].to_vec());
+                let row = rows[..index].iter().sum::<usize>()
+                    + wrapped_rows(up_to_match, inner.width)
+                    - 1;
+                // Show the match a few rows below the top
+                view_state.scroll = (row.saturating_sub(3).min(u16::MAX as usize) as u16)
+                    .min(view_state.max_scroll);
+            }
+        }
+    }
 
-    let paragraph = Paragraph::new(item.content.clone())
+    let paragraph = Paragraph::new(text)
         .block(block)
         .wrap(Wrap { trim: false })
         .scroll((view_state.scroll, 0));
@@ -258,32 +398,132 @@ fn draw_content(frame: &mut Frame, area: Rect, item: &Item, view_state: &mut Vie
     }
 }
 
-fn draw_status_bar(frame: &mut Frame, area: Rect, is_viewing_old: bool) {
+/// Split `line`'s spans around each match of `query`, ignoring ASCII case.
+/// `is_current` is called with each match's index within the line and
+/// says whether it is the current match, which gets a stronger highlight.
+/// Returns the index of the current match's span, if it is in this line.
+fn highlight_matches(
+    line: &mut Line,
+    query: &[char],
+    mut is_current: impl FnMut(usize) -> bool,
+) -> Option<usize> {
+    let mut spans = Vec::new();
+    let mut occurrence = 0;
+    let mut current = None;
+
+    for span in line.spans.drain(..) {
+        let chars: Vec<char> = span.content.chars().collect();
+        let mut plain_start = 0;
+        let mut i = 0;
+        while i + query.len() <= chars.len() {
+            let found = chars[i..i + query.len()]
+                .iter()
+                .zip(query)
+                .all(|(a, b)| a.eq_ignore_ascii_case(b));
+            if !found {
+                i += 1;
+                continue;
+            }
+
+            if plain_start < i {
+                spans.push(Span::styled(
+                    chars[plain_start..i].iter().collect::<String>(),
+                    span.style,
+                ));
+            }
+            let style = if is_current(occurrence) {
+                current = Some(spans.len());
+                span.style.fg(Color::Black).bg(Color::Yellow)
+            } else {
+                span.style.fg(Color::Black).bg(Color::DarkGray)
+            };
+            spans.push(Span::styled(
+                chars[i..i + query.len()].iter().collect::<String>(),
+                style,
+            ));
+            occurrence += 1;
+            i += query.len();
+            plain_start = i;
+        }
+
+        if plain_start == 0 {
+            spans.push(span);
+        } else if plain_start < chars.len() {
+            spans.push(Span::styled(
+                chars[plain_start..].iter().collect::<String>(),
+                span.style,
+            ));
+        }
+    }
+
+    line.spans = spans;
+    current
+}
+
+fn draw_status_bar(
+    frame: &mut Frame,
+    area: Rect,
+    is_viewing_old: bool,
+    copy_menu: bool,
+    view_state: &ViewState,
+) {
+    if let Some(ref input) = view_state.find_input {
+        let prompt = Paragraph::new(Line::from(vec![
+            Span::styled("/", Style::default().fg(Color::Yellow)),
+            Span::raw(input.clone()),
+            Span::styled("█", Style::default().fg(Color::Yellow)),
+        ]))
+        .style(Style::default().bg(Color::Black));
+        frame.render_widget(prompt, area);
+        return;
+    }
+
+    let render_markdown = view_state.render_markdown;
+    let mut spans = Vec::new();
+    if !view_state.find_query.is_empty() {
+        let position = match view_state.find_matches.len() {
+            0 => format!("/{}: no matches  ", view_state.find_query),
+            count => format!(
+                "/{}: {}/{}  ",
+                view_state.find_query,
+                view_state.find_current + 1,
+                count
+            ),
+        };
+        spans.push(Span::styled(position, Style::default().fg(Color::Cyan)));
+    }
+
     let mut shortcuts = vec![
+        ("/ ", "find"),
         ("e ", "edit"),
         ("c ", "copy"),
         ("C-a ", "ai-assist"),
         ("h ", "history"),
+        ("m ", if render_markdown { "source" } else { "render" }),
     ];
 
     if is_viewing_old {
         shortcuts.push(("L ", "latest"));
     }
 
+    if !view_state.find_query.is_empty() {
+        shortcuts.push(("n/N ", "next/prev"));
+    }
+
     shortcuts.extend([("x ", "export"), ("dd ", "delete"), ("ESC ", "back")]);
+    if copy_menu {
+        shortcuts = COPY_MENU.to_vec();
+    }
 
-    let spans: Vec<Span> = shortcuts
-        .iter()
-        .flat_map(|(key, action)| {
-            vec![
-                Span::styled(*key, Style::default().fg(Color::Yellow)),
-                Span::styled(
-                    format!("{}  ", action),
-                    Style::default().fg(Color::DarkGray),
-                ),
-            ]
-        })
-        .collect();
+    spans.extend(shortcuts.iter().flat_map(|(key, action)| {
+        vec![
+            Span::styled(*key, Style::default().fg(Color::Yellow)),
+            Span::styled(
+                format!("{}  ", action),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ]
+    }));
 
     let status = Paragraph::new(Line::from(spans)).style(Style::default().bg(Color::Black));
 
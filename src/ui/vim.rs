@@ -0,0 +1,396 @@
+use super::text_cursor::{next_boundary, prev_boundary};
+use super::EditState;
+use crossterm::event::KeyCode;
+
+/// Mode of the optional vim keys in the content field
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum VimMode {
+    #[default]
+    Normal,
+    Insert,
+    /// Whole lines from the one holding `anchor` (a char position) to the
+    /// cursor's
+    VisualLine {
+        anchor: usize,
+    },
+}
+
+impl VimMode {
+    pub fn label(&self) -> &'static str {
+        match self {
+            VimMode::Normal => "NORMAL",
+            VimMode::Insert => "INSERT",
+            VimMode::VisualLine { .. } => "VISUAL LINE",
+        }
+    }
+}
+
+/// Vim editing state: mode, the pending operator, and the yank register
+#[derive(Debug, Default)]
+pub struct VimState {
+    pub mode: VimMode,
+    /// Operator keys typed so far, e.g. `d` or `ci`
+    pub pending: String,
+    /// Last yanked or deleted text
+    pub register: String,
+    /// Whether `register` holds whole lines (pasted below / above)
+    pub register_linewise: bool,
+}
+
+/// Position of the first character of the line holding `pos`
+fn line_start(chars: &[char], pos: usize) -> usize {
+    let pos = pos.min(chars.len());
+    chars[..pos]
+        .iter()
+        .rposition(|c| *c == '\n')
+        .map_or(0, |i| i + 1)
+}
+
+/// Position of the newline ending the line holding `pos` (or the end)
+fn line_end(chars: &[char], pos: usize) -> usize {
+    let pos = pos.min(chars.len());
+    chars[pos..]
+        .iter()
+        .position(|c| *c == '\n')
+        .map_or(chars.len(), |i| pos + i)
+}
+
+/// Char range covering the whole lines holding `a` and `b`, including the
+/// newline after the last one when there is one
+fn lines_range(chars: &[char], a: usize, b: usize) -> (usize, usize) {
+    let start = line_start(chars, a.min(b));
+    let end = line_end(chars, a.max(b));
+    (start, (end + 1).min(chars.len()))
+}
+
+#[derive(PartialEq)]
+enum CharClass {
+    Space,
+    Word,
+    Punct,
+}
+
+fn class(c: char) -> CharClass {
+    if c.is_whitespace() {
+        CharClass::Space
+    } else if c.is_alphanumeric() || c == '_' {
+        CharClass::Word
+    } else {
+        CharClass::Punct
+    }
+}
+
+/// `w`: start of the next word
+fn word_forward(chars: &[char], pos: usize) -> usize {
+    let mut i = pos;
+    if let Some(&c) = chars.get(i) {
+        let start = class(c);
+        while i < chars.len() && class(chars[i]) == start && start != CharClass::Space {
+            i += 1;
+        }
+    }
+    while i < chars.len() && class(chars[i]) == CharClass::Space {
+        i += 1;
+    }
+    i.min(chars.len())
+}
+
+/// `b`: start of the current or previous word
+fn word_backward(chars: &[char], pos: usize) -> usize {
+    let mut i = pos.min(chars.len());
+    while i > 0 && class(chars[i - 1]) == CharClass::Space {
+        i -= 1;
+    }
+    if i == 0 {
+        return 0;
+    }
+    let run = class(chars[i - 1]);
+    while i > 0 && class(chars[i - 1]) == run {
+        i -= 1;
+    }
+    i
+}
+
+/// `e`: last character of the current or next word
+fn word_end(chars: &[char], pos: usize) -> usize {
+    let mut i = pos + 1;
+    while i < chars.len() && class(chars[i]) == CharClass::Space {
+        i += 1;
+    }
+    if i >= chars.len() {
+        return chars.len().saturating_sub(1);
+    }
+    let run = class(chars[i]);
+    while i + 1 < chars.len() && class(chars[i + 1]) == run {
+        i += 1;
+    }
+    i
+}
+
+/// `iw`: the run of same-class characters around `pos`, as a range
+fn inner_word(chars: &[char], pos: usize) -> (usize, usize) {
+    let Some(&c) = chars.get(pos) else {
+        return (pos, pos);
+    };
+    if c == '\n' {
+        return (pos, pos);
+    }
+    let run = class(c);
+    let mut start = pos;
+    while start > 0 && chars[start - 1] != '\n' && class(chars[start - 1]) == run {
+        start -= 1;
+    }
+    let mut end = pos + 1;
+    while end < chars.len() && chars[end] != '\n' && class(chars[end]) == run {
+        end += 1;
+    }
+    (start, end)
+}
+
+impl EditState {
+    /// Leave insert mode, stepping back onto the last inserted character
+    pub fn vim_leave_insert(&mut self) {
+        let chars: Vec<char> = self.item.content.chars().collect();
+        if self.cursor_pos > line_start(&chars, self.cursor_pos) {
+            self.set_cursor(prev_boundary(&self.item.content, self.cursor_pos));
+        } else {
+            self.set_cursor(self.cursor_pos);
+        }
+        self.vim.mode = VimMode::Normal;
+    }
+
+    /// A key in normal mode: motions, operators (`d`, `c`, `y` with `w`,
+    /// `e`, `iw`, `$`, or doubled for the line), edits, and mode changes
+    pub fn vim_normal_key(&mut self, code: KeyCode) {
+        let c = match code {
+            KeyCode::Char(c) => c,
+            KeyCode::Left => 'h',
+            KeyCode::Right => 'l',
+            KeyCode::Up => 'k',
+            KeyCode::Down | KeyCode::Enter => 'j',
+            _ => {
+                self.vim.pending.clear();
+                return;
+            }
+        };
+
+        let chars: Vec<char> = self.item.content.chars().collect();
+        let pos = self.cursor_pos.min(chars.len());
+        let start = line_start(&chars, pos);
+        let end = line_end(&chars, pos);
+        // Neighbouring grapheme boundaries, so emoji move as one character
+        let (prev, next) = (
+            prev_boundary(&self.item.content, pos),
+            next_boundary(&self.item.content, pos),
+        );
+        let pending = std::mem::take(&mut self.vim.pending);
+
+        match (pending.as_str(), c) {
+            // Operators waiting for a motion
+            ("", 'd' | 'c' | 'y' | 'g') | ("d" | "c" | "y", 'i') => {
+                self.vim.pending = format!("{}{}", pending, c);
+            }
+            ("d", 'd') => self.vim_delete_lines(pos, pos),
+            ("y", 'y') => self.vim_yank_lines(pos, pos),
+            ("c", 'c') => {
+                self.vim_cut(start, end);
+                self.vim.mode = VimMode::Insert;
+            }
+            ("d" | "c" | "y", 'w' | 'e' | '$') | ("di" | "ci" | "yi", 'w') => {
+                let (from, to) = match c {
+                    'w' if pending.ends_with('i') => inner_word(&chars, pos),
+                    // `cw` changes to the end of the word, like vim
+                    'w' if pending != "c" => (pos, word_forward(&chars, pos)),
+                    '$' => (pos, end),
+                    _ => (pos, (word_end(&chars, pos) + 1).min(chars.len())),
+                };
+                if pending.starts_with('y') {
+                    self.vim.register = chars[from..to].iter().collect();
+                    self.vim.register_linewise = false;
+                } else {
+                    self.vim_cut(from, to);
+                    if pending.starts_with('c') {
+                        self.vim.mode = VimMode::Insert;
+                    }
+                }
+            }
+            ("g", 'g') => self.set_cursor(0),
+            ("", 'D') => self.vim_cut(pos, end),
+            ("", 'C') => {
+                self.vim_cut(pos, end);
+                self.vim.mode = VimMode::Insert;
+            }
+
+            // Motions
+            ("", 'h') if pos > start => self.set_cursor(prev),
+            ("", 'l') if next < end => self.set_cursor(next),
+            ("", 'j') => {
+                self.move_cursor_down();
+            }
+            ("", 'k') => {
+                self.move_cursor_up();
+            }
+            ("", 'w') => self.set_cursor(word_forward(&chars, pos)),
+            ("", 'b') => self.set_cursor(word_backward(&chars, pos)),
+            ("", 'e') => self.set_cursor(word_end(&chars, pos)),
+            ("", '0') => self.set_cursor(start),
+            ("", '^') => self.set_cursor(first_non_blank(&chars, pos)),
+            ("", '$') => self.set_cursor(prev_boundary(&self.item.content, end).max(start)),
+            ("", 'G') => self.set_cursor(line_start(&chars, chars.len())),
+
+            // Entering insert mode
+            ("", 'i') => self.vim_insert_at(pos),
+            ("", 'a') => self.vim_insert_at(next.min(end)),
+            ("", 'I') => self.vim_insert_at(first_non_blank(&chars, pos)),
+            ("", 'A') => self.vim_insert_at(end),
+            ("", 'o') => {
+                self.set_cursor(end);
+                self.insert_str("\n");
+                self.vim.mode = VimMode::Insert;
+            }
+            ("", 'O') => {
+                self.set_cursor(start);
+                self.insert_str("\n");
+                self.set_cursor(start);
+                self.vim.mode = VimMode::Insert;
+            }
+
+            // Edits
+            ("", 'x') if pos < end => self.vim_cut(pos, next),
+            ("", 'p') => self.vim_paste(pos, true),
+            ("", 'P') => self.vim_paste(pos, false),
+            ("", 'u') => self.undo(),
+            ("", 'V') => self.vim.mode = VimMode::VisualLine { anchor: pos },
+
+            // Anything else cancels the pending operator
+            _ => {}
+        }
+    }
+
+    /// A key in visual line mode: move to extend the selection, then `d`,
+    /// `c`, or `y` the selected lines
+    pub fn vim_visual_key(&mut self, code: KeyCode, anchor: usize) {
+        let pos = self.cursor_pos;
+        match code {
+            KeyCode::Char('j') | KeyCode::Down => self.move_cursor_down(),
+            KeyCode::Char('k') | KeyCode::Up => self.move_cursor_up(),
+            KeyCode::Char('d') | KeyCode::Char('x') => {
+                self.vim_delete_lines(anchor, pos);
+                self.vim.mode = VimMode::Normal;
+            }
+            KeyCode::Char('y') => {
+                self.vim_yank_lines(anchor, pos);
+                self.vim.mode = VimMode::Normal;
+            }
+            KeyCode::Char('c') => {
+                let chars: Vec<char> = self.item.content.chars().collect();
+                let (start, end) = lines_range(&chars, anchor, pos);
+                // Keep one empty line to type into
+                let end = if end > start && chars[end - 1] == '\n' {
+                    end - 1
+                } else {
+                    end
+                };
+                self.vim_cut(start, end);
+                self.vim.register_linewise = true;
+                self.vim.register.push('\n');
+                self.vim.mode = VimMode::Insert;
+            }
+            KeyCode::Esc | KeyCode::Char('V') => self.vim.mode = VimMode::Normal,
+            _ => {}
+        }
+    }
+
+    fn vim_insert_at(&mut self, pos: usize) {
+        self.set_cursor(pos);
+        self.vim.mode = VimMode::Insert;
+    }
+
+    /// Delete `start..end` into the register
+    fn vim_cut(&mut self, start: usize, end: usize) {
+        let removed = self.delete_range(start, end);
+        if !removed.is_empty() {
+            self.vim.register = removed;
+            self.vim.register_linewise = false;
+        }
+    }
+
+    fn vim_yank_lines(&mut self, a: usize, b: usize) {
+        let chars: Vec<char> = self.item.content.chars().collect();
+        let (start, end) = lines_range(&chars, a, b);
+        let mut text: String = chars[start..end].iter().collect();
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        self.vim.register = text;
+        self.vim.register_linewise = true;
+        self.set_cursor(start);
+    }
+
+    fn vim_delete_lines(&mut self, a: usize, b: usize) {
+        let chars: Vec<char> = self.item.content.chars().collect();
+        let (mut start, end) = lines_range(&chars, a, b);
+        // The last line has no newline of its own; take the one before it
+        if end == chars.len() && start > 0 && chars.get(end.wrapping_sub(1)) != Some(&'\n') {
+            start -= 1;
+        }
+
+        let removed = self.delete_range(start, end);
+        let mut text = removed.strip_prefix('\n').unwrap_or(&removed).to_string();
+        if !text.ends_with('\n') {
+            text.push('\n');
+        }
+        self.vim.register = text;
+        self.vim.register_linewise = true;
+
+        let chars: Vec<char> = self.item.content.chars().collect();
+        let pos = self.cursor_pos.min(chars.len());
+        self.set_cursor(line_start(&chars, pos));
+    }
+
+    /// `p` / `P`: put the register after or before the cursor, or below or
+    /// above the current line for whole lines
+    fn vim_paste(&mut self, pos: usize, after: bool) {
+        if self.vim.register.is_empty() {
+            return;
+        }
+        let text = self.vim.register.clone();
+        let chars: Vec<char> = self.item.content.chars().collect();
+
+        if self.vim.register_linewise {
+            let line = if after {
+                let end = line_end(&chars, pos);
+                if end == chars.len() {
+                    // Below the last line: start a new one first
+                    self.set_cursor(end);
+                    self.insert_str(&format!("\n{}", text.trim_end_matches('\n')));
+                    self.set_cursor(end + 1);
+                    return;
+                }
+                end + 1
+            } else {
+                line_start(&chars, pos)
+            };
+            self.set_cursor(line);
+            self.insert_str(&text);
+            self.set_cursor(line);
+        } else {
+            let at = if after && pos < line_end(&chars, pos) {
+                pos + 1
+            } else {
+                pos
+            };
+            self.set_cursor(at);
+            self.insert_str(&text);
+            self.set_cursor(self.cursor_pos.saturating_sub(1));
+        }
+    }
+}
+
+fn first_non_blank(chars: &[char], pos: usize) -> usize {
+    let start = line_start(chars, pos);
+    let end = line_end(chars, pos);
+    (start..end)
+        .find(|i| !chars[*i].is_whitespace())
+        .unwrap_or(end)
+}